@@ -0,0 +1,292 @@
+use crate::{
+  analytics::{
+    PortfolioSeries,
+    TRADING_DAYS_PER_YEAR,
+    mean,
+    ratio,
+    std_dev,
+  },
+  history::trading_date,
+  models::Bar,
+};
+use chrono::{
+  DateTime,
+  Datelike,
+  NaiveDate,
+  NaiveTime,
+  Utc,
+};
+use serde::Serialize;
+use std::fmt::Write;
+
+///
+///Session date of every timestamp. Daily series stamped at UTC midnight, as crypto and some daily
+/// histories are, keep their UTC date; everything else belongs to its trading day in
+/// [`crate::history::MARKET_TIMEZONE`], so a US equity daily bar at 04:00 or 05:00 UTC and a
+/// portfolio sample at the 16:00 close land on the same day
+pub fn session_dates(timestamps: &[DateTime<Utc>]) -> Vec<NaiveDate> {
+  let utc_midnights = !timestamps.is_empty() && timestamps.iter().all(|timestamp| timestamp.time() == NaiveTime::MIN);
+  timestamps
+    .iter()
+    .map(|timestamp| match utc_midnights {
+      true => timestamp.date_naive(),
+      false => trading_date(*timestamp),
+    })
+    .collect()
+}
+
+///
+///Last value of every session date, in date order
+fn last_per_date(samples: &[(DateTime<Utc>, f64)]) -> Vec<(NaiveDate, f64)> {
+  let timestamps: Vec<DateTime<Utc>> = samples.iter().map(|(timestamp, _)| *timestamp).collect();
+  let mut levels: Vec<(NaiveDate, f64)> = vec![];
+  for (date, (_, value)) in session_dates(&timestamps).into_iter().zip(samples) {
+    match levels.last_mut() {
+      Some((last, level)) if *last == date => *level = *value,
+      _ => levels.push((date, *value)),
+    }
+  }
+  levels
+}
+
+///
+///Closing levels of a benchmark such as SPY by session date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+  pub symbol: String,
+  pub closes: Vec<(NaiveDate, f64)>,
+}
+
+impl Benchmark {
+  ///
+  ///Benchmark from bars of any timeframe, the last close of a session being its level
+  pub fn from_bars(symbol: impl Into<String>, bars: &[Bar]) -> Self {
+    let mut samples: Vec<(DateTime<Utc>, f64)> = bars.iter().map(|bar| (bar.timestamp, bar.close)).collect();
+    samples.sort_by_key(|(timestamp, _)| *timestamp);
+    Benchmark {
+      symbol: symbol.into(),
+      closes: last_per_date(&samples),
+    }
+  }
+}
+
+///
+///Portfolio and benchmark return of one calendar month
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MonthlyComparison {
+  pub year: i32,
+  pub month: u32,
+  pub portfolio: f64,
+  pub benchmark: f64,
+  pub excess: f64,
+}
+
+///
+///Portfolio against a benchmark over the session dates both have, returns are net of cash flows
+/// and ratios annualized over [`TRADING_DAYS_PER_YEAR`]. Sessions only one side traded, like
+/// weekends of a crypto account, are folded into the next common session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkComparison {
+  pub benchmark: String,
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+  pub periods: usize,
+  pub portfolio_return: f64,
+  pub benchmark_return: f64,
+  pub excess_return: f64,
+  pub portfolio_volatility: f64,
+  pub benchmark_volatility: f64,
+  pub beta: Option<f64>,
+  ///
+  ///Annualized Jensen's alpha over the risk free rate
+  pub alpha: Option<f64>,
+  pub correlation: Option<f64>,
+  pub tracking_error: f64,
+  pub information_ratio: Option<f64>,
+  ///
+  ///Geometric mean portfolio return over the benchmark's in the periods the benchmark rose, above
+  /// `1.0` the portfolio captured more of the upside
+  pub up_capture: Option<f64>,
+  ///
+  ///Same over the periods the benchmark fell, below `1.0` the portfolio lost less
+  pub down_capture: Option<f64>,
+  pub monthly: Vec<MonthlyComparison>,
+}
+
+impl BenchmarkComparison {
+  pub fn compute(series: &PortfolioSeries, benchmark: &Benchmark, risk_free_rate: f64) -> Self {
+    let wealth = last_per_date(&series.wealth_index());
+    let mut levels: Vec<(NaiveDate, f64, f64)> = vec![];
+    let mut benchmark_closes = benchmark.closes.iter().peekable();
+    for (date, portfolio) in wealth {
+      while benchmark_closes.next_if(|(close_date, _)| *close_date < date).is_some() {}
+      if let Some((_, close)) = benchmark_closes.next_if(|(close_date, _)| *close_date == date) {
+        levels.push((date, portfolio, *close));
+      }
+    }
+    let returns: Vec<(NaiveDate, f64, f64)> = levels
+      .windows(2)
+      .filter(|pair| pair[0].1 > 0.0 && pair[0].2 > 0.0)
+      .map(|pair| (pair[1].0, pair[1].1 / pair[0].1 - 1.0, pair[1].2 / pair[0].2 - 1.0))
+      .collect();
+    let portfolio: Vec<f64> = returns.iter().map(|(_, portfolio, _)| *portfolio).collect();
+    let benchmark_returns: Vec<f64> = returns.iter().map(|(_, _, benchmark)| *benchmark).collect();
+    let active: Vec<f64> = returns
+      .iter()
+      .map(|(_, portfolio, benchmark)| portfolio - benchmark)
+      .collect();
+
+    let daily_risk_free = (1.0 + risk_free_rate).powf(1.0 / TRADING_DAYS_PER_YEAR) - 1.0;
+    let benchmark_variance = covariance(&benchmark_returns, &benchmark_returns);
+    let beta = ratio(covariance(&portfolio, &benchmark_returns), benchmark_variance);
+    let alpha = beta.map(|beta| {
+      (mean(&portfolio) - daily_risk_free - beta * (mean(&benchmark_returns) - daily_risk_free)) * TRADING_DAYS_PER_YEAR
+    });
+    let correlation = ratio(
+      covariance(&portfolio, &benchmark_returns),
+      std_dev(&portfolio) * std_dev(&benchmark_returns),
+    );
+    let tracking_error = std_dev(&active) * TRADING_DAYS_PER_YEAR.sqrt();
+    let (portfolio_return, benchmark_return) = (compound(&portfolio), compound(&benchmark_returns));
+
+    BenchmarkComparison {
+      benchmark: benchmark.symbol.clone(),
+      start: levels.first().map(|(date, _, _)| *date),
+      end: levels.last().map(|(date, _, _)| *date),
+      periods: returns.len(),
+      portfolio_return,
+      benchmark_return,
+      excess_return: portfolio_return - benchmark_return,
+      portfolio_volatility: std_dev(&portfolio) * TRADING_DAYS_PER_YEAR.sqrt(),
+      benchmark_volatility: std_dev(&benchmark_returns) * TRADING_DAYS_PER_YEAR.sqrt(),
+      beta,
+      alpha,
+      correlation,
+      tracking_error,
+      information_ratio: ratio(mean(&active) * TRADING_DAYS_PER_YEAR, tracking_error),
+      up_capture: capture(&returns, |benchmark| benchmark > 0.0),
+      down_capture: capture(&returns, |benchmark| benchmark < 0.0),
+      monthly: monthly(&returns),
+    }
+  }
+
+  ///
+  ///Markdown summary with a headline table, the risk statistics and the monthly returns, ready to
+  /// paste into an investor letter
+  pub fn to_markdown(&self) -> String {
+    let percent = |value: f64| format!("{:.2}%", value * 100.0);
+    let optional = |value: Option<f64>, format: &dyn Fn(f64) -> String| value.map(format).unwrap_or("n/a".to_string());
+    let number = |value: f64| format!("{value:.2}");
+    let mut markdown = String::new();
+    let period = match (self.start, self.end) {
+      (Some(start), Some(end)) => format!("{start} to {end}"),
+      _ => "no common sessions".to_string(),
+    };
+    let _ = writeln!(
+      markdown,
+      "## Performance against {}\n\n{period}, {} sessions\n",
+      self.benchmark, self.periods
+    );
+    let _ = writeln!(markdown, "| | Portfolio | {} |\n|---|---:|---:|", self.benchmark);
+    let _ = writeln!(
+      markdown,
+      "| Return | {} | {} |",
+      percent(self.portfolio_return),
+      percent(self.benchmark_return)
+    );
+    let _ = writeln!(
+      markdown,
+      "| Volatility | {} | {} |\n",
+      percent(self.portfolio_volatility),
+      percent(self.benchmark_volatility)
+    );
+    let _ = writeln!(markdown, "| Statistic | Value |\n|---|---:|");
+    let statistics = [
+      ("Excess return", percent(self.excess_return)),
+      ("Alpha", optional(self.alpha, &percent)),
+      ("Beta", optional(self.beta, &number)),
+      ("Correlation", optional(self.correlation, &number)),
+      ("Tracking error", percent(self.tracking_error)),
+      ("Information ratio", optional(self.information_ratio, &number)),
+      ("Up capture", optional(self.up_capture, &percent)),
+      ("Down capture", optional(self.down_capture, &percent)),
+    ];
+    for (name, value) in statistics {
+      let _ = writeln!(markdown, "| {name} | {value} |");
+    }
+    if !self.monthly.is_empty() {
+      let _ = writeln!(
+        markdown,
+        "\n| Month | Portfolio | {} | Excess |\n|---|---:|---:|---:|",
+        self.benchmark
+      );
+      for month in &self.monthly {
+        let _ = writeln!(
+          markdown,
+          "| {}-{:02} | {} | {} | {} |",
+          month.year,
+          month.month,
+          percent(month.portfolio),
+          percent(month.benchmark),
+          percent(month.excess)
+        );
+      }
+    }
+    markdown
+  }
+}
+
+///
+///Sample covariance, zero below two values
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+  if a.len() < 2 || a.len() != b.len() {
+    return 0.0;
+  }
+  let (mean_a, mean_b) = (mean(a), mean(b));
+  a.iter().zip(b).map(|(a, b)| (a - mean_a) * (b - mean_b)).sum::<f64>() / (a.len() - 1) as f64
+}
+
+fn compound(returns: &[f64]) -> f64 {
+  returns
+    .iter()
+    .fold(1.0, |wealth, period_return| wealth * (1.0 + period_return))
+    - 1.0
+}
+
+fn geometric_mean(returns: &[f64]) -> f64 {
+  match returns.len() {
+    0 => 0.0,
+    len => (1.0 + compound(returns)).powf(1.0 / len as f64) - 1.0,
+  }
+}
+
+fn capture(returns: &[(NaiveDate, f64, f64)], benchmark_filter: impl Fn(f64) -> bool) -> Option<f64> {
+  let (portfolio, benchmark): (Vec<f64>, Vec<f64>) = returns
+    .iter()
+    .filter(|(_, _, benchmark)| benchmark_filter(*benchmark))
+    .map(|(_, portfolio, benchmark)| (*portfolio, *benchmark))
+    .unzip();
+  ratio(geometric_mean(&portfolio), geometric_mean(&benchmark))
+}
+
+fn monthly(returns: &[(NaiveDate, f64, f64)]) -> Vec<MonthlyComparison> {
+  let mut months: Vec<MonthlyComparison> = vec![];
+  for (date, portfolio, benchmark) in returns {
+    let (year, month) = (date.year(), date.month());
+    match months.last_mut() {
+      Some(last) if (last.year, last.month) == (year, month) => {
+        last.portfolio = (1.0 + last.portfolio) * (1.0 + portfolio) - 1.0;
+        last.benchmark = (1.0 + last.benchmark) * (1.0 + benchmark) - 1.0;
+        last.excess = last.portfolio - last.benchmark;
+      }
+      _ => months.push(MonthlyComparison {
+        year,
+        month,
+        portfolio: *portfolio,
+        benchmark: *benchmark,
+        excess: portfolio - benchmark,
+      }),
+    }
+  }
+  months
+}
@@ -0,0 +1,231 @@
+use chrono::{
+  DateTime,
+  Utc,
+};
+use std::{
+  f64::consts::PI,
+  fmt::Write,
+};
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 12.0;
+const MARGIN_BOTTOM: f64 = 28.0;
+
+///
+///Colors of pie slices, reused in order
+const PALETTE: [&str; 10] = [
+  "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac",
+];
+
+pub(crate) fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+///
+///Fill of a line chart, `Area` shades between the line and zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineStyle {
+  Line,
+  Area,
+}
+
+///
+///Time series chart with the value range on the left axis and the first and last date below,
+/// `format` labels the values
+pub(crate) fn line_chart(
+  points: &[(DateTime<Utc>, f64)],
+  color: &str,
+  style: LineStyle,
+  format: impl Fn(f64) -> String,
+) -> String {
+  let mut svg = format!(
+    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {HEIGHT}" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="11">"#
+  );
+  let (Some(first), Some(last)) = (points.first(), points.last()) else {
+    svg.push_str(r#"<text x="50%" y="50%" text-anchor="middle">No data</text></svg>"#);
+    return svg;
+  };
+  let mut min = points.iter().map(|(_, value)| *value).fold(f64::INFINITY, f64::min);
+  let mut max = points.iter().map(|(_, value)| *value).fold(f64::NEG_INFINITY, f64::max);
+  if style == LineStyle::Area {
+    (min, max) = (min.min(0.0), max.max(0.0));
+  }
+  if min == max {
+    (min, max) = (min - 1.0, max + 1.0);
+  }
+  let span = (last.0 - first.0).num_seconds().max(1) as f64;
+  let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+  let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+  let x = |timestamp: DateTime<Utc>| MARGIN_LEFT + (timestamp - first.0).num_seconds() as f64 / span * plot_width;
+  let y = |value: f64| MARGIN_TOP + (max - value) / (max - min) * plot_height;
+
+  let bottom = HEIGHT - MARGIN_BOTTOM;
+  let right = WIDTH - MARGIN_RIGHT;
+  let _ = write!(
+    svg,
+    r##"<line x1="{MARGIN_LEFT}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="#999"/><line x1="{MARGIN_LEFT}" y1="{MARGIN_TOP}" x2="{MARGIN_LEFT}" y2="{bottom}" stroke="#999"/>"##
+  );
+  for value in [max, min] {
+    let _ = write!(
+      svg,
+      r#"<text x="{:.1}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+      MARGIN_LEFT - 6.0,
+      y(value),
+      escape(&format(value))
+    );
+  }
+  let _ = write!(
+    svg,
+    r#"<text x="{MARGIN_LEFT}" y="{:.1}">{}</text><text x="{right}" y="{:.1}" text-anchor="end">{}</text>"#,
+    HEIGHT - 8.0,
+    first.0.date_naive(),
+    HEIGHT - 8.0,
+    last.0.date_naive()
+  );
+
+  let path: Vec<String> = points
+    .iter()
+    .map(|(timestamp, value)| format!("{:.1},{:.1}", x(*timestamp), y(*value)))
+    .collect();
+  if style == LineStyle::Area {
+    let zero = y(0.0);
+    let _ = write!(
+      svg,
+      r#"<polygon points="{:.1},{zero:.1} {} {:.1},{zero:.1}" fill="{color}" fill-opacity="0.35" stroke="none"/>"#,
+      x(first.0),
+      path.join(" "),
+      x(last.0)
+    );
+  }
+  let _ = write!(
+    svg,
+    r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/></svg>"#,
+    path.join(" ")
+  );
+  svg
+}
+
+///
+///Pie chart of non-negative `slices` with a legend on the right, taller than the other charts when
+/// the legend needs it
+pub(crate) fn pie_chart(slices: &[(String, f64)]) -> String {
+  const LEGEND_ROW: f64 = 18.0;
+  let total: f64 = slices.iter().map(|(_, value)| value.max(0.0)).sum();
+  let height = HEIGHT.max(MARGIN_TOP * 2.0 + slices.len() as f64 * LEGEND_ROW);
+  let mut svg = format!(
+    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {height}" width="{WIDTH}" height="{height}" font-family="sans-serif" font-size="11">"#
+  );
+  if total <= 0.0 {
+    svg.push_str(r#"<text x="50%" y="50%" text-anchor="middle">No holdings</text></svg>"#);
+    return svg;
+  }
+  let (cx, cy, radius) = (HEIGHT / 2.0, HEIGHT / 2.0, HEIGHT / 2.0 - MARGIN_TOP);
+  let point = |angle: f64| (cx + radius * angle.sin(), cy - radius * angle.cos());
+  let mut angle = 0.0;
+  for (index, (label, value)) in slices.iter().enumerate() {
+    let share = value.max(0.0) / total;
+    let color = PALETTE[index % PALETTE.len()];
+    if share >= 1.0 {
+      let _ = write!(svg, r#"<circle cx="{cx}" cy="{cy}" r="{radius}" fill="{color}"/>"#);
+    } else if share > 0.0 {
+      let (start, end) = (point(angle), point(angle + share * 2.0 * PI));
+      let large_arc = u8::from(share > 0.5);
+      let _ = write!(
+        svg,
+        r##"<path d="M{cx},{cy} L{:.1},{:.1} A{radius},{radius} 0 {large_arc} 1 {:.1},{:.1} Z" fill="{color}" stroke="#fff"/>"##,
+        start.0, start.1, end.0, end.1
+      );
+    }
+    angle += share * 2.0 * PI;
+    let legend_y = MARGIN_TOP + 10.0 + index as f64 * LEGEND_ROW;
+    let _ = write!(
+      svg,
+      r#"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{color}"/><text x="{:.1}" y="{:.1}">{} {:.1}%</text>"#,
+      HEIGHT + 24.0,
+      legend_y - 9.0,
+      HEIGHT + 40.0,
+      legend_y,
+      escape(label),
+      share * 100.0
+    );
+  }
+  svg.push_str("</svg>");
+  svg
+}
+
+///
+///Background of a heatmap cell, green for gains and red for losses, saturating at 10%
+fn heat_color(value: f64) -> String {
+  let intensity = (value.abs() / 0.10).min(1.0);
+  let fade = |full: f64| (255.0 - (255.0 - full) * intensity).round() as u8;
+  match value >= 0.0 {
+    true => format!("rgb({},{},{})", fade(46.0), fade(160.0), fade(67.0)),
+    false => format!("rgb({},{},{})", fade(214.0), fade(39.0), fade(40.0)),
+  }
+}
+
+///
+///Monthly returns as a year by month grid with the compounded year in the last column
+pub(crate) fn monthly_heatmap(months: &[(i32, u32, f64)]) -> String {
+  const CELL_WIDTH: f64 = 50.0;
+  const CELL_HEIGHT: f64 = 24.0;
+  let mut years: Vec<i32> = months.iter().map(|(year, _, _)| *year).collect();
+  years.dedup();
+  let width = MARGIN_LEFT + 13.0 * CELL_WIDTH;
+  let height = CELL_HEIGHT * (years.len() + 1) as f64;
+  let mut svg = format!(
+    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width} {height}" width="{width}" height="{height}" font-family="sans-serif" font-size="11">"#
+  );
+  let headers = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec", "Year",
+  ];
+  for (column, header) in headers.iter().enumerate() {
+    let _ = write!(
+      svg,
+      r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-weight="bold">{header}</text>"#,
+      MARGIN_LEFT + (column as f64 + 0.5) * CELL_WIDTH,
+      CELL_HEIGHT * 0.65
+    );
+  }
+  let cell = |svg: &mut String, row: usize, column: usize, value: f64| {
+    let (x, y) = (MARGIN_LEFT + column as f64 * CELL_WIDTH, (row + 1) as f64 * CELL_HEIGHT);
+    let _ = write!(
+      svg,
+      r##"<rect x="{x:.1}" y="{y:.1}" width="{CELL_WIDTH}" height="{CELL_HEIGHT}" fill="{}" stroke="#fff"/><text x="{:.1}" y="{:.1}" text-anchor="middle">{:.1}%</text>"##,
+      heat_color(value),
+      x + CELL_WIDTH / 2.0,
+      y + CELL_HEIGHT * 0.65,
+      value * 100.0
+    );
+  };
+  for (row, year) in years.iter().enumerate() {
+    let _ = write!(
+      svg,
+      r#"<text x="{:.1}" y="{:.1}" text-anchor="end" font-weight="bold">{year}</text>"#,
+      MARGIN_LEFT - 8.0,
+      (row as f64 + 1.65) * CELL_HEIGHT
+    );
+    let mut annual = 1.0;
+    for (_, month, monthly_return) in months.iter().filter(|(month_year, _, _)| month_year == year) {
+      cell(&mut svg, row, *month as usize - 1, *monthly_return);
+      annual *= 1.0 + monthly_return;
+    }
+    cell(&mut svg, row, 12, annual - 1.0);
+  }
+  svg.push_str("</svg>");
+  svg
+}
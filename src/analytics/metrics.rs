@@ -0,0 +1,178 @@
+use crate::{
+  analytics::{
+    Drawdown,
+    PortfolioSeries,
+  },
+  models::{
+    Account,
+    Position,
+    PositionSide,
+  },
+};
+use chrono::{
+  DateTime,
+  NaiveDate,
+  Utc,
+};
+use serde::Serialize;
+
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+pub fn mean(values: &[f64]) -> f64 {
+  match values.len() {
+    0 => 0.0,
+    len => values.iter().sum::<f64>() / len as f64,
+  }
+}
+
+///
+///Sample standard deviation, zero below two values
+pub fn std_dev(values: &[f64]) -> f64 {
+  if values.len() < 2 {
+    return 0.0;
+  }
+  let mean = mean(values);
+  let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+  variance.sqrt()
+}
+
+///
+///Ratio that is `None` instead of infinite or NaN when the denominator is zero
+pub(crate) fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+  (denominator != 0.0 && denominator.is_finite()).then(|| numerator / denominator)
+}
+
+///
+///Headline performance of a portfolio, ratios are annualized from daily returns over
+/// [`TRADING_DAYS_PER_YEAR`] and `None` when undefined, e.g. without volatility
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerformanceMetrics {
+  pub start: Option<DateTime<Utc>>,
+  pub end: Option<DateTime<Utc>>,
+  pub trading_days: usize,
+  pub start_equity: f64,
+  pub end_equity: f64,
+  pub net_cash_flow: f64,
+  pub total_return: f64,
+  pub time_weighted_return: f64,
+  pub annualized_return: f64,
+  ///
+  ///Annualized, see [`PortfolioSeries::money_weighted_return`]
+  pub money_weighted_return: Option<f64>,
+  pub volatility: f64,
+  pub downside_deviation: f64,
+  pub sharpe: Option<f64>,
+  pub sortino: Option<f64>,
+  pub calmar: Option<f64>,
+  pub max_drawdown: Option<Drawdown>,
+  pub longest_drawdown: Option<Drawdown>,
+  pub best_day: Option<(NaiveDate, f64)>,
+  pub worst_day: Option<(NaiveDate, f64)>,
+  pub positive_days: usize,
+  pub negative_days: usize,
+}
+
+impl PerformanceMetrics {
+  ///
+  ///Compute the metrics of `series` against an annual `risk_free_rate` such as `0.04`
+  pub fn compute(series: &PortfolioSeries, risk_free_rate: f64) -> Self {
+    let daily_returns = series.daily_returns();
+    let returns: Vec<f64> = daily_returns.iter().map(|(_, daily_return)| *daily_return).collect();
+    let daily_risk_free = (1.0 + risk_free_rate).powf(1.0 / TRADING_DAYS_PER_YEAR) - 1.0;
+    let excess: Vec<f64> = returns
+      .iter()
+      .map(|daily_return| daily_return - daily_risk_free)
+      .collect();
+
+    let time_weighted_return = series.time_weighted_return();
+    let annualized_return = match returns.len() {
+      0 => 0.0,
+      days => (1.0 + time_weighted_return).powf(TRADING_DAYS_PER_YEAR / days as f64) - 1.0,
+    };
+    let volatility = std_dev(&returns) * TRADING_DAYS_PER_YEAR.sqrt();
+    let downside_deviation = match excess.len() {
+      0 => 0.0,
+      len => {
+        let squares: f64 = excess.iter().map(|excess| excess.min(0.0).powi(2)).sum();
+        (squares / len as f64).sqrt() * TRADING_DAYS_PER_YEAR.sqrt()
+      }
+    };
+    let annual_excess = mean(&excess) * TRADING_DAYS_PER_YEAR;
+    let max_drawdown = series.max_drawdown();
+    let by_return = |a: &&(NaiveDate, f64), b: &&(NaiveDate, f64)| a.1.total_cmp(&b.1);
+
+    PerformanceMetrics {
+      start: series.start(),
+      end: series.end(),
+      trading_days: returns.len(),
+      start_equity: series.points.first().map(|point| point.equity).unwrap_or_default(),
+      end_equity: series.points.last().map(|point| point.equity).unwrap_or_default(),
+      net_cash_flow: series.net_cash_flow(),
+      total_return: series.total_return(),
+      time_weighted_return,
+      annualized_return,
+      money_weighted_return: series.money_weighted_return(),
+      volatility,
+      downside_deviation,
+      sharpe: ratio(annual_excess, volatility),
+      sortino: ratio(annual_excess, downside_deviation),
+      calmar: max_drawdown.and_then(|drawdown| ratio(annualized_return, drawdown.depth.abs())),
+      max_drawdown,
+      longest_drawdown: series.longest_drawdown(),
+      best_day: daily_returns.iter().max_by(by_return).copied(),
+      worst_day: daily_returns.iter().min_by(by_return).copied(),
+      positive_days: returns.iter().filter(|daily_return| **daily_return > 0.0).count(),
+      negative_days: returns.iter().filter(|daily_return| **daily_return < 0.0).count(),
+    }
+  }
+}
+
+///
+///Market exposure as fractions of equity, `gross` adds both sides and `net` nets shorts against
+/// longs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Exposure {
+  pub long: f64,
+  pub short: f64,
+  pub gross: f64,
+  pub net: f64,
+}
+
+impl Exposure {
+  fn new(long_value: f64, short_value: f64, equity: f64) -> Self {
+    if equity <= 0.0 {
+      return Exposure {
+        long: 0.0,
+        short: 0.0,
+        gross: 0.0,
+        net: 0.0,
+      };
+    }
+    let (long, short) = (long_value / equity, short_value.abs() / equity);
+    Exposure {
+      long,
+      short,
+      gross: long + short,
+      net: long - short,
+    }
+  }
+
+  pub fn from_account(account: &Account) -> Self {
+    Exposure::new(
+      account.long_market_value.value(),
+      account.short_market_value.value(),
+      account.equity.value(),
+    )
+  }
+
+  pub fn from_positions(positions: &[Position], equity: f64) -> Self {
+    let side_value = |side: PositionSide| {
+      positions
+        .iter()
+        .filter(|position| position.side == side)
+        .map(|position| position.market_value.value().abs())
+        .sum::<f64>()
+    };
+    Exposure::new(side_value(PositionSide::Long), side_value(PositionSide::Short), equity)
+  }
+}
@@ -0,0 +1,10 @@
+mod benchmark;
+mod charts;
+mod metrics;
+mod report;
+mod series;
+
+pub use benchmark::*;
+pub use metrics::*;
+pub use report::*;
+pub use series::*;
@@ -0,0 +1,268 @@
+use crate::{
+  analytics::{
+    PerformanceMetrics,
+    PortfolioSeries,
+    charts::{
+      LineStyle,
+      escape,
+      line_chart,
+      monthly_heatmap,
+      pie_chart,
+    },
+  },
+  models::{
+    Order,
+    PortfolioHistory,
+    Position,
+    PositionSide,
+  },
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::Serialize;
+use std::{
+  fmt::Write,
+  path::Path,
+};
+
+const STYLE: &str = concat!(
+  "body{font-family:sans-serif;margin:2em auto;max-width:760px;color:#222}",
+  "h1{font-size:1.5em}h2{font-size:1.15em;margin-top:2em;border-bottom:1px solid #ddd}",
+  "table{border-collapse:collapse;font-size:0.9em}td,th{padding:3px 10px;border-bottom:1px solid #eee}",
+  "td.number{text-align:right}.muted{color:#777}",
+);
+
+///
+///Self-contained HTML performance report with inline SVG charts: summary statistics, equity curve,
+/// drawdowns, monthly returns heatmap, allocation pie and the filled orders. The page references
+/// no scripts, fonts or stylesheets, so it renders the same when opened from an archive years
+/// later.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::analytics::PerformanceReport;
+/// # fn example(history: alpaca_trade_api_rust::prelude::PortfolioHistory) -> anyhow::Result<()> {
+/// PerformanceReport::new("Paper account", &history)
+///   .with_cash(2_500.0)
+///   .write("report.html")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PerformanceReport<'a> {
+  title: String,
+  series: PortfolioSeries,
+  orders: &'a [Order],
+  positions: &'a [Position],
+  cash: Option<f64>,
+  risk_free_rate: f64,
+  generated_at: DateTime<Utc>,
+}
+
+impl<'a> PerformanceReport<'a> {
+  pub fn new(title: impl Into<String>, history: &PortfolioHistory) -> Self {
+    PerformanceReport::from_series(title, PortfolioSeries::from(history))
+  }
+
+  pub fn from_series(title: impl Into<String>, series: PortfolioSeries) -> Self {
+    PerformanceReport {
+      title: title.into(),
+      series,
+      orders: &[],
+      positions: &[],
+      cash: None,
+      risk_free_rate: 0.0,
+      generated_at: Utc::now(),
+    }
+  }
+
+  ///
+  ///Orders for the trade list, only the ones with fills are shown
+  pub fn with_orders(mut self, orders: &'a [Order]) -> Self {
+    self.orders = orders;
+    self
+  }
+
+  pub fn with_positions(mut self, positions: &'a [Position]) -> Self {
+    self.positions = positions;
+    self
+  }
+
+  ///
+  ///Cash balance shown as its own slice of the allocation
+  pub fn with_cash(mut self, cash: f64) -> Self {
+    self.cash = Some(cash);
+    self
+  }
+
+  ///
+  ///Annual rate for the Sharpe and Sortino ratios, `0.0` by default
+  pub fn with_risk_free_rate(mut self, risk_free_rate: f64) -> Self {
+    self.risk_free_rate = risk_free_rate;
+    self
+  }
+
+  ///
+  ///Time printed in the header, now by default
+  pub fn with_generated_at(mut self, generated_at: DateTime<Utc>) -> Self {
+    self.generated_at = generated_at;
+    self
+  }
+
+  pub fn render(&self) -> String {
+    let metrics = PerformanceMetrics::compute(&self.series, self.risk_free_rate);
+    let mut html = String::new();
+    let _ = write!(
+      html,
+      "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta \
+       charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+      title = escape(&self.title)
+    );
+    let period = match (metrics.start, metrics.end) {
+      (Some(start), Some(end)) => format!("{} to {}", start.date_naive(), end.date_naive()),
+      _ => "no history".to_string(),
+    };
+    let _ = writeln!(
+      html,
+      "<p class=\"muted\">{period}, generated {}</p>",
+      self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+
+    html.push_str("<h2>Summary</h2>\n");
+    html.push_str(&summary_table(&metrics));
+    html.push_str("<h2>Equity</h2>\n");
+    let equity: Vec<(DateTime<Utc>, f64)> = self
+      .series
+      .points
+      .iter()
+      .map(|point| (point.timestamp, point.equity))
+      .collect();
+    html.push_str(&line_chart(&equity, "#4e79a7", LineStyle::Line, |value| {
+      format!("{value:.0}")
+    }));
+    html.push_str("\n<h2>Drawdown</h2>\n");
+    html.push_str(&line_chart(
+      &self.series.underwater(),
+      "#e15759",
+      LineStyle::Area,
+      percent,
+    ));
+    html.push_str("\n<h2>Monthly returns</h2>\n");
+    html.push_str(&monthly_heatmap(&self.series.monthly_returns()));
+    html.push_str("\n<h2>Allocation</h2>\n");
+    html.push_str(&pie_chart(&self.allocation()));
+    html.push_str("\n<h2>Trades</h2>\n");
+    html.push_str(&self.trade_table());
+    html.push_str("</body>\n</html>\n");
+    html
+  }
+
+  pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    std::fs::write(path, self.render())?;
+    Ok(())
+  }
+
+  ///
+  ///Market value of every position, shorts by their absolute value, then cash
+  fn allocation(&self) -> Vec<(String, f64)> {
+    let mut slices: Vec<(String, f64)> = self
+      .positions
+      .iter()
+      .map(|position| {
+        let label = match position.side {
+          PositionSide::Short => format!("{} (short)", position.symbol),
+          _ => position.symbol.clone(),
+        };
+        (label, position.market_value.value().abs())
+      })
+      .collect();
+    slices.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if let Some(cash) = self.cash.filter(|cash| *cash > 0.0) {
+      slices.push(("Cash".to_string(), cash));
+    }
+    slices
+  }
+
+  fn trade_table(&self) -> String {
+    let mut fills: Vec<&Order> = self
+      .orders
+      .iter()
+      .filter(|order| order.filled_at.is_some() && order.filled_qty.as_ref().is_some_and(|qty| qty.value() > 0.0))
+      .collect();
+    fills.sort_by_key(|order| std::cmp::Reverse(order.filled_at));
+    if fills.is_empty() {
+      return "<p class=\"muted\">No filled orders</p>\n".to_string();
+    }
+    let mut table = String::from(
+      "<table>\n<tr><th>Filled</th><th>Symbol</th><th>Side</th><th>Type</th><th>Qty</th><th>Price</th><th>Notional</\
+       th></tr>\n",
+    );
+    for order in fills {
+      let qty = order.filled_qty.as_ref().map(|qty| qty.value()).unwrap_or_default();
+      let price = order
+        .filled_avg_price
+        .as_ref()
+        .map(|price| price.value())
+        .unwrap_or_default();
+      let _ = writeln!(
+        table,
+        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"number\">{qty}</td><td \
+         class=\"number\">{price:.2}</td><td class=\"number\">{:.2}</td></tr>",
+        order
+          .filled_at
+          .map(|filled_at| filled_at.format("%Y-%m-%d %H:%M").to_string())
+          .unwrap_or_default(),
+        escape(&order.symbol),
+        label(&order.side),
+        label(&order._type),
+        qty * price
+      );
+    }
+    table.push_str("</table>\n");
+    table
+  }
+}
+
+fn percent(value: f64) -> String {
+  format!("{:.2}%", value * 100.0)
+}
+
+///
+///Serialized name of an API enum such as `buy` or `trailing_stop`
+fn label<T: Serialize>(value: &T) -> String {
+  match serde_json::to_value(value) {
+    Ok(serde_json::Value::String(name)) => escape(&name),
+    _ => String::new(),
+  }
+}
+
+fn summary_table(metrics: &PerformanceMetrics) -> String {
+  let ratio = |value: Option<f64>| value.map(|value| format!("{value:.2}")).unwrap_or("n/a".to_string());
+  let rows = [
+    ("Start equity", format!("{:.2}", metrics.start_equity)),
+    ("End equity", format!("{:.2}", metrics.end_equity)),
+    ("Net deposits", format!("{:.2}", metrics.net_cash_flow)),
+    ("Time-weighted return", percent(metrics.time_weighted_return)),
+    ("Annualized return", percent(metrics.annualized_return)),
+    (
+      "Money-weighted return",
+      metrics.money_weighted_return.map(percent).unwrap_or("n/a".to_string()),
+    ),
+    ("Volatility", percent(metrics.volatility)),
+    ("Sharpe", ratio(metrics.sharpe)),
+    ("Sortino", ratio(metrics.sortino)),
+    (
+      "Max drawdown",
+      metrics
+        .max_drawdown
+        .map(|drawdown| percent(drawdown.depth))
+        .unwrap_or("n/a".to_string()),
+    ),
+  ];
+  let mut table = String::from("<table>\n");
+  for (name, value) in rows {
+    let _ = writeln!(table, "<tr><td>{name}</td><td class=\"number\">{value}</td></tr>");
+  }
+  table.push_str("</table>\n");
+  table
+}
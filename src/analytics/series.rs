@@ -0,0 +1,329 @@
+use crate::{
+  history::trading_date,
+  models::PortfolioHistory,
+};
+use chrono::{
+  DateTime,
+  Datelike,
+  NaiveDate,
+  TimeDelta,
+  Utc,
+};
+use serde::Serialize;
+
+///
+///One sample of a portfolio history, `cash_flow` being the external money that moved in (positive)
+/// or out (negative) of the account during the period ending at `timestamp`, see
+/// [`crate::models::ActivityType::is_external`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PortfolioPoint {
+  pub timestamp: DateTime<Utc>,
+  pub equity: f64,
+  pub profit_loss: f64,
+  pub profit_loss_pct: f64,
+  pub cash_flow: f64,
+}
+
+///
+///Stretch of the wealth index below its previous peak
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Drawdown {
+  ///
+  ///Fall from the peak as a negative fraction, `-0.12` is a 12% drawdown
+  pub depth: f64,
+  pub peak: DateTime<Utc>,
+  pub trough: DateTime<Utc>,
+  ///
+  ///First sample back at the peak, `None` while still under water
+  pub recovered: Option<DateTime<Utc>>,
+  ///
+  ///From the peak to the recovery, or to the end of the series while under water
+  #[serde(serialize_with = "serialize_days")]
+  pub duration: TimeDelta,
+}
+
+fn serialize_days<S>(duration: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  serializer.serialize_f64(duration.num_seconds() as f64 / 86_400.0)
+}
+
+///
+///Typed time series view of a [`PortfolioHistory`], sorted by time
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PortfolioSeries {
+  pub points: Vec<PortfolioPoint>,
+}
+
+impl From<&PortfolioHistory> for PortfolioSeries {
+  fn from(history: &PortfolioHistory) -> Self {
+    let cash_flows = history.external_cash_flows();
+    let points = history
+      .timestamp
+      .iter()
+      .enumerate()
+      .filter_map(|(index, timestamp)| {
+        Some(PortfolioPoint {
+          timestamp: DateTime::from_timestamp(*timestamp as i64, 0)?,
+          equity: *history.equity.get(index)?,
+          profit_loss: history.profit_loss.get(index).copied().unwrap_or_default(),
+          profit_loss_pct: history.profit_loss_pct.get(index).copied().unwrap_or_default(),
+          cash_flow: cash_flows[index],
+        })
+      })
+      .collect();
+    PortfolioSeries::new(points)
+  }
+}
+
+impl PortfolioSeries {
+  pub fn new(mut points: Vec<PortfolioPoint>) -> Self {
+    points.sort_by_key(|point| point.timestamp);
+    PortfolioSeries { points }
+  }
+
+  ///
+  ///Replace the cash flows, e.g. with deposits and withdrawals taken from account activities,
+  /// `cash_flows[i]` belonging to `points[i]`
+  pub fn with_cash_flows(mut self, cash_flows: &[f64]) -> Self {
+    for (index, point) in self.points.iter_mut().enumerate() {
+      point.cash_flow = cash_flows.get(index).copied().unwrap_or_default();
+    }
+    self
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.points.is_empty()
+  }
+
+  pub fn start(&self) -> Option<DateTime<Utc>> {
+    self.points.first().map(|point| point.timestamp)
+  }
+
+  pub fn end(&self) -> Option<DateTime<Utc>> {
+    self.points.last().map(|point| point.timestamp)
+  }
+
+  ///
+  ///Last sample of every trading day with the day's cash flows summed, intraday histories become
+  /// daily ones
+  pub fn daily(&self) -> PortfolioSeries {
+    let mut days: Vec<(NaiveDate, PortfolioPoint)> = vec![];
+    for point in &self.points {
+      let date = trading_date(point.timestamp);
+      match days.last_mut() {
+        Some((day, last)) if *day == date => {
+          let cash_flow = last.cash_flow + point.cash_flow;
+          *last = PortfolioPoint { cash_flow, ..*point };
+        }
+        _ => days.push((date, *point)),
+      }
+    }
+    PortfolioSeries {
+      points: days.into_iter().map(|(_, point)| point).collect(),
+    }
+  }
+
+  ///
+  ///Time-weighted return of every period, flows being counted at the end of the period they
+  /// arrived in; periods starting without equity, such as before the account was funded, are
+  /// skipped
+  pub fn period_returns(&self) -> Vec<(DateTime<Utc>, f64)> {
+    self
+      .points
+      .windows(2)
+      .filter(|pair| pair[0].equity > 0.0)
+      .map(|pair| {
+        let (previous, current) = (pair[0], pair[1]);
+        (
+          current.timestamp,
+          (current.equity - current.cash_flow) / previous.equity - 1.0,
+        )
+      })
+      .collect()
+  }
+
+  ///
+  ///Period returns of [`PortfolioSeries::daily`] keyed by trading day
+  pub fn daily_returns(&self) -> Vec<(NaiveDate, f64)> {
+    self
+      .daily()
+      .period_returns()
+      .into_iter()
+      .map(|(timestamp, period_return)| (trading_date(timestamp), period_return))
+      .collect()
+  }
+
+  ///
+  ///Compounded daily returns of every calendar month as `(year, month, return)`
+  pub fn monthly_returns(&self) -> Vec<(i32, u32, f64)> {
+    let mut months: Vec<(i32, u32, f64)> = vec![];
+    for (date, daily_return) in self.daily_returns() {
+      match months.last_mut() {
+        Some((year, month, monthly_return)) if (*year, *month) == (date.year(), date.month()) => {
+          *monthly_return = (1.0 + *monthly_return) * (1.0 + daily_return) - 1.0;
+        }
+        _ => months.push((date.year(), date.month(), daily_return)),
+      }
+    }
+    months
+  }
+
+  ///
+  ///Growth of one unit invested at the first funded sample, net of cash flows
+  pub fn wealth_index(&self) -> Vec<(DateTime<Utc>, f64)> {
+    let returns = self.period_returns();
+    let Some(first) = returns.first() else {
+      return vec![];
+    };
+    let start = self
+      .points
+      .iter()
+      .rev()
+      .find(|point| point.timestamp < first.0)
+      .map(|point| point.timestamp)
+      .unwrap_or(first.0);
+    let mut wealth = 1.0;
+    std::iter::once((start, wealth))
+      .chain(returns.into_iter().map(|(timestamp, period_return)| {
+        wealth *= 1.0 + period_return;
+        (timestamp, wealth)
+      }))
+      .collect()
+  }
+
+  ///
+  ///End equity over start equity, ignoring cash flows
+  pub fn total_return(&self) -> f64 {
+    let start = self.points.iter().find(|point| point.equity > 0.0);
+    match (start, self.points.last()) {
+      (Some(start), Some(end)) => end.equity / start.equity - 1.0,
+      _ => 0.0,
+    }
+  }
+
+  ///
+  ///Cumulative return chaining the period returns, deposits and withdrawals do not count as
+  /// performance
+  pub fn time_weighted_return(&self) -> f64 {
+    self
+      .period_returns()
+      .iter()
+      .fold(1.0, |wealth, (_, period_return)| wealth * (1.0 + period_return))
+      - 1.0
+  }
+
+  pub fn net_cash_flow(&self) -> f64 {
+    self.points.iter().skip(1).map(|point| point.cash_flow).sum()
+  }
+
+  ///
+  ///Annualized internal rate of return of the account, investing the equity of the first funded
+  /// sample, every later cash flow when it arrived and getting the final equity back; unlike the
+  /// time-weighted return it rewards adding money before good periods. `None` without a funded
+  /// period or when no rate between -100% and 1,000,000% solves it
+  pub fn money_weighted_return(&self) -> Option<f64> {
+    let first_return = self.period_returns().first()?.0;
+    let start = self.points.iter().rev().find(|point| point.timestamp < first_return)?;
+    let end = self.points.last()?;
+    let years = |timestamp: DateTime<Utc>| (timestamp - start.timestamp).num_seconds() as f64 / (365.0 * 86_400.0);
+    let mut flows = vec![(0.0, -start.equity)];
+    flows.extend(
+      self
+        .points
+        .iter()
+        .filter(|point| point.timestamp > start.timestamp && point.cash_flow != 0.0)
+        .map(|point| (years(point.timestamp), -point.cash_flow)),
+    );
+    flows.push((years(end.timestamp), end.equity));
+    let net_present_value =
+      |rate: f64| -> f64 { flows.iter().map(|(years, flow)| flow / (1.0 + rate).powf(*years)).sum() };
+
+    // the value falls as the rate rises while the final equity dominates, bisect on the sign
+    let (mut low, mut high) = (-0.999_999, 10_000.0);
+    if net_present_value(low).signum() == net_present_value(high).signum() {
+      return None;
+    }
+    for _ in 0..200 {
+      let rate = (low + high) / 2.0;
+      if net_present_value(rate).signum() == net_present_value(low).signum() {
+        low = rate;
+      } else {
+        high = rate;
+      }
+    }
+    Some((low + high) / 2.0)
+  }
+
+  ///
+  ///Distance of the wealth index below its running peak at every sample, zero at new highs
+  pub fn underwater(&self) -> Vec<(DateTime<Utc>, f64)> {
+    let mut peak = f64::MIN;
+    self
+      .wealth_index()
+      .into_iter()
+      .map(|(timestamp, wealth)| {
+        peak = peak.max(wealth);
+        (timestamp, wealth / peak - 1.0)
+      })
+      .collect()
+  }
+
+  ///
+  ///Every drawdown of the wealth index, in order
+  pub fn drawdowns(&self) -> Vec<Drawdown> {
+    let wealth = self.wealth_index();
+    let Some(&(end, _)) = wealth.last() else {
+      return vec![];
+    };
+    let mut drawdowns = vec![];
+    let (mut peak_time, mut peak) = wealth[0];
+    let mut current: Option<Drawdown> = None;
+    for &(timestamp, value) in &wealth[1..] {
+      if value >= peak {
+        if let Some(mut drawdown) = current.take() {
+          drawdown.recovered = Some(timestamp);
+          drawdown.duration = timestamp - drawdown.peak;
+          drawdowns.push(drawdown);
+        }
+        (peak_time, peak) = (timestamp, value);
+        continue;
+      }
+      let depth = value / peak - 1.0;
+      match &mut current {
+        Some(drawdown) if depth < drawdown.depth => {
+          drawdown.depth = depth;
+          drawdown.trough = timestamp;
+        }
+        Some(_) => {}
+        None => {
+          current = Some(Drawdown {
+            depth,
+            peak: peak_time,
+            trough: timestamp,
+            recovered: None,
+            duration: TimeDelta::zero(),
+          })
+        }
+      }
+    }
+    if let Some(mut drawdown) = current {
+      drawdown.duration = end - drawdown.peak;
+      drawdowns.push(drawdown);
+    }
+    drawdowns
+  }
+
+  ///
+  ///Deepest drawdown
+  pub fn max_drawdown(&self) -> Option<Drawdown> {
+    self.drawdowns().into_iter().min_by(|a, b| a.depth.total_cmp(&b.depth))
+  }
+
+  ///
+  ///Drawdown that lasted longest
+  pub fn longest_drawdown(&self) -> Option<Drawdown> {
+    self.drawdowns().into_iter().max_by_key(|drawdown| drawdown.duration)
+  }
+}
@@ -0,0 +1,95 @@
+use crate::{
+  client::Client,
+  models::{
+    AccountConfigurations,
+    ErrorResponse,
+  },
+};
+use anyhow::bail;
+use serde::Serialize;
+
+pub trait AccountConfigurationsApi {
+  fn get_account_configurations(&self) -> impl Future<Output = anyhow::Result<AccountConfigurations>>;
+
+  fn update_account_configurations(
+    &self,
+    request_body: &AccountConfigurationsReqBody,
+  ) -> impl Future<Output = anyhow::Result<AccountConfigurations>>;
+}
+
+impl AccountConfigurationsApi for Client {
+  async fn get_account_configurations(&self) -> anyhow::Result<AccountConfigurations> {
+    let url = format!("{}/v2/account/configurations", self.base_url);
+    match self.client.get(url).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let configurations = response.json::<AccountConfigurations>().await?;
+          Ok(configurations)
+        } else {
+          let error_response = response.json::<ErrorResponse>().await?;
+          bail!(error_response)
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn update_account_configurations(
+    &self,
+    request_body: &AccountConfigurationsReqBody,
+  ) -> anyhow::Result<AccountConfigurations> {
+    let url = format!("{}/v2/account/configurations", self.base_url);
+    match self.client.patch(url).json(&request_body).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let configurations = response.json::<AccountConfigurations>().await?;
+          Ok(configurations)
+        } else {
+          let error_response = response.json::<ErrorResponse>().await?;
+          bail!(error_response)
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+///
+///Only the fields that are set are changed
+#[derive(Debug, Default, Serialize)]
+pub struct AccountConfigurationsReqBody {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub dtbp_check: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub trade_confirm_email: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub suspend_trade: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub no_shorting: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fractional_trading: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_margin_multiplier: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_options_trading_level: Option<u8>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pdt_check: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ptp_no_exception_entry: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::api::AccountConfigurationsReqBody;
+
+  #[test]
+  fn test_account_configurations_req_body() {
+    let request_body = AccountConfigurationsReqBody {
+      suspend_trade: Some(true),
+      ..Default::default()
+    };
+
+    let serialized = serde_json::to_string(&request_body).unwrap();
+    assert_eq!(serialized, r#"{"suspend_trade":true}"#)
+  }
+}
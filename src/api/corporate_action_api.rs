@@ -11,7 +11,10 @@ use crate::{
 };
 use anyhow::bail;
 use chrono::NaiveDate;
-use serde::Serialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 use uuid::Uuid;
 
 pub trait CorporateActionApi {
@@ -60,7 +63,7 @@ impl CorporateActionApi for Client {
   }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CorporateActionsDateType {
   DeclarationDate,
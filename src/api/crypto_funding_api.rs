@@ -10,7 +10,10 @@ use crate::{
   },
 };
 use anyhow::bail;
-use serde::Serialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 
 pub trait CryptoFundingApi {
   fn get_all_crypto_funding_wallet(
@@ -180,7 +183,7 @@ pub struct FundingWalletsParameter {
   pub network: Option<CryptonNetwork>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CryptonNetwork {
   Ethereum,
@@ -0,0 +1,360 @@
+use crate::{
+  api::utils::{
+    Sort,
+    TimeFrame,
+    serialize_vec_to_coma_separated_str,
+  },
+  client::Client,
+  models::{
+    Bar,
+    CryptoLocation,
+    CryptoOrderbook,
+    CryptoQuote,
+    CryptoSnapshot,
+    CryptoTrade,
+    ErrorResponse,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::collections::HashMap;
+
+pub trait CryptoMarketDataApi {
+  fn get_crypto_bars(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoBarsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoBarsResponse>>;
+
+  fn get_crypto_trades(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoHistoricalQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoTradesResponse>>;
+
+  fn get_crypto_quotes(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoHistoricalQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoQuotesResponse>>;
+
+  fn get_crypto_latest_bars(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoLatestBarsResponse>>;
+
+  fn get_crypto_latest_quotes(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoLatestQuotesResponse>>;
+
+  fn get_crypto_latest_trades(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoLatestTradesResponse>>;
+
+  fn get_crypto_latest_orderbooks(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoLatestOrderbooksResponse>>;
+
+  fn get_crypto_snapshots(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<CryptoSnapshotsResponse>>;
+}
+
+impl CryptoMarketDataApi for Client {
+  async fn get_crypto_bars(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoBarsQueryParameter,
+  ) -> anyhow::Result<CryptoBarsResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/bars", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let bars = response.json::<CryptoBarsResponse>().await?;
+          Ok(bars)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_trades(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoHistoricalQueryParameter,
+  ) -> anyhow::Result<CryptoTradesResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/trades", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let trades = response.json::<CryptoTradesResponse>().await?;
+          Ok(trades)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_quotes(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoHistoricalQueryParameter,
+  ) -> anyhow::Result<CryptoQuotesResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/quotes", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let quotes = response.json::<CryptoQuotesResponse>().await?;
+          Ok(quotes)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_latest_bars(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> anyhow::Result<CryptoLatestBarsResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/latest/bars", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let bars = response.json::<CryptoLatestBarsResponse>().await?;
+          Ok(bars)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_latest_quotes(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> anyhow::Result<CryptoLatestQuotesResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/latest/quotes", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let quotes = response.json::<CryptoLatestQuotesResponse>().await?;
+          Ok(quotes)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_latest_trades(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> anyhow::Result<CryptoLatestTradesResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/latest/trades", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let trades = response.json::<CryptoLatestTradesResponse>().await?;
+          Ok(trades)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_latest_orderbooks(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> anyhow::Result<CryptoLatestOrderbooksResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/latest/orderbooks", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let orderbooks = response.json::<CryptoLatestOrderbooksResponse>().await?;
+          Ok(orderbooks)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_crypto_snapshots(
+    &self,
+    location: CryptoLocation,
+    query_parameter: &CryptoSymbolsQueryParameter,
+  ) -> anyhow::Result<CryptoSnapshotsResponse> {
+    let url = format!("{}/v1beta3/crypto/{}/snapshots", self.data_url, location);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let snapshots = response.json::<CryptoSnapshotsResponse>().await?;
+          Ok(snapshots)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CryptoBarsQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  pub timeframe: TimeFrame,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CryptoHistoricalQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CryptoSymbolsQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoBarsResponse {
+  pub bars: HashMap<String, Vec<Bar>>,
+  pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoTradesResponse {
+  pub trades: HashMap<String, Vec<CryptoTrade>>,
+  pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoQuotesResponse {
+  pub quotes: HashMap<String, Vec<CryptoQuote>>,
+  pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoLatestBarsResponse {
+  pub bars: HashMap<String, Bar>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoLatestQuotesResponse {
+  pub quotes: HashMap<String, CryptoQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoLatestTradesResponse {
+  pub trades: HashMap<String, CryptoTrade>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoLatestOrderbooksResponse {
+  pub orderbooks: HashMap<String, CryptoOrderbook>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CryptoSnapshotsResponse {
+  pub snapshots: HashMap<String, CryptoSnapshot>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::api::{
+    CryptoBarsQueryParameter,
+    Sort,
+    TimeFrame,
+  };
+  use chrono::{
+    TimeZone,
+    Utc,
+  };
+
+  #[test]
+  fn test_crypto_bars_query_parameter_serialization() {
+    let parameter = CryptoBarsQueryParameter {
+      symbols: vec!["BTC/USD".to_string(), "ETH/USD".to_string()],
+      timeframe: TimeFrame::Minute(15),
+      start: Some(Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap()),
+      end: None,
+      limit: Some(1000),
+      page_token: None,
+      sort: Some(Sort::Asc),
+    };
+
+    let json = serde_json::to_string(&parameter).unwrap();
+    let expected =
+      r#"{"symbols":"BTC/USD,ETH/USD","timeframe":"15Min","start":"2025-01-03T00:00:00Z","limit":1000,"sort":"asc"}"#;
+    assert_eq!(json, expected)
+  }
+}
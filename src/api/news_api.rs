@@ -0,0 +1,101 @@
+use crate::{
+  api::utils::{
+    Sort,
+    serialize_vec_to_coma_separated_str,
+  },
+  client::Client,
+  models::{
+    ErrorResponse,
+    NewsArticle,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+
+pub trait NewsApi {
+  fn get_news(&self, query_parameter: &NewsQueryParameter) -> impl Future<Output = anyhow::Result<NewsResponse>>;
+}
+
+impl NewsApi for Client {
+  async fn get_news(&self, query_parameter: &NewsQueryParameter) -> anyhow::Result<NewsResponse> {
+    let url = format!("{}/v1beta1/news", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let news = response.json::<NewsResponse>().await?;
+          Ok(news)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct NewsQueryParameter {
+  #[serde(
+    skip_serializing_if = "Vec::is_empty",
+    serialize_with = "serialize_vec_to_coma_separated_str"
+  )]
+  pub symbols: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub include_content: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub exclude_contentless: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u16>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewsResponse {
+  pub news: Vec<NewsArticle>,
+  pub next_page_token: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::api::{
+    NewsQueryParameter,
+    Sort,
+  };
+  use chrono::{
+    TimeZone,
+    Utc,
+  };
+
+  #[test]
+  fn test_news_query_parameter_serialization() {
+    let parameter = NewsQueryParameter {
+      symbols: vec!["AAPL".to_string(), "TSLA".to_string()],
+      start: Some(Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap()),
+      sort: Some(Sort::Desc),
+      include_content: Some(true),
+      exclude_contentless: Some(true),
+      limit: Some(50),
+      ..Default::default()
+    };
+
+    let json = serde_json::to_string(&parameter).unwrap();
+    let expected = r#"{"symbols":"AAPL,TSLA","start":"2025-11-01T00:00:00Z","sort":"desc","include_content":true,"exclude_contentless":true,"limit":50}"#;
+    assert_eq!(json, expected)
+  }
+}
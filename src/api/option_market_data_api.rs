@@ -0,0 +1,315 @@
+use crate::{
+  api::utils::{
+    Sort,
+    TimeFrame,
+    serialize_vec_to_coma_separated_str,
+  },
+  client::Client,
+  models::{
+    Bar,
+    ErrorResponse,
+    OptionFeed,
+    OptionQuote,
+    OptionSnapshot,
+    OptionTrade,
+    OptionType,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  NaiveDate,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::collections::HashMap;
+
+pub trait OptionMarketDataApi {
+  fn get_option_bars(
+    &self,
+    query_parameter: &OptionBarsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionBarsResponse>>;
+
+  fn get_option_trades(
+    &self,
+    query_parameter: &OptionTradesQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionTradesResponse>>;
+
+  fn get_option_latest_quotes(
+    &self,
+    query_parameter: &OptionLatestQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionLatestQuotesResponse>>;
+
+  fn get_option_latest_trades(
+    &self,
+    query_parameter: &OptionLatestQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionLatestTradesResponse>>;
+
+  fn get_option_snapshots(
+    &self,
+    query_parameter: &OptionSnapshotsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionSnapshotsResponse>>;
+
+  fn get_option_chain(
+    &self,
+    underlying_symbol: &str,
+    query_parameter: &OptionChainQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<OptionSnapshotsResponse>>;
+}
+
+impl OptionMarketDataApi for Client {
+  async fn get_option_bars(&self, query_parameter: &OptionBarsQueryParameter) -> anyhow::Result<OptionBarsResponse> {
+    let url = format!("{}/v1beta1/options/bars", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let bars = response.json::<OptionBarsResponse>().await?;
+          Ok(bars)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_option_trades(
+    &self,
+    query_parameter: &OptionTradesQueryParameter,
+  ) -> anyhow::Result<OptionTradesResponse> {
+    let url = format!("{}/v1beta1/options/trades", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let trades = response.json::<OptionTradesResponse>().await?;
+          Ok(trades)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_option_latest_quotes(
+    &self,
+    query_parameter: &OptionLatestQueryParameter,
+  ) -> anyhow::Result<OptionLatestQuotesResponse> {
+    let url = format!("{}/v1beta1/options/quotes/latest", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let quotes = response.json::<OptionLatestQuotesResponse>().await?;
+          Ok(quotes)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_option_latest_trades(
+    &self,
+    query_parameter: &OptionLatestQueryParameter,
+  ) -> anyhow::Result<OptionLatestTradesResponse> {
+    let url = format!("{}/v1beta1/options/trades/latest", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let trades = response.json::<OptionLatestTradesResponse>().await?;
+          Ok(trades)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_option_snapshots(
+    &self,
+    query_parameter: &OptionSnapshotsQueryParameter,
+  ) -> anyhow::Result<OptionSnapshotsResponse> {
+    let url = format!("{}/v1beta1/options/snapshots", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let snapshots = response.json::<OptionSnapshotsResponse>().await?;
+          Ok(snapshots)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_option_chain(
+    &self,
+    underlying_symbol: &str,
+    query_parameter: &OptionChainQueryParameter,
+  ) -> anyhow::Result<OptionSnapshotsResponse> {
+    let url = format!("{}/v1beta1/options/snapshots/{}", self.data_url, underlying_symbol);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let snapshots = response.json::<OptionSnapshotsResponse>().await?;
+          Ok(snapshots)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptionBarsQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  pub timeframe: TimeFrame,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptionTradesQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptionLatestQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub feed: Option<OptionFeed>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptionSnapshotsQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub feed: Option<OptionFeed>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct OptionChainQueryParameter {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub feed: Option<OptionFeed>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+  pub _type: Option<OptionType>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub strike_price_gte: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub strike_price_lte: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub expiration_date: Option<NaiveDate>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub expiration_date_gte: Option<NaiveDate>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub expiration_date_lte: Option<NaiveDate>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub root_symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionBarsResponse {
+  pub bars: HashMap<String, Vec<Bar>>,
+  pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionTradesResponse {
+  pub trades: HashMap<String, Vec<OptionTrade>>,
+  pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionLatestQuotesResponse {
+  pub quotes: HashMap<String, OptionQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionLatestTradesResponse {
+  pub trades: HashMap<String, OptionTrade>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptionSnapshotsResponse {
+  pub snapshots: HashMap<String, OptionSnapshot>,
+  pub next_page_token: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    api::OptionChainQueryParameter,
+    models::{
+      OptionFeed,
+      OptionType,
+    },
+  };
+  use chrono::NaiveDate;
+
+  #[test]
+  fn test_option_chain_query_parameter_serialization() {
+    let parameter = OptionChainQueryParameter {
+      feed: Some(OptionFeed::Indicative),
+      limit: Some(100),
+      _type: Some(OptionType::Put),
+      strike_price_gte: Some(180.0),
+      expiration_date_lte: Some(NaiveDate::from_ymd_opt(2025, 12, 19).unwrap()),
+      ..Default::default()
+    };
+
+    let json = serde_json::to_string(&parameter).unwrap();
+    let expected =
+      r#"{"feed":"indicative","limit":100,"type":"put","strike_price_gte":180.0,"expiration_date_lte":"2025-12-19"}"#;
+    assert_eq!(json, expected)
+  }
+}
@@ -165,7 +165,7 @@ impl OrderApi for Client {
   }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OrderRequestBody {
   pub symbol: String,
   #[serde(skip_serializing_if = "Option::is_none")]
@@ -187,14 +187,14 @@ pub struct OrderRequestBody {
   pub extended_hours: bool,
   pub client_order_id: Option<String>,
   pub order_class: Option<OrderClass>,
-  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub legs: Vec<Leg>,
   pub take_profit: Option<TakeProfit>,
   pub stop_loss: Option<StopLoss>,
   pub position_intent: Option<PositionIntent>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Leg {
   pub side: Side,
   pub position_intent: PositionIntent,
@@ -202,15 +202,16 @@ pub struct Leg {
   pub ratio_qty: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TakeProfit {
   pub limit_price: Money,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StopLoss {
   pub stop_price: Money,
-  pub limit_price: Money,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit_price: Option<Money>,
 }
 
 #[derive(Debug, Serialize)]
@@ -239,7 +240,7 @@ pub struct AllOrdersQueryParameter {
   pub after_order_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DeleteAllOrdersResponse {
   pub id: Uuid,
   pub status: u16,
@@ -250,17 +251,22 @@ pub struct GetOrderByClientIdParameter<'a> {
   client_order_id: &'a str,
 }
 
-#[derive(Debug, Serialize)]
+///
+///Prices left out keep their current value, send only the ones that apply to the order type
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ReplaceOrderByIdRequestBody {
   pub qty: NumberAsString,
   pub time_in_force: TimeInForce,
-  pub limit_price: Money,
-  pub stop_price: Money,
-  pub trail: Money,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit_price: Option<Money>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stop_price: Option<Money>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub trail: Option<Money>,
   pub client_order_id: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
   Open,
@@ -331,7 +337,7 @@ mod tests {
       }),
       stop_loss: Some(StopLoss {
         stop_price: Money::from_f64(20.43),
-        limit_price: Money::from_f64(23.23),
+        limit_price: Some(Money::from_f64(23.23)),
       }),
       position_intent: Some(PositionIntent::BuyToClose),
     };
@@ -376,9 +382,9 @@ mod tests {
     let body = ReplaceOrderByIdRequestBody {
       qty: NumberAsString::from_f64(4.0),
       time_in_force: TimeInForce::DAY,
-      limit_price: Money::from_f64(100.0),
-      stop_price: Money::from_f64(90.0),
-      trail: Money::from_f64(10.0),
+      limit_price: Some(Money::from_f64(100.0)),
+      stop_price: Some(Money::from_f64(90.0)),
+      trail: Some(Money::from_f64(10.0)),
       client_order_id: String::from("test_client_order_id"),
     };
 
@@ -387,4 +393,21 @@ mod tests {
 
     assert_eq!(serialized, expected)
   }
+
+  #[test]
+  fn replace_order_by_id_request_body_should_skip_missing_prices() {
+    let body = ReplaceOrderByIdRequestBody {
+      qty: NumberAsString::from_f64(4.0),
+      time_in_force: TimeInForce::DAY,
+      limit_price: Some(Money::from_f64(100.0)),
+      stop_price: None,
+      trail: None,
+      client_order_id: String::from("test_client_order_id"),
+    };
+
+    let serialized = serde_json::to_string(&body).unwrap();
+    let expected = r#"{"qty":"4","time_in_force":"day","limit_price":"100","client_order_id":"test_client_order_id"}"#;
+
+    assert_eq!(serialized, expected)
+  }
 }
@@ -1,6 +1,7 @@
 use crate::{
   client::Client,
   models::{
+    ActivityType,
     ErrorResponse,
     PortfolioHistory,
   },
@@ -75,6 +76,7 @@ pub enum CashflowTypes {
   All,
   None,
   ComaSeparatedString(String),
+  Activities(Vec<ActivityType>),
 }
 
 #[derive(Debug, Serialize)]
@@ -142,9 +144,14 @@ where
   match cashflow_types {
     Some(types) => {
       let s = match types {
-        CashflowTypes::None => "NONE",
-        CashflowTypes::All => "ALL",
-        CashflowTypes::ComaSeparatedString(str) => str,
+        CashflowTypes::None => "NONE".to_string(),
+        CashflowTypes::All => "ALL".to_string(),
+        CashflowTypes::ComaSeparatedString(str) => str.clone(),
+        CashflowTypes::Activities(activity_types) => activity_types
+          .iter()
+          .map(ActivityType::as_str)
+          .collect::<Vec<_>>()
+          .join(","),
       };
       serializer.serialize_str(&s)
     }
@@ -180,4 +187,26 @@ mod tests {
 
     assert_eq!(serialized, expected)
   }
+
+  #[test]
+  fn test_cashflow_types_should_join_activity_types() {
+    let query_params = PortfolioHistoryQueryParameter {
+      period: None,
+      timeframe: None,
+      intraday_reporting: None,
+      start: None,
+      pnl_reset: None,
+      end: None,
+      extended_hours: None,
+      cashflow_types: Some(CashflowTypes::Activities(vec![
+        ActivityType::Csd,
+        ActivityType::Csw,
+        ActivityType::Div,
+      ])),
+    };
+
+    let serialized = serde_json::to_string(&query_params).unwrap();
+
+    assert_eq!(serialized, r#"{"cashflow_types":"CSD,CSW,DIV"}"#)
+  }
 }
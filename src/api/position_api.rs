@@ -127,11 +127,31 @@ impl PositionApi for Client {
   }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ClosePositionInfo {
   pub symbol: String,
   pub status: String,
-  pub body: Order,
+  pub body: ClosePositionBody,
+}
+
+impl ClosePositionInfo {
+  ///
+  ///The closing order, `None` when the position could not be closed
+  pub fn order(&self) -> Option<&Order> {
+    match &self.body {
+      ClosePositionBody::Order(order) => Some(order),
+      ClosePositionBody::Error(_) => None,
+    }
+  }
+}
+
+///
+///The closing order of a position, or the error the API answered for that symbol
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ClosePositionBody {
+  Order(Box<Order>),
+  Error(ErrorResponse),
 }
 
 #[derive(Debug)]
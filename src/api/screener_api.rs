@@ -0,0 +1,149 @@
+use crate::{
+  api::{
+    AssetsApi,
+    WatchListApi,
+    WatchListReqBody,
+  },
+  client::Client,
+  models::{
+    Asset,
+    ErrorResponse,
+    MarketMovers,
+    MarketType,
+    MostActives,
+    ScreenerResult,
+    WatchList,
+  },
+};
+use anyhow::bail;
+use serde::Serialize;
+
+pub trait ScreenerApi {
+  fn get_most_actives(
+    &self,
+    query_parameter: &MostActivesQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<MostActives>>;
+
+  fn get_market_movers(
+    &self,
+    market_type: MarketType,
+    query_parameter: &MarketMoversQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<MarketMovers>>;
+}
+
+impl ScreenerApi for Client {
+  async fn get_most_actives(&self, query_parameter: &MostActivesQueryParameter) -> anyhow::Result<MostActives> {
+    let url = format!("{}/v1beta1/screener/stocks/most-actives", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let most_actives = response.json::<MostActives>().await?;
+          Ok(most_actives)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+
+  async fn get_market_movers(
+    &self,
+    market_type: MarketType,
+    query_parameter: &MarketMoversQueryParameter,
+  ) -> anyhow::Result<MarketMovers> {
+    let market_type = match market_type {
+      MarketType::Stocks => "stocks",
+      MarketType::Crypto => "crypto",
+    };
+    let url = format!("{}/v1beta1/screener/{}/movers", self.data_url, market_type);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let movers = response.json::<MarketMovers>().await?;
+          Ok(movers)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+///
+///Look up the full `Asset` of every screener result, in order
+pub async fn resolve_screener_assets<C, R>(client: &C, results: &[R]) -> anyhow::Result<Vec<Asset>>
+where
+  C: AssetsApi,
+  R: ScreenerResult,
+{
+  let mut assets = Vec::with_capacity(results.len());
+  for result in results {
+    assets.push(client.get_asset_by_symbol_or_id(result.symbol()).await?);
+  }
+  Ok(assets)
+}
+
+///
+///Replace the symbols of the watchlist called `name` with the screener results, creating it if
+/// needed
+pub async fn sync_screener_watch_list<C, R>(client: &C, name: &str, results: &[R]) -> anyhow::Result<WatchList>
+where
+  C: WatchListApi,
+  R: ScreenerResult,
+{
+  let request_body = WatchListReqBody {
+    name: name.to_string(),
+    symbols: results.iter().map(|result| result.symbol().to_string()).collect(),
+  };
+  let existing = client.get_all_watch_lists().await?;
+  match existing.iter().find(|watch_list| watch_list.name == name) {
+    Some(watch_list) => client.update_watch_list_by_id(&watch_list.id, &request_body).await,
+    None => client.create_watch_list(&request_body).await,
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MostActivesBy {
+  Volume,
+  Trades,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MostActivesQueryParameter {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub by: Option<MostActivesBy>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub top: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarketMoversQueryParameter {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub top: Option<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::api::{
+    MostActivesBy,
+    MostActivesQueryParameter,
+  };
+
+  #[test]
+  fn test_most_actives_query_parameter_serialization() {
+    let parameter = MostActivesQueryParameter {
+      by: Some(MostActivesBy::Trades),
+      top: Some(20),
+    };
+
+    let json = serde_json::to_string(&parameter).unwrap();
+    assert_eq!(json, r#"{"by":"trades","top":20}"#)
+  }
+}
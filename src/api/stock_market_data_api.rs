@@ -0,0 +1,118 @@
+use crate::{
+  api::utils::{
+    Sort,
+    TimeFrame,
+    serialize_vec_to_coma_separated_str,
+  },
+  client::Client,
+  models::{
+    Adjustment,
+    Bar,
+    ErrorResponse,
+    StockFeed,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  NaiveDate,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::collections::HashMap;
+
+pub trait StockMarketDataApi {
+  fn get_stock_bars(
+    &self,
+    query_parameter: &StockBarsQueryParameter,
+  ) -> impl Future<Output = anyhow::Result<StockBarsResponse>>;
+}
+
+impl StockMarketDataApi for Client {
+  async fn get_stock_bars(&self, query_parameter: &StockBarsQueryParameter) -> anyhow::Result<StockBarsResponse> {
+    let url = format!("{}/v2/stocks/bars", self.data_url);
+    match self.client.get(url).query(query_parameter).send().await {
+      Ok(response) => {
+        if response.status().is_success() {
+          let bars = response.json::<StockBarsResponse>().await?;
+          Ok(bars)
+        } else {
+          let statuscode = response.status().as_u16();
+          let message = response.text().await?;
+          bail!(ErrorResponse::new(statuscode as u32, message))
+        }
+      }
+      Err(error) => bail!(error),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockBarsQueryParameter {
+  #[serde(serialize_with = "serialize_vec_to_coma_separated_str")]
+  pub symbols: Vec<String>,
+  pub timeframe: TimeFrame,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub start: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub end: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub adjustment: Option<Adjustment>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asof: Option<NaiveDate>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub feed: Option<StockFeed>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StockBarsResponse {
+  pub bars: HashMap<String, Vec<Bar>>,
+  pub next_page_token: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    api::{
+      StockBarsQueryParameter,
+      TimeFrame,
+    },
+    models::{
+      Adjustment,
+      StockFeed,
+    },
+  };
+  use chrono::{
+    TimeZone,
+    Utc,
+  };
+
+  #[test]
+  fn test_stock_bars_query_parameter_serialization() {
+    let parameter = StockBarsQueryParameter {
+      symbols: vec!["AAPL".to_string(), "MSFT".to_string()],
+      timeframe: TimeFrame::Day(1),
+      start: Some(Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap()),
+      end: None,
+      limit: Some(10000),
+      adjustment: Some(Adjustment::Split),
+      asof: None,
+      feed: Some(StockFeed::Sip),
+      page_token: None,
+      sort: None,
+    };
+
+    let json = serde_json::to_string(&parameter).unwrap();
+    let expected = r#"{"symbols":"AAPL,MSFT","timeframe":"1Day","start":"2025-01-03T00:00:00Z","limit":10000,"adjustment":"split","feed":"sip"}"#;
+    assert_eq!(json, expected)
+  }
+}
@@ -47,3 +47,42 @@ where
   let s = format!("{}", date.format(FORMAT));
   serializer.serialize_str(&s)
 }
+
+pub fn serialize_vec_to_coma_separated_str<S>(values: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  serializer.serialize_str(&values.join(","))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+  Asc,
+  Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFrame {
+  Minute(u32),
+  Hour(u32),
+  Day(u32),
+  Week(u32),
+  Month(u32),
+}
+
+impl Serialize for TimeFrame {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    let s = match self {
+      TimeFrame::Minute(n) => format!("{n}Min"),
+      TimeFrame::Hour(n) => format!("{n}Hour"),
+      TimeFrame::Day(n) => format!("{n}Day"),
+      TimeFrame::Week(n) => format!("{n}Week"),
+      TimeFrame::Month(n) => format!("{n}Month"),
+    };
+    serializer.serialize_str(&s)
+  }
+}
@@ -263,7 +263,7 @@ impl WatchListApi for Client {
   }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BasicWatchListInfo {
   pub id: Uuid,
   pub account_id: Uuid,
@@ -272,13 +272,13 @@ pub struct BasicWatchListInfo {
   pub name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct WatchListReqBody {
   pub name: String,
   pub symbols: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AddAssetReqBody {
   pub symbol: String,
 }
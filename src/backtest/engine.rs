@@ -0,0 +1,458 @@
+use crate::{
+  api::{
+    AccountApi,
+    AllOrdersQueryParameter,
+    ClockApi,
+    ClosePositionInfo,
+    ClosePositionParam,
+    DeleteAllOrdersResponse,
+    OrderApi,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    OrdersDirection,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+    TimeFrame,
+  },
+  history::{
+    BarStore,
+    market_time,
+    trading_date,
+  },
+  models::{
+    Account,
+    Bar,
+    ClosedPosition,
+    CorporateAction,
+    MarketCalendar,
+    MarketClock,
+    Order,
+    PortfolioHistory,
+    Position,
+    utils::Money,
+  },
+  sim::{
+    Broker,
+    FeeModel,
+    FillModel,
+    SimError,
+    close_position_info,
+    closed_position,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Local,
+  NaiveDate,
+  Utc,
+};
+use std::{
+  collections::BTreeMap,
+  sync::{
+    Mutex,
+    MutexGuard,
+  },
+};
+use uuid::Uuid;
+
+///
+///Replays stored bars through a simulated [`Broker`], exposing it through the trading API traits
+///
+///Strategy code written against [`OrderApi`], [`PositionApi`], [`AccountApi`] and [`ClockApi`]
+/// runs unchanged: every [`Backtest::step`] advances the clock to the next bar timestamp and
+/// executes the working orders against the bars of that timestamp. Orders submitted after a step
+/// execute from the next bar on, market orders at its open.
+///
+///Corporate actions are applied to positions and open orders, so they should be paired with raw
+/// (unadjusted) bars: splits on their effective date, cash dividends entitled on the effective date
+/// and paid on the payable date.
+#[derive(Debug)]
+pub struct Backtest {
+  broker: Mutex<Broker>,
+  timeframe: TimeFrame,
+  initial_cash: f64,
+  bars: BTreeMap<String, Vec<Bar>>,
+  sessions: BTreeMap<NaiveDate, (DateTime<Utc>, DateTime<Utc>)>,
+  corporate_actions: Vec<CorporateAction>,
+  timeline: Vec<DateTime<Utc>>,
+  cursor: usize,
+  bar_cursors: BTreeMap<String, usize>,
+  current_date: Option<NaiveDate>,
+  pending_dividends: Vec<(NaiveDate, f64)>,
+  equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl Backtest {
+  ///
+  ///`timeframe` is the spacing of the bars, reported as the timeframe of the equity curve
+  pub fn new(cash: f64, timeframe: TimeFrame) -> Self {
+    Backtest {
+      broker: Mutex::new(Broker::new(cash).with_immediate_fills(false)),
+      timeframe,
+      initial_cash: cash,
+      bars: BTreeMap::new(),
+      sessions: BTreeMap::new(),
+      corporate_actions: vec![],
+      timeline: vec![],
+      cursor: 0,
+      bar_cursors: BTreeMap::new(),
+      current_date: None,
+      pending_dividends: vec![],
+      equity_curve: vec![],
+    }
+  }
+
+  ///
+  ///How orders execute against the bars, [`crate::sim::NextBarOpen`] by default
+  pub fn with_fill_model(self, fill_model: impl FillModel + 'static) -> Self {
+    self.map_broker(|broker| broker.with_fill_model(fill_model))
+  }
+
+  ///
+  ///Commissions and fees charged on every fill, none by default
+  pub fn with_fee_model(self, fee_model: impl FeeModel + 'static) -> Self {
+    self.map_broker(|broker| broker.with_fee_model(fee_model))
+  }
+
+  pub fn with_bars(mut self, symbol: &str, mut bars: Vec<Bar>) -> Self {
+    bars.sort_by_key(|bar| bar.timestamp);
+    self.timeline.extend(bars.iter().map(|bar| bar.timestamp));
+    self.timeline.sort();
+    self.timeline.dedup();
+    self.bars.insert(symbol.to_string(), bars);
+    self.bar_cursors.insert(symbol.to_string(), 0);
+    self
+  }
+
+  ///
+  ///Load the bars of `symbols` for the trading days between `start` and `end` from a local store
+  pub fn with_store_bars(
+    mut self,
+    store: &impl BarStore,
+    symbols: &[&str],
+    start: NaiveDate,
+    end: NaiveDate,
+  ) -> anyhow::Result<Self> {
+    for symbol in symbols {
+      self = self.with_bars(symbol, store.read_bars(symbol, start, end)?);
+    }
+    Ok(self)
+  }
+
+  ///
+  ///Regular sessions of the replayed days, without them every bar counts as regular hours
+  pub fn with_sessions(mut self, calendar: &[MarketCalendar]) -> Self {
+    self.sessions = calendar
+      .iter()
+      .filter_map(|session| {
+        let open = market_time(session.date, session.open)?;
+        let close = market_time(session.date, session.close)?;
+        Some((session.date, (open, close)))
+      })
+      .collect();
+    self
+  }
+
+  ///
+  ///Splits and cash dividends to apply, other action types are ignored
+  pub fn with_corporate_actions(mut self, corporate_actions: Vec<CorporateAction>) -> Self {
+    self.corporate_actions = corporate_actions;
+    self
+  }
+
+  ///
+  ///Timestamp of the last replayed bar, `None` before the first step
+  pub fn now(&self) -> Option<DateTime<Utc>> {
+    self.cursor.checked_sub(1).map(|index| self.timeline[index])
+  }
+
+  ///
+  ///Bars of `symbol` replayed so far
+  pub fn bars(&self, symbol: &str) -> &[Bar] {
+    match (self.bars.get(symbol), self.bar_cursors.get(symbol)) {
+      (Some(bars), Some(cursor)) => &bars[..*cursor],
+      _ => &[],
+    }
+  }
+
+  pub fn latest_bar(&self, symbol: &str) -> Option<&Bar> {
+    self.bars(symbol).last()
+  }
+
+  ///
+  ///Run `f` with exclusive access to the simulated broker
+  pub fn with_broker<R>(&self, f: impl FnOnce(&mut Broker) -> R) -> R {
+    f(&mut self.lock())
+  }
+
+  ///
+  ///Replay the bars of the next timestamp, returns that timestamp or `None` once every bar is
+  /// replayed
+  pub fn step(&mut self) -> Option<DateTime<Utc>> {
+    let now = *self.timeline.get(self.cursor)?;
+    let date = trading_date(now);
+    if self.current_date != Some(date) {
+      self.start_day(date);
+    }
+    let regular_hours = self.is_regular_hours(now);
+    let broker = self.broker.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+    broker.set_time(now);
+    for (symbol, bars) in &self.bars {
+      let cursor = self.bar_cursors.get_mut(symbol).unwrap();
+      if let Some(bar) = bars.get(*cursor)
+        && bar.timestamp == now
+      {
+        broker.process_bar(symbol, bar, regular_hours);
+        *cursor += 1;
+      }
+    }
+    self.equity_curve.push((now, broker.equity()));
+    self.current_date = Some(date);
+    self.cursor += 1;
+    Some(now)
+  }
+
+  ///
+  ///Step through every remaining bar
+  pub fn run_to_end(&mut self) {
+    while self.step().is_some() {}
+  }
+
+  ///
+  ///Equity after every step, in the shape of `GET /v2/account/portfolio/history`
+  pub fn portfolio_history(&self) -> PortfolioHistory {
+    let equity: Vec<f64> = self.equity_curve.iter().map(|(_, equity)| *equity).collect();
+    PortfolioHistory {
+      timestamp: self
+        .equity_curve
+        .iter()
+        .map(|(timestamp, _)| timestamp.timestamp() as u64)
+        .collect(),
+      profit_loss: equity.iter().map(|equity| equity - self.initial_cash).collect(),
+      profit_loss_pct: equity
+        .iter()
+        .map(|equity| (equity - self.initial_cash) / self.initial_cash)
+        .collect(),
+      equity,
+      base_value: Money::from_f64(self.initial_cash),
+      base_value_asof: match self.timeline.first() {
+        Some(timestamp) => trading_date(*timestamp),
+        None => Utc::now().date_naive(),
+      },
+      timeframe: match self.timeframe {
+        TimeFrame::Minute(n) => format!("{n}Min"),
+        TimeFrame::Hour(n) => format!("{n}H"),
+        TimeFrame::Day(n) => format!("{n}D"),
+        TimeFrame::Week(n) => format!("{n}W"),
+        TimeFrame::Month(n) => format!("{n}M"),
+      },
+      cashflow: None,
+    }
+  }
+
+  ///
+  ///Close the previous trading day and open `date`: expire DAY orders, apply corporate actions,
+  /// pay dividends
+  fn start_day(&mut self, date: NaiveDate) {
+    let previous = self.current_date;
+    let last_timestamp = self.now();
+    let broker = self.broker.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // orders submitted after the last bar of a day belong to the next session
+    if let Some(last_timestamp) = last_timestamp {
+      broker.expire_day_orders(last_timestamp);
+    }
+    broker.roll_day();
+
+    if let Some(previous) = previous {
+      for action in &self.corporate_actions {
+        let Some(effective_date) = action.effective_date else {
+          continue;
+        };
+        if effective_date <= previous || effective_date > date {
+          continue;
+        }
+        let symbol = &action.initiating_symbol;
+        match (action.ca_type.as_str(), action.ca_sub_type.as_str()) {
+          ("split", _) | (_, "stock_split" | "reverse_split" | "unit_split") => {
+            if let (Ok(new_rate), Ok(old_rate)) = (action.new_rate.parse::<f64>(), action.old_rate.parse::<f64>())
+              && new_rate > 0.0
+              && old_rate > 0.0
+            {
+              broker.apply_split(symbol, new_rate / old_rate);
+            }
+          }
+          ("dividend", "cash") => {
+            if let Ok(rate) = action.cash.parse::<f64>() {
+              let amount = broker.position_qty(symbol) * rate;
+              if amount != 0.0 {
+                self
+                  .pending_dividends
+                  .push((action.payable_date.unwrap_or(effective_date), amount));
+              }
+            }
+          }
+          _ => {}
+        }
+      }
+    }
+
+    let (payable, pending): (Vec<_>, Vec<_>) = self
+      .pending_dividends
+      .drain(..)
+      .partition(|(payable_date, _)| *payable_date <= date);
+    self.pending_dividends = pending;
+    for (_, amount) in payable {
+      broker.credit_cash(amount);
+    }
+  }
+
+  fn is_regular_hours(&self, timestamp: DateTime<Utc>) -> bool {
+    if self.sessions.is_empty() {
+      return true;
+    }
+    match self.sessions.get(&trading_date(timestamp)) {
+      Some((open, close)) => timestamp >= *open && timestamp < *close,
+      None => false,
+    }
+  }
+
+  fn map_broker(mut self, f: impl FnOnce(Broker) -> Broker) -> Self {
+    let broker = self.broker.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *broker = f(broker.clone());
+    self
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Broker> {
+    self.broker.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+fn api_result<T>(result: Result<T, SimError>) -> anyhow::Result<T> {
+  match result {
+    Ok(value) => Ok(value),
+    Err(error) => bail!(error.to_error_response()),
+  }
+}
+
+impl AccountApi for Backtest {
+  async fn get_account(&self) -> anyhow::Result<Account> {
+    Ok(self.lock().account())
+  }
+}
+
+impl ClockApi for Backtest {
+  async fn get_market_clock_info(&self) -> anyhow::Result<MarketClock> {
+    let now = self.now().or(self.timeline.first().copied()).unwrap_or_else(Utc::now);
+    let (next_open, next_close) = if self.sessions.is_empty() {
+      // without a calendar the replayed data is one continuous session
+      let last = self.timeline.last().copied().unwrap_or(now);
+      (now, last)
+    } else {
+      let next_open = self.sessions.values().map(|(open, _)| *open).find(|open| *open > now);
+      let next_close = self
+        .sessions
+        .values()
+        .map(|(_, close)| *close)
+        .find(|close| *close > now);
+      (next_open.unwrap_or(now), next_close.unwrap_or(now))
+    };
+    Ok(MarketClock {
+      timestamp: now.with_timezone(&Local),
+      is_open: self.is_regular_hours(now),
+      next_open: next_open.with_timezone(&Local),
+      next_close: next_close.with_timezone(&Local),
+    })
+  }
+}
+
+impl OrderApi for Backtest {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    api_result(self.lock().submit_order(order))
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    let status = match query_parameter.status {
+      Some(OrdersFilter::Closed) => OrdersFilter::Closed,
+      Some(OrdersFilter::All) => OrdersFilter::All,
+      _ => OrdersFilter::Open,
+    };
+    let symbols: Vec<String> = match &query_parameter.symbols {
+      Some(symbols) => symbols.values.iter().map(|symbol| symbol.to_string()).collect(),
+      None => vec![],
+    };
+    let limit = query_parameter.limit.unwrap_or(50) as usize;
+    let mut orders = self
+      .lock()
+      .orders(&status, &symbols, query_parameter.nested.unwrap_or(false), limit);
+    if let Some(OrdersDirection::Asc) = query_parameter.direction {
+      orders.reverse();
+    }
+    Ok(orders)
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    Ok(
+      self
+        .lock()
+        .cancel_all_orders()
+        .into_iter()
+        .map(|id| DeleteAllOrdersResponse { id, status: 200 })
+        .collect(),
+    )
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    api_result(self.lock().order_by_client_order_id(client_order_id))
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    api_result(self.lock().order(id))
+  }
+
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    api_result(self.lock().replace_order(order_id, order))
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    api_result(self.lock().cancel_order(order_id))
+  }
+}
+
+impl PositionApi for Backtest {
+  async fn get_all_open_positions(&self) -> anyhow::Result<Vec<Position>> {
+    Ok(self.lock().positions())
+  }
+
+  async fn get_open_position_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Position> {
+    api_result(self.lock().position(symbol_or_id))
+  }
+
+  async fn close_open_position_by_symbol_or_id(
+    &self,
+    symbol_or_id: &str,
+    param: &ClosePositionParam,
+  ) -> anyhow::Result<ClosedPosition> {
+    let (qty, percentage) = match param {
+      ClosePositionParam::Qty(qty) => (Some(*qty), None),
+      ClosePositionParam::Percentage(percentage) => (None, Some(*percentage)),
+    };
+    let order = api_result(self.lock().close_position(symbol_or_id, qty, percentage))?;
+    Ok(closed_position(order))
+  }
+
+  async fn exercise_option_contract_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<()> {
+    bail!("cannot exercise {}, options are not simulated", symbol_or_id)
+  }
+
+  async fn clost_all_open_positions(&self, cancel_orders: bool) -> anyhow::Result<Vec<ClosePositionInfo>> {
+    Ok(
+      self
+        .lock()
+        .close_all_positions(cancel_orders)
+        .into_iter()
+        .map(|(symbol, result)| close_position_info(symbol, result))
+        .collect(),
+    )
+  }
+}
@@ -0,0 +1,39 @@
+use alpaca_trade_api_rust::sim::{
+  Broker,
+  SimServer,
+};
+use anyhow::{
+  Context,
+  bail,
+};
+use tokio::net::TcpListener;
+
+const USAGE: &str = "usage: alpaca-sim [--port <port>] [--cash <amount>] [--price <symbol>=<price>]...";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+  let mut port = 8_080u16;
+  let mut cash = 100_000.0;
+  let mut prices = vec![];
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().context(USAGE);
+    match arg.as_str() {
+      "--port" => port = value()?.parse()?,
+      "--cash" => cash = value()?.parse()?,
+      "--price" => match value()?.split_once('=') {
+        Some((symbol, price)) => prices.push((symbol.to_string(), price.parse::<f64>()?)),
+        None => bail!(USAGE),
+      },
+      _ => bail!(USAGE),
+    }
+  }
+
+  let mut broker = Broker::new(cash);
+  for (symbol, price) in prices {
+    broker.set_price(&symbol, price);
+  }
+  let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+  println!("simulator listening on http://{}", listener.local_addr()?);
+  SimServer::new(broker).serve(listener).await
+}
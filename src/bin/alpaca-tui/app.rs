@@ -0,0 +1,301 @@
+use alpaca_trade_api_rust::prelude::{
+  Account,
+  Order,
+  OrderStatus,
+  Position,
+  TradeUpdate,
+  TradeUpdateEvent,
+  enums::Side,
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use ratatui::crossterm::event::{
+  KeyCode,
+  KeyEvent,
+  KeyModifiers,
+};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+const MAX_FILLS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillRow {
+  pub time: DateTime<Utc>,
+  pub symbol: String,
+  pub side: Side,
+  pub qty: f64,
+  pub price: f64,
+  pub position_qty: Option<f64>,
+  pub partial: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+  Normal,
+  ConfirmCancel(Uuid),
+  ///
+  ///Editing `field=value` changes such as `limit=190 qty=5` for the order
+  Replace {
+    id: Uuid,
+    input: String,
+  },
+}
+
+///
+///Changes to the selected order typed in replace mode, unset fields keep the order's values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaceFields {
+  pub qty: Option<f64>,
+  pub limit_price: Option<f64>,
+  pub stop_price: Option<f64>,
+  pub trail: Option<f64>,
+}
+
+impl ReplaceFields {
+  pub fn parse(input: &str) -> Result<Self, String> {
+    let mut fields = ReplaceFields::default();
+    for change in input.split_whitespace() {
+      let Some((name, value)) = change.split_once('=') else {
+        return Err(format!("expected field=value, got {change}"));
+      };
+      let value: f64 = value.parse().map_err(|_| format!("invalid {name} {value}"))?;
+      match name {
+        "qty" => fields.qty = Some(value),
+        "limit" => fields.limit_price = Some(value),
+        "stop" => fields.stop_price = Some(value),
+        "trail" => fields.trail = Some(value),
+        _ => return Err(format!("unknown field {name}, expected qty, limit, stop or trail")),
+      }
+    }
+    if fields == ReplaceFields::default() {
+      return Err("nothing to replace".to_string());
+    }
+    Ok(fields)
+  }
+}
+
+///
+///Work the event loop has to do against the API
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+  Refresh,
+  Cancel(Uuid),
+  Replace(Uuid, ReplaceFields),
+}
+
+pub struct App {
+  pub title: String,
+  pub live: bool,
+  pub account: Option<Account>,
+  pub positions: Vec<Position>,
+  pub orders: Vec<Order>,
+  pub fills: VecDeque<FillRow>,
+  pub selected: usize,
+  pub mode: Mode,
+  pub status: String,
+  ///
+  ///Account and positions moved since they were loaded
+  pub stale: bool,
+  pub quit: bool,
+}
+
+///
+///Orders that can still execute, and so can be canceled or replaced
+pub fn is_working(order: &Order) -> bool {
+  matches!(
+    order.status,
+    OrderStatus::New
+      | OrderStatus::PartiallyFilled
+      | OrderStatus::Accepted
+      | OrderStatus::PendingNew
+      | OrderStatus::AcceptedForBidding
+      | OrderStatus::PendingCancel
+      | OrderStatus::PendingReplace
+      | OrderStatus::Held
+  )
+}
+
+impl App {
+  pub fn new(title: String, live: bool) -> Self {
+    App {
+      title,
+      live,
+      account: None,
+      positions: vec![],
+      orders: vec![],
+      fills: VecDeque::new(),
+      selected: 0,
+      mode: Mode::Normal,
+      status: "loading".to_string(),
+      stale: true,
+      quit: false,
+    }
+  }
+
+  pub fn set_orders(&mut self, orders: Vec<Order>) {
+    self.orders = orders.into_iter().filter(is_working).collect();
+    self.clamp_selection();
+  }
+
+  ///
+  ///Fold a stream event into the working orders and fills log
+  pub fn apply(&mut self, update: TradeUpdate) {
+    if update.is_fill() {
+      self.stale = true;
+      if let (Some(qty), Some(price)) = (&update.qty, &update.price) {
+        self.fills.push_front(FillRow {
+          time: update.timestamp.unwrap_or_else(Utc::now),
+          symbol: update.order.symbol.clone(),
+          side: update.order.side,
+          qty: qty.value(),
+          price: price.value(),
+          position_qty: update.position_qty.as_ref().map(|qty| qty.value()),
+          partial: update.event == TradeUpdateEvent::PartialFill,
+        });
+        self.fills.truncate(MAX_FILLS);
+      }
+    }
+    let index = self.orders.iter().position(|order| order.id == update.order.id);
+    match (index, is_working(&update.order)) {
+      (Some(index), true) => self.orders[index] = update.order,
+      (Some(index), false) => {
+        self.orders.remove(index);
+      }
+      (None, true) => self.orders.insert(0, update.order),
+      (None, false) => {}
+    }
+    self.clamp_selection();
+  }
+
+  pub fn selected_order(&self) -> Option<&Order> {
+    self.orders.get(self.selected)
+  }
+
+  fn clamp_selection(&mut self) {
+    self.selected = self.selected.min(self.orders.len().saturating_sub(1));
+  }
+
+  pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+      self.quit = true;
+      return None;
+    }
+    match &mut self.mode {
+      Mode::Normal => match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+        KeyCode::Down | KeyCode::Char('j') => {
+          self.selected = (self.selected + 1).min(self.orders.len().saturating_sub(1));
+        }
+        KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+        KeyCode::Char('r') => return Some(Action::Refresh),
+        KeyCode::Char('c') | KeyCode::Delete => {
+          if let Some(order) = self.selected_order() {
+            self.mode = Mode::ConfirmCancel(order.id);
+          }
+        }
+        KeyCode::Char('e') | KeyCode::Enter => {
+          if let Some(order) = self.selected_order() {
+            self.mode = Mode::Replace {
+              id: order.id,
+              input: String::new(),
+            };
+          }
+        }
+        _ => {}
+      },
+      Mode::ConfirmCancel(id) => {
+        let id = *id;
+        self.mode = Mode::Normal;
+        if key.code == KeyCode::Char('y') {
+          return Some(Action::Cancel(id));
+        }
+      }
+      Mode::Replace { id, input } => match key.code {
+        KeyCode::Esc => self.mode = Mode::Normal,
+        KeyCode::Backspace => {
+          input.pop();
+        }
+        KeyCode::Char(character) => input.push(character),
+        KeyCode::Enter => match ReplaceFields::parse(input) {
+          Ok(fields) => {
+            let id = *id;
+            self.mode = Mode::Normal;
+            return Some(Action::Replace(id, fields));
+          }
+          Err(error) => self.status = error,
+        },
+        _ => {}
+      },
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn update(event: &str, id: Uuid, status: &str, fill: Option<(f64, f64)>) -> TradeUpdate {
+    serde_json::from_value(json!({
+      "event": event,
+      "timestamp": "2026-01-05T15:00:00Z",
+      "price": fill.map(|(_, price)| price.to_string()),
+      "qty": fill.map(|(qty, _)| qty.to_string()),
+      "position_qty": fill.map(|(qty, _)| qty.to_string()),
+      "order": {
+        "id": id, "client_order_id": "c", "created_at": null, "updated_at": null, "submitted_at": null,
+        "filled_at": null, "expired_at": null, "canceled_at": null, "failed_at": null, "replaced_at": null,
+        "replaced_by": null, "replaces": null, "asset_id": Uuid::nil(), "symbol": "AAPL",
+        "asset_class": "us_equity", "national": null, "qty": "5", "filled_qty": "0", "filled_avg_price": null,
+        "order_class": "simple", "type": "limit", "side": "buy", "time_in_force": "day",
+        "limit_price": "190", "stop_price": null, "status": status, "extended_hours": false, "legs": null,
+        "trail_price": null, "trail_percent": null, "hwm": null, "position_intent": "buy_to_open"
+      }
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_app_should_track_working_orders_fills_and_keys() {
+    let mut app = App::new("paper".to_string(), false);
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+    app.apply(update("new", first, "new", None));
+    app.apply(update("new", second, "new", None));
+    assert_eq!(app.orders.len(), 2);
+    assert_eq!(app.selected_order().unwrap().id, second);
+
+    app.stale = false;
+    app.apply(update("fill", second, "filled", Some((5.0, 189.5))));
+    assert!(app.stale);
+    assert_eq!(app.orders.len(), 1);
+    assert_eq!(app.fills[0].price, 189.5);
+    assert!(!app.fills[0].partial);
+
+    assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Char('c'))), None);
+    assert_eq!(app.mode, Mode::ConfirmCancel(first));
+    assert_eq!(
+      app.handle_key(KeyEvent::from(KeyCode::Char('y'))),
+      Some(Action::Cancel(first))
+    );
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('e')));
+    for character in "limit=191 qty=2".chars() {
+      app.handle_key(KeyEvent::from(KeyCode::Char(character)));
+    }
+    let replace = ReplaceFields {
+      qty: Some(2.0),
+      limit_price: Some(191.0),
+      ..Default::default()
+    };
+    assert_eq!(
+      app.handle_key(KeyEvent::from(KeyCode::Enter)),
+      Some(Action::Replace(first, replace))
+    );
+    assert!(ReplaceFields::parse("price=1").is_err());
+    assert!(ReplaceFields::parse("").is_err());
+  }
+}
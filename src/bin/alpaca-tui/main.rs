@@ -0,0 +1,209 @@
+mod app;
+mod ui;
+
+use alpaca_trade_api_rust::{
+  api::{
+    AccountApi,
+    AllOrdersQueryParameter,
+    OrderApi,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+  },
+  prelude::{
+    Client,
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  profile::Profile,
+  stream::{
+    StreamConfig,
+    TradeUpdatesStream,
+  },
+};
+use anyhow::{
+  Context,
+  bail,
+};
+use app::{
+  Action,
+  App,
+  ReplaceFields,
+};
+use ratatui::{
+  DefaultTerminal,
+  crossterm::event::{
+    self,
+    Event,
+    KeyEventKind,
+  },
+};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+const USAGE: &str = "usage: alpaca-tui [-p <profile>] [--refresh <seconds>]
+
+Account, positions, working orders and fills of a profile, following the trade updates stream.
+Prices move without trade updates, so account and positions are also reloaded every --refresh
+seconds (30 by default). Run against `alpaca-sim` with a profile whose base_url is the simulator.";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+  let mut profile_name = None;
+  let mut refresh = Duration::from_secs(30);
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().context(USAGE);
+    match arg.as_str() {
+      "-p" | "--profile" => profile_name = Some(value()?),
+      "--refresh" => refresh = Duration::from_secs_f64(value()?.parse()?),
+      "-h" | "--help" => {
+        println!("{USAGE}");
+        return Ok(());
+      }
+      _ => bail!(USAGE),
+    }
+  }
+
+  let profile = Profile::resolve(profile_name.as_deref())?;
+  let client = profile.client();
+  // listen before loading orders so nothing falls between the snapshot and the stream
+  let config = StreamConfig::trade_updates(
+    profile.base_url.clone(),
+    profile.key_id.clone(),
+    profile.secret_key.clone(),
+  );
+  let mut updates = TradeUpdatesStream::connect(config).await?;
+  let mut app = App::new(profile.base_url.clone(), profile.is_live());
+  load_orders(&client, &mut app).await?;
+
+  let mut terminal = ratatui::init();
+  let result = run(&mut terminal, &client, &mut updates, &mut app, refresh).await;
+  ratatui::restore();
+  result
+}
+
+async fn run(
+  terminal: &mut DefaultTerminal,
+  client: &Client,
+  updates: &mut TradeUpdatesStream,
+  app: &mut App,
+  refresh: Duration,
+) -> anyhow::Result<()> {
+  let (event_sender, mut events) = mpsc::unbounded_channel();
+  std::thread::spawn(move || {
+    while let Ok(event) = event::read() {
+      if event_sender.send(event).is_err() {
+        return;
+      }
+    }
+  });
+  let mut ticker = tokio::time::interval(refresh);
+  let mut reconnects = updates.reconnects();
+
+  loop {
+    if updates.reconnects() != reconnects {
+      reconnects = updates.reconnects();
+      if let Err(error) = load_orders(client, app).await {
+        app.status = format!("reloading orders failed: {error}");
+      }
+      app.stale = true;
+    }
+    if app.stale {
+      app.stale = false;
+      app.status = match load_account(client, app).await {
+        Ok(()) => format!("updated {}", chrono::Local::now().format("%H:%M:%S")),
+        Err(error) => format!("refresh failed: {error}"),
+      };
+    }
+    terminal.draw(|frame| ui::draw(frame, app))?;
+    if app.quit {
+      return Ok(());
+    }
+
+    tokio::select! {
+      event = events.recv() => match event {
+        Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+          if let Some(action) = app.handle_key(key) {
+            perform(client, app, action).await;
+          }
+        }
+        Some(_) => {}
+        None => return Ok(()),
+      },
+      update = updates.next() => match update {
+        Some(update) => {
+          app.apply(update);
+          while let Some(update) = updates.try_next() {
+            app.apply(update);
+          }
+        }
+        None => bail!("trade updates stream closed"),
+      },
+      _ = ticker.tick() => app.stale = true,
+    }
+  }
+}
+
+async fn load_orders(client: &Client, app: &mut App) -> anyhow::Result<()> {
+  let query_parameter = AllOrdersQueryParameter {
+    status: Some(OrdersFilter::Open),
+    limit: Some(500),
+    after: None,
+    until: None,
+    direction: None,
+    nested: None,
+    symbols: None,
+    side: None,
+    asset_class: None,
+    before_order_id: None,
+    after_order_id: None,
+  };
+  app.set_orders(client.get_all_orders(&query_parameter).await?);
+  Ok(())
+}
+
+async fn load_account(client: &Client, app: &mut App) -> anyhow::Result<()> {
+  app.account = Some(client.get_account().await?);
+  app.positions = client.get_all_open_positions().await?;
+  Ok(())
+}
+
+async fn perform(client: &Client, app: &mut App, action: Action) {
+  let result = match action {
+    Action::Refresh => {
+      app.stale = true;
+      load_orders(client, app).await.map(|_| "orders reloaded".to_string())
+    }
+    Action::Cancel(id) => client
+      .delete_order_by_id(&id)
+      .await
+      .map(|_| format!("cancel of {id} requested")),
+    Action::Replace(id, fields) => replace(client, app, &id, fields).await,
+  };
+  app.status = result.unwrap_or_else(|error| error.to_string());
+}
+
+async fn replace(client: &Client, app: &App, id: &Uuid, fields: ReplaceFields) -> anyhow::Result<String> {
+  let Some(order) = app.orders.iter().find(|order| order.id == *id) else {
+    bail!("order {id} is no longer working");
+  };
+  let request_body = ReplaceOrderByIdRequestBody {
+    qty: NumberAsString::from_f64(
+      fields
+        .qty
+        .unwrap_or_else(|| order.qty.as_ref().map(NumberAsString::value).unwrap_or_default()),
+    ),
+    time_in_force: order.time_in_force,
+    // prices left out keep their current value
+    limit_price: fields.limit_price.map(Money::from_f64),
+    stop_price: fields.stop_price.map(Money::from_f64),
+    trail: fields.trail.map(Money::from_f64),
+    client_order_id: Uuid::new_v4().to_string(),
+  };
+  let replacement = client.replace_order_by_id(id, &request_body).await?;
+  Ok(format!("{} replaced by {}", id, replacement.id))
+}
@@ -0,0 +1,263 @@
+use crate::app::{
+  App,
+  Mode,
+};
+use alpaca_trade_api_rust::prelude::{
+  Order,
+  enums::Side,
+};
+use ratatui::{
+  Frame,
+  layout::{
+    Constraint,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Cell,
+    Paragraph,
+    Row,
+    Table,
+    TableState,
+  },
+};
+use serde::Serialize;
+
+///
+///Name the API uses for an enum value, e.g. `buy` or `stop_limit`
+fn name(value: impl Serialize) -> String {
+  match serde_json::to_value(value) {
+    Ok(serde_json::Value::String(name)) => name,
+    _ => String::new(),
+  }
+}
+
+fn signed(value: f64) -> Style {
+  match value {
+    value if value > 0.0 => Style::new().fg(Color::Green),
+    value if value < 0.0 => Style::new().fg(Color::Red),
+    _ => Style::new(),
+  }
+}
+
+fn side_style(side: Side) -> Style {
+  match side {
+    Side::Buy => Style::new().fg(Color::Green),
+    Side::Sell => Style::new().fg(Color::Red),
+  }
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+  let [header, body, footer] =
+    Layout::vertical([Constraint::Length(4), Constraint::Min(8), Constraint::Length(1)]).areas(frame.area());
+  let [positions, orders, fills] = Layout::vertical([
+    Constraint::Percentage(35),
+    Constraint::Percentage(35),
+    Constraint::Percentage(30),
+  ])
+  .areas(body);
+  draw_account(frame, app, header);
+  draw_positions(frame, app, positions);
+  draw_orders(frame, app, orders);
+  draw_fills(frame, app, fills);
+  draw_footer(frame, app, footer);
+}
+
+fn draw_account(frame: &mut Frame, app: &App, area: Rect) {
+  let environment = if app.live {
+    Span::styled(" LIVE ", Style::new().fg(Color::White).bg(Color::Red))
+  } else {
+    Span::styled(" PAPER ", Style::new().fg(Color::Black).bg(Color::Cyan))
+  };
+  let block = Block::bordered().title(Line::from(vec![environment, Span::raw(format!(" {} ", app.title))]));
+  let lines = match &app.account {
+    Some(account) => {
+      let equity = account.equity.value();
+      let change = equity - account.last_equity.value();
+      let change_pct = match account.last_equity.value() {
+        0.0 => 0.0,
+        last_equity => change / last_equity * 100.0,
+      };
+      vec![
+        Line::from(vec![
+          Span::raw(format!("equity {equity:.2}  ")),
+          Span::styled(format!("{change:+.2} ({change_pct:+.2}%)"), signed(change)),
+          Span::raw(format!("  cash {:.2}", account.cash.value())),
+        ]),
+        Line::from(format!(
+          "buying power {:.2}  long {:.2}  short {:.2}  day trades {}",
+          account.buying_power.value(),
+          account.long_market_value.value(),
+          account.short_market_value.value(),
+          account.daytrade_count
+        )),
+      ]
+    }
+    None => vec![Line::from("loading account")],
+  };
+  frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_positions(frame: &mut Frame, app: &App, area: Rect) {
+  let rows = app.positions.iter().map(|position| {
+    let unrealized_pl = position.unrealized_pl.value();
+    Row::new(vec![
+      Cell::from(position.symbol.clone()),
+      Cell::from(format!("{}", position.qty.value())),
+      Cell::from(format!("{:.2}", position.avg_entry_price.value())),
+      Cell::from(format!("{:.2}", position.current_price.value())),
+      Cell::from(format!("{:.2}", position.market_value.value())),
+      Cell::from(format!("{unrealized_pl:+.2}")).style(signed(unrealized_pl)),
+      Cell::from(format!("{:+.2}%", position.unrealized_plpc.value() * 100.0)).style(signed(unrealized_pl)),
+    ])
+  });
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(22),
+      Constraint::Length(10),
+      Constraint::Length(10),
+      Constraint::Length(10),
+      Constraint::Length(12),
+      Constraint::Length(12),
+      Constraint::Length(9),
+    ],
+  )
+  .header(Row::new(["symbol", "qty", "avg entry", "price", "value", "unreal p/l", "p/l %"]).style(Style::new().bold()))
+  .block(Block::bordered().title(format!(" positions ({}) ", app.positions.len())));
+  frame.render_widget(table, area);
+}
+
+fn order_price(order: &Order) -> String {
+  let trail = order
+    .trail_price
+    .as_ref()
+    .map(|trail| format!("trail {}", trail.value()))
+    .or(
+      order
+        .trail_percent
+        .as_ref()
+        .map(|trail| format!("trail {}%", trail.value())),
+    );
+  match (&order.limit_price, &order.stop_price, trail) {
+    (_, _, Some(trail)) => trail,
+    (Some(limit), Some(stop), None) => format!("{stop} / {limit}"),
+    (Some(price), None, None) | (None, Some(price), None) => price.clone(),
+    (None, None, None) => "market".to_string(),
+  }
+}
+
+fn draw_orders(frame: &mut Frame, app: &App, area: Rect) {
+  let rows = app.orders.iter().map(|order| {
+    let qty = order
+      .qty
+      .as_ref()
+      .map(|qty| qty.value().to_string())
+      .or(order.national.clone().map(|notional| format!("${notional}")))
+      .unwrap_or_default();
+    let filled = order.filled_qty.as_ref().map(|qty| qty.value()).unwrap_or_default();
+    Row::new(vec![
+      Cell::from(order.symbol.clone()),
+      Cell::from(name(order.side)).style(side_style(order.side)),
+      Cell::from(name(order._type)),
+      Cell::from(qty),
+      Cell::from(filled.to_string()),
+      Cell::from(order_price(order)),
+      Cell::from(name(order.time_in_force)),
+      Cell::from(name(order.status)),
+      Cell::from(
+        order
+          .created_at
+          .map(|created_at| created_at.format("%m-%d %H:%M:%S").to_string())
+          .unwrap_or_default(),
+      ),
+    ])
+  });
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(22),
+      Constraint::Length(5),
+      Constraint::Length(14),
+      Constraint::Length(10),
+      Constraint::Length(8),
+      Constraint::Length(18),
+      Constraint::Length(4),
+      Constraint::Length(17),
+      Constraint::Length(15),
+    ],
+  )
+  .header(
+    Row::new([
+      "symbol", "side", "type", "qty", "filled", "price", "tif", "status", "created",
+    ])
+    .style(Style::new().bold()),
+  )
+  .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+  .block(Block::bordered().title(format!(" working orders ({}) ", app.orders.len())));
+  let mut state = TableState::default().with_selected((!app.orders.is_empty()).then_some(app.selected));
+  frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_fills(frame: &mut Frame, app: &App, area: Rect) {
+  let rows = app.fills.iter().map(|fill| {
+    Row::new(vec![
+      Cell::from(fill.time.format("%H:%M:%S").to_string()),
+      Cell::from(fill.symbol.clone()),
+      Cell::from(name(fill.side)).style(side_style(fill.side)),
+      Cell::from(fill.qty.to_string()),
+      Cell::from(format!("{:.2}", fill.price)),
+      Cell::from(fill.position_qty.map(|qty| qty.to_string()).unwrap_or_default()),
+      Cell::from(if fill.partial { "partial" } else { "" }),
+    ])
+  });
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(9),
+      Constraint::Length(22),
+      Constraint::Length(5),
+      Constraint::Length(10),
+      Constraint::Length(10),
+      Constraint::Length(10),
+      Constraint::Length(8),
+    ],
+  )
+  .header(Row::new(["time", "symbol", "side", "qty", "price", "position", ""]).style(Style::new().bold()))
+  .block(Block::bordered().title(" fills "));
+  frame.render_widget(table, area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+  let line = match &app.mode {
+    Mode::Normal => Line::from(vec![
+      Span::styled("q", Style::new().bold()),
+      Span::raw(" quit  "),
+      Span::styled("↑/↓", Style::new().bold()),
+      Span::raw(" select  "),
+      Span::styled("c", Style::new().bold()),
+      Span::raw(" cancel  "),
+      Span::styled("e", Style::new().bold()),
+      Span::raw(" replace  "),
+      Span::styled("r", Style::new().bold()),
+      Span::raw(format!(" refresh  | {}", app.status)),
+    ]),
+    Mode::ConfirmCancel(id) => Line::styled(format!("cancel order {id}? y/n"), Style::new().fg(Color::Yellow)),
+    Mode::Replace { input, .. } => Line::from(vec![
+      Span::styled("replace (qty= limit= stop= trail=): ", Style::new().fg(Color::Yellow)),
+      Span::raw(input.clone()),
+      Span::styled("█", Style::new().add_modifier(Modifier::SLOW_BLINK)),
+    ]),
+  };
+  frame.render_widget(Paragraph::new(line), area);
+}
@@ -0,0 +1,151 @@
+use anyhow::{
+  Context,
+  bail,
+};
+use serde::de::DeserializeOwned;
+use std::{
+  collections::{
+    HashMap,
+    HashSet,
+  },
+  str::FromStr,
+};
+
+///
+///Options that take no value
+const FLAGS: [&str; 5] = ["all", "cancel-orders", "extended-hours", "help", "yes"];
+
+///
+///Command line split into positional words, `--name value` options and `--flag`s
+#[derive(Debug, Default)]
+pub struct Args {
+  positional: Vec<String>,
+  options: HashMap<String, String>,
+  flags: HashSet<String>,
+}
+
+impl Args {
+  pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      let name = match arg.as_str() {
+        "-o" => "output",
+        "-p" => "profile",
+        "-y" => "yes",
+        "-h" => "help",
+        _ => match arg.strip_prefix("--") {
+          Some(name) => name,
+          None => {
+            parsed.positional.push(arg);
+            continue;
+          }
+        },
+      };
+      if let Some((name, value)) = name.split_once('=') {
+        parsed.options.insert(name.to_string(), value.to_string());
+      } else if FLAGS.contains(&name) {
+        parsed.flags.insert(name.to_string());
+      } else {
+        let value = args.next().with_context(|| format!("--{name} needs a value"))?;
+        parsed.options.insert(name.to_string(), value);
+      }
+    }
+    Ok(parsed)
+  }
+
+  pub fn positional(&self, index: usize) -> Option<&str> {
+    self.positional.get(index).map(String::as_str)
+  }
+
+  pub fn positionals_from(&self, index: usize) -> &[String] {
+    self.positional.get(index..).unwrap_or_default()
+  }
+
+  pub fn required_positional(&self, index: usize, name: &str) -> anyhow::Result<&str> {
+    self.positional(index).with_context(|| format!("missing <{name}>"))
+  }
+
+  pub fn option(&self, name: &str) -> Option<&str> {
+    self.options.get(name).map(String::as_str)
+  }
+
+  pub fn required(&self, name: &str) -> anyhow::Result<&str> {
+    self.option(name).with_context(|| format!("missing --{name}"))
+  }
+
+  pub fn parsed<T: FromStr>(&self, name: &str) -> anyhow::Result<Option<T>>
+  where
+    T::Err: std::fmt::Display,
+  {
+    match self.option(name) {
+      Some(value) => match value.parse() {
+        Ok(value) => Ok(Some(value)),
+        Err(error) => bail!("invalid --{name} {value}: {error}"),
+      },
+      None => Ok(None),
+    }
+  }
+
+  ///
+  ///Option holding one of the API's names of an enum, e.g. `--side buy`
+  pub fn named<T: DeserializeOwned>(&self, name: &str) -> anyhow::Result<Option<T>> {
+    match self.option(name) {
+      Some(value) => match serde_json::from_value(serde_json::Value::String(value.to_string())) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => bail!("invalid --{name} {value}"),
+      },
+      None => Ok(None),
+    }
+  }
+
+  pub fn flag(&self, name: &str) -> bool {
+    self.flags.contains(name)
+  }
+
+  ///
+  ///Comma separated option, borrowed from the parsed arguments
+  pub fn list(&self, name: &str) -> Option<Vec<&str>> {
+    self
+      .option(name)
+      .map(|values| values.split(',').filter(|value| !value.is_empty()).collect())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use alpaca_trade_api_rust::prelude::enums::Side;
+
+  #[test]
+  fn test_args_should_split_positionals_options_and_flags() {
+    let args = Args::parse(
+      [
+        "orders",
+        "submit",
+        "--symbol",
+        "AAPL",
+        "--side=buy",
+        "-o",
+        "json",
+        "--yes",
+        "--qty",
+        "2",
+        "--symbols",
+        "AAPL,,MSFT",
+      ]
+      .map(str::to_string),
+    )
+    .unwrap();
+
+    assert_eq!(args.positional(1), Some("submit"));
+    assert_eq!(args.option("symbol"), Some("AAPL"));
+    assert_eq!(args.named::<Side>("side").unwrap(), Some(Side::Buy));
+    assert_eq!(args.option("output"), Some("json"));
+    assert_eq!(args.parsed::<f64>("qty").unwrap(), Some(2.0));
+    assert_eq!(args.list("symbols"), Some(vec!["AAPL", "MSFT"]));
+    assert!(args.flag("yes"));
+    assert!(args.named::<Side>("symbol").is_err());
+    assert!(Args::parse(["--limit".to_string()]).is_err());
+  }
+}
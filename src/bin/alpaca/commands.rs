@@ -0,0 +1,431 @@
+use crate::{
+  args::Args,
+  output::Output,
+};
+use alpaca_trade_api_rust::{
+  api::{
+    AccountApi,
+    AccountConfigurationsApi,
+    AddAssetReqBody,
+    AllOrdersQueryParameter,
+    AssetsApi,
+    AssetsQueryParameter,
+    AssetsStatus,
+    CalendarApi,
+    CalendarApiQueryParameter,
+    ClockApi,
+    ClosePositionParam,
+    ComaSeparatedStrings,
+    CorporateActionApi,
+    CorporateActionsQueryParameter,
+    CryptoFundingApi,
+    DefaultBoolean,
+    FundingWalletsParameter,
+    OptionApi,
+    OptionContractsQueryParameter,
+    OrderApi,
+    OrderRequestBody,
+    OrdersDirection,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+    WatchListApi,
+    WatchListReqBody,
+    WithdrawalReqBody,
+  },
+  prelude::{
+    Client,
+    OptionStatus,
+    TimeInForce,
+    enums::OrderType,
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+};
+use anyhow::{
+  Context,
+  bail,
+};
+use chrono::{
+  Days,
+  NaiveDate,
+  Utc,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const ORDER_COLUMNS: &[&str] = &[
+  "id",
+  "symbol",
+  "side",
+  "type",
+  "qty",
+  "filled_qty",
+  "limit_price",
+  "stop_price",
+  "time_in_force",
+  "status",
+  "created_at",
+];
+const POSITION_COLUMNS: &[&str] = &[
+  "symbol",
+  "side",
+  "qty",
+  "avg_entry_price",
+  "current_price",
+  "market_value",
+  "unrealized_pl",
+];
+const ASSET_COLUMNS: &[&str] = &[
+  "symbol",
+  "name",
+  "class",
+  "exchange",
+  "status",
+  "tradable",
+  "shortable",
+  "fractionable",
+];
+const CONTRACT_COLUMNS: &[&str] = &[
+  "symbol",
+  "underlying_symbol",
+  "type",
+  "style",
+  "strike_price",
+  "expiration_date",
+  "open_interest",
+  "close_price",
+];
+const CORPORATE_ACTION_COLUMNS: &[&str] = &[
+  "ca_type",
+  "ca_sub_type",
+  "initiating_symbol",
+  "target_symbol",
+  "declaration_date",
+  "effective_date",
+  "payable_date",
+  "cash",
+  "old_rate",
+  "new_rate",
+];
+
+///
+///Asks before live mutations, gets a description of the change
+pub type Confirm<'a> = &'a dyn Fn(&str) -> anyhow::Result<()>;
+
+pub async fn run(client: &Client, args: &'static Args, confirm: Confirm<'_>) -> anyhow::Result<Output> {
+  match (args.positional(0), args.positional(1)) {
+    (Some("account"), None) => Output::new(client.get_account().await?, &[]),
+    (Some("account"), Some("config")) => Output::new(client.get_account_configurations().await?, &[]),
+    (Some("orders"), action) => orders(client, args, action, confirm).await,
+    (Some("positions"), action) => positions(client, args, action, confirm).await,
+    (Some("assets"), None | Some("list")) => {
+      let query_parameter = AssetsQueryParameter {
+        status: match args.option("status") {
+          None | Some("active") => AssetsStatus::Active,
+          Some("inactive") => AssetsStatus::Inactive,
+          Some("all") => AssetsStatus::All,
+          Some(status) => bail!("invalid --status {status}"),
+        },
+        asset_class: args.named("class")?,
+        exchange: args.named("exchange")?,
+        attributes: args.list("attributes").map(|values| ComaSeparatedStrings { values }),
+      };
+      Output::new(client.get_assets(&query_parameter).await?, ASSET_COLUMNS)
+    }
+    (Some("assets"), Some("get")) => Output::new(
+      client
+        .get_asset_by_symbol_or_id(args.required_positional(2, "symbol")?)
+        .await?,
+      &[],
+    ),
+    (Some("options"), Some("contracts")) => {
+      let query_parameter = OptionContractsQueryParameter {
+        underlying_symbols: args.list("underlying").map(|values| ComaSeparatedStrings { values }),
+        show_deliverables: DefaultBoolean { value: false },
+        status: args.named("status")?.unwrap_or(OptionStatus::Active),
+        expiration_date: args.option("expiration").map(str::to_string),
+        expiration_date_gte: args.option("expiration-gte").map(str::to_string),
+        expiration_date_lte: args.option("expiration-lte").map(str::to_string),
+        root_symbol: args.option("root").map(str::to_string),
+        _type: args.named("type")?,
+        style: args.named("style")?,
+        strike_price_gte: args.parsed("strike-gte")?,
+        strike_price_lte: args.parsed("strike-lte")?,
+        page_token: args.option("page-token").map(str::to_string),
+        limit: args.parsed("limit")?,
+        ppind: None,
+      };
+      Output::new(
+        client.get_option_contracts(&query_parameter).await?.option_contracts,
+        CONTRACT_COLUMNS,
+      )
+    }
+    (Some("options"), Some("get")) => Output::new(
+      client
+        .get_option_contract_by_symbol_or_id(args.required_positional(2, "symbol")?)
+        .await?,
+      &[],
+    ),
+    (Some("watchlists"), action) => watchlists(client, args, action, confirm).await,
+    (Some("calendar"), None) => {
+      let query_parameter = CalendarApiQueryParameter {
+        start: args.parsed("start")?,
+        end: args.parsed("end")?,
+        date_type: None,
+      };
+      Output::new(
+        client.get_market_calendar_info(&query_parameter).await?,
+        &["date", "open", "close", "settlement_date"],
+      )
+    }
+    (Some("clock"), None) => Output::new(client.get_market_clock_info().await?, &[]),
+    (Some("corporate-actions"), None) => {
+      let until = args.parsed::<NaiveDate>("until")?.unwrap_or(Utc::now().date_naive());
+      let since = match args.parsed::<NaiveDate>("since")? {
+        Some(since) => since,
+        None => until.checked_sub_days(Days::new(30)).unwrap_or(until),
+      };
+      let query_parameter = CorporateActionsQueryParameter {
+        ca_types: ComaSeparatedStrings {
+          values: args
+            .list("types")
+            .unwrap_or(vec!["dividend", "merger", "spinoff", "split"]),
+        },
+        since,
+        until,
+        symbols: args.option("symbols").map(str::to_string),
+        cusip: args.option("cusip").map(str::to_string),
+        date_type: args.named("date-type")?,
+      };
+      Output::new(
+        client.get_corporate_actions(&query_parameter).await?,
+        CORPORATE_ACTION_COLUMNS,
+      )
+    }
+    (Some("wallets"), None | Some("list")) => {
+      let request_parameter = FundingWalletsParameter {
+        asset: args.option("asset").map(str::to_string),
+        network: args.named("network")?,
+      };
+      Output::new(client.get_all_crypto_funding_wallet(&request_parameter).await?, &[])
+    }
+    (Some("wallets"), Some("transfers")) => Output::new(
+      client.get_all_crypto_funding_transfer().await?,
+      &[
+        "id",
+        "direction",
+        "status",
+        "asset",
+        "amount",
+        "to_address",
+        "created_at",
+      ],
+    ),
+    (Some("wallets"), Some("withdraw")) => {
+      let request_body = WithdrawalReqBody {
+        amount: Money::from_f64(args.required("amount")?.parse().context("invalid --amount")?),
+        address: args.required("address")?.to_string(),
+        asset: args.required("asset")?.to_string(),
+      };
+      confirm(&format!(
+        "withdraw {} {} to {}",
+        request_body.amount.value(),
+        request_body.asset,
+        request_body.address
+      ))?;
+      Output::new(client.new_withdrawal(&request_body).await?, &[])
+    }
+    _ => bail!("unknown command, see alpaca --help"),
+  }
+}
+
+async fn orders(
+  client: &Client,
+  args: &'static Args,
+  action: Option<&str>,
+  confirm: Confirm<'_>,
+) -> anyhow::Result<Output> {
+  match action {
+    None | Some("list") => {
+      let query_parameter = AllOrdersQueryParameter {
+        status: args.named("status")?,
+        limit: args.parsed("limit")?,
+        after: args.option("after").map(str::to_string),
+        until: args.option("until").map(str::to_string),
+        direction: Some(OrdersDirection::Desc),
+        nested: Some(true),
+        symbols: args.list("symbols").map(|values| ComaSeparatedStrings { values }),
+        side: None,
+        asset_class: None,
+        before_order_id: None,
+        after_order_id: None,
+      };
+      Output::new(client.get_all_orders(&query_parameter).await?, ORDER_COLUMNS)
+    }
+    Some("get") => {
+      let id = args.required_positional(2, "id")?;
+      match id.parse::<Uuid>() {
+        Ok(id) => Output::new(client.get_order_by_id(&id).await?, &[]),
+        Err(_) => Output::new(client.get_order_by_client_order_id(id).await?, &[]),
+      }
+    }
+    Some("submit") => {
+      let _type = args.named("type")?.unwrap_or(OrderType::Market);
+      let order = OrderRequestBody {
+        symbol: args.required("symbol")?.to_string(),
+        qty: args.parsed("qty")?.map(NumberAsString::from_f64),
+        notional: args.parsed("notional")?.map(Money::from_f64),
+        side: args.named("side")?.context("missing --side, buy or sell")?,
+        _type,
+        time_in_force: args.named("tif")?.unwrap_or(TimeInForce::DAY),
+        limit_price: args.parsed("limit-price")?.map(Money::from_f64),
+        stop_price: args.parsed("stop-price")?.map(Money::from_f64),
+        trail_price: args.parsed("trail-price")?.map(Money::from_f64),
+        trail_percent: args.parsed("trail-percent")?.map(Money::from_f64),
+        extended_hours: args.flag("extended-hours"),
+        client_order_id: args.option("client-order-id").map(str::to_string),
+        order_class: None,
+        legs: vec![],
+        take_profit: None,
+        stop_loss: None,
+        position_intent: None,
+      };
+      confirm(&format!(
+        "submit {} {:?} order for {} {}",
+        serde_json::to_value(order.side)?.as_str().unwrap_or_default(),
+        order._type,
+        order
+          .qty
+          .as_ref()
+          .map(|qty| qty.value().to_string())
+          .or(order.notional.as_ref().map(|notional| format!("${}", notional.value())))
+          .unwrap_or_default(),
+        order.symbol
+      ))?;
+      Output::new(client.create_order(&order).await?, &[])
+    }
+    Some("cancel") if args.flag("all") => {
+      confirm("cancel every open order")?;
+      Output::new(client.delete_all_orders().await?, &["id", "status"])
+    }
+    Some("cancel") => {
+      let id: Uuid = args.required_positional(2, "id")?.parse()?;
+      confirm(&format!("cancel order {id}"))?;
+      client.delete_order_by_id(&id).await?;
+      Output::new(json!({ "id": id, "status": "canceled" }), &[])
+    }
+    Some("replace") => {
+      let id: Uuid = args.required_positional(2, "id")?.parse()?;
+      let original = client.get_order_by_id(&id).await?;
+      let order = ReplaceOrderByIdRequestBody {
+        qty: NumberAsString::from_f64(match args.parsed("qty")? {
+          Some(qty) => qty,
+          None => original.qty.as_ref().map(NumberAsString::value).unwrap_or_default(),
+        }),
+        time_in_force: args.named("tif")?.unwrap_or(original.time_in_force),
+        limit_price: args.parsed("limit-price")?.map(Money::from_f64),
+        stop_price: args.parsed("stop-price")?.map(Money::from_f64),
+        trail: args.parsed("trail")?.map(Money::from_f64),
+        client_order_id: args
+          .option("client-order-id")
+          .map(str::to_string)
+          .unwrap_or_else(|| Uuid::new_v4().to_string()),
+      };
+      confirm(&format!("replace order {id} of {}", original.symbol))?;
+      Output::new(client.replace_order_by_id(&id, &order).await?, &[])
+    }
+    _ => bail!("unknown orders command, expected list, get, submit, cancel or replace"),
+  }
+}
+
+async fn positions(client: &Client, args: &Args, action: Option<&str>, confirm: Confirm<'_>) -> anyhow::Result<Output> {
+  match action {
+    None | Some("list") => Output::new(client.get_all_open_positions().await?, POSITION_COLUMNS),
+    Some("get") => Output::new(
+      client
+        .get_open_position_by_symbol_or_id(args.required_positional(2, "symbol")?)
+        .await?,
+      &[],
+    ),
+    Some("close") if args.flag("all") => {
+      let cancel_orders = args.flag("cancel-orders");
+      confirm("close every open position")?;
+      let infos = client.clost_all_open_positions(cancel_orders).await?;
+      let rows: Vec<_> = infos
+        .iter()
+        .map(|info| json!({ "symbol": info.symbol, "status": info.status, "order_id": info.order().map(|order| order.id) }))
+        .collect();
+      Output::new(rows, &[])
+    }
+    Some("close") => {
+      let symbol = args.required_positional(2, "symbol")?;
+      let param = match (args.parsed("qty")?, args.parsed("percentage")?) {
+        (Some(qty), None) => ClosePositionParam::Qty(qty),
+        (None, Some(percentage)) => ClosePositionParam::Percentage(percentage),
+        (None, None) => ClosePositionParam::Percentage(100.0),
+        _ => bail!("--qty and --percentage are exclusive"),
+      };
+      confirm(&format!("close {param:?} of {symbol}"))?;
+      Output::new(
+        client.close_open_position_by_symbol_or_id(symbol, &param).await?,
+        ORDER_COLUMNS,
+      )
+    }
+    Some("exercise") => {
+      let symbol = args.required_positional(2, "symbol")?;
+      confirm(&format!("exercise {symbol}"))?;
+      client.exercise_option_contract_by_symbol_or_id(symbol).await?;
+      Output::new(json!({ "symbol": symbol, "status": "exercised" }), &[])
+    }
+    _ => bail!("unknown positions command, expected list, get, close or exercise"),
+  }
+}
+
+async fn watchlists(
+  client: &Client,
+  args: &Args,
+  action: Option<&str>,
+  confirm: Confirm<'_>,
+) -> anyhow::Result<Output> {
+  const WATCH_LIST_COLUMNS: &[&str] = &["id", "name", "created_at", "updated_at"];
+  let id = || -> anyhow::Result<Uuid> { Ok(args.required_positional(2, "id")?.parse()?) };
+  match action {
+    None | Some("list") => Output::new(client.get_all_watch_lists().await?, WATCH_LIST_COLUMNS),
+    Some("get") => Output::new(client.get_watch_list_by_id(&id()?).await?.assets, ASSET_COLUMNS),
+    Some("create") => {
+      let request_body = WatchListReqBody {
+        name: args.required_positional(2, "name")?.to_string(),
+        symbols: args.positionals_from(3).to_vec(),
+      };
+      confirm(&format!("create watchlist {}", request_body.name))?;
+      Output::new(client.create_watch_list(&request_body).await?.assets, ASSET_COLUMNS)
+    }
+    Some("add") => {
+      let symbol = AddAssetReqBody {
+        symbol: args.required_positional(3, "symbol")?.to_string(),
+      };
+      confirm(&format!("add {} to watchlist", symbol.symbol))?;
+      Output::new(
+        client.add_asset_to_watch_list(&id()?, &symbol).await?.assets,
+        ASSET_COLUMNS,
+      )
+    }
+    Some("remove") => {
+      let symbol = args.required_positional(3, "symbol")?;
+      confirm(&format!("remove {symbol} from watchlist"))?;
+      Output::new(
+        client.delete_asset_from_watch_list(&id()?, symbol).await?.assets,
+        ASSET_COLUMNS,
+      )
+    }
+    Some("delete") => {
+      let id = id()?;
+      confirm(&format!("delete watchlist {id}"))?;
+      client.delete_watch_list_by_id(&id).await?;
+      Output::new(json!({ "id": id, "status": "deleted" }), &[])
+    }
+    _ => bail!("unknown watchlists command, expected list, get, create, add, remove or delete"),
+  }
+}
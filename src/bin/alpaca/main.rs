@@ -0,0 +1,93 @@
+mod args;
+mod commands;
+mod output;
+
+use alpaca_trade_api_rust::profile::Profile;
+use anyhow::bail;
+use args::Args;
+use output::Format;
+use std::{
+  io::{
+    BufRead,
+    Write,
+  },
+  sync::OnceLock,
+};
+
+const USAGE: &str = "usage: alpaca [-p <profile>] [-o table|json|csv] [--columns <a,b,..>] [-y] <command>
+
+commands:
+  account [config]
+  orders list [--status open|closed|all] [--limit <n>] [--symbols <a,b>] [--after <time>] [--until <time>]
+  orders get <id|client-order-id>
+  orders submit --symbol <symbol> --side buy|sell (--qty <qty>|--notional <amount>) [--type <type>]
+                [--limit-price <price>] [--stop-price <price>] [--trail-price <price>] [--trail-percent <pct>]
+                [--tif <tif>] [--extended-hours] [--client-order-id <id>]
+  orders cancel (<id>|--all)
+  orders replace <id> [--qty <qty>] [--limit-price <price>] [--stop-price <price>] [--trail <trail>] [--tif <tif>]
+  positions list
+  positions get <symbol>
+  positions close (<symbol> [--qty <qty>|--percentage <pct>]|--all [--cancel-orders])
+  positions exercise <symbol>
+  assets list [--status active|inactive] [--class us_equity|us_option|crypto] [--exchange <exchange>]
+  assets get <symbol>
+  options contracts [--underlying <a,b>] [--expiration <date>] [--expiration-gte <date>] [--expiration-lte <date>]
+                    [--type call|put] [--strike-gte <price>] [--strike-lte <price>] [--limit <n>]
+  options get <symbol>
+  watchlists list
+  watchlists get <id>
+  watchlists create <name> [<symbol>..]
+  watchlists add <id> <symbol>
+  watchlists remove <id> <symbol>
+  watchlists delete <id>
+  calendar [--start <date>] [--end <date>]
+  clock
+  corporate-actions [--types <a,b>] [--since <date>] [--until <date>] [--symbols <a,b>]
+  wallets list [--asset <asset>]
+  wallets transfers
+  wallets withdraw --amount <amount> --address <address> --asset <asset>
+
+profiles are read from $ALPACA_CONFIG or ~/.config/alpaca/profiles.json, falling back to the APCA_* variables;
+changes against a live profile ask for confirmation unless --yes is given";
+
+///
+///Arguments of the process, kept for the whole run so list options can be borrowed as the
+/// `'static` values the query parameters take
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+  let args = Args::parse(std::env::args().skip(1))?;
+  let args = ARGS.get_or_init(|| args);
+  if args.flag("help") || args.positional(0).is_none() {
+    println!("{USAGE}");
+    return Ok(());
+  }
+  let format: Format = args.parsed("output")?.unwrap_or(Format::Table);
+  let columns: Option<Vec<String>> = args
+    .option("columns")
+    .map(|columns| columns.split(',').map(str::to_string).collect());
+
+  let profile = Profile::resolve(args.option("profile"))?;
+  let live = profile.is_live() && !args.flag("yes");
+  let confirm = |change: &str| -> anyhow::Result<()> {
+    if !live {
+      return Ok(());
+    }
+    eprint!(
+      "{change} on the LIVE account {}? type yes to continue: ",
+      profile.base_url
+    );
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    if answer.trim() != "yes" {
+      bail!("aborted");
+    }
+    Ok(())
+  };
+
+  let output = commands::run(&profile.client(), args, &confirm).await?;
+  println!("{}", output.render(format, columns.as_deref())?);
+  Ok(())
+}
@@ -0,0 +1,183 @@
+use anyhow::bail;
+use serde_json::Value;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  Table,
+  Json,
+  Csv,
+}
+
+impl FromStr for Format {
+  type Err = anyhow::Error;
+
+  fn from_str(value: &str) -> anyhow::Result<Self> {
+    match value {
+      "table" => Ok(Format::Table),
+      "json" => Ok(Format::Json),
+      "csv" => Ok(Format::Csv),
+      _ => bail!("unknown output format {value}, expected table, json or csv"),
+    }
+  }
+}
+
+///
+///Response of a command and the columns worth showing by default, all of them when empty
+#[derive(Debug)]
+pub struct Output {
+  pub value: Value,
+  pub columns: &'static [&'static str],
+}
+
+impl Output {
+  pub fn new(value: impl serde::Serialize, columns: &'static [&'static str]) -> anyhow::Result<Self> {
+    Ok(Output {
+      value: serde_json::to_value(value)?,
+      columns,
+    })
+  }
+
+  ///
+  ///Render as `format`, `columns` overriding the default columns
+  pub fn render(&self, format: Format, columns: Option<&[String]>) -> anyhow::Result<String> {
+    if format == Format::Json {
+      return Ok(serde_json::to_string_pretty(&self.value)?);
+    }
+    let rows: Vec<&Value> = match &self.value {
+      Value::Array(rows) => rows.iter().collect(),
+      Value::Null => vec![],
+      value => vec![value],
+    };
+    // a single object reads best as one field per line
+    if format == Format::Table && columns.is_none() && self.value.is_object() {
+      let fields: Vec<Vec<String>> = self
+        .value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| vec![name.clone(), cell(value)])
+        .collect();
+      return Ok(table(&["field".to_string(), "value".to_string()], &fields));
+    }
+    let columns: Vec<String> = match columns {
+      Some(columns) => columns.to_vec(),
+      None if !self.columns.is_empty() => self.columns.iter().map(|column| column.to_string()).collect(),
+      None => {
+        let mut columns: Vec<String> = vec![];
+        for row in &rows {
+          for name in row.as_object().into_iter().flat_map(|row| row.keys()) {
+            if !columns.contains(name) {
+              columns.push(name.clone());
+            }
+          }
+        }
+        if columns.is_empty() {
+          columns.push("value".to_string());
+        }
+        columns
+      }
+    };
+    let cells: Vec<Vec<String>> = rows
+      .iter()
+      .map(|row| {
+        columns
+          .iter()
+          .map(|column| match row {
+            Value::Object(row) => row.get(column).map(cell).unwrap_or_default(),
+            value => cell(value),
+          })
+          .collect()
+      })
+      .collect();
+    Ok(match format {
+      Format::Table => table(&columns, &cells),
+      _ => csv(&columns, &cells),
+    })
+  }
+}
+
+fn cell(value: &Value) -> String {
+  match value {
+    Value::Null => String::new(),
+    Value::String(text) => text.clone(),
+    Value::Number(_) | Value::Bool(_) => value.to_string(),
+    nested => nested.to_string(),
+  }
+}
+
+fn table(columns: &[String], rows: &[Vec<String>]) -> String {
+  let widths: Vec<usize> = (0..columns.len())
+    .map(|index| {
+      rows
+        .iter()
+        .map(|row| row[index].chars().count())
+        .chain([columns[index].len()])
+        .max()
+        .unwrap_or_default()
+    })
+    .collect();
+  let line = |cells: &[String]| {
+    cells
+      .iter()
+      .zip(&widths)
+      .map(|(cell, width)| format!("{cell:<width$}"))
+      .collect::<Vec<_>>()
+      .join("  ")
+      .trim_end()
+      .to_string()
+  };
+  let mut lines = vec![line(columns)];
+  lines.push(line(&widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>()));
+  lines.extend(rows.iter().map(|row| line(row)));
+  lines.join("\n")
+}
+
+fn csv(columns: &[String], rows: &[Vec<String>]) -> String {
+  let escape = |cell: &String| {
+    if cell.contains([',', '"', '\n']) {
+      format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+      cell.clone()
+    }
+  };
+  std::iter::once(columns)
+    .chain(rows.iter().map(Vec::as_slice))
+    .map(|row| row.iter().map(escape).collect::<Vec<_>>().join(","))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn test_output_should_render_table_csv_and_fields() {
+    let output = Output::new(
+      json!([
+        { "symbol": "AAPL", "qty": "10", "side": "long", "legs": null },
+        { "symbol": "BRK, B", "qty": "1", "side": "long", "legs": [1] }
+      ]),
+      &["symbol", "qty"],
+    )
+    .unwrap();
+    assert_eq!(
+      output.render(Format::Table, None).unwrap(),
+      "symbol  qty\n------  ---\nAAPL    10\nBRK, B  1"
+    );
+    assert_eq!(
+      output
+        .render(Format::Csv, Some(&["symbol".to_string(), "legs".to_string()]))
+        .unwrap(),
+      "symbol,legs\nAAPL,\n\"BRK, B\",[1]"
+    );
+
+    let account = Output::new(json!({ "cash": "100", "status": "ACTIVE" }), &[]).unwrap();
+    assert_eq!(
+      account.render(Format::Table, None).unwrap(),
+      "field   value\n------  ------\ncash    100\nstatus  ACTIVE"
+    );
+  }
+}
@@ -12,9 +12,11 @@ use reqwest_middleware::ClientWithMiddleware;
 ///   "testApiSecretKey".to_string(),
 /// );
 /// assert_eq!(client.base_url, "localhost:8080");
+/// assert_eq!(client.data_url, "localhost:8080");
 /// ```
 pub struct Client {
   pub base_url: String,
+  pub data_url: String,
   pub client: ClientWithMiddleware,
 }
 
@@ -49,6 +51,30 @@ impl Client {
       ))
       .with(reqwest_tracing::TracingMiddleware::default())
       .build();
-    Client { base_url, client }
+    let data_url = base_url.clone();
+    Client {
+      base_url,
+      data_url,
+      client,
+    }
+  }
+
+  ///
+  ///Point market data requests to a different host, e.g. `https://data.alpaca.markets`
+  ///
+  /// ```
+  /// use alpaca_trade_api_rust::prelude::Client;
+  ///
+  /// let client = Client::new(
+  ///   "https://paper-api.alpaca.markets".to_string(),
+  ///   "testApiKey".to_string(),
+  ///   "testApiSecretKey".to_string(),
+  /// )
+  /// .with_data_url("https://data.alpaca.markets".to_string());
+  /// assert_eq!(client.data_url, "https://data.alpaca.markets");
+  /// ```
+  pub fn with_data_url(mut self, data_url: String) -> Self {
+    self.data_url = data_url;
+    self
   }
 }
@@ -0,0 +1,968 @@
+use crate::{
+  api::{
+    AccountApi,
+    AddAssetReqBody,
+    AllOrdersQueryParameter,
+    AssetsApi,
+    AssetsQueryParameter,
+    BasicWatchListInfo,
+    CalendarApi,
+    CalendarApiQueryParameter,
+    ClockApi,
+    ClosePositionBody,
+    ClosePositionInfo,
+    ClosePositionParam,
+    CryptoFundingApi,
+    DeleteAllOrdersResponse,
+    FundingWalletsParameter,
+    OrderApi,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+    ReturnGasFeeParameter,
+    WatchListApi,
+    WatchListReqBody,
+    WhitelistedAddressReqBody,
+    WithdrawalReqBody,
+  },
+  dry_run::{
+    Journal,
+    JournalEntry,
+  },
+  models::{
+    Account,
+    AddressStatus,
+    Asset,
+    ClosedPosition,
+    CryptoDirection,
+    CryptoStatus,
+    CryptoTransfer,
+    CryptoWalletInfo,
+    GasFee,
+    MarketCalendar,
+    MarketClock,
+    Order,
+    OrderClass,
+    OrderStatus,
+    Position,
+    PositionIntent,
+    PositionSide,
+    TimeInForce,
+    WatchList,
+    WhiteListedAddress,
+    enums::{
+      AssetClass,
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  sim::{
+    SimError,
+    closed_position,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::Serialize;
+use serde_json::{
+  Value,
+  json,
+};
+use std::{
+  collections::{
+    HashMap,
+    HashSet,
+  },
+  fs::OpenOptions,
+  io::Write,
+  path::PathBuf,
+  sync::Mutex,
+};
+use uuid::Uuid;
+
+///
+///Wraps a client so that a strategy runs against live market state without trading
+///
+///Read calls go to the wrapped client. Mutating calls are validated against the live account,
+/// answered with synthetic responses shaped like the real ones and recorded in a [`Journal`]; they
+/// never reach the API. Synthetic orders, and live orders canceled or replaced by the dry run, are
+/// served back by [`OrderApi::get_order_by_id`] and [`OrderApi::get_order_by_client_order_id`],
+/// while listings such as [`OrderApi::get_all_orders`] and positions stay those of the live
+/// account.
+#[derive(Debug)]
+pub struct DryRun<C> {
+  client: C,
+  journal: Mutex<Journal>,
+  journal_file: Option<PathBuf>,
+  orders: Mutex<HashMap<Uuid, Value>>,
+}
+
+impl<C> DryRun<C> {
+  pub fn new(client: C) -> Self {
+    DryRun {
+      client,
+      journal: Mutex::new(Journal::new()),
+      journal_file: None,
+      orders: Mutex::new(HashMap::new()),
+    }
+  }
+
+  ///
+  ///Also append every journal entry to the JSON lines file at `path` as it is recorded
+  pub fn with_journal_file(mut self, path: impl Into<PathBuf>) -> Self {
+    self.journal_file = Some(path.into());
+    self
+  }
+
+  pub fn inner(&self) -> &C {
+    &self.client
+  }
+
+  pub fn journal(&self) -> Journal {
+    self
+      .journal
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .clone()
+  }
+
+  ///
+  ///Orders created or changed by the dry run, in no particular order
+  pub fn orders(&self) -> Vec<Order> {
+    self
+      .orders
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .values()
+      .filter_map(|order| serde_json::from_value(order.clone()).ok())
+      .collect()
+  }
+
+  fn record<T: Serialize>(&self, call: &str, request: Value, result: &anyhow::Result<T>) -> anyhow::Result<()> {
+    let entry = JournalEntry {
+      timestamp: Utc::now(),
+      call: call.to_string(),
+      request,
+      response: result.as_ref().ok().map(serde_json::to_value).transpose()?,
+      error: result.as_ref().err().map(|error| error.to_string()),
+    };
+    if let Some(path) = &self.journal_file {
+      let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+      writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    }
+    self
+      .journal
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .push(entry);
+    Ok(())
+  }
+
+  ///
+  ///Record the call and hand its result back
+  fn journaled<T: Serialize>(&self, call: &str, request: Value, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    self.record(call, request, &result)?;
+    result
+  }
+
+  fn store(&self, order: &Order) -> anyhow::Result<()> {
+    let value = serde_json::to_value(order)?;
+    self
+      .orders
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .insert(order.id, value);
+    Ok(())
+  }
+
+  ///
+  ///Cost of the synthetic buy orders still open, bracket and OTO exits excluded
+  fn reserved(&self) -> f64 {
+    let orders = self.orders();
+    let legs: HashSet<Uuid> = orders
+      .iter()
+      .flat_map(|order| order.legs.iter().flatten())
+      .map(|leg| leg.id)
+      .collect();
+    orders
+      .iter()
+      .filter(|order| order.side == Side::Buy && is_open(order.status) && !legs.contains(&order.id))
+      .filter_map(|order| {
+        let parse = |value: &Option<String>| value.as_deref().and_then(|value| value.parse::<f64>().ok());
+        cost(
+          parse(&order.national),
+          order.qty.as_ref().map(NumberAsString::value),
+          parse(&order.limit_price).or(parse(&order.stop_price)),
+          order.asset_class,
+        )
+      })
+      .sum()
+  }
+
+  fn stored(&self, predicate: impl Fn(&Order) -> bool) -> Option<Order> {
+    self
+      .orders
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .values()
+      .filter_map(|order| serde_json::from_value::<Order>(order.clone()).ok())
+      .find(predicate)
+  }
+}
+
+impl<C: OrderApi> DryRun<C> {
+  ///
+  ///The order as the dry run sees it, its own version first
+  async fn order(&self, order_id: &Uuid) -> anyhow::Result<Order> {
+    match self.stored(|order| &order.id == order_id) {
+      Some(order) => Ok(order),
+      None => self.client.get_order_by_id(order_id).await,
+    }
+  }
+
+  async fn cancel(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    let mut order = self.order(order_id).await?;
+    if !is_open(order.status) {
+      bail!(unprocessable("order is not cancelable"));
+    }
+    let now = Utc::now();
+    order.status = OrderStatus::Canceled;
+    order.canceled_at = Some(now);
+    order.updated_at = Some(now);
+    self.store(&order)
+  }
+
+  async fn cancel_all(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    let live = self
+      .client
+      .get_all_orders(&AllOrdersQueryParameter {
+        status: Some(OrdersFilter::Open),
+        limit: Some(500),
+        after: None,
+        until: None,
+        direction: None,
+        nested: Some(false),
+        symbols: None,
+        side: None,
+        asset_class: None,
+        before_order_id: None,
+        after_order_id: None,
+      })
+      .await?;
+    let mut ids: Vec<Uuid> = live.iter().map(|order| order.id).collect();
+    ids.extend(self.orders().into_iter().map(|order| order.id));
+    ids.sort();
+    ids.dedup();
+    let mut responses = vec![];
+    for id in ids {
+      // orders the dry run already closed are not canceled again
+      if is_open(self.order(&id).await?.status) {
+        self.cancel(&id).await?;
+        responses.push(DeleteAllOrdersResponse { id, status: 200 });
+      }
+    }
+    Ok(responses)
+  }
+}
+
+impl<C: OrderApi + AssetsApi + AccountApi> DryRun<C> {
+  async fn submit(&self, request: &OrderRequestBody) -> anyhow::Result<Order> {
+    let asset = self.client.get_asset_by_symbol_or_id(&request.symbol).await?;
+    if let Err(message) = validate(request, &asset) {
+      bail!(unprocessable(&message));
+    }
+    if let Some(client_order_id) = &request.client_order_id
+      && self.stored(|order| &order.client_order_id == client_order_id).is_some()
+    {
+      bail!(unprocessable("client_order_id must be unique"));
+    }
+    if request.side == Side::Buy {
+      let price = request
+        .limit_price
+        .as_ref()
+        .or(request.stop_price.as_ref())
+        .map(Money::value);
+      let cost = cost(
+        request.notional.as_ref().map(Money::value),
+        request.qty.as_ref().map(NumberAsString::value),
+        price,
+        asset.class,
+      );
+      // the live account does not know about the synthetic buys still working
+      let buying_power = self.client.get_account().await?.buying_power.value() - self.reserved();
+      if let Some(cost) = cost
+        && cost > buying_power
+      {
+        bail!(SimError::InsufficientBuyingPower.to_error_response());
+      }
+    }
+
+    let now = Utc::now();
+    let mut order = synthetic_order(asset.id, &asset.symbol, asset.class, request.side, request._type, now);
+    if let Some(client_order_id) = &request.client_order_id {
+      order.client_order_id = client_order_id.clone();
+    }
+    order.order_class = match request.order_class {
+      None | Some(OrderClass::Empty) => OrderClass::Simple,
+      Some(order_class) => order_class,
+    };
+    order.time_in_force = request.time_in_force;
+    order.national = request.notional.as_ref().map(|notional| notional.value().to_string());
+    order.qty = request.qty.as_ref().map(|qty| NumberAsString::from_f64(qty.value()));
+    order.limit_price = request.limit_price.as_ref().map(|price| price.value().to_string());
+    order.stop_price = request.stop_price.as_ref().map(|price| price.value().to_string());
+    order.trail_price = request.trail_price.as_ref().map(|price| Money::from_f64(price.value()));
+    order.trail_percent = request
+      .trail_percent
+      .as_ref()
+      .map(|percent| Money::from_f64(percent.value()));
+    order.extended_hours = request.extended_hours;
+    if let Some(position_intent) = request.position_intent {
+      order.position_intent = position_intent;
+    }
+
+    let exit_side = match request.side {
+      Side::Buy => Side::Sell,
+      Side::Sell => Side::Buy,
+    };
+    // the legs of an OCO order work from the start, those of bracket and OTO orders wait for the entry
+    let leg_status = if order.order_class == OrderClass::Oco {
+      OrderStatus::New
+    } else {
+      OrderStatus::Held
+    };
+    let mut legs = vec![];
+    if let Some(take_profit) = &request.take_profit
+      && order.order_class != OrderClass::Oco
+    {
+      let mut leg = synthetic_order(asset.id, &asset.symbol, asset.class, exit_side, OrderType::Limit, now);
+      leg.limit_price = Some(take_profit.limit_price.value().to_string());
+      legs.push(leg);
+    }
+    if let Some(stop_loss) = &request.stop_loss {
+      let order_type = match stop_loss.limit_price {
+        Some(_) => OrderType::StopLimit,
+        None => OrderType::Stop,
+      };
+      let mut leg = synthetic_order(asset.id, &asset.symbol, asset.class, exit_side, order_type, now);
+      leg.stop_price = Some(stop_loss.stop_price.value().to_string());
+      leg.limit_price = stop_loss.limit_price.as_ref().map(|price| price.value().to_string());
+      legs.push(leg);
+    }
+    for leg in legs.iter_mut() {
+      leg.order_class = order.order_class;
+      leg.qty = order.qty.as_ref().map(|qty| NumberAsString::from_f64(qty.value()));
+      leg.time_in_force = order.time_in_force;
+      leg.status = leg_status;
+      self.store(leg)?;
+    }
+    if !legs.is_empty() {
+      order.legs = Some(legs);
+    }
+    self.store(&order)?;
+    Ok(order)
+  }
+
+  async fn replace(&self, order_id: &Uuid, request: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    let mut original = self.order(order_id).await?;
+    if !is_open(original.status) {
+      bail!(unprocessable("order is not replaceable"));
+    }
+    if request.qty.value() <= 0.0 {
+      bail!(unprocessable("qty must be positive"));
+    }
+    let now = Utc::now();
+    let mut order = synthetic_order(
+      original.asset_id,
+      &original.symbol,
+      original.asset_class,
+      original.side,
+      original._type,
+      now,
+    );
+    order.client_order_id = request.client_order_id.clone();
+    order.order_class = original.order_class;
+    order.qty = Some(NumberAsString::from_f64(request.qty.value()));
+    order.time_in_force = request.time_in_force;
+    order.extended_hours = original.extended_hours;
+    order.position_intent = original.position_intent;
+    order.replaces = Some(original.id);
+    order.limit_price = original.limit_price.clone();
+    order.stop_price = original.stop_price.clone();
+    order.trail_price = original
+      .trail_price
+      .as_ref()
+      .map(|price| Money::from_f64(price.value()));
+    order.trail_percent = original
+      .trail_percent
+      .as_ref()
+      .map(|percent| Money::from_f64(percent.value()));
+    // prices left out of the request keep their current value
+    let limit_price = request.limit_price.as_ref().map(|price| price.value().to_string());
+    let stop_price = request.stop_price.as_ref().map(|price| price.value().to_string());
+    let trail = request.trail.as_ref().map(|trail| Money::from_f64(trail.value()));
+    match order._type {
+      OrderType::Market => {}
+      OrderType::Limit => order.limit_price = limit_price.or(order.limit_price),
+      OrderType::Stop => order.stop_price = stop_price.or(order.stop_price),
+      OrderType::StopLimit => {
+        order.limit_price = limit_price.or(order.limit_price);
+        order.stop_price = stop_price.or(order.stop_price);
+      }
+      OrderType::TrailingStop if order.trail_price.is_some() => order.trail_price = trail.or(order.trail_price),
+      OrderType::TrailingStop => order.trail_percent = trail.or(order.trail_percent),
+    }
+
+    original.status = OrderStatus::Replaced;
+    original.replaced_at = Some(now);
+    original.replaced_by = Some(order.id);
+    original.updated_at = Some(now);
+    self.store(&original)?;
+    self.store(&order)?;
+    Ok(order)
+  }
+}
+
+impl<C: PositionApi> DryRun<C> {
+  ///
+  ///Synthetic market order closing `qty` of `position`
+  fn liquidation(&self, position: &Position, qty: f64) -> anyhow::Result<Order> {
+    let side = match position.side {
+      PositionSide::Long => Side::Sell,
+      PositionSide::Short => Side::Buy,
+    };
+    let mut order = synthetic_order(
+      position.asset_id,
+      &position.symbol,
+      position.asset_class,
+      side,
+      OrderType::Market,
+      Utc::now(),
+    );
+    order.qty = Some(NumberAsString::from_f64(qty));
+    order.position_intent = match side {
+      Side::Buy => PositionIntent::BuyToClose,
+      Side::Sell => PositionIntent::SellToClose,
+    };
+    self.store(&order)?;
+    Ok(order)
+  }
+
+  async fn close_position(&self, symbol_or_id: &str, param: &ClosePositionParam) -> anyhow::Result<ClosedPosition> {
+    let position = self.client.get_open_position_by_symbol_or_id(symbol_or_id).await?;
+    let available = position.qty.value().abs();
+    let qty = match param {
+      ClosePositionParam::Qty(qty) => *qty,
+      ClosePositionParam::Percentage(percentage) => available * percentage / 100.0,
+    };
+    if qty <= 0.0 || qty > available + 1e-9 {
+      bail!(
+        SimError::InsufficientQty {
+          requested: qty,
+          available,
+        }
+        .to_error_response()
+      );
+    }
+    Ok(closed_position(self.liquidation(&position, qty)?))
+  }
+}
+
+impl<C: OrderApi + AssetsApi + AccountApi> OrderApi for DryRun<C> {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    let result = self.submit(order).await;
+    self.journaled("create_order", serde_json::to_value(order)?, result)
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    self.client.get_all_orders(query_parameter).await
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    let result = self.cancel_all().await;
+    self.journaled("delete_all_orders", Value::Null, result)
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    match self.stored(|order| order.client_order_id == client_order_id) {
+      Some(order) => Ok(order),
+      None => self.client.get_order_by_client_order_id(client_order_id).await,
+    }
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    self.order(id).await
+  }
+
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    let result = self.replace(order_id, order).await;
+    self.journaled(
+      "replace_order_by_id",
+      json!({ "order_id": order_id, "body": order }),
+      result,
+    )
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    let result = self.cancel(order_id).await;
+    self.journaled("delete_order_by_id", json!({ "order_id": order_id }), result)
+  }
+}
+
+impl<C: PositionApi + OrderApi> PositionApi for DryRun<C> {
+  async fn get_all_open_positions(&self) -> anyhow::Result<Vec<Position>> {
+    self.client.get_all_open_positions().await
+  }
+
+  async fn get_open_position_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Position> {
+    self.client.get_open_position_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn close_open_position_by_symbol_or_id(
+    &self,
+    symbol_or_id: &str,
+    param: &ClosePositionParam,
+  ) -> anyhow::Result<ClosedPosition> {
+    let result = self.close_position(symbol_or_id, param).await;
+    self.journaled(
+      "close_open_position_by_symbol_or_id",
+      json!({ "symbol_or_id": symbol_or_id, "param": param }),
+      result,
+    )
+  }
+
+  async fn exercise_option_contract_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<()> {
+    let result = match self.client.get_open_position_by_symbol_or_id(symbol_or_id).await {
+      Ok(position) if position.asset_class != AssetClass::UsOption => {
+        Err(unprocessable("only option positions can be exercised").into())
+      }
+      Ok(_) => Ok(()),
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "exercise_option_contract_by_symbol_or_id",
+      json!({ "symbol_or_id": symbol_or_id }),
+      result,
+    )
+  }
+
+  async fn clost_all_open_positions(&self, cancel_orders: bool) -> anyhow::Result<Vec<ClosePositionInfo>> {
+    let result = async {
+      if cancel_orders {
+        self.cancel_all().await?;
+      }
+      let mut infos = vec![];
+      for position in self.client.get_all_open_positions().await? {
+        infos.push(ClosePositionInfo {
+          symbol: position.symbol.clone(),
+          status: "200".to_string(),
+          body: ClosePositionBody::Order(Box::new(self.liquidation(&position, position.qty.value().abs())?)),
+        });
+      }
+      Ok(infos)
+    }
+    .await;
+    self.journaled(
+      "clost_all_open_positions",
+      json!({ "cancel_orders": cancel_orders }),
+      result,
+    )
+  }
+}
+
+impl<C: CryptoFundingApi> CryptoFundingApi for DryRun<C> {
+  async fn get_all_crypto_funding_wallet(
+    &self,
+    request_parameter: &FundingWalletsParameter,
+  ) -> anyhow::Result<Vec<CryptoWalletInfo>> {
+    self.client.get_all_crypto_funding_wallet(request_parameter).await
+  }
+
+  async fn get_all_crypto_funding_transfer(&self) -> anyhow::Result<Vec<CryptoTransfer>> {
+    self.client.get_all_crypto_funding_transfer().await
+  }
+
+  async fn new_withdrawal(&self, request_body: &WithdrawalReqBody) -> anyhow::Result<CryptoTransfer> {
+    let result = async {
+      if request_body.amount.value() <= 0.0 || request_body.address.is_empty() {
+        bail!(unprocessable("a positive amount and an address are required"));
+      }
+      let gas_fee = self
+        .client
+        .return_estimate_gas_fee(&ReturnGasFeeParameter {
+          asset: Some(request_body.asset.clone()),
+          from_address: None,
+          to_address: Some(request_body.address.clone()),
+          amount: Some(Money::from_f64(request_body.amount.value())),
+        })
+        .await?;
+      Ok(CryptoTransfer {
+        id: Uuid::new_v4(),
+        tx_hash: String::new(),
+        direction: CryptoDirection::Outgoing,
+        status: CryptoStatus::Processing,
+        amount: Money::from_f64(request_body.amount.value()),
+        usd_value: Money::from_f64(0.0),
+        network_fee: gas_fee.fee,
+        fees: Money::from_f64(0.0),
+        chain: String::new(),
+        asset: request_body.asset.clone(),
+        from_address: String::new(),
+        to_address: request_body.address.clone(),
+        created_at: Utc::now(),
+      })
+    }
+    .await;
+    self.journaled("new_withdrawal", serde_json::to_value(request_body)?, result)
+  }
+
+  async fn get_crypto_funding_transfer(&self, transfer_id: &str) -> anyhow::Result<CryptoTransfer> {
+    self.client.get_crypto_funding_transfer(transfer_id).await
+  }
+
+  async fn get_whitelisted_addresses(&self) -> anyhow::Result<WhiteListedAddress> {
+    self.client.get_whitelisted_addresses().await
+  }
+
+  async fn new_whitelisted_address(
+    &self,
+    request_body: &WhitelistedAddressReqBody,
+  ) -> anyhow::Result<WhiteListedAddress> {
+    let result = Ok(WhiteListedAddress {
+      id: Uuid::new_v4().to_string(),
+      chain: String::new(),
+      asset: request_body.asset.clone(),
+      address: request_body.address.clone(),
+      status: AddressStatus::Pending,
+      created_at: Utc::now(),
+    });
+    self.journaled("new_whitelisted_address", serde_json::to_value(request_body)?, result)
+  }
+
+  async fn delete_whitelisted_address(&self, whitelisted_address_id: &str) -> anyhow::Result<()> {
+    self.journaled(
+      "delete_whitelisted_address",
+      json!({ "whitelisted_address_id": whitelisted_address_id }),
+      Ok(()),
+    )
+  }
+
+  async fn return_estimate_gas_fee(&self, request_parameter: &ReturnGasFeeParameter) -> anyhow::Result<GasFee> {
+    self.client.return_estimate_gas_fee(request_parameter).await
+  }
+}
+
+impl<C: WatchListApi + AssetsApi> DryRun<C> {
+  async fn assets(&self, symbols: &[String]) -> anyhow::Result<Vec<Asset>> {
+    let mut assets = vec![];
+    for symbol in symbols {
+      assets.push(self.client.get_asset_by_symbol_or_id(symbol).await?);
+    }
+    Ok(assets)
+  }
+
+  ///
+  ///`watch_list` renamed and holding `symbols`
+  async fn updated(&self, mut watch_list: WatchList, request_body: &WatchListReqBody) -> anyhow::Result<WatchList> {
+    watch_list.name = request_body.name.clone();
+    watch_list.assets = self.assets(&request_body.symbols).await?;
+    watch_list.updated_at = Utc::now();
+    Ok(watch_list)
+  }
+
+  async fn with_asset(&self, mut watch_list: WatchList, symbol: &str) -> anyhow::Result<WatchList> {
+    if watch_list.assets.iter().any(|asset| asset.symbol == symbol) {
+      bail!(unprocessable(&format!("{symbol} is already in the watchlist")));
+    }
+    watch_list
+      .assets
+      .push(self.client.get_asset_by_symbol_or_id(symbol).await?);
+    watch_list.updated_at = Utc::now();
+    Ok(watch_list)
+  }
+}
+
+impl<C: WatchListApi + AssetsApi + AccountApi> WatchListApi for DryRun<C> {
+  async fn get_all_watch_lists(&self) -> anyhow::Result<Vec<BasicWatchListInfo>> {
+    self.client.get_all_watch_lists().await
+  }
+
+  async fn create_watch_list(&self, request_body: &WatchListReqBody) -> anyhow::Result<WatchList> {
+    let result = async {
+      let now = Utc::now();
+      Ok(WatchList {
+        id: Uuid::new_v4(),
+        account_id: self.client.get_account().await?.id,
+        created_at: now,
+        updated_at: now,
+        name: request_body.name.clone(),
+        assets: self.assets(&request_body.symbols).await?,
+      })
+    }
+    .await;
+    self.journaled("create_watch_list", serde_json::to_value(request_body)?, result)
+  }
+
+  async fn get_watch_list_by_id(&self, watchlist_id: &Uuid) -> anyhow::Result<WatchList> {
+    self.client.get_watch_list_by_id(watchlist_id).await
+  }
+
+  async fn update_watch_list_by_id(
+    &self,
+    watchlist_id: &Uuid,
+    request_body: &WatchListReqBody,
+  ) -> anyhow::Result<WatchList> {
+    let result = match self.client.get_watch_list_by_id(watchlist_id).await {
+      Ok(watch_list) => self.updated(watch_list, request_body).await,
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "update_watch_list_by_id",
+      json!({ "watchlist_id": watchlist_id, "body": request_body }),
+      result,
+    )
+  }
+
+  async fn add_asset_to_watch_list(&self, watchlist_id: &Uuid, symbol: &AddAssetReqBody) -> anyhow::Result<WatchList> {
+    let result = match self.client.get_watch_list_by_id(watchlist_id).await {
+      Ok(watch_list) => self.with_asset(watch_list, &symbol.symbol).await,
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "add_asset_to_watch_list",
+      json!({ "watchlist_id": watchlist_id, "body": symbol }),
+      result,
+    )
+  }
+
+  async fn delete_watch_list_by_id(&self, watchlist_id: &Uuid) -> anyhow::Result<()> {
+    let result = self.client.get_watch_list_by_id(watchlist_id).await.map(|_| ());
+    self.journaled(
+      "delete_watch_list_by_id",
+      json!({ "watchlist_id": watchlist_id }),
+      result,
+    )
+  }
+
+  async fn get_watch_list_by_name(&self, watchlist_name: &str) -> anyhow::Result<WatchList> {
+    self.client.get_watch_list_by_name(watchlist_name).await
+  }
+
+  async fn update_watch_list_by_name(
+    &self,
+    watchlist_name: &str,
+    request_body: &WatchListReqBody,
+  ) -> anyhow::Result<WatchList> {
+    let result = match self.client.get_watch_list_by_name(watchlist_name).await {
+      Ok(watch_list) => self.updated(watch_list, request_body).await,
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "update_watch_list_by_name",
+      json!({ "name": watchlist_name, "body": request_body }),
+      result,
+    )
+  }
+
+  async fn add_asset_to_watch_list_by_name(
+    &self,
+    watchlist_name: &str,
+    symbol: &AddAssetReqBody,
+  ) -> anyhow::Result<WatchList> {
+    let result = match self.client.get_watch_list_by_name(watchlist_name).await {
+      Ok(watch_list) => self.with_asset(watch_list, &symbol.symbol).await,
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "add_asset_to_watch_list_by_name",
+      json!({ "name": watchlist_name, "body": symbol }),
+      result,
+    )
+  }
+
+  async fn delete_watch_list_by_name(&self, name: &str) -> anyhow::Result<()> {
+    let result = self.client.get_watch_list_by_name(name).await.map(|_| ());
+    self.journaled("delete_watch_list_by_name", json!({ "name": name }), result)
+  }
+
+  async fn delete_asset_from_watch_list(&self, watchlist_id: &Uuid, symbol: &str) -> anyhow::Result<WatchList> {
+    let result = match self.client.get_watch_list_by_id(watchlist_id).await {
+      Ok(mut watch_list) => match watch_list.assets.iter().position(|asset| asset.symbol == symbol) {
+        Some(index) => {
+          watch_list.assets.remove(index);
+          watch_list.updated_at = Utc::now();
+          Ok(watch_list)
+        }
+        None => Err(SimError::NotFound(symbol.to_string()).to_error_response().into()),
+      },
+      Err(error) => Err(error),
+    };
+    self.journaled(
+      "delete_asset_from_watch_list",
+      json!({ "watchlist_id": watchlist_id, "symbol": symbol }),
+      result,
+    )
+  }
+}
+
+impl<C: AccountApi> AccountApi for DryRun<C> {
+  async fn get_account(&self) -> anyhow::Result<Account> {
+    self.client.get_account().await
+  }
+}
+
+impl<C: AssetsApi> AssetsApi for DryRun<C> {
+  async fn get_assets(&self, query_parameter: &AssetsQueryParameter) -> anyhow::Result<Vec<Asset>> {
+    self.client.get_assets(query_parameter).await
+  }
+
+  async fn get_asset_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Asset> {
+    self.client.get_asset_by_symbol_or_id(symbol_or_id).await
+  }
+}
+
+impl<C: CalendarApi> CalendarApi for DryRun<C> {
+  async fn get_market_calendar_info(
+    &self,
+    query_parameter: &CalendarApiQueryParameter,
+  ) -> anyhow::Result<Vec<MarketCalendar>> {
+    self.client.get_market_calendar_info(query_parameter).await
+  }
+}
+
+impl<C: ClockApi> ClockApi for DryRun<C> {
+  async fn get_market_clock_info(&self) -> anyhow::Result<MarketClock> {
+    self.client.get_market_clock_info().await
+  }
+}
+
+fn unprocessable(message: &str) -> crate::models::ErrorResponse {
+  SimError::Unprocessable(message.to_string()).to_error_response()
+}
+
+///
+///Buying power an order takes, unknown for market orders sized by qty
+fn cost(notional: Option<f64>, qty: Option<f64>, price: Option<f64>, class: AssetClass) -> Option<f64> {
+  let multiplier = if class == AssetClass::UsOption { 100.0 } else { 1.0 };
+  match (notional, qty, price) {
+    (Some(notional), _, _) => Some(notional),
+    (None, Some(qty), Some(price)) => Some(qty * price * multiplier),
+    _ => None,
+  }
+}
+
+fn is_open(status: OrderStatus) -> bool {
+  !matches!(
+    status,
+    OrderStatus::Filled
+      | OrderStatus::DoneForDay
+      | OrderStatus::Canceled
+      | OrderStatus::Expired
+      | OrderStatus::Replaced
+      | OrderStatus::Stopped
+      | OrderStatus::Rejected
+  )
+}
+
+///
+///The checks the API runs on a new order before accepting it
+fn validate(request: &OrderRequestBody, asset: &Asset) -> Result<(), String> {
+  if !asset.tradable {
+    return Err(format!("asset {} is not tradable", asset.symbol));
+  }
+  match (&request.qty, &request.notional) {
+    (Some(qty), None) if qty.value() > 0.0 => {}
+    (None, Some(notional)) if notional.value() > 0.0 && request._type == OrderType::Market => {}
+    (None, Some(_)) if request._type != OrderType::Market => {
+      return Err("notional orders must be market orders".to_string());
+    }
+    _ => return Err("either a positive qty or notional is required".to_string()),
+  }
+  match request._type {
+    OrderType::Limit if request.limit_price.is_none() => return Err("limit orders require a limit_price".to_string()),
+    OrderType::Stop if request.stop_price.is_none() => return Err("stop orders require a stop_price".to_string()),
+    OrderType::StopLimit if request.limit_price.is_none() || request.stop_price.is_none() => {
+      return Err("stop limit orders require a stop_price and a limit_price".to_string());
+    }
+    OrderType::TrailingStop if request.trail_price.is_some() == request.trail_percent.is_some() => {
+      return Err("trailing stop orders require either trail_price or trail_percent".to_string());
+    }
+    _ => {}
+  }
+  if request.extended_hours
+    && asset.class != AssetClass::Crypto
+    && (request._type != OrderType::Limit || request.time_in_force != TimeInForce::DAY)
+  {
+    return Err("extended hours orders must be DAY limit orders".to_string());
+  }
+  match (request.order_class, &request.take_profit, &request.stop_loss) {
+    (Some(OrderClass::Bracket), Some(_), Some(_))
+    | (Some(OrderClass::Otc), Some(_), None)
+    | (Some(OrderClass::Otc), None, Some(_)) => {}
+    (Some(OrderClass::Oco), Some(_), Some(_)) if request._type == OrderType::Limit => {}
+    (Some(OrderClass::Bracket), _, _) => return Err("bracket orders require take_profit and stop_loss".to_string()),
+    (Some(OrderClass::Otc), _, _) => return Err("oto orders require either take_profit or stop_loss".to_string()),
+    (Some(OrderClass::Oco), _, _) => {
+      return Err("oco orders must be limit orders with take_profit and stop_loss".to_string());
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+///
+///A freshly accepted order without quantity or prices
+fn synthetic_order(
+  asset_id: Uuid,
+  symbol: &str,
+  asset_class: AssetClass,
+  side: Side,
+  order_type: OrderType,
+  now: DateTime<Utc>,
+) -> Order {
+  Order {
+    id: Uuid::new_v4(),
+    client_order_id: Uuid::new_v4().to_string(),
+    created_at: Some(now),
+    updated_at: Some(now),
+    submitted_at: Some(now),
+    filled_at: None,
+    expired_at: None,
+    canceled_at: None,
+    failed_at: None,
+    replaced_at: None,
+    replaced_by: None,
+    replaces: None,
+    asset_id,
+    symbol: symbol.to_string(),
+    asset_class,
+    national: None,
+    qty: None,
+    filled_qty: Some(Money::from_f64(0.0)),
+    filled_avg_price: None,
+    order_class: OrderClass::Simple,
+    _type: order_type,
+    side,
+    time_in_force: TimeInForce::DAY,
+    limit_price: None,
+    stop_price: None,
+    status: OrderStatus::Accepted,
+    extended_hours: false,
+    legs: None,
+    trail_price: None,
+    trail_percent: None,
+    hwm: None,
+    position_intent: match side {
+      Side::Buy => PositionIntent::BuyToOpen,
+      Side::Sell => PositionIntent::SellToClose,
+    },
+  }
+}
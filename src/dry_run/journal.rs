@@ -0,0 +1,194 @@
+use crate::models::{
+  Order,
+  enums::{
+    OrderType,
+    Side,
+  },
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use serde_json::Value;
+use std::{
+  fs::OpenOptions,
+  io::Write,
+  path::Path,
+};
+
+///
+///One mutating call intercepted by [`super::DryRun`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+  pub timestamp: DateTime<Utc>,
+  ///
+  ///Name of the trait method, e.g. `create_order`
+  pub call: String,
+  pub request: Value,
+  ///
+  ///The synthetic response, absent when the call was rejected
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub response: Option<Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl JournalEntry {
+  ///
+  ///The orders this call would have placed
+  pub fn order_intents(&self) -> Vec<OrderIntent> {
+    let Some(response) = &self.response else {
+      return vec![];
+    };
+    match self.call.as_str() {
+      "create_order" | "replace_order_by_id" | "close_open_position_by_symbol_or_id" => {
+        OrderIntent::from_value(response).into_iter().collect()
+      }
+      "clost_all_open_positions" => response
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|info| OrderIntent::from_value(&info["body"]))
+        .collect(),
+      _ => vec![],
+    }
+  }
+}
+
+///
+///What an order asked for, without ids, timestamps or its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderIntent {
+  pub symbol: String,
+  pub side: Side,
+  pub order_type: OrderType,
+  pub qty: Option<f64>,
+  pub notional: Option<f64>,
+  pub limit_price: Option<f64>,
+  pub stop_price: Option<f64>,
+}
+
+impl OrderIntent {
+  ///
+  ///Read the intent from the JSON of an order or a closed position
+  fn from_value(value: &Value) -> Option<Self> {
+    let number = |key: &str| {
+      let number = match &value[key] {
+        Value::String(text) => text.parse::<f64>().ok(),
+        value => value.as_f64(),
+      };
+      // closed positions answer with zero for prices that are not set
+      number.filter(|number| *number != 0.0)
+    };
+    Some(OrderIntent {
+      symbol: value["symbol"].as_str()?.to_string(),
+      side: serde_json::from_value(value["side"].clone()).ok()?,
+      order_type: serde_json::from_value(value["type"].clone()).ok()?,
+      qty: number("qty"),
+      notional: number("notional").or(number("national")),
+      limit_price: number("limit_price"),
+      stop_price: number("stop_price"),
+    })
+  }
+}
+
+impl From<&Order> for OrderIntent {
+  fn from(order: &Order) -> Self {
+    OrderIntent {
+      symbol: order.symbol.clone(),
+      side: order.side,
+      order_type: order._type,
+      qty: order.qty.as_ref().map(|qty| qty.value()),
+      notional: order.national.as_ref().and_then(|notional| notional.parse().ok()),
+      limit_price: order.limit_price.as_ref().and_then(|price| price.parse().ok()),
+      stop_price: order.stop_price.as_ref().and_then(|price| price.parse().ok()),
+    }
+  }
+}
+
+///
+///Orders only one side placed, see [`Journal::diff_orders`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderDiff {
+  pub dry_run_only: Vec<OrderIntent>,
+  pub live_only: Vec<OrderIntent>,
+}
+
+impl OrderDiff {
+  pub fn is_empty(&self) -> bool {
+    self.dry_run_only.is_empty() && self.live_only.is_empty()
+  }
+}
+
+///
+///Record of every mutating call of a dry run, stored as JSON lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Journal {
+  entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+  pub fn new() -> Self {
+    Journal::default()
+  }
+
+  ///
+  ///Read a journal written by [`super::DryRun::with_journal_file`]
+  pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    let content = std::fs::read_to_string(path)?;
+    let entries = content
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .map(serde_json::from_str)
+      .collect::<Result<_, _>>()?;
+    Ok(Journal { entries })
+  }
+
+  ///
+  ///Append every entry to the JSON lines file at `path`
+  pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for entry in &self.entries {
+      writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    Ok(())
+  }
+
+  pub fn push(&mut self, entry: JournalEntry) {
+    self.entries.push(entry);
+  }
+
+  pub fn entries(&self) -> &[JournalEntry] {
+    &self.entries
+  }
+
+  ///
+  ///Every order the dry run would have placed, in order
+  pub fn order_intents(&self) -> Vec<OrderIntent> {
+    self.entries.iter().flat_map(JournalEntry::order_intents).collect()
+  }
+
+  ///
+  ///Compare the orders of the dry run with the orders live trading placed over the same period,
+  /// each order on one side matches at most one identical order on the other
+  pub fn diff_orders(&self, live: &[Order]) -> OrderDiff {
+    let mut live_only: Vec<OrderIntent> = live.iter().map(OrderIntent::from).collect();
+    let mut dry_run_only = vec![];
+    for intent in self.order_intents() {
+      match live_only.iter().position(|live| *live == intent) {
+        Some(index) => {
+          live_only.remove(index);
+        }
+        None => dry_run_only.push(intent),
+      }
+    }
+    OrderDiff {
+      dry_run_only,
+      live_only,
+    }
+  }
+}
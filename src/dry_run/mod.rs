@@ -0,0 +1,5 @@
+mod client;
+mod journal;
+
+pub use client::*;
+pub use journal::*;
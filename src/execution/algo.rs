@@ -0,0 +1,638 @@
+use crate::{
+  api::{
+    ClockApi,
+    OrderApi,
+    OrderRequestBody,
+  },
+  execution::VolumeProfile,
+  models::{
+    MarketDataMessage,
+    Order,
+    OrderStatus,
+    Subscription,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  stream::MarketDataStream,
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  TimeDelta,
+  Utc,
+};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+const EPSILON: f64 = 1e-9;
+
+///
+///Children rejected in a row before the algo stops with [`ExecutionStatus::Failed`]
+const MAX_REJECTIONS: u32 = 3;
+
+///
+///How an [`ExecutionAlgo`] spreads the parent order over its window
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionStrategy {
+  ///
+  ///Equal slices over the window
+  Twap,
+  ///
+  ///Slices following a historical intraday volume profile
+  Vwap(VolumeProfile),
+  ///
+  ///A share of the market volume, `0.1` for 10%, counted from the trades [`ExecutionAlgo::run`]
+  /// follows or reported with [`ExecutionAlgo::record_market_volume`]
+  Pov { participation: f64 },
+  ///
+  ///One child of at most `display_qty` at a time, the next one once it is done
+  Iceberg { display_qty: f64 },
+}
+
+impl ExecutionStrategy {
+  pub fn name(&self) -> &'static str {
+    match self {
+      ExecutionStrategy::Twap => "twap",
+      ExecutionStrategy::Vwap(_) => "vwap",
+      ExecutionStrategy::Pov { .. } => "pov",
+      ExecutionStrategy::Iceberg { .. } => "iceberg",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+  Working,
+  ///
+  ///The whole parent quantity filled
+  Completed,
+  ///
+  ///The window ended first, the open child was canceled
+  Expired,
+  ///
+  ///The clock reported the market closed, the open child was canceled
+  MarketClosed,
+  ///
+  ///Stopped with [`ExecutionAlgo::cancel`]
+  Canceled,
+  ///
+  ///Children were rejected too often in a row, e.g. for an unknown symbol or a lack of buying
+  /// power, the open child was canceled
+  Failed,
+}
+
+impl ExecutionStatus {
+  pub fn is_done(&self) -> bool {
+    *self != ExecutionStatus::Working
+  }
+}
+
+///
+///One child order of an [`ExecutionReport`], `error` is set when it was never accepted
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChildOrderReport {
+  pub client_order_id: String,
+  pub order_id: Option<Uuid>,
+  pub qty: f64,
+  pub limit_price: Option<f64>,
+  pub submitted_at: DateTime<Utc>,
+  pub status: Option<OrderStatus>,
+  pub filled_qty: f64,
+  pub filled_avg_price: Option<f64>,
+  pub error: Option<String>,
+}
+
+impl ChildOrderReport {
+  fn is_open(&self) -> bool {
+    self.order_id.is_some() && !self.status.is_some_and(|status| status.is_final())
+  }
+
+  fn update(&mut self, order: &Order) {
+    self.order_id = Some(order.id);
+    self.status = Some(order.status);
+    self.filled_qty = order.filled_qty.as_ref().map(Money::value).unwrap_or_default();
+    self.filled_avg_price = order.filled_avg_price.as_ref().map(Money::value);
+  }
+}
+
+///
+///Progress of an [`ExecutionAlgo`] and the cost of its fills against the arrival price
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionReport {
+  pub strategy: &'static str,
+  pub symbol: String,
+  pub side: Side,
+  pub qty: f64,
+  pub filled_qty: f64,
+  pub remaining_qty: f64,
+  pub avg_fill_price: Option<f64>,
+  pub arrival_price: Option<f64>,
+  ///
+  ///Average fill price against the arrival price in basis points, positive when the fills cost
+  /// more than trading everything on arrival
+  pub slippage_bps: Option<f64>,
+  ///
+  ///The same cost in dollars over the filled quantity
+  pub slippage: Option<f64>,
+  pub status: ExecutionStatus,
+  pub children: Vec<ChildOrderReport>,
+}
+
+///
+///Works a large parent order by slicing it into child market or limit orders over a time window
+///
+///The algo is a state machine driven by [`ExecutionAlgo::step`], which is given the current time
+/// so the same calls always produce the same orders, against the simulator as much as against the
+/// API. [`ExecutionAlgo::run`] drives it from the market clock until the parent is done, the
+/// window ends or the market closes. TWAP, VWAP and POV size a new child at the start of every
+/// slice so the parent is on schedule by the end of the slice; the unfilled rest of the previous
+/// child is canceled first, so partial fills roll into the next slice, and whatever a canceled
+/// child may still fill until the API closes it is left out of the next one. Every child's
+/// `client_order_id` starts with the algo's prefix.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::{
+///   execution::{
+///     ExecutionAlgo,
+///     ExecutionStrategy,
+///   },
+///   prelude::{
+///     Client,
+///     enums::Side,
+///   },
+/// };
+/// use chrono::{
+///   TimeDelta,
+///   Utc,
+/// };
+/// use std::time::Duration;
+///
+/// # async fn example(client: Client) -> anyhow::Result<()> {
+/// let start = Utc::now();
+/// let mut algo = ExecutionAlgo::new(
+///   ExecutionStrategy::Twap,
+///   "AAPL",
+///   Side::Buy,
+///   5_000.0,
+///   start,
+///   start + TimeDelta::hours(1),
+/// )?
+/// .with_slice_interval(Duration::from_secs(300))
+/// .with_arrival_price(187.25);
+/// let report = algo.run(&client, None).await?;
+/// println!(
+///   "{:?}: {:.1} bps",
+///   report.status,
+///   report.slippage_bps.unwrap_or_default()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecutionAlgo {
+  strategy: ExecutionStrategy,
+  symbol: String,
+  side: Side,
+  qty: f64,
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  slice_interval: TimeDelta,
+  limit_price: Option<f64>,
+  arrival_price: Option<f64>,
+  client_order_id_prefix: String,
+  time_in_force: TimeInForce,
+  lot_size: f64,
+  poll_interval: Duration,
+  market_volume: f64,
+  last_slice: Option<i64>,
+  working: Option<usize>,
+  rejections: u32,
+  children: Vec<ChildOrderReport>,
+  status: ExecutionStatus,
+}
+
+impl ExecutionAlgo {
+  ///
+  ///Algo working `qty` of `symbol` between `start` and `end`
+  pub fn new(
+    strategy: ExecutionStrategy,
+    symbol: impl Into<String>,
+    side: Side,
+    qty: f64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> anyhow::Result<Self> {
+    if !qty.is_finite() || qty <= 0.0 {
+      bail!("parent quantity must be positive, got {qty}");
+    }
+    if end <= start {
+      bail!("window must end after it starts, got {start} to {end}");
+    }
+    match strategy {
+      ExecutionStrategy::Pov { participation } if !(participation > 0.0 && participation <= 1.0) => {
+        bail!("participation must be within (0, 1], got {participation}")
+      }
+      ExecutionStrategy::Iceberg { display_qty } if !display_qty.is_finite() || display_qty <= 0.0 => {
+        bail!("displayed quantity must be positive, got {display_qty}")
+      }
+      _ => {}
+    }
+    let id = Uuid::new_v4().simple().to_string();
+    Ok(ExecutionAlgo {
+      client_order_id_prefix: format!("{}-{}", strategy.name(), &id[..8]),
+      strategy,
+      symbol: symbol.into(),
+      side,
+      qty,
+      start,
+      end,
+      slice_interval: TimeDelta::minutes(1),
+      limit_price: None,
+      arrival_price: None,
+      time_in_force: TimeInForce::DAY,
+      lot_size: 1.0,
+      poll_interval: Duration::from_secs(1),
+      market_volume: 0.0,
+      last_slice: None,
+      working: None,
+      rejections: 0,
+      children: vec![],
+      status: ExecutionStatus::Working,
+    })
+  }
+
+  ///
+  ///Length of a TWAP, VWAP or POV slice, one minute by default
+  pub fn with_slice_interval(mut self, slice_interval: Duration) -> Self {
+    self.slice_interval = TimeDelta::from_std(slice_interval)
+      .unwrap_or(TimeDelta::MAX)
+      .max(TimeDelta::seconds(1));
+    self
+  }
+
+  ///
+  ///Child orders become limit orders at this price, market orders by default
+  pub fn with_limit_price(mut self, limit_price: f64) -> Self {
+    self.limit_price = Some(limit_price);
+    self
+  }
+
+  ///
+  ///Price when the parent order arrived, the slippage benchmark; the first fill is used without
+  /// one
+  pub fn with_arrival_price(mut self, arrival_price: f64) -> Self {
+    self.arrival_price = Some(arrival_price);
+    self
+  }
+
+  ///
+  ///Children are tagged `{prefix}-{n}`, the strategy and a random id by default
+  pub fn with_client_order_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+    self.client_order_id_prefix = prefix.into();
+    self
+  }
+
+  pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+    self.time_in_force = time_in_force;
+    self
+  }
+
+  ///
+  ///Children are rounded down to multiples of it, whole shares by default; the last child takes
+  /// whatever remains
+  pub fn with_lot_size(mut self, lot_size: f64) -> Self {
+    self.lot_size = lot_size.max(EPSILON);
+    self
+  }
+
+  ///
+  ///How often [`ExecutionAlgo::run`] steps, one second by default
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  pub fn status(&self) -> ExecutionStatus {
+    self.status
+  }
+
+  pub fn client_order_id_prefix(&self) -> &str {
+    &self.client_order_id_prefix
+  }
+
+  ///
+  ///Add market volume traded since the last call, what a POV algo participates in
+  pub fn record_market_volume(&mut self, volume: f64) {
+    self.market_volume += volume.max(0.0);
+  }
+
+  pub fn filled_qty(&self) -> f64 {
+    self.children.iter().map(|child| child.filled_qty).sum()
+  }
+
+  pub fn remaining_qty(&self) -> f64 {
+    (self.qty - self.filled_qty()).max(0.0)
+  }
+
+  ///
+  ///Refresh the open children and submit the next one when it is due at `now`
+  pub async fn step<C: OrderApi>(&mut self, client: &C, now: DateTime<Utc>) -> anyhow::Result<ExecutionStatus> {
+    if self.status.is_done() {
+      return Ok(self.status);
+    }
+    self.refresh(client).await;
+    if self.remaining_qty() <= EPSILON {
+      self.status = ExecutionStatus::Completed;
+      return Ok(self.status);
+    }
+    if now >= self.end {
+      self.finish(client, ExecutionStatus::Expired).await;
+      return Ok(self.status);
+    }
+    if now < self.start {
+      return Ok(self.status);
+    }
+
+    let remaining = self.remaining_qty() - self.open_qty();
+    if let ExecutionStrategy::Iceberg { display_qty } = self.strategy {
+      if self.working.is_none() {
+        let qty = self.round(display_qty.min(remaining), remaining);
+        self.submit(client, qty, now).await;
+      }
+      return Ok(self.status);
+    }
+
+    let slice = (now - self.start).num_milliseconds() / self.slice_interval.num_milliseconds();
+    if self.last_slice == Some(slice) {
+      return Ok(self.status);
+    }
+    self.last_slice = Some(slice);
+    self.cancel_working(client).await;
+    let slice_end = self
+      .slice_interval
+      .checked_mul((slice + 1) as i32)
+      .and_then(|offset| self.start.checked_add_signed(offset))
+      .unwrap_or(self.end)
+      .min(self.end);
+    // a canceled child can still fill until the API closes it, so it counts as on its way
+    let remaining = self.remaining_qty() - self.open_qty();
+    let qty = self.round(
+      self.target_qty(slice_end) - self.filled_qty() - self.open_qty(),
+      remaining,
+    );
+    self.submit(client, qty, now).await;
+    Ok(self.status)
+  }
+
+  ///
+  ///Step from the market clock until the parent is done, cutting the window short at the next
+  /// close and stopping as soon as the market is closed or the children keep being rejected
+  ///
+  ///Between steps the trades of the symbol on `trades` are added to the market volume, a POV
+  /// algo needs them since nothing else can record volume while it runs.
+  pub async fn run<C>(
+    &mut self,
+    client: &C,
+    mut trades: Option<&mut MarketDataStream>,
+  ) -> anyhow::Result<ExecutionReport>
+  where
+    C: OrderApi + ClockApi,
+  {
+    if let ExecutionStrategy::Pov { .. } = self.strategy
+      && trades.is_none()
+    {
+      bail!("a POV algo needs a trade stream to follow the market volume");
+    }
+    if let Some(trades) = trades.as_mut() {
+      trades.subscribe(Subscription {
+        trades: vec![self.symbol.clone()],
+        ..Default::default()
+      })?;
+    }
+    while !self.status.is_done() {
+      let clock = client.get_market_clock_info().await?;
+      if !clock.is_open {
+        self.finish(client, ExecutionStatus::MarketClosed).await;
+        break;
+      }
+      self.end = self.end.min(clock.next_close.with_timezone(&Utc));
+      if self.step(client, clock.timestamp.with_timezone(&Utc)).await?.is_done() {
+        break;
+      }
+      self.follow_trades(&mut trades).await?;
+    }
+    Ok(self.report())
+  }
+
+  ///
+  ///Cancel the open children and stop, fills so far stay
+  pub async fn cancel<C: OrderApi>(&mut self, client: &C) {
+    if !self.status.is_done() {
+      self.finish(client, ExecutionStatus::Canceled).await;
+    }
+  }
+
+  pub fn report(&self) -> ExecutionReport {
+    let filled_qty = self.filled_qty();
+    let filled_value: f64 = self
+      .children
+      .iter()
+      .map(|child| child.filled_qty * child.filled_avg_price.unwrap_or_default())
+      .sum();
+    let avg_fill_price = (filled_qty > EPSILON).then(|| filled_value / filled_qty);
+    let arrival_price = self.arrival_price.or_else(|| {
+      self
+        .children
+        .iter()
+        .find(|child| child.filled_qty > EPSILON)
+        .and_then(|child| child.filled_avg_price)
+    });
+    let sign = match self.side {
+      Side::Buy => 1.0,
+      Side::Sell => -1.0,
+    };
+    let cost = match (avg_fill_price, arrival_price) {
+      (Some(avg), Some(arrival)) if arrival > 0.0 => Some(sign * (avg - arrival) / arrival),
+      _ => None,
+    };
+    ExecutionReport {
+      strategy: self.strategy.name(),
+      symbol: self.symbol.clone(),
+      side: self.side,
+      qty: self.qty,
+      filled_qty,
+      remaining_qty: self.remaining_qty(),
+      avg_fill_price,
+      arrival_price,
+      slippage_bps: cost.map(|cost| cost * 10_000.0),
+      slippage: cost
+        .zip(arrival_price)
+        .map(|(cost, arrival)| cost * arrival * filled_qty),
+      status: self.status,
+      children: self.children.clone(),
+    }
+  }
+
+  ///
+  ///Count the volume traded in the symbol until the next step is due
+  async fn follow_trades(&mut self, trades: &mut Option<&mut MarketDataStream>) -> anyhow::Result<()> {
+    let next_step = sleep(self.poll_interval);
+    tokio::pin!(next_step);
+    loop {
+      tokio::select! {
+        _ = &mut next_step => return Ok(()),
+        message = next_message(trades) => match message {
+          Some(MarketDataMessage::Trade(trade)) if trade.symbol == self.symbol => self.record_market_volume(trade.size),
+          Some(_) => {}
+          None => bail!("market data stream closed while the algo was working"),
+        },
+      }
+    }
+  }
+
+  ///
+  ///Quantity that should have filled by `at`
+  fn target_qty(&self, at: DateTime<Utc>) -> f64 {
+    let fraction = match &self.strategy {
+      ExecutionStrategy::Vwap(profile) => profile.fraction(self.start, self.end, at),
+      ExecutionStrategy::Pov { participation } => return (participation * self.market_volume).min(self.qty),
+      _ => (at - self.start).num_milliseconds() as f64 / (self.end - self.start).num_milliseconds() as f64,
+    };
+    self.qty * fraction.clamp(0.0, 1.0)
+  }
+
+  ///
+  ///Unfilled quantity of the children the API has not closed yet
+  fn open_qty(&self) -> f64 {
+    self
+      .children
+      .iter()
+      .filter(|child| child.is_open())
+      .map(|child| (child.qty - child.filled_qty).max(0.0))
+      .sum()
+  }
+
+  ///
+  ///Whole lots of `qty`, or all of `remaining` when that is what is left
+  fn round(&self, qty: f64, remaining: f64) -> f64 {
+    if remaining <= EPSILON {
+      return 0.0;
+    }
+    if qty >= remaining - EPSILON {
+      return remaining;
+    }
+    ((qty / self.lot_size + EPSILON).floor() * self.lot_size).max(0.0)
+  }
+
+  async fn submit<C: OrderApi>(&mut self, client: &C, qty: f64, now: DateTime<Utc>) {
+    if qty <= EPSILON {
+      return;
+    }
+    let mut child = ChildOrderReport {
+      client_order_id: format!("{}-{}", self.client_order_id_prefix, self.children.len()),
+      order_id: None,
+      qty,
+      limit_price: self.limit_price,
+      submitted_at: now,
+      status: None,
+      filled_qty: 0.0,
+      filled_avg_price: None,
+      error: None,
+    };
+    let order = OrderRequestBody {
+      symbol: self.symbol.clone(),
+      qty: Some(NumberAsString::from_f64(qty)),
+      notional: None,
+      side: self.side,
+      _type: match self.limit_price {
+        Some(_) => OrderType::Limit,
+        None => OrderType::Market,
+      },
+      time_in_force: self.time_in_force,
+      limit_price: self.limit_price.map(Money::from_f64),
+      stop_price: None,
+      trail_price: None,
+      trail_percent: None,
+      extended_hours: false,
+      client_order_id: Some(child.client_order_id.clone()),
+      order_class: None,
+      legs: vec![],
+      take_profit: None,
+      stop_loss: None,
+      position_intent: None,
+    };
+    match client.create_order(&order).await {
+      Ok(order) => child.update(&order),
+      Err(error) => child.error = Some(error.to_string()),
+    }
+    let open = child.is_open();
+    let rejected = child.error.is_some() || child.status == Some(OrderStatus::Rejected);
+    self.children.push(child);
+    if open {
+      self.working = Some(self.children.len() - 1);
+    }
+    self.rejections = match rejected {
+      true => self.rejections + 1,
+      false => 0,
+    };
+    if self.rejections >= MAX_REJECTIONS {
+      self.finish(client, ExecutionStatus::Failed).await;
+    }
+  }
+
+  ///
+  ///Fetch every open child, including canceled ones the API has not closed yet
+  async fn refresh<C: OrderApi>(&mut self, client: &C) {
+    for child in self.children.iter_mut().filter(|child| child.is_open()) {
+      if let Some(order_id) = child.order_id
+        && let Ok(order) = client.get_order_by_id(&order_id).await
+      {
+        child.update(&order);
+      }
+    }
+    if self.working.is_some_and(|index| !self.children[index].is_open()) {
+      self.working = None;
+    }
+  }
+
+  async fn cancel_working<C: OrderApi>(&mut self, client: &C) {
+    let Some(index) = self.working.take() else {
+      return;
+    };
+    let child = &mut self.children[index];
+    if let Some(order_id) = child.order_id {
+      let _ = client.delete_order_by_id(&order_id).await;
+      if let Ok(order) = client.get_order_by_id(&order_id).await {
+        child.update(&order);
+      }
+    }
+  }
+
+  async fn finish<C: OrderApi>(&mut self, client: &C, status: ExecutionStatus) {
+    self.cancel_working(client).await;
+    self.refresh(client).await;
+    self.status = match self.remaining_qty() <= EPSILON {
+      true => ExecutionStatus::Completed,
+      false => status,
+    };
+  }
+}
+
+///
+///Next message of an optional stream, never ready without one
+pub(crate) async fn next_message(stream: &mut Option<&mut MarketDataStream>) -> Option<MarketDataMessage> {
+  match stream {
+    Some(stream) => stream.next().await,
+    None => std::future::pending().await,
+  }
+}
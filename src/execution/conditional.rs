@@ -0,0 +1,679 @@
+use super::{
+  algo::next_message,
+  emulated::{
+    read_state,
+    write_state,
+  },
+};
+use crate::{
+  api::{
+    CalendarApi,
+    CalendarApiQueryParameter,
+    OrderApi,
+    OrderRequestBody,
+  },
+  history::{
+    market_time,
+    trading_date,
+  },
+  models::{
+    MarketDataMessage,
+    OrderStatus,
+    Subscription,
+    TimeInForce,
+  },
+  stream::MarketDataStream,
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Days,
+  NaiveDate,
+  NaiveTime,
+  TimeDelta,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::{
+  collections::BTreeMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+  time::Duration,
+};
+use uuid::Uuid;
+
+///
+///Submissions of an order before the conditional order is marked
+/// [`ConditionalOrderStatus::Failed`]
+const MAX_ATTEMPTS: u32 = 3;
+
+///
+///Opening auction orders are refused from this long before the open
+const OPG_CUTOFF: TimeDelta = TimeDelta::minutes(2);
+
+///
+///Time of day in New York from which opening auction orders for the next session are accepted
+const OPG_ACCEPTED_FROM: NaiveTime = NaiveTime::from_hms_opt(19, 0, 0).unwrap();
+
+///
+///Time of day in New York when pre-market trading starts
+const PRE_MARKET_OPEN: NaiveTime = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+
+///
+///How long after-hours trading lasts past the close
+const AFTER_HOURS: TimeDelta = TimeDelta::hours(4);
+
+///
+///What a [`ConditionalOrder`] waits for before its order is submitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+  ///
+  ///A trade of `symbol` at or above `price` after one below it, `symbol` need not be the symbol
+  /// of the order
+  PriceAbove {
+    symbol: String,
+    price: f64,
+  },
+  ///
+  ///A trade of `symbol` at or below `price` after one above it
+  PriceBelow {
+    symbol: String,
+    price: f64,
+  },
+  At {
+    time: DateTime<Utc>,
+  },
+  ///
+  ///Submitted on the next tick, with an expiry this emulates a good till date order
+  Immediately,
+}
+
+impl Condition {
+  pub fn price_above(symbol: &str, price: f64) -> Self {
+    Condition::PriceAbove {
+      symbol: symbol.to_string(),
+      price,
+    }
+  }
+
+  pub fn price_below(symbol: &str, price: f64) -> Self {
+    Condition::PriceBelow {
+      symbol: symbol.to_string(),
+      price,
+    }
+  }
+
+  ///
+  ///`time` of day in New York on `date`, 15:55 for "buy at 15:55 ET"
+  pub fn market_time(date: NaiveDate, time: NaiveTime) -> anyhow::Result<Self> {
+    match market_time(date, time) {
+      Some(time) => Ok(Condition::At { time }),
+      None => bail!("{date} {time} does not exist in New York"),
+    }
+  }
+
+  ///
+  ///Symbol whose trades the condition follows
+  pub fn symbol(&self) -> Option<&str> {
+    match self {
+      Condition::PriceAbove { symbol, .. } | Condition::PriceBelow { symbol, .. } => Some(symbol),
+      Condition::At { .. } | Condition::Immediately => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionalOrderStatus {
+  Pending,
+  ///
+  ///The condition was met, the order is not known to be accepted yet
+  Submitting,
+  ///
+  ///Submitted with an expiry, the order is canceled if still open by then
+  Working,
+  Submitted,
+  Canceled,
+  ///
+  ///The expiry passed before the condition was met, the order was canceled at its expiry, or its
+  /// time fell outside a trading session
+  Expired,
+  ///
+  ///The order was refused too often
+  Failed,
+}
+
+impl ConditionalOrderStatus {
+  pub fn is_active(&self) -> bool {
+    matches!(
+      self,
+      ConditionalOrderStatus::Pending | ConditionalOrderStatus::Submitting | ConditionalOrderStatus::Working
+    )
+  }
+}
+
+///
+///An order held back by a [`ConditionalOrders`] until its condition is met
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionalOrder {
+  pub id: Uuid,
+  pub condition: Condition,
+  ///
+  ///Submitted as is, except for its `client_order_id` which becomes the id of the conditional
+  /// order
+  pub order: OrderRequestBody,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub status: ConditionalOrderStatus,
+  ///
+  ///Whether a price condition saw a trade on the other side of its price, only then can it cross
+  pub armed: bool,
+  pub order_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub submitted_at: Option<DateTime<Utc>>,
+  pub attempts: u32,
+  pub error: Option<String>,
+}
+
+impl ConditionalOrder {
+  pub fn new(condition: Condition, mut order: OrderRequestBody) -> Self {
+    let id = Uuid::new_v4();
+    order.client_order_id = Some(id.to_string());
+    ConditionalOrder {
+      id,
+      condition,
+      order,
+      expires_at: None,
+      status: ConditionalOrderStatus::Pending,
+      armed: false,
+      order_id: None,
+      created_at: Utc::now(),
+      submitted_at: None,
+      attempts: 0,
+      error: None,
+    }
+  }
+
+  ///
+  ///Give up on the condition at `expires_at`, and cancel the order if it is still open by then
+  pub fn with_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+    self.expires_at = Some(expires_at);
+    self
+  }
+
+  fn is_expired(&self, now: DateTime<Utc>) -> bool {
+    self.expires_at.is_some_and(|expires_at| now >= expires_at)
+  }
+}
+
+///
+///Holds orders back until a condition the API does not support is met: a price level crossed by
+/// any symbol, a time of day, or the next session's opening auction, and cancels orders at their
+/// expiry to emulate good till date
+///
+///Price conditions follow the trades of a market data stream, time conditions and expiries are
+/// checked by [`ConditionalOrders::on_time`] against the market calendar: an order due at a time
+/// outside a session expires rather than being queued for the next open, and an immediate order
+/// waits for the next session. Sessions include the extended hours for orders that allow them,
+/// crypto orders and auction orders are never held. With [`ConditionalOrders::open`] every change
+/// is written to a state file so pending conditions survive a restart; call
+/// [`ConditionalOrders::recover`] after opening to settle the orders that were being submitted
+/// when the process stopped.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::{
+///   api::OrderRequestBody,
+///   execution::{
+///     Condition,
+///     ConditionalOrder,
+///     ConditionalOrders,
+///   },
+///   prelude::Client,
+///   stream::MarketDataStream,
+/// };
+/// use chrono::{
+///   NaiveDate,
+///   NaiveTime,
+/// };
+///
+/// # async fn example(client: Client, order: OrderRequestBody, mut stream: MarketDataStream) -> anyhow::Result<()> {
+/// let mut conditional = ConditionalOrders::open("conditional_orders.json")?;
+/// conditional.recover(&client).await?;
+/// let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+/// let time = NaiveTime::from_hms_opt(15, 55, 0).unwrap();
+/// conditional.register(ConditionalOrder::new(Condition::market_time(date, time)?, order))?;
+/// conditional.run(&client, Some(&mut stream)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ConditionalOrders {
+  orders: Vec<ConditionalOrder>,
+  path: Option<PathBuf>,
+  poll_interval: Duration,
+  ///
+  ///Regular hours of the dates fetched from the calendar, `None` when the market is closed
+  sessions: BTreeMap<NaiveDate, Option<Session>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Session {
+  open: DateTime<Utc>,
+  close: DateTime<Utc>,
+}
+
+impl Default for ConditionalOrders {
+  fn default() -> Self {
+    ConditionalOrders::new()
+  }
+}
+
+impl ConditionalOrders {
+  ///
+  ///Conditional orders kept in memory only
+  pub fn new() -> Self {
+    ConditionalOrders {
+      orders: vec![],
+      path: None,
+      poll_interval: Duration::from_secs(1),
+      sessions: BTreeMap::new(),
+    }
+  }
+
+  ///
+  ///Conditional orders persisted to the JSON file at `path`, loading the orders it already holds
+  pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    Ok(ConditionalOrders {
+      orders: read_state(&path)?,
+      path: Some(path),
+      ..ConditionalOrders::new()
+    })
+  }
+
+  ///
+  ///How often [`ConditionalOrders::run`] checks time conditions and expiries, every second by
+  /// default
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  pub fn list(&self) -> &[ConditionalOrder] {
+    &self.orders
+  }
+
+  pub fn order(&self, id: &Uuid) -> Option<&ConditionalOrder> {
+    self.orders.iter().find(|order| order.id == *id)
+  }
+
+  pub fn has_active_orders(&self) -> bool {
+    self.orders.iter().any(|order| order.status.is_active())
+  }
+
+  ///
+  ///Trades of every symbol a pending price condition follows
+  pub fn subscription(&self) -> Subscription {
+    let mut trades: Vec<String> = self
+      .orders
+      .iter()
+      .filter(|order| order.status == ConditionalOrderStatus::Pending)
+      .filter_map(|order| order.condition.symbol().map(str::to_string))
+      .collect();
+    trades.sort();
+    trades.dedup();
+    Subscription {
+      trades,
+      ..Default::default()
+    }
+  }
+
+  pub fn register(&mut self, order: ConditionalOrder) -> anyhow::Result<Uuid> {
+    match &order.condition {
+      Condition::PriceAbove { price, .. } | Condition::PriceBelow { price, .. }
+        if !price.is_finite() || *price <= 0.0 =>
+      {
+        bail!("condition price must be positive, got {price}")
+      }
+      Condition::At { time } if order.is_expired(*time) => {
+        bail!("conditional order expires before {time}")
+      }
+      _ => {}
+    }
+    let id = order.id;
+    self.orders.push(order);
+    self.save()?;
+    Ok(id)
+  }
+
+  ///
+  ///Submit `order` for the opening auction of the next session that still accepts it, as soon as
+  /// auction orders for that session are accepted from 19:00 New York time the day before
+  pub async fn opening_auction<C: CalendarApi>(
+    &mut self,
+    client: &C,
+    mut order: OrderRequestBody,
+    now: DateTime<Utc>,
+  ) -> anyhow::Result<Uuid> {
+    let today = trading_date(now);
+    let sessions = client
+      .get_market_calendar_info(&CalendarApiQueryParameter {
+        start: Some(today),
+        end: Some(today + Days::new(10)),
+        date_type: None,
+      })
+      .await?;
+    let Some(session) = sessions
+      .iter()
+      .find(|session| market_time(session.date, session.open).is_some_and(|open| now < open - OPG_CUTOFF))
+    else {
+      bail!("no session opens within ten days of {today}");
+    };
+    let accepted_from = session
+      .date
+      .checked_sub_days(Days::new(1))
+      .and_then(|date| market_time(date, OPG_ACCEPTED_FROM))
+      .unwrap_or(now);
+    order.time_in_force = TimeInForce::OPG;
+    self.register(ConditionalOrder::new(
+      Condition::At {
+        time: accepted_from.max(now),
+      },
+      order,
+    ))
+  }
+
+  ///
+  ///Stop waiting for the condition of an order, canceling the order when it was submitted
+  pub async fn cancel<C: OrderApi>(&mut self, client: &C, id: &Uuid) -> anyhow::Result<()> {
+    let Some(order) = self.orders.iter_mut().find(|order| order.id == *id) else {
+      bail!("no conditional order {id}");
+    };
+    if !order.status.is_active() {
+      bail!("conditional order {id} is already {:?}", order.status);
+    }
+    let order_id = match order.order_id {
+      Some(order_id) => Some(order_id),
+      None if order.status == ConditionalOrderStatus::Submitting => client
+        .get_order_by_client_order_id(&id.to_string())
+        .await
+        .ok()
+        .map(|submitted| submitted.id),
+      None => None,
+    };
+    if let Some(order_id) = order_id {
+      let _ = client.delete_order_by_id(&order_id).await;
+    }
+    order.status = ConditionalOrderStatus::Canceled;
+    self.save()
+  }
+
+  ///
+  ///Forget the orders that are no longer active
+  pub fn prune(&mut self) -> anyhow::Result<()> {
+    self.orders.retain(|order| order.status.is_active());
+    self.save()
+  }
+
+  ///
+  ///Submit or find the orders a restart left being submitted
+  pub async fn recover<C: OrderApi>(&mut self, client: &C) -> anyhow::Result<()> {
+    for index in 0..self.orders.len() {
+      if self.orders[index].status == ConditionalOrderStatus::Submitting {
+        self.submit(client, index, Utc::now()).await?;
+      }
+    }
+    Ok(())
+  }
+
+  ///
+  ///Feed a market data message, returns the ids of the orders it submitted
+  pub async fn on_message<C: OrderApi>(
+    &mut self,
+    client: &C,
+    message: &MarketDataMessage,
+  ) -> anyhow::Result<Vec<Uuid>> {
+    match message {
+      MarketDataMessage::Trade(trade) => self.on_price(client, &trade.symbol, trade.price, trade.timestamp).await,
+      _ => Ok(vec![]),
+    }
+  }
+
+  ///
+  ///Feed a trade price of `symbol`, returns the ids of the orders whose price condition it met
+  pub async fn on_price<C: OrderApi>(
+    &mut self,
+    client: &C,
+    symbol: &str,
+    price: f64,
+    timestamp: DateTime<Utc>,
+  ) -> anyhow::Result<Vec<Uuid>> {
+    let mut submitted = vec![];
+    for index in 0..self.orders.len() {
+      let order = &mut self.orders[index];
+      if order.status != ConditionalOrderStatus::Pending
+        || order.condition.symbol() != Some(symbol)
+        || order.is_expired(timestamp)
+      {
+        continue;
+      }
+      let reached = match order.condition {
+        Condition::PriceAbove { price: level, .. } => price >= level,
+        Condition::PriceBelow { price: level, .. } => price <= level,
+        _ => continue,
+      };
+      match (order.armed, reached) {
+        (true, true) => {
+          submitted.push(order.id);
+          self.submit(client, index, timestamp).await?;
+        }
+        (false, false) => {
+          order.armed = true;
+          self.save()?;
+        }
+        _ => {}
+      }
+    }
+    Ok(submitted)
+  }
+
+  ///
+  ///Submit the orders whose time came, expire the conditions and cancel the orders whose expiry
+  /// passed and retry refused submissions, returns the ids of the orders whose condition was met
+  pub async fn on_time<C>(&mut self, client: &C, now: DateTime<Utc>) -> anyhow::Result<Vec<Uuid>>
+  where
+    C: OrderApi + CalendarApi,
+  {
+    let mut submitted = vec![];
+    for index in 0..self.orders.len() {
+      let order = &mut self.orders[index];
+      match order.status {
+        ConditionalOrderStatus::Pending if order.is_expired(now) => {
+          order.status = ConditionalOrderStatus::Expired;
+          self.save()?;
+        }
+        ConditionalOrderStatus::Pending => {
+          let time = match order.condition {
+            Condition::At { time } if now >= time => Some(time),
+            Condition::Immediately => None,
+            _ => continue,
+          };
+          let tradable = self.is_tradable(client, index, time, now).await?;
+          let order = &mut self.orders[index];
+          match (tradable, time) {
+            (true, _) => {
+              submitted.push(order.id);
+              self.submit(client, index, now).await?;
+            }
+            (false, Some(time)) => {
+              order.status = ConditionalOrderStatus::Expired;
+              order.error = Some(format!("{time} is outside a trading session"));
+              self.save()?;
+            }
+            (false, None) => {}
+          }
+        }
+        ConditionalOrderStatus::Submitting => self.submit(client, index, now).await?,
+        ConditionalOrderStatus::Working if order.is_expired(now) => self.expire(client, index).await?,
+        _ => {}
+      }
+    }
+    Ok(submitted)
+  }
+
+  ///
+  ///Check time conditions every poll interval and follow the trades of `stream` until no order is
+  /// active, the stream is subscribed to the symbols of the pending price conditions
+  pub async fn run<C>(&mut self, client: &C, mut stream: Option<&mut MarketDataStream>) -> anyhow::Result<()>
+  where
+    C: OrderApi + CalendarApi,
+  {
+    if let Some(stream) = stream.as_mut() {
+      stream.subscribe(self.subscription())?;
+    }
+    let mut interval = tokio::time::interval(self.poll_interval);
+    while self.has_active_orders() {
+      tokio::select! {
+        _ = interval.tick() => {
+          self.on_time(client, Utc::now()).await?;
+        }
+        message = next_message(&mut stream) => {
+          let Some(message) = message else {
+            bail!("market data stream closed with conditional orders still active");
+          };
+          self.on_message(client, &message).await?;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn save(&self) -> anyhow::Result<()> {
+    match &self.path {
+      Some(path) => write_state(path, &self.orders),
+      None => Ok(()),
+    }
+  }
+
+  ///
+  ///Whether the order at `index` trades at `now`, within its session or the extended hours of it,
+  /// and an order due at `time` only within the session of that time
+  async fn is_tradable<C: CalendarApi>(
+    &mut self,
+    client: &C,
+    index: usize,
+    time: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+  ) -> anyhow::Result<bool> {
+    let order = &self.orders[index].order;
+    if order.symbol.contains('/') || matches!(order.time_in_force, TimeInForce::OPG | TimeInForce::CLS) {
+      return Ok(true);
+    }
+    if time.is_some_and(|time| trading_date(time) != trading_date(now)) {
+      return Ok(false);
+    }
+    let extended_hours = order.extended_hours;
+    let date = trading_date(now);
+    let Some(Session { open, close }) = self.session(client, date).await? else {
+      return Ok(false);
+    };
+    let (open, close) = match extended_hours {
+      true => (market_time(date, PRE_MARKET_OPEN).unwrap_or(open), close + AFTER_HOURS),
+      false => (open, close),
+    };
+    Ok(open <= now && now < close)
+  }
+
+  ///
+  ///Regular hours of `date`, fetched from the calendar once
+  async fn session<C: CalendarApi>(&mut self, client: &C, date: NaiveDate) -> anyhow::Result<Option<Session>> {
+    if let Some(session) = self.sessions.get(&date) {
+      return Ok(*session);
+    }
+    let sessions = client
+      .get_market_calendar_info(&CalendarApiQueryParameter {
+        start: Some(date),
+        end: Some(date),
+        date_type: None,
+      })
+      .await?;
+    let session = sessions
+      .iter()
+      .find(|session| session.date == date)
+      .and_then(|session| {
+        Some(Session {
+          open: market_time(date, session.open)?,
+          close: market_time(date, session.close)?,
+        })
+      });
+    self.sessions.insert(date, session);
+    Ok(session)
+  }
+
+  ///
+  ///Place the order of a met condition, unless an earlier attempt already did
+  async fn submit<C: OrderApi>(&mut self, client: &C, index: usize, now: DateTime<Utc>) -> anyhow::Result<()> {
+    let order = &mut self.orders[index];
+    if order.status != ConditionalOrderStatus::Submitting {
+      order.status = ConditionalOrderStatus::Submitting;
+      order.submitted_at = Some(now);
+      self.save()?;
+    }
+    let order = &mut self.orders[index];
+    let existing = match order.attempts {
+      0 => None,
+      _ => client.get_order_by_client_order_id(&order.id.to_string()).await.ok(),
+    };
+    let result = match existing {
+      Some(submitted) => Ok(submitted),
+      None => {
+        order.attempts += 1;
+        client.create_order(&order.order).await
+      }
+    };
+    match result {
+      Ok(submitted) => {
+        order.status = match order.expires_at {
+          Some(_) => ConditionalOrderStatus::Working,
+          None => ConditionalOrderStatus::Submitted,
+        };
+        order.order_id = Some(submitted.id);
+        order.error = None;
+      }
+      Err(error) => {
+        order.error = Some(error.to_string());
+        if order.attempts >= MAX_ATTEMPTS {
+          order.status = ConditionalOrderStatus::Failed;
+        }
+      }
+    }
+    self.save()
+  }
+
+  ///
+  ///Cancel the order of a working conditional order whose expiry passed, it is left submitted when
+  /// it filled in the meantime
+  async fn expire<C: OrderApi>(&mut self, client: &C, index: usize) -> anyhow::Result<()> {
+    let order = &mut self.orders[index];
+    let Some(order_id) = order.order_id else {
+      return Ok(());
+    };
+    let _ = client.delete_order_by_id(&order_id).await;
+    match client.get_order_by_id(&order_id).await {
+      Ok(submitted) if submitted.status == OrderStatus::Canceled => order.status = ConditionalOrderStatus::Expired,
+      Ok(submitted) if submitted.status.is_final() => order.status = ConditionalOrderStatus::Submitted,
+      // retried on the next tick
+      Ok(_) => return Ok(()),
+      Err(error) => {
+        order.error = Some(error.to_string());
+        return Ok(());
+      }
+    }
+    self.save()
+  }
+}
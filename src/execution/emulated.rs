@@ -0,0 +1,687 @@
+use crate::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+  },
+  models::{
+    MarketDataMessage,
+    Subscription,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  stream::MarketDataStream,
+};
+use anyhow::{
+  Context,
+  bail,
+};
+use chrono::{
+  DateTime,
+  TimeDelta,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+  de::DeserializeOwned,
+};
+use std::{
+  fs,
+  path::{
+    Path,
+    PathBuf,
+  },
+  time::Duration,
+};
+use uuid::Uuid;
+
+///
+///Submissions of an exit order before the emulated order is marked [`EmulatedOrderStatus::Failed`]
+const MAX_ATTEMPTS: u32 = 3;
+
+///
+///Distance a trailing stop keeps from the high-water mark
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trail {
+  Price(f64),
+  ///
+  ///Percent of the high-water mark, `2.0` for 2% like `trail_percent` of the API
+  Percent(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmulatedOrderKind {
+  TrailingStop {
+    trail: Trail,
+  },
+  ///
+  ///Take profit and stop loss for a position already held, whichever triggers first
+  Oco {
+    take_profit: f64,
+    stop_loss: f64,
+  },
+  ///
+  ///An entry order whose fill arms a take profit and a stop loss
+  Bracket {
+    take_profit: f64,
+    stop_loss: f64,
+  },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmulatedOrderStatus {
+  ///
+  ///A bracket waiting for the first fill of its entry order
+  AwaitingEntry,
+  Armed,
+  ///
+  ///Triggered, the exit order is not known to be accepted yet
+  Triggering,
+  Triggered,
+  Canceled,
+  ///
+  ///The exit order was refused too often, or the bracket entry ended without a fill
+  Failed,
+}
+
+impl EmulatedOrderStatus {
+  pub fn is_active(&self) -> bool {
+    matches!(
+      self,
+      EmulatedOrderStatus::AwaitingEntry | EmulatedOrderStatus::Armed | EmulatedOrderStatus::Triggering
+    )
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitLeg {
+  TrailingStop,
+  TakeProfit,
+  StopLoss,
+}
+
+impl ExitLeg {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ExitLeg::TrailingStop => "trail",
+      ExitLeg::TakeProfit => "tp",
+      ExitLeg::StopLoss => "sl",
+    }
+  }
+}
+
+///
+///An order the API does not support for crypto, watched locally by an [`OrderEmulator`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmulatedOrder {
+  pub id: Uuid,
+  pub symbol: String,
+  ///
+  ///Side of the exit order, sell to protect a long position
+  pub side: Side,
+  pub qty: f64,
+  pub kind: EmulatedOrderKind,
+  pub status: EmulatedOrderStatus,
+  ///
+  ///Best price since the order was armed: the highest for a sell, the lowest for a buy
+  pub hwm: Option<f64>,
+  pub entry_order_id: Option<Uuid>,
+  ///
+  ///Whether the entry of an armed bracket may still fill, its `qty` then grows with the fills
+  #[serde(default)]
+  pub entry_open: bool,
+  pub exit_leg: Option<ExitLeg>,
+  pub exit_order_id: Option<Uuid>,
+  pub trigger_price: Option<f64>,
+  pub created_at: DateTime<Utc>,
+  pub triggered_at: Option<DateTime<Utc>>,
+  pub attempts: u32,
+  pub error: Option<String>,
+  #[serde(skip)]
+  entry_checked_at: Option<DateTime<Utc>>,
+}
+
+impl EmulatedOrder {
+  fn new(symbol: &str, side: Side, qty: f64, kind: EmulatedOrderKind) -> Self {
+    EmulatedOrder {
+      id: Uuid::new_v4(),
+      symbol: symbol.to_string(),
+      side,
+      qty,
+      kind,
+      status: EmulatedOrderStatus::Armed,
+      hwm: None,
+      entry_order_id: None,
+      entry_open: false,
+      exit_leg: None,
+      exit_order_id: None,
+      trigger_price: None,
+      created_at: Utc::now(),
+      triggered_at: None,
+      attempts: 0,
+      error: None,
+      entry_checked_at: None,
+    }
+  }
+
+  ///
+  ///Price a trailing stop triggers at, given its high-water mark
+  pub fn stop_price(&self) -> Option<f64> {
+    let (EmulatedOrderKind::TrailingStop { trail }, Some(hwm)) = (self.kind, self.hwm) else {
+      return None;
+    };
+    let distance = match trail {
+      Trail::Price(price) => price,
+      Trail::Percent(percent) => hwm * percent / 100.0,
+    };
+    match self.side {
+      Side::Sell => Some(hwm - distance),
+      Side::Buy => Some(hwm + distance),
+    }
+  }
+
+  pub fn client_order_id(&self, leg: &str) -> String {
+    format!("{}-{leg}", self.id)
+  }
+
+  ///
+  ///Track `price` and return the leg it triggers
+  fn trigger(&mut self, price: f64) -> Option<ExitLeg> {
+    let sell = self.side == Side::Sell;
+    match self.kind {
+      EmulatedOrderKind::TrailingStop { .. } => {
+        self.hwm = Some(match (self.hwm, sell) {
+          (Some(hwm), true) => hwm.max(price),
+          (Some(lwm), false) => lwm.min(price),
+          (None, _) => price,
+        });
+        let stop = self.stop_price()?;
+        let hit = match sell {
+          true => price <= stop,
+          false => price >= stop,
+        };
+        hit.then_some(ExitLeg::TrailingStop)
+      }
+      EmulatedOrderKind::Oco { take_profit, stop_loss } | EmulatedOrderKind::Bracket { take_profit, stop_loss } => {
+        match sell {
+          true if price >= take_profit => Some(ExitLeg::TakeProfit),
+          true if price <= stop_loss => Some(ExitLeg::StopLoss),
+          false if price <= take_profit => Some(ExitLeg::TakeProfit),
+          false if price >= stop_loss => Some(ExitLeg::StopLoss),
+          _ => None,
+        }
+      }
+    }
+  }
+}
+
+///
+///Emulates trailing stops, brackets and OCO orders for crypto pairs, which the API only accepts
+/// for equities
+///
+///The emulator follows the trades of the crypto market data stream and places a real market
+/// order when a trigger hits. With [`OrderEmulator::open`] every change is written to a state file
+/// so protective stops survive a restart; call [`OrderEmulator::recover`] after opening to settle
+/// the orders that were triggering when the process stopped. Exit orders carry the
+/// `client_order_id` `{id}-{leg}`, so a trigger is never submitted twice.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::{
+///   execution::{
+///     OrderEmulator,
+///     Trail,
+///   },
+///   prelude::{
+///     Client,
+///     CryptoLocation,
+///     enums::Side,
+///   },
+///   stream::{
+///     MARKET_DATA_STREAM_URL,
+///     MarketDataFeed,
+///     MarketDataStream,
+///     StreamConfig,
+///   },
+/// };
+///
+/// # async fn example(client: Client) -> anyhow::Result<()> {
+/// let mut emulator = OrderEmulator::open("emulated_orders.json")?;
+/// emulator.recover(&client).await?;
+/// emulator.trailing_stop("BTC/USD", Side::Sell, 0.5, Trail::Percent(3.0))?;
+/// let config = StreamConfig::new(
+///   MARKET_DATA_STREAM_URL.to_string(),
+///   MarketDataFeed::Crypto(CryptoLocation::Us),
+///   "key".to_string(),
+///   "secret".to_string(),
+/// );
+/// let mut stream = MarketDataStream::connect(config).await?;
+/// emulator.run(&client, &mut stream).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderEmulator {
+  orders: Vec<EmulatedOrder>,
+  path: Option<PathBuf>,
+  entry_poll_interval: TimeDelta,
+}
+
+impl Default for OrderEmulator {
+  fn default() -> Self {
+    OrderEmulator::new()
+  }
+}
+
+impl OrderEmulator {
+  ///
+  ///Emulator keeping its orders in memory only
+  pub fn new() -> Self {
+    OrderEmulator {
+      orders: vec![],
+      path: None,
+      entry_poll_interval: TimeDelta::seconds(1),
+    }
+  }
+
+  ///
+  ///Emulator persisted to the JSON file at `path`, loading the orders it already holds
+  pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    Ok(OrderEmulator {
+      orders: read_state(&path)?,
+      path: Some(path),
+      ..OrderEmulator::new()
+    })
+  }
+
+  ///
+  ///How often a bracket's entry order is fetched while trades come in, one second of market time
+  /// by default
+  pub fn with_entry_poll_interval(mut self, entry_poll_interval: Duration) -> Self {
+    self.entry_poll_interval = TimeDelta::from_std(entry_poll_interval).unwrap_or(TimeDelta::MAX);
+    self
+  }
+
+  pub fn orders(&self) -> &[EmulatedOrder] {
+    &self.orders
+  }
+
+  pub fn order(&self, id: &Uuid) -> Option<&EmulatedOrder> {
+    self.orders.iter().find(|order| order.id == *id)
+  }
+
+  pub fn has_active_orders(&self) -> bool {
+    self.orders.iter().any(|order| order.status.is_active())
+  }
+
+  ///
+  ///Trades of every symbol with an active order
+  pub fn subscription(&self) -> Subscription {
+    let mut trades: Vec<String> = self
+      .orders
+      .iter()
+      .filter(|order| order.status.is_active())
+      .map(|order| order.symbol.clone())
+      .collect();
+    trades.sort();
+    trades.dedup();
+    Subscription {
+      trades,
+      ..Default::default()
+    }
+  }
+
+  ///
+  ///Trailing stop of `qty` following the best price since now
+  pub fn trailing_stop(&mut self, symbol: &str, side: Side, qty: f64, trail: Trail) -> anyhow::Result<Uuid> {
+    validate(symbol, qty)?;
+    match trail {
+      Trail::Price(price) if !price.is_finite() || price <= 0.0 => bail!("trail price must be positive, got {price}"),
+      Trail::Percent(percent) if !(percent > 0.0 && percent < 100.0) => {
+        bail!("trail percent must be within (0, 100), got {percent}")
+      }
+      _ => {}
+    }
+    self.add(EmulatedOrder::new(
+      symbol,
+      side,
+      qty,
+      EmulatedOrderKind::TrailingStop { trail },
+    ))
+  }
+
+  ///
+  ///Take profit and stop loss of `qty`, `side` being the side of the exit
+  pub fn oco(&mut self, symbol: &str, side: Side, qty: f64, take_profit: f64, stop_loss: f64) -> anyhow::Result<Uuid> {
+    validate(symbol, qty)?;
+    validate_exits(side, take_profit, stop_loss)?;
+    self.add(EmulatedOrder::new(
+      symbol,
+      side,
+      qty,
+      EmulatedOrderKind::Oco { take_profit, stop_loss },
+    ))
+  }
+
+  ///
+  ///Submit `entry` now and arm a take profit and a stop loss for the quantity it filled, its
+  /// `client_order_id` is replaced by `{id}-entry`
+  ///
+  ///The exits are armed on the first fill and cover what filled so far. When they trigger while
+  /// the entry is still working, the rest of the entry is canceled first.
+  pub async fn bracket<C: OrderApi>(
+    &mut self,
+    client: &C,
+    mut entry: OrderRequestBody,
+    take_profit: f64,
+    stop_loss: f64,
+  ) -> anyhow::Result<Uuid> {
+    let qty = entry.qty.as_ref().map(NumberAsString::value);
+    validate(&entry.symbol, qty.unwrap_or(1.0))?;
+    let exit_side = match entry.side {
+      Side::Buy => Side::Sell,
+      Side::Sell => Side::Buy,
+    };
+    validate_exits(exit_side, take_profit, stop_loss)?;
+    let mut order = EmulatedOrder::new(
+      &entry.symbol,
+      exit_side,
+      qty.unwrap_or_default(),
+      EmulatedOrderKind::Bracket { take_profit, stop_loss },
+    );
+    entry.client_order_id = Some(order.client_order_id("entry"));
+    let entry = client.create_order(&entry).await?;
+    order.status = EmulatedOrderStatus::AwaitingEntry;
+    order.entry_order_id = Some(entry.id);
+    order.entry_open = true;
+    self.add(order)
+  }
+
+  ///
+  ///Stop watching an order, canceling the entry order of a bracket still waiting for it
+  pub async fn cancel<C: OrderApi>(&mut self, client: &C, id: &Uuid) -> anyhow::Result<()> {
+    let Some(order) = self.orders.iter_mut().find(|order| order.id == *id) else {
+      bail!("no emulated order {id}");
+    };
+    if !order.status.is_active() {
+      bail!("emulated order {id} is already {:?}", order.status);
+    }
+    if order.entry_open
+      && let Some(entry_order_id) = order.entry_order_id
+    {
+      let _ = client.delete_order_by_id(&entry_order_id).await;
+    }
+    order.status = EmulatedOrderStatus::Canceled;
+    self.save()
+  }
+
+  ///
+  ///Forget the orders that are no longer active
+  pub fn prune(&mut self) -> anyhow::Result<()> {
+    self.orders.retain(|order| order.status.is_active());
+    self.save()
+  }
+
+  ///
+  ///Settle what a restart left open: submit or find the exit orders of triggering orders and
+  /// fetch the entry orders of brackets
+  pub async fn recover<C: OrderApi>(&mut self, client: &C) -> anyhow::Result<()> {
+    for index in 0..self.orders.len() {
+      match self.orders[index].status {
+        EmulatedOrderStatus::Triggering => self.submit_exit(client, index).await?,
+        EmulatedOrderStatus::AwaitingEntry | EmulatedOrderStatus::Armed if self.orders[index].entry_open => {
+          self.refresh_entry(client, index).await?
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+
+  ///
+  ///Feed a market data message, returns the ids of the orders it triggered
+  pub async fn on_message<C: OrderApi>(
+    &mut self,
+    client: &C,
+    message: &MarketDataMessage,
+  ) -> anyhow::Result<Vec<Uuid>> {
+    match message {
+      MarketDataMessage::Trade(trade) => self.on_price(client, &trade.symbol, trade.price, trade.timestamp).await,
+      _ => Ok(vec![]),
+    }
+  }
+
+  ///
+  ///Feed a trade price of `symbol`, returns the ids of the orders it triggered
+  pub async fn on_price<C: OrderApi>(
+    &mut self,
+    client: &C,
+    symbol: &str,
+    price: f64,
+    timestamp: DateTime<Utc>,
+  ) -> anyhow::Result<Vec<Uuid>> {
+    let mut triggered = vec![];
+    for index in 0..self.orders.len() {
+      if self.orders[index].symbol != symbol {
+        continue;
+      }
+      if self.orders[index].entry_open
+        && self.orders[index]
+          .entry_checked_at
+          .is_none_or(|checked_at| timestamp - checked_at >= self.entry_poll_interval)
+      {
+        self.orders[index].entry_checked_at = Some(timestamp);
+        self.refresh_entry(client, index).await?;
+      }
+      match self.orders[index].status {
+        EmulatedOrderStatus::Armed => {
+          let order = &mut self.orders[index];
+          let hwm = order.hwm;
+          match order.trigger(price) {
+            Some(leg) => {
+              order.status = EmulatedOrderStatus::Triggering;
+              order.exit_leg = Some(leg);
+              order.trigger_price = Some(price);
+              order.triggered_at = Some(timestamp);
+              triggered.push(order.id);
+              self.close_entry(client, index).await;
+              self.save()?;
+              self.submit_exit(client, index).await?;
+            }
+            None if order.hwm != hwm => self.save()?,
+            None => {}
+          }
+        }
+        EmulatedOrderStatus::Triggering => self.submit_exit(client, index).await?,
+        _ => {}
+      }
+    }
+    Ok(triggered)
+  }
+
+  ///
+  ///Follow the trades of the active orders until none is left or the stream closes
+  pub async fn run<C: OrderApi>(&mut self, client: &C, stream: &mut MarketDataStream) -> anyhow::Result<()> {
+    stream.subscribe(self.subscription())?;
+    while self.has_active_orders() {
+      let Some(message) = stream.next().await else {
+        bail!("market data stream closed with emulated orders still active");
+      };
+      self.on_message(client, &message).await?;
+    }
+    Ok(())
+  }
+
+  fn add(&mut self, order: EmulatedOrder) -> anyhow::Result<Uuid> {
+    let id = order.id;
+    self.orders.push(order);
+    self.save()?;
+    Ok(id)
+  }
+
+  fn save(&self) -> anyhow::Result<()> {
+    match &self.path {
+      Some(path) => write_state(path, &self.orders),
+      None => Ok(()),
+    }
+  }
+
+  ///
+  ///Arm a bracket on the first fill of its entry and keep its quantity at what filled since
+  async fn refresh_entry<C: OrderApi>(&mut self, client: &C, index: usize) -> anyhow::Result<()> {
+    let order = &mut self.orders[index];
+    let Some(entry_order_id) = order.entry_order_id else {
+      return Ok(());
+    };
+    let Ok(entry) = client.get_order_by_id(&entry_order_id).await else {
+      return Ok(());
+    };
+    let filled_qty = entry.filled_qty.as_ref().map(Money::value).unwrap_or_default();
+    let before = (order.status, order.qty, order.entry_open);
+    order.entry_open = !entry.status.is_final();
+    if filled_qty > 0.0 {
+      order.status = EmulatedOrderStatus::Armed;
+      order.qty = filled_qty;
+    } else if !order.entry_open {
+      order.status = EmulatedOrderStatus::Failed;
+      order.error = Some(format!("entry order ended {:?} without a fill", entry.status));
+    }
+    match (order.status, order.qty, order.entry_open) == before {
+      true => Ok(()),
+      false => self.save(),
+    }
+  }
+
+  ///
+  ///Cancel what is left of a triggered bracket's entry, so the exit covers the whole position
+  async fn close_entry<C: OrderApi>(&mut self, client: &C, index: usize) {
+    let order = &mut self.orders[index];
+    let (true, Some(entry_order_id)) = (order.entry_open, order.entry_order_id) else {
+      return;
+    };
+    let _ = client.delete_order_by_id(&entry_order_id).await;
+    if let Ok(entry) = client.get_order_by_id(&entry_order_id).await {
+      order.qty = order
+        .qty
+        .max(entry.filled_qty.as_ref().map(Money::value).unwrap_or_default());
+    }
+    order.entry_open = false;
+  }
+
+  ///
+  ///Place the exit of a triggered order, unless an earlier attempt already did
+  async fn submit_exit<C: OrderApi>(&mut self, client: &C, index: usize) -> anyhow::Result<()> {
+    let order = &mut self.orders[index];
+    let Some(leg) = order.exit_leg else {
+      return Ok(());
+    };
+    let client_order_id = order.client_order_id(leg.as_str());
+    let existing = match order.attempts {
+      0 => None,
+      _ => client.get_order_by_client_order_id(&client_order_id).await.ok(),
+    };
+    let result = match existing {
+      Some(exit) => Ok(exit),
+      None => {
+        order.attempts += 1;
+        client.create_order(&exit_order(order, client_order_id)).await
+      }
+    };
+    match result {
+      Ok(exit) => {
+        order.status = EmulatedOrderStatus::Triggered;
+        order.exit_order_id = Some(exit.id);
+        order.error = None;
+      }
+      Err(error) => {
+        order.error = Some(error.to_string());
+        if order.attempts >= MAX_ATTEMPTS {
+          order.status = EmulatedOrderStatus::Failed;
+        }
+      }
+    }
+    self.save()
+  }
+}
+
+///
+///Orders kept in the JSON state file at `path`, none when it does not exist yet
+pub(crate) fn read_state<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Vec<T>> {
+  match path.exists() {
+    true => serde_json::from_str(&fs::read_to_string(path)?)
+      .with_context(|| format!("failed to read orders from {}", path.display())),
+    false => Ok(vec![]),
+  }
+}
+
+///
+///Write `orders` to a temporary file and move it over the state file, so a crash never leaves
+/// half a file behind
+pub(crate) fn write_state<T: Serialize>(path: &Path, orders: &[T]) -> anyhow::Result<()> {
+  let temp_path = path.with_extension("json.tmp");
+  fs::write(&temp_path, serde_json::to_string_pretty(orders)?)
+    .with_context(|| format!("failed to write {}", temp_path.display()))?;
+  fs::rename(&temp_path, path)?;
+  Ok(())
+}
+
+fn validate(symbol: &str, qty: f64) -> anyhow::Result<()> {
+  // crypto pairs are the only symbols with a slash, e.g. BTC/USD
+  if !symbol.contains('/') {
+    bail!("{symbol} is not a crypto pair, equities support these orders natively");
+  }
+  if !qty.is_finite() || qty <= 0.0 {
+    bail!("quantity must be positive, got {qty}");
+  }
+  Ok(())
+}
+
+fn validate_exits(side: Side, take_profit: f64, stop_loss: f64) -> anyhow::Result<()> {
+  if !(take_profit > 0.0 && stop_loss > 0.0) {
+    bail!("take profit and stop loss must be positive, got {take_profit} and {stop_loss}");
+  }
+  match side {
+    Side::Sell if take_profit <= stop_loss => {
+      bail!("selling take profit {take_profit} must be above the stop loss {stop_loss}")
+    }
+    Side::Buy if take_profit >= stop_loss => {
+      bail!("buying take profit {take_profit} must be below the stop loss {stop_loss}")
+    }
+    _ => Ok(()),
+  }
+}
+
+///
+///Market order closing `order`
+fn exit_order(order: &EmulatedOrder, client_order_id: String) -> OrderRequestBody {
+  OrderRequestBody {
+    symbol: order.symbol.clone(),
+    qty: Some(NumberAsString::from_f64(order.qty)),
+    notional: None,
+    side: order.side,
+    _type: OrderType::Market,
+    time_in_force: TimeInForce::GTC,
+    limit_price: None,
+    stop_price: None,
+    trail_price: None,
+    trail_percent: None,
+    extended_hours: false,
+    client_order_id: Some(client_order_id),
+    order_class: None,
+    legs: vec![],
+    take_profit: None,
+    stop_loss: None,
+    position_intent: None,
+  }
+}
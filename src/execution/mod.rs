@@ -0,0 +1,9 @@
+mod algo;
+mod conditional;
+mod emulated;
+mod profile;
+
+pub use algo::*;
+pub use conditional::*;
+pub use emulated::*;
+pub use profile::*;
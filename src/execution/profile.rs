@@ -0,0 +1,111 @@
+use crate::{
+  history::MARKET_TIMEZONE,
+  models::Bar,
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  DurationRound,
+  NaiveTime,
+  TimeDelta,
+  Timelike,
+  Utc,
+};
+use std::{
+  collections::BTreeMap,
+  time::Duration,
+};
+
+///
+///Share of a day's volume traded in each bucket of the session, by New York time
+///
+///Built from historical intraday bars, [`VolumeProfile::fraction`] then tells how much of the
+/// volume expected within a window trades by a given time, which is what a VWAP schedule follows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeProfile {
+  bucket_minutes: u32,
+  ///
+  ///Keyed by the first minute of the bucket after midnight, sums to one
+  weights: BTreeMap<u32, f64>,
+}
+
+impl VolumeProfile {
+  ///
+  ///Profile of the summed volume of `bars` over any number of days, in buckets of `bucket`
+  pub fn from_bars(bars: &[Bar], bucket: Duration) -> anyhow::Result<Self> {
+    VolumeProfile::new(
+      bars
+        .iter()
+        .map(|bar| (bar.timestamp.with_timezone(&MARKET_TIMEZONE).time(), bar.volume)),
+      bucket,
+    )
+  }
+
+  ///
+  ///Profile of the volume traded at each time of day, the volumes need not add up to one
+  pub fn new(volumes: impl IntoIterator<Item = (NaiveTime, f64)>, bucket: Duration) -> anyhow::Result<Self> {
+    let bucket_minutes = (bucket.as_secs() / 60) as u32;
+    if bucket_minutes == 0 || bucket_minutes > 24 * 60 || !bucket.as_secs().is_multiple_of(60) {
+      bail!("bucket must be a whole number of minutes within a day, got {bucket:?}");
+    }
+    let mut weights = BTreeMap::new();
+    for (time, volume) in volumes {
+      if !volume.is_finite() || volume < 0.0 {
+        bail!("volume at {time} must not be negative, got {volume}");
+      }
+      let minute = time.hour() * 60 + time.minute();
+      *weights.entry(minute / bucket_minutes * bucket_minutes).or_insert(0.0) += volume;
+    }
+    let total: f64 = weights.values().sum();
+    if total <= 0.0 {
+      bail!("a volume profile needs some volume");
+    }
+    weights.values_mut().for_each(|weight| *weight /= total);
+    Ok(VolumeProfile {
+      bucket_minutes,
+      weights,
+    })
+  }
+
+  ///
+  ///Share of the day's volume traded in the bucket containing `time`
+  pub fn weight(&self, time: NaiveTime) -> f64 {
+    let minute = time.hour() * 60 + time.minute();
+    self
+      .weights
+      .get(&(minute / self.bucket_minutes * self.bucket_minutes))
+      .copied()
+      .unwrap_or_default()
+  }
+
+  ///
+  ///Share of the volume expected between `start` and `end` that trades by `at`, linear in time
+  /// when the profile has no volume within the window
+  pub fn fraction(&self, start: DateTime<Utc>, end: DateTime<Utc>, at: DateTime<Utc>) -> f64 {
+    if end <= start {
+      return 1.0;
+    }
+    let at = at.clamp(start, end);
+    let total = self.volume_between(start, end);
+    match total > 0.0 {
+      true => self.volume_between(start, at) / total,
+      false => (at - start).num_milliseconds() as f64 / (end - start).num_milliseconds() as f64,
+    }
+  }
+
+  ///
+  ///Expected share of a day's volume between two times, spreading every bucket evenly over its
+  /// minutes
+  fn volume_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let mut volume = 0.0;
+    let mut time = start;
+    while time < end {
+      let minute_start = time.duration_trunc(TimeDelta::minutes(1)).unwrap_or(time);
+      let next = (minute_start + TimeDelta::minutes(1)).min(end);
+      let covered = (next - time).num_milliseconds() as f64 / 60_000.0;
+      volume += self.weight(time.with_timezone(&MARKET_TIMEZONE).time()) / self.bucket_minutes as f64 * covered;
+      time = next;
+    }
+    volume
+  }
+}
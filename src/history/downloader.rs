@@ -0,0 +1,326 @@
+use crate::{
+  api::{
+    CalendarApi,
+    CalendarApiQueryParameter,
+    StockBarsQueryParameter,
+    StockMarketDataApi,
+    TimeFrame,
+  },
+  history::{
+    BarStore,
+    MARKET_TIMEZONE,
+    trading_date,
+  },
+  models::{
+    Adjustment,
+    Bar,
+    StockFeed,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Days,
+  NaiveDate,
+  TimeDelta,
+  Utc,
+};
+use std::collections::{
+  BTreeMap,
+  BTreeSet,
+};
+
+#[derive(Debug, Clone)]
+pub struct BarDownloadConfig {
+  pub symbols: Vec<String>,
+  pub timeframe: TimeFrame,
+  pub start: NaiveDate,
+  pub end: NaiveDate,
+  pub adjustment: Option<Adjustment>,
+  pub feed: Option<StockFeed>,
+  ///
+  ///Trading days fetched per request run, each run is committed to the store before the next one
+  pub chunk_days: usize,
+  pub page_limit: u32,
+}
+
+impl BarDownloadConfig {
+  pub fn new(symbols: Vec<String>, timeframe: TimeFrame, start: NaiveDate, end: NaiveDate) -> Self {
+    BarDownloadConfig {
+      symbols,
+      timeframe,
+      start,
+      end,
+      adjustment: None,
+      feed: None,
+      chunk_days: 20,
+      page_limit: 10_000,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarGap {
+  pub symbol: String,
+  ///
+  ///Trading days never downloaded
+  pub missing: Vec<NaiveDate>,
+  ///
+  ///Trading days downloaded without a single bar, e.g. halted or not yet listed
+  pub empty: Vec<NaiveDate>,
+}
+
+impl BarGap {
+  pub fn is_empty(&self) -> bool {
+    self.missing.is_empty() && self.empty.is_empty()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolSync {
+  pub symbol: String,
+  pub days: usize,
+  pub bars: usize,
+}
+
+///
+///Downloads bars of a symbol universe into a [`BarStore`], fetching only missing trading days
+pub struct BarDownloader<'a, C, S> {
+  client: &'a C,
+  store: S,
+  config: BarDownloadConfig,
+  trading_days: Option<Vec<NaiveDate>>,
+}
+
+impl<'a, C, S> BarDownloader<'a, C, S>
+where
+  C: StockMarketDataApi + CalendarApi,
+  S: BarStore,
+{
+  pub fn new(client: &'a C, store: S, config: BarDownloadConfig) -> anyhow::Result<Self> {
+    if matches!(config.timeframe, TimeFrame::Week(_) | TimeFrame::Month(_)) {
+      bail!("weekly and monthly bars do not map to trading days, download daily bars and resample them")
+    }
+    if config.start > config.end {
+      bail!("start {} is after end {}", config.start, config.end)
+    }
+    if config.chunk_days == 0 {
+      bail!("chunk_days must be positive")
+    }
+    Ok(BarDownloader {
+      client,
+      store,
+      config,
+      trading_days: None,
+    })
+  }
+
+  pub fn store(&self) -> &S {
+    &self.store
+  }
+
+  pub fn into_store(self) -> S {
+    self.store
+  }
+
+  ///
+  ///Sessions of the configured range that have already closed, fetched once from the calendar
+  pub async fn trading_days(&mut self) -> anyhow::Result<Vec<NaiveDate>> {
+    if let Some(trading_days) = &self.trading_days {
+      return Ok(trading_days.clone());
+    }
+    let query_parameter = CalendarApiQueryParameter {
+      start: Some(self.config.start),
+      end: Some(self.config.end),
+      date_type: None,
+    };
+    let now = Utc::now();
+    let trading_days: Vec<NaiveDate> = self
+      .client
+      .get_market_calendar_info(&query_parameter)
+      .await?
+      .into_iter()
+      .filter(|session| {
+        session.date >= self.config.start
+          && session.date <= self.config.end
+          && session
+            .date
+            .and_time(session.close)
+            .and_local_timezone(MARKET_TIMEZONE)
+            .earliest()
+            .is_some_and(|close| close.to_utc() <= now)
+      })
+      .map(|session| session.date)
+      .collect();
+    self.trading_days = Some(trading_days.clone());
+    Ok(trading_days)
+  }
+
+  ///
+  ///Compare the store against the trading calendar, only symbols with gaps are reported
+  pub async fn find_gaps(&mut self) -> anyhow::Result<Vec<BarGap>> {
+    let trading_days = self.trading_days().await?;
+    let mut gaps = vec![];
+    for symbol in &self.config.symbols {
+      let index = self.store.day_index(symbol)?;
+      let gap = BarGap {
+        symbol: symbol.clone(),
+        missing: trading_days
+          .iter()
+          .filter(|date| !index.contains_key(date))
+          .cloned()
+          .collect(),
+        empty: trading_days
+          .iter()
+          .filter(|date| index.get(date) == Some(&0))
+          .cloned()
+          .collect(),
+      };
+      if !gap.is_empty() {
+        gaps.push(gap);
+      }
+    }
+    Ok(gaps)
+  }
+
+  ///
+  ///Download every trading day missing from the store, safe to rerun after an interruption
+  pub async fn sync(&mut self) -> anyhow::Result<Vec<SymbolSync>> {
+    let trading_days = self.trading_days().await?;
+    let mut summary = vec![];
+    for symbol in self.config.symbols.clone() {
+      let index = self.store.day_index(&symbol)?;
+      let missing: BTreeSet<NaiveDate> = trading_days
+        .iter()
+        .filter(|date| !index.contains_key(date))
+        .cloned()
+        .collect();
+      summary.push(self.download(&symbol, &trading_days, &missing).await?);
+    }
+    Ok(summary)
+  }
+
+  ///
+  ///Drop and fetch again the trading days of `symbol` between `start` and `end`, e.g. after a
+  /// split. Only the days of the configured range are touched, stored days outside it are kept.
+  pub async fn redownload(&mut self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<SymbolSync> {
+    let trading_days = self.trading_days().await?;
+    let window: BTreeSet<NaiveDate> = trading_days
+      .iter()
+      .filter(|date| **date >= start && **date <= end)
+      .cloned()
+      .collect();
+    if let (Some(first), Some(last)) = (window.first(), window.last()) {
+      self.store.remove_days(symbol, *first, *last)?;
+    }
+    self.download(symbol, &trading_days, &window).await
+  }
+
+  async fn download(
+    &mut self,
+    symbol: &str,
+    trading_days: &[NaiveDate],
+    wanted: &BTreeSet<NaiveDate>,
+  ) -> anyhow::Result<SymbolSync> {
+    let mut sync = SymbolSync {
+      symbol: symbol.to_string(),
+      days: 0,
+      bars: 0,
+    };
+    for run in consecutive_runs(trading_days, wanted, self.config.chunk_days) {
+      let (first, last) = (run[0], run[run.len() - 1]);
+      let mut days: BTreeMap<NaiveDate, Vec<Bar>> = run.iter().map(|date| (*date, vec![])).collect();
+      for bar in self
+        .fetch(symbol, market_midnight(first), market_midnight(last + Days::new(1)))
+        .await?
+      {
+        if let Some(day) = days.get_mut(&trading_date(bar.timestamp)) {
+          day.push(bar);
+        }
+      }
+      sync.days += days.len();
+      sync.bars += days.values().map(Vec::len).sum::<usize>();
+      self.store.write_days(symbol, &days)?;
+    }
+    Ok(sync)
+  }
+
+  async fn fetch(&self, symbol: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> anyhow::Result<Vec<Bar>> {
+    let mut query_parameter = StockBarsQueryParameter {
+      symbols: vec![symbol.to_string()],
+      timeframe: self.config.timeframe,
+      start: Some(start),
+      end: Some(end - TimeDelta::microseconds(1)),
+      limit: Some(self.config.page_limit),
+      adjustment: self.config.adjustment,
+      asof: None,
+      feed: self.config.feed,
+      page_token: None,
+      sort: None,
+    };
+    let mut bars = vec![];
+    loop {
+      let mut response = self.client.get_stock_bars(&query_parameter).await?;
+      bars.extend(response.bars.remove(symbol).unwrap_or_default());
+      match response.next_page_token {
+        Some(token) => query_parameter.page_token = Some(token),
+        None => return Ok(bars),
+      }
+    }
+  }
+}
+
+///
+///Split the wanted days into runs that are consecutive in the calendar and at most `max_len` long
+fn consecutive_runs(trading_days: &[NaiveDate], wanted: &BTreeSet<NaiveDate>, max_len: usize) -> Vec<Vec<NaiveDate>> {
+  let mut runs: Vec<Vec<NaiveDate>> = vec![];
+  let mut previous = None;
+  for (position, date) in trading_days.iter().enumerate() {
+    if !wanted.contains(date) {
+      continue;
+    }
+    match runs.last_mut() {
+      Some(run) if previous == Some(position - 1) && run.len() < max_len => run.push(*date),
+      _ => runs.push(vec![*date]),
+    }
+    previous = Some(position);
+  }
+  runs
+}
+
+fn market_midnight(date: NaiveDate) -> DateTime<Utc> {
+  match date
+    .and_time(Default::default())
+    .and_local_timezone(MARKET_TIMEZONE)
+    .earliest()
+  {
+    Some(midnight) => midnight.to_utc(),
+    None => date.and_time(Default::default()).and_utc(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::consecutive_runs;
+  use chrono::NaiveDate;
+  use std::collections::BTreeSet;
+
+  #[test]
+  fn test_consecutive_runs_should_break_on_present_days_and_chunk_size() {
+    let days: Vec<NaiveDate> = (1..=8)
+      .map(|day| NaiveDate::from_ymd_opt(2025, 12, day).unwrap())
+      .collect();
+    let wanted: BTreeSet<NaiveDate> = [0, 1, 2, 4, 5, 7].iter().map(|index| days[*index]).collect();
+
+    let runs = consecutive_runs(&days, &wanted, 2);
+    assert_eq!(
+      runs,
+      vec![
+        vec![days[0], days[1]],
+        vec![days[2]],
+        vec![days[4], days[5]],
+        vec![days[7]]
+      ]
+    );
+  }
+}
@@ -0,0 +1,42 @@
+mod downloader;
+#[cfg(feature = "parquet")]
+mod parquet_format;
+mod resample;
+#[cfg(feature = "sqlite")]
+mod sqlite_store;
+mod store;
+
+use chrono::{
+  DateTime,
+  NaiveDate,
+  NaiveTime,
+  Utc,
+};
+use chrono_tz::Tz;
+pub use downloader::*;
+#[cfg(feature = "parquet")]
+pub use parquet_format::*;
+pub use resample::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_store::*;
+pub use store::*;
+
+///
+///Timezone of the US equity sessions, trading days are calendar dates in this zone
+pub const MARKET_TIMEZONE: Tz = chrono_tz::America::New_York;
+
+///
+///Trading day a bar belongs to, including its pre-market and after-hours bars
+pub fn trading_date(timestamp: DateTime<Utc>) -> NaiveDate {
+  timestamp.with_timezone(&MARKET_TIMEZONE).date_naive()
+}
+
+///
+///Instant of a wall clock time in [`MARKET_TIMEZONE`], `None` when it falls in a DST gap
+pub fn market_time(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+  date
+    .and_time(time)
+    .and_local_timezone(MARKET_TIMEZONE)
+    .earliest()
+    .map(|time| time.to_utc())
+}
@@ -0,0 +1,194 @@
+use crate::{
+  history::{
+    BarFileFormat,
+    FileBarStore,
+  },
+  models::Bar,
+};
+use anyhow::bail;
+use chrono::DateTime;
+use parquet::{
+  basic::Compression,
+  data_type::{
+    DoubleType,
+    Int64Type,
+  },
+  file::{
+    properties::WriterProperties,
+    reader::FileReader,
+    serialized_reader::SerializedFileReader,
+    writer::SerializedFileWriter,
+  },
+  record::Field,
+  schema::parser::parse_message_type,
+};
+use std::{
+  fs::File,
+  path::Path,
+  sync::Arc,
+};
+
+const PARQUET_SCHEMA: &str = "
+  message bar {
+    REQUIRED INT64 timestamp (TIMESTAMP(MICROS,true));
+    REQUIRED DOUBLE open;
+    REQUIRED DOUBLE high;
+    REQUIRED DOUBLE low;
+    REQUIRED DOUBLE close;
+    REQUIRED DOUBLE volume;
+    OPTIONAL INT64 trade_count;
+    OPTIONAL DOUBLE vwap;
+  }
+";
+
+///
+///Snappy compressed Parquet partitions, readable by pandas, polars or DuckDB
+#[derive(Debug, Clone)]
+pub struct Parquet;
+
+pub type ParquetBarStore = FileBarStore<Parquet>;
+
+impl BarFileFormat for Parquet {
+  const EXTENSION: &'static str = "parquet";
+
+  fn read(path: &Path) -> anyhow::Result<Vec<Bar>> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let mut bars = vec![];
+    for row in reader.get_row_iter(None)? {
+      let row = row?;
+      let fields: Vec<&Field> = row.get_column_iter().map(|(_, field)| field).collect();
+      let bar = match fields.as_slice() {
+        [
+          Field::TimestampMicros(timestamp),
+          Field::Double(open),
+          Field::Double(high),
+          Field::Double(low),
+          Field::Double(close),
+          Field::Double(volume),
+          trade_count,
+          vwap,
+        ] => Bar {
+          timestamp: match DateTime::from_timestamp_micros(*timestamp) {
+            Some(timestamp) => timestamp,
+            None => bail!("timestamp {} out of range", timestamp),
+          },
+          open: *open,
+          high: *high,
+          low: *low,
+          close: *close,
+          volume: *volume,
+          trade_count: match trade_count {
+            Field::Long(count) => Some(*count as u64),
+            _ => None,
+          },
+          vwap: match vwap {
+            Field::Double(vwap) => Some(*vwap),
+            _ => None,
+          },
+        },
+        _ => bail!("unexpected parquet row {}", row),
+      };
+      bars.push(bar);
+    }
+    Ok(bars)
+  }
+
+  fn write(path: &Path, bars: &[Bar]) -> anyhow::Result<()> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+
+    let timestamps: Vec<i64> = bars.iter().map(|bar| bar.timestamp.timestamp_micros()).collect();
+    let prices = [
+      bars.iter().map(|bar| bar.open).collect::<Vec<_>>(),
+      bars.iter().map(|bar| bar.high).collect(),
+      bars.iter().map(|bar| bar.low).collect(),
+      bars.iter().map(|bar| bar.close).collect(),
+      bars.iter().map(|bar| bar.volume).collect(),
+    ];
+    let trade_counts: Vec<i64> = bars
+      .iter()
+      .filter_map(|bar| bar.trade_count)
+      .map(|count| count as i64)
+      .collect();
+    let trade_count_levels: Vec<i16> = bars.iter().map(|bar| bar.trade_count.is_some() as i16).collect();
+    let vwaps: Vec<f64> = bars.iter().filter_map(|bar| bar.vwap).collect();
+    let vwap_levels: Vec<i16> = bars.iter().map(|bar| bar.vwap.is_some() as i16).collect();
+
+    let mut column_index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+      match column_index {
+        0 => {
+          column.typed::<Int64Type>().write_batch(&timestamps, None, None)?;
+        }
+        1..=5 => {
+          column
+            .typed::<DoubleType>()
+            .write_batch(&prices[column_index - 1], None, None)?;
+        }
+        6 => {
+          column
+            .typed::<Int64Type>()
+            .write_batch(&trade_counts, Some(&trade_count_levels), None)?;
+        }
+        _ => {
+          column
+            .typed::<DoubleType>()
+            .write_batch(&vwaps, Some(&vwap_levels), None)?;
+        }
+      }
+      column.close()?;
+      column_index += 1;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    history::{
+      BarFileFormat,
+      Parquet,
+    },
+    models::Bar,
+  };
+  use chrono::{
+    TimeZone,
+    Utc,
+  };
+
+  #[test]
+  fn test_parquet_format_should_round_trip_optional_columns() {
+    let path = std::env::temp_dir().join(format!("{}.parquet", uuid::Uuid::new_v4()));
+    let bars = vec![
+      Bar {
+        timestamp: Utc.with_ymd_and_hms(2025, 12, 1, 14, 30, 0).unwrap(),
+        open: 10.0,
+        high: 11.0,
+        low: 9.5,
+        close: 10.5,
+        volume: 1200.0,
+        trade_count: Some(14),
+        vwap: None,
+      },
+      Bar {
+        timestamp: Utc.with_ymd_and_hms(2025, 12, 1, 14, 31, 0).unwrap(),
+        open: 10.5,
+        high: 10.6,
+        low: 10.1,
+        close: 10.2,
+        volume: 800.0,
+        trade_count: None,
+        vwap: Some(10.31),
+      },
+    ];
+
+    Parquet::write(&path, &bars).unwrap();
+    assert_eq!(Parquet::read(&path).unwrap(), bars);
+    std::fs::remove_file(path).unwrap();
+  }
+}
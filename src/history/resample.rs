@@ -0,0 +1,513 @@
+use crate::{
+  api::TimeFrame,
+  history::{
+    MARKET_TIMEZONE,
+    market_time,
+    trading_date,
+  },
+  models::{
+    Bar,
+    CryptoTrade,
+    MarketCalendar,
+    OptionTrade,
+    StreamTrade,
+  },
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Datelike,
+  Days,
+  NaiveDate,
+  NaiveTime,
+  TimeDelta,
+  Utc,
+};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+///
+///A single execution that can be folded into a bar
+pub trait TradePrint {
+  fn timestamp(&self) -> DateTime<Utc>;
+
+  fn price(&self) -> f64;
+
+  fn size(&self) -> f64;
+}
+
+impl TradePrint for StreamTrade {
+  fn timestamp(&self) -> DateTime<Utc> {
+    self.timestamp
+  }
+
+  fn price(&self) -> f64 {
+    self.price
+  }
+
+  fn size(&self) -> f64 {
+    self.size
+  }
+}
+
+impl TradePrint for CryptoTrade {
+  fn timestamp(&self) -> DateTime<Utc> {
+    self.timestamp
+  }
+
+  fn price(&self) -> f64 {
+    self.price
+  }
+
+  fn size(&self) -> f64 {
+    self.size
+  }
+}
+
+impl TradePrint for OptionTrade {
+  fn timestamp(&self) -> DateTime<Utc> {
+    self.timestamp
+  }
+
+  fn price(&self) -> f64 {
+    self.price
+  }
+
+  fn size(&self) -> f64 {
+    self.size as f64
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Session {
+  open: DateTime<Utc>,
+  close: DateTime<Utc>,
+}
+
+///
+///Aggregates bars or trades into a coarser timeframe
+///
+/// ```
+/// use alpaca_trade_api_rust::{
+///   api::TimeFrame,
+///   history::BarResampler,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let resampler = BarResampler::new(TimeFrame::Minute(15))?.with_extended_hours(true);
+/// assert!(resampler.resample(&[]).is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BarResampler {
+  timeframe: TimeFrame,
+  timezone: Tz,
+  sessions: BTreeMap<NaiveDate, Session>,
+  extended_hours: bool,
+}
+
+impl BarResampler {
+  ///
+  ///Buckets aligned to the clock of [`MARKET_TIMEZONE`], every bar is kept
+  ///
+  ///Fails on a timeframe of zero units
+  pub fn new(timeframe: TimeFrame) -> anyhow::Result<Self> {
+    let (TimeFrame::Minute(n) | TimeFrame::Hour(n) | TimeFrame::Day(n) | TimeFrame::Week(n) | TimeFrame::Month(n)) =
+      timeframe;
+    if n == 0 {
+      bail!("cannot resample into a timeframe of zero units, got {timeframe:?}");
+    }
+    Ok(BarResampler {
+      timeframe,
+      timezone: MARKET_TIMEZONE,
+      sessions: BTreeMap::new(),
+      extended_hours: true,
+    })
+  }
+
+  ///
+  ///Timezone of the clock grid and of day, week and month boundaries, e.g. `UTC` for crypto
+  pub fn with_timezone(mut self, timezone: Tz) -> Self {
+    self.timezone = timezone;
+    self
+  }
+
+  ///
+  ///Align intraday buckets to each session's open and cut them at its close, even an early one
+  ///
+  ///Bars outside the sessions are dropped unless extended hours are enabled afterwards
+  pub fn with_sessions(mut self, calendar: &[MarketCalendar]) -> Self {
+    self.sessions = calendar
+      .iter()
+      .filter_map(|session| {
+        let open = market_time(session.date, session.open)?;
+        let close = market_time(session.date, session.close)?;
+        Some((session.date, Session { open, close }))
+      })
+      .collect();
+    self.extended_hours = false;
+    self
+  }
+
+  ///
+  ///Keep pre-market and after-hours bars, aligned to the session open before it and the close
+  /// after
+  pub fn with_extended_hours(mut self, extended_hours: bool) -> Self {
+    self.extended_hours = extended_hours;
+    self
+  }
+
+  ///
+  ///Aggregate `bars`, which must be of a finer timeframe, into one bar per bucket
+  pub fn resample(&self, bars: &[Bar]) -> Vec<Bar> {
+    let mut sorted: Vec<&Bar> = bars.iter().collect();
+    sorted.sort_by_key(|bar| bar.timestamp);
+
+    let mut buckets: BTreeMap<DateTime<Utc>, BarAggregate> = BTreeMap::new();
+    let mut day_buckets = DayBuckets::default();
+    for bar in sorted {
+      if let Some(start) = self.bucket_start(bar.timestamp, &mut day_buckets) {
+        match buckets.get_mut(&start) {
+          Some(aggregate) => aggregate.add(bar),
+          None => {
+            buckets.insert(start, BarAggregate::new(start, bar));
+          }
+        }
+      }
+    }
+    buckets.into_values().map(BarAggregate::finish).collect()
+  }
+
+  ///
+  ///Build bars from raw trades, `trade_count` and `vwap` are always filled
+  pub fn resample_trades<T: TradePrint>(&self, trades: &[T]) -> Vec<Bar> {
+    let ticks: Vec<Bar> = trades
+      .iter()
+      .map(|trade| Bar {
+        timestamp: trade.timestamp(),
+        open: trade.price(),
+        high: trade.price(),
+        low: trade.price(),
+        close: trade.price(),
+        volume: trade.size(),
+        trade_count: Some(1),
+        vwap: Some(trade.price()),
+      })
+      .collect();
+    self.resample(&ticks)
+  }
+
+  fn bucket_start(&self, timestamp: DateTime<Utc>, day_buckets: &mut DayBuckets) -> Option<DateTime<Utc>> {
+    let date = timestamp.with_timezone(&self.timezone).date_naive();
+    // sessions are keyed by their New York date whatever the timezone of the grid
+    let session = self.sessions.get(&trading_date(timestamp));
+    if !self.sessions.is_empty() && !self.extended_hours {
+      match session {
+        Some(session) if timestamp >= session.open && timestamp < session.close => {}
+        _ => return None,
+      }
+    }
+    match self.timeframe {
+      TimeFrame::Minute(n) => self.intraday_bucket(timestamp, date, session, TimeDelta::minutes(n as i64)),
+      TimeFrame::Hour(n) => self.intraday_bucket(timestamp, date, session, TimeDelta::hours(n as i64)),
+      TimeFrame::Day(n) => self.midnight(day_buckets.first_day_of(date, n)),
+      TimeFrame::Week(n) => {
+        let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+        let weeks = (monday - epoch_monday()).num_weeks();
+        self.midnight(epoch_monday() + TimeDelta::weeks(weeks - weeks.rem_euclid(n as i64)))
+      }
+      TimeFrame::Month(n) => {
+        let months = date.year() * 12 + date.month0() as i32;
+        let bucket = months - months.rem_euclid(n as i32);
+        self.midnight(NaiveDate::from_ymd_opt(
+          bucket.div_euclid(12),
+          bucket.rem_euclid(12) as u32 + 1,
+          1,
+        )?)
+      }
+    }
+  }
+
+  fn intraday_bucket(
+    &self,
+    timestamp: DateTime<Utc>,
+    date: NaiveDate,
+    session: Option<&Session>,
+    duration: TimeDelta,
+  ) -> Option<DateTime<Utc>> {
+    // pre-market buckets end at the open and after-hours buckets start at the close
+    let anchor = match session {
+      Some(session) if timestamp >= session.close => session.close,
+      Some(session) => session.open,
+      None => self.midnight(date)?,
+    };
+    let elapsed = (timestamp - anchor).num_seconds();
+    Some(anchor + TimeDelta::seconds(elapsed - elapsed.rem_euclid(duration.num_seconds())))
+  }
+
+  fn midnight(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+    date
+      .and_time(NaiveTime::MIN)
+      .and_local_timezone(self.timezone)
+      .earliest()
+      .map(|midnight| midnight.to_utc())
+  }
+}
+
+///
+///Groups consecutive trading days present in the data into buckets of `n` days
+#[derive(Default)]
+struct DayBuckets {
+  current: Option<(NaiveDate, NaiveDate, u32)>,
+}
+
+impl DayBuckets {
+  fn first_day_of(&mut self, date: NaiveDate, n: u32) -> NaiveDate {
+    match self.current {
+      Some((first, last, _)) if date == last => first,
+      Some((first, _, count)) if count < n => {
+        self.current = Some((first, date, count + 1));
+        first
+      }
+      _ => {
+        self.current = Some((date, date, 1));
+        date
+      }
+    }
+  }
+}
+
+struct BarAggregate {
+  bar: Bar,
+  vwap_notional: Option<f64>,
+}
+
+impl BarAggregate {
+  fn new(start: DateTime<Utc>, bar: &Bar) -> Self {
+    BarAggregate {
+      bar: Bar {
+        timestamp: start,
+        ..bar.clone()
+      },
+      vwap_notional: bar.vwap.map(|vwap| vwap * bar.volume),
+    }
+  }
+
+  fn add(&mut self, bar: &Bar) {
+    self.bar.high = self.bar.high.max(bar.high);
+    self.bar.low = self.bar.low.min(bar.low);
+    self.bar.close = bar.close;
+    self.bar.volume += bar.volume;
+    self.bar.trade_count = self
+      .bar
+      .trade_count
+      .zip(bar.trade_count)
+      .map(|(total, count)| total + count);
+    self.vwap_notional = self
+      .vwap_notional
+      .zip(bar.vwap)
+      .map(|(total, vwap)| total + vwap * bar.volume);
+  }
+
+  fn finish(mut self) -> Bar {
+    self.bar.vwap = match self.vwap_notional {
+      Some(notional) if self.bar.volume > 0.0 => Some(notional / self.bar.volume),
+      _ => None,
+    };
+    self.bar
+  }
+}
+
+fn epoch_monday() -> NaiveDate {
+  NaiveDate::from_ymd_opt(1970, 1, 5).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    api::TimeFrame,
+    history::BarResampler,
+    models::{
+      Bar,
+      CryptoTrade,
+      MarketCalendar,
+      TakerSide,
+    },
+  };
+  use chrono::{
+    DateTime,
+    NaiveDate,
+    NaiveTime,
+    TimeZone,
+    Utc,
+  };
+
+  fn bar(timestamp: DateTime<Utc>, close: f64, volume: f64) -> Bar {
+    Bar {
+      timestamp,
+      open: close,
+      high: close + 1.0,
+      low: close - 1.0,
+      close,
+      volume,
+      trade_count: Some(2),
+      vwap: Some(close),
+    }
+  }
+
+  #[test]
+  fn test_resample_should_aggregate_ohlcv_and_vwap() {
+    let bars = vec![
+      bar(Utc.with_ymd_and_hms(2025, 12, 1, 15, 1, 0).unwrap(), 10.0, 100.0),
+      bar(Utc.with_ymd_and_hms(2025, 12, 1, 15, 0, 0).unwrap(), 12.0, 300.0),
+      bar(Utc.with_ymd_and_hms(2025, 12, 1, 15, 5, 0).unwrap(), 11.0, 100.0),
+    ];
+
+    let resampled = BarResampler::new(TimeFrame::Minute(5)).unwrap().resample(&bars);
+    assert_eq!(resampled.len(), 2);
+    assert_eq!(
+      resampled[0].timestamp,
+      Utc.with_ymd_and_hms(2025, 12, 1, 15, 0, 0).unwrap()
+    );
+    assert_eq!(resampled[0].open, 12.0);
+    assert_eq!(resampled[0].close, 10.0);
+    assert_eq!(resampled[0].high, 13.0);
+    assert_eq!(resampled[0].low, 9.0);
+    assert_eq!(resampled[0].volume, 400.0);
+    assert_eq!(resampled[0].trade_count, Some(4));
+    assert_eq!(resampled[0].vwap, Some(11.5));
+  }
+
+  #[test]
+  fn test_resample_should_drop_vwap_when_a_bar_lacks_it() {
+    let mut bars = vec![
+      bar(Utc.with_ymd_and_hms(2025, 12, 1, 15, 0, 0).unwrap(), 10.0, 100.0),
+      bar(Utc.with_ymd_and_hms(2025, 12, 1, 15, 1, 0).unwrap(), 11.0, 100.0),
+    ];
+    bars[1].vwap = None;
+    bars[1].trade_count = None;
+
+    let resampled = BarResampler::new(TimeFrame::Hour(1)).unwrap().resample(&bars);
+    assert_eq!(resampled[0].vwap, None);
+    assert_eq!(resampled[0].trade_count, None);
+  }
+
+  #[test]
+  fn test_resample_should_align_to_early_close_session() {
+    // 2025-11-28 closes at 13:00 New York time, 18:00 UTC
+    let calendar = vec![MarketCalendar {
+      date: NaiveDate::from_ymd_opt(2025, 11, 28).unwrap(),
+      open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      close: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+      settlement_date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+    }];
+    let bars: Vec<Bar> = [(14, 0), (14, 30), (15, 29), (17, 45), (18, 0), (18, 30)]
+      .iter()
+      .map(|(hour, minute)| {
+        bar(
+          Utc.with_ymd_and_hms(2025, 11, 28, *hour, *minute, 0).unwrap(),
+          10.0,
+          1.0,
+        )
+      })
+      .collect();
+
+    let regular = BarResampler::new(TimeFrame::Hour(1)).unwrap().with_sessions(&calendar);
+    let starts: Vec<DateTime<Utc>> = regular.resample(&bars).iter().map(|bar| bar.timestamp).collect();
+    assert_eq!(
+      starts,
+      vec![
+        Utc.with_ymd_and_hms(2025, 11, 28, 14, 30, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 11, 28, 17, 30, 0).unwrap(),
+      ]
+    );
+
+    let extended = regular.with_extended_hours(true).resample(&bars);
+    let starts: Vec<DateTime<Utc>> = extended.iter().map(|bar| bar.timestamp).collect();
+    assert_eq!(
+      starts,
+      vec![
+        Utc.with_ymd_and_hms(2025, 11, 28, 13, 30, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 11, 28, 14, 30, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 11, 28, 17, 30, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 11, 28, 18, 0, 0).unwrap(),
+      ]
+    );
+    assert_eq!(extended[3].volume, 2.0);
+  }
+
+  #[test]
+  fn test_resample_should_find_sessions_across_a_timezone_date_line() {
+    // 10:30 New York time on 2025-11-28 is already 00:30 on 2025-11-29 in Tokyo
+    let calendar = vec![MarketCalendar {
+      date: NaiveDate::from_ymd_opt(2025, 11, 28).unwrap(),
+      open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      close: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+      settlement_date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+    }];
+    let bars = vec![
+      bar(Utc.with_ymd_and_hms(2025, 11, 28, 14, 45, 0).unwrap(), 10.0, 1.0),
+      bar(Utc.with_ymd_and_hms(2025, 11, 28, 15, 45, 0).unwrap(), 10.0, 1.0),
+    ];
+
+    let resampler = BarResampler::new(TimeFrame::Hour(1))
+      .unwrap()
+      .with_timezone(chrono_tz::Asia::Tokyo)
+      .with_sessions(&calendar);
+    let starts: Vec<DateTime<Utc>> = resampler.resample(&bars).iter().map(|bar| bar.timestamp).collect();
+    assert_eq!(
+      starts,
+      vec![
+        Utc.with_ymd_and_hms(2025, 11, 28, 14, 30, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 11, 28, 15, 30, 0).unwrap(),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_resample_trades_should_build_daily_and_weekly_bars() {
+    let trade = |day: u32, price: f64, size: f64| CryptoTrade {
+      timestamp: Utc.with_ymd_and_hms(2025, 12, day, 12, 0, 0).unwrap(),
+      price,
+      size,
+      taker_side: TakerSide::Buy,
+      id: day as u64,
+    };
+    let trades = vec![
+      trade(1, 100.0, 1.0),
+      trade(1, 102.0, 3.0),
+      trade(3, 99.0, 2.0),
+      trade(8, 98.0, 1.0),
+    ];
+
+    let daily = BarResampler::new(TimeFrame::Day(1))
+      .unwrap()
+      .with_timezone(chrono_tz::UTC)
+      .resample_trades(&trades);
+    assert_eq!(daily.len(), 3);
+    assert_eq!(daily[0].timestamp, Utc.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap());
+    assert_eq!(daily[0].trade_count, Some(2));
+    assert_eq!(daily[0].vwap, Some(101.5));
+
+    let weekly = BarResampler::new(TimeFrame::Week(1)).unwrap().resample_trades(&trades);
+    assert_eq!(weekly.len(), 2);
+    assert_eq!(weekly[0].timestamp, Utc.with_ymd_and_hms(2025, 12, 1, 5, 0, 0).unwrap());
+    assert_eq!(weekly[0].low, 99.0);
+    assert_eq!(weekly[0].close, 99.0);
+    assert_eq!(weekly[1].open, 98.0);
+  }
+
+  #[test]
+  fn test_resampler_should_reject_a_timeframe_of_zero_units() {
+    for timeframe in [
+      TimeFrame::Minute(0),
+      TimeFrame::Hour(0),
+      TimeFrame::Day(0),
+      TimeFrame::Week(0),
+      TimeFrame::Month(0),
+    ] {
+      assert!(BarResampler::new(timeframe).is_err());
+    }
+  }
+}
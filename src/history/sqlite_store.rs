@@ -0,0 +1,208 @@
+use crate::{
+  history::{
+    BarStore,
+    trading_date,
+  },
+  models::Bar,
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  NaiveDate,
+};
+use rusqlite::{
+  Connection,
+  params,
+};
+use std::{
+  collections::BTreeMap,
+  path::Path,
+};
+
+const SQLITE_SCHEMA: &str = "
+  CREATE TABLE IF NOT EXISTS bars (
+    symbol TEXT NOT NULL,
+    trading_date TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume REAL NOT NULL,
+    trade_count INTEGER,
+    vwap REAL,
+    PRIMARY KEY (symbol, timestamp)
+  );
+  CREATE INDEX IF NOT EXISTS bars_by_day ON bars (symbol, trading_date);
+  CREATE TABLE IF NOT EXISTS downloaded_days (
+    symbol TEXT NOT NULL,
+    trading_date TEXT NOT NULL,
+    bars INTEGER NOT NULL,
+    PRIMARY KEY (symbol, trading_date)
+  );
+";
+
+///
+///Stores bars of every symbol in one SQLite database, timestamps in UTC microseconds
+pub struct SqliteBarStore {
+  connection: Connection,
+}
+
+impl SqliteBarStore {
+  pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    Self::from_connection(Connection::open(path)?)
+  }
+
+  pub fn open_in_memory() -> anyhow::Result<Self> {
+    Self::from_connection(Connection::open_in_memory()?)
+  }
+
+  fn from_connection(connection: Connection) -> anyhow::Result<Self> {
+    connection.execute_batch(SQLITE_SCHEMA)?;
+    Ok(SqliteBarStore { connection })
+  }
+}
+
+impl BarStore for SqliteBarStore {
+  fn day_index(&self, symbol: &str) -> anyhow::Result<BTreeMap<NaiveDate, usize>> {
+    let mut statement = self
+      .connection
+      .prepare("SELECT trading_date, bars FROM downloaded_days WHERE symbol = ?1")?;
+    let rows = statement.query_map(params![symbol], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    let mut index = BTreeMap::new();
+    for row in rows {
+      let (date, count) = row?;
+      index.insert(date.parse()?, count as usize);
+    }
+    Ok(index)
+  }
+
+  fn write_days(&mut self, symbol: &str, days: &BTreeMap<NaiveDate, Vec<Bar>>) -> anyhow::Result<()> {
+    let transaction = self.connection.transaction()?;
+    for (date, bars) in days {
+      let date = date.to_string();
+      transaction.execute(
+        "DELETE FROM bars WHERE symbol = ?1 AND trading_date = ?2",
+        params![symbol, date],
+      )?;
+      for bar in bars {
+        transaction.execute(
+          "INSERT OR REPLACE INTO bars VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+          params![
+            symbol,
+            trading_date(bar.timestamp).to_string(),
+            bar.timestamp.timestamp_micros(),
+            bar.open,
+            bar.high,
+            bar.low,
+            bar.close,
+            bar.volume,
+            bar.trade_count.map(|count| count as i64),
+            bar.vwap,
+          ],
+        )?;
+      }
+      transaction.execute(
+        "INSERT OR REPLACE INTO downloaded_days VALUES (?1, ?2, ?3)",
+        params![symbol, date, bars.len() as i64],
+      )?;
+    }
+    transaction.commit()?;
+    Ok(())
+  }
+
+  fn remove_days(&mut self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<()> {
+    let (start, end) = (start.to_string(), end.to_string());
+    let transaction = self.connection.transaction()?;
+    transaction.execute(
+      "DELETE FROM bars WHERE symbol = ?1 AND trading_date BETWEEN ?2 AND ?3",
+      params![symbol, start, end],
+    )?;
+    transaction.execute(
+      "DELETE FROM downloaded_days WHERE symbol = ?1 AND trading_date BETWEEN ?2 AND ?3",
+      params![symbol, start, end],
+    )?;
+    transaction.commit()?;
+    Ok(())
+  }
+
+  fn read_bars(&self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<Vec<Bar>> {
+    let mut statement = self.connection.prepare(
+      "SELECT timestamp, open, high, low, close, volume, trade_count, vwap FROM bars
+       WHERE symbol = ?1 AND trading_date BETWEEN ?2 AND ?3 ORDER BY timestamp",
+    )?;
+    let rows = statement.query_map(params![symbol, start.to_string(), end.to_string()], |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        Bar {
+          timestamp: DateTime::UNIX_EPOCH,
+          open: row.get(1)?,
+          high: row.get(2)?,
+          low: row.get(3)?,
+          close: row.get(4)?,
+          volume: row.get(5)?,
+          trade_count: row.get::<_, Option<i64>>(6)?.map(|count| count as u64),
+          vwap: row.get(7)?,
+        },
+      ))
+    })?;
+    let mut bars = vec![];
+    for row in rows {
+      let (timestamp, mut bar) = row?;
+      bar.timestamp = match DateTime::from_timestamp_micros(timestamp) {
+        Some(timestamp) => timestamp,
+        None => bail!("timestamp {} out of range", timestamp),
+      };
+      bars.push(bar);
+    }
+    Ok(bars)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    history::{
+      BarStore,
+      SqliteBarStore,
+    },
+    models::Bar,
+  };
+  use chrono::{
+    NaiveDate,
+    TimeZone,
+    Utc,
+  };
+  use std::collections::BTreeMap;
+
+  #[test]
+  fn test_sqlite_store_should_replace_and_remove_days() {
+    let mut store = SqliteBarStore::open_in_memory().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+    let bar = Bar {
+      timestamp: Utc.with_ymd_and_hms(2025, 12, 1, 14, 30, 0).unwrap(),
+      open: 10.0,
+      high: 11.0,
+      low: 9.5,
+      close: 10.5,
+      volume: 1200.0,
+      trade_count: None,
+      vwap: Some(10.2),
+    };
+
+    store
+      .write_days("SPY", &BTreeMap::from([(date, vec![bar.clone()])]))
+      .unwrap();
+    store
+      .write_days("SPY", &BTreeMap::from([(date, vec![bar.clone()])]))
+      .unwrap();
+    assert_eq!(store.read_bars("SPY", date, date).unwrap(), vec![bar]);
+    assert_eq!(store.day_index("SPY").unwrap(), BTreeMap::from([(date, 1)]));
+
+    store.remove_days("SPY", date, date).unwrap();
+    assert!(store.read_bars("SPY", date, date).unwrap().is_empty());
+    assert!(store.day_index("SPY").unwrap().is_empty());
+  }
+}
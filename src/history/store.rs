@@ -0,0 +1,326 @@
+use crate::{
+  history::trading_date,
+  models::Bar,
+};
+use anyhow::{
+  Context,
+  bail,
+};
+use chrono::{
+  DateTime,
+  Datelike,
+  NaiveDate,
+};
+use std::{
+  collections::{
+    BTreeMap,
+    BTreeSet,
+  },
+  fs,
+  marker::PhantomData,
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+///
+///Local storage of bars for a single timeframe, partitioned by symbol and trading day
+pub trait BarStore {
+  ///
+  ///Trading days already downloaded for `symbol`, with the number of bars stored for each
+  fn day_index(&self, symbol: &str) -> anyhow::Result<BTreeMap<NaiveDate, usize>>;
+
+  ///
+  ///Replace the bars of every given trading day and mark those days as downloaded
+  fn write_days(&mut self, symbol: &str, days: &BTreeMap<NaiveDate, Vec<Bar>>) -> anyhow::Result<()>;
+
+  ///
+  ///Forget the bars and download markers of the trading days between `start` and `end`
+  fn remove_days(&mut self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<()>;
+
+  ///
+  ///Bars of the trading days between `start` and `end`, both inclusive, in timestamp order
+  fn read_bars(&self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<Vec<Bar>>;
+}
+
+///
+///Encoding of one partition file of a [`FileBarStore`]
+pub trait BarFileFormat {
+  const EXTENSION: &'static str;
+
+  fn read(path: &Path) -> anyhow::Result<Vec<Bar>>;
+
+  fn write(path: &Path, bars: &[Bar]) -> anyhow::Result<()>;
+}
+
+///
+///Stores bars under `{root}/{symbol}/{year}.{extension}` with a `days.csv` download index
+#[derive(Debug, Clone)]
+pub struct FileBarStore<F> {
+  root: PathBuf,
+  format: PhantomData<F>,
+}
+
+pub type CsvBarStore = FileBarStore<Csv>;
+
+impl<F: BarFileFormat> FileBarStore<F> {
+  pub fn new(root: impl Into<PathBuf>) -> Self {
+    FileBarStore {
+      root: root.into(),
+      format: PhantomData,
+    }
+  }
+
+  fn symbol_dir(&self, symbol: &str) -> PathBuf {
+    self.root.join(symbol.replace('/', "_"))
+  }
+
+  fn partition_path(&self, symbol: &str, year: i32) -> PathBuf {
+    self.symbol_dir(symbol).join(format!("{}.{}", year, F::EXTENSION))
+  }
+
+  fn index_path(&self, symbol: &str) -> PathBuf {
+    self.symbol_dir(symbol).join("days.csv")
+  }
+
+  fn read_partition(&self, symbol: &str, year: i32) -> anyhow::Result<Vec<Bar>> {
+    let path = self.partition_path(symbol, year);
+    if path.exists() {
+      F::read(&path).with_context(|| format!("failed to read {}", path.display()))
+    } else {
+      Ok(vec![])
+    }
+  }
+
+  fn write_partition(&self, symbol: &str, year: i32, bars: &[Bar]) -> anyhow::Result<()> {
+    let path = self.partition_path(symbol, year);
+    if bars.is_empty() {
+      if path.exists() {
+        fs::remove_file(&path)?;
+      }
+      return Ok(());
+    }
+    let temp_path = path.with_extension(format!("{}.tmp", F::EXTENSION));
+    F::write(&temp_path, bars).with_context(|| format!("failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+  }
+
+  fn write_index(&self, symbol: &str, index: &BTreeMap<NaiveDate, usize>) -> anyhow::Result<()> {
+    let path = self.index_path(symbol);
+    let temp_path = path.with_extension("csv.tmp");
+    let mut content = String::from("date,bars\n");
+    for (date, count) in index {
+      content.push_str(&format!("{},{}\n", date, count));
+    }
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+  }
+
+  ///
+  ///Rewrite the partitions of `years`, dropping the bars of trading days matching `remove`
+  fn rewrite_partitions(
+    &self,
+    symbol: &str,
+    years: BTreeSet<i32>,
+    remove: impl Fn(NaiveDate) -> bool,
+    days: &BTreeMap<NaiveDate, Vec<Bar>>,
+  ) -> anyhow::Result<()> {
+    for year in years {
+      let mut bars = self.read_partition(symbol, year)?;
+      bars.retain(|bar| !remove(trading_date(bar.timestamp)));
+      bars.extend(
+        days
+          .iter()
+          .filter(|(date, _)| date.year() == year)
+          .flat_map(|(_, day_bars)| day_bars.iter().cloned()),
+      );
+      bars.sort_by_key(|bar| bar.timestamp);
+      self.write_partition(symbol, year, &bars)?;
+    }
+    Ok(())
+  }
+}
+
+impl<F: BarFileFormat> BarStore for FileBarStore<F> {
+  fn day_index(&self, symbol: &str) -> anyhow::Result<BTreeMap<NaiveDate, usize>> {
+    let path = self.index_path(symbol);
+    if !path.exists() {
+      return Ok(BTreeMap::new());
+    }
+    let mut index = BTreeMap::new();
+    for line in fs::read_to_string(&path)?.lines().skip(1) {
+      match line.split_once(',') {
+        Some((date, count)) => {
+          index.insert(date.parse()?, count.parse()?);
+        }
+        None => bail!("malformed line {:?} in {}", line, path.display()),
+      }
+    }
+    Ok(index)
+  }
+
+  fn write_days(&mut self, symbol: &str, days: &BTreeMap<NaiveDate, Vec<Bar>>) -> anyhow::Result<()> {
+    if days.is_empty() {
+      return Ok(());
+    }
+    fs::create_dir_all(self.symbol_dir(symbol))?;
+    let years = days.keys().map(|date| date.year()).collect();
+    self.rewrite_partitions(symbol, years, |date| days.contains_key(&date), days)?;
+
+    let mut index = self.day_index(symbol)?;
+    index.extend(days.iter().map(|(date, bars)| (*date, bars.len())));
+    self.write_index(symbol, &index)
+  }
+
+  fn remove_days(&mut self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<()> {
+    if !self.symbol_dir(symbol).exists() {
+      return Ok(());
+    }
+    let years = (start.year()..=end.year()).collect();
+    self.rewrite_partitions(symbol, years, |date| date >= start && date <= end, &BTreeMap::new())?;
+
+    let mut index = self.day_index(symbol)?;
+    index.retain(|date, _| *date < start || *date > end);
+    self.write_index(symbol, &index)
+  }
+
+  fn read_bars(&self, symbol: &str, start: NaiveDate, end: NaiveDate) -> anyhow::Result<Vec<Bar>> {
+    let mut bars = vec![];
+    for year in start.year()..=end.year() {
+      bars.extend(self.read_partition(symbol, year)?.into_iter().filter(|bar| {
+        let date = trading_date(bar.timestamp);
+        date >= start && date <= end
+      }));
+    }
+    Ok(bars)
+  }
+}
+
+///
+///Plain text partitions with a `timestamp,open,high,low,close,volume,trade_count,vwap` header
+#[derive(Debug, Clone)]
+pub struct Csv;
+
+const CSV_HEADER: &str = "timestamp,open,high,low,close,volume,trade_count,vwap";
+
+impl BarFileFormat for Csv {
+  const EXTENSION: &'static str = "csv";
+
+  fn read(path: &Path) -> anyhow::Result<Vec<Bar>> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.lines();
+    if lines.next() != Some(CSV_HEADER) {
+      bail!("unexpected csv header")
+    }
+    lines
+      .map(|line| {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != 8 {
+          bail!("malformed csv line {:?}", line)
+        }
+        Ok(Bar {
+          timestamp: DateTime::parse_from_rfc3339(fields[0])?.to_utc(),
+          open: fields[1].parse()?,
+          high: fields[2].parse()?,
+          low: fields[3].parse()?,
+          close: fields[4].parse()?,
+          volume: fields[5].parse()?,
+          trade_count: match fields[6] {
+            "" => None,
+            value => Some(value.parse()?),
+          },
+          vwap: match fields[7] {
+            "" => None,
+            value => Some(value.parse()?),
+          },
+        })
+      })
+      .collect()
+  }
+
+  fn write(path: &Path, bars: &[Bar]) -> anyhow::Result<()> {
+    let mut content = format!("{}\n", CSV_HEADER);
+    for bar in bars {
+      content.push_str(&format!(
+        "{},{},{},{},{},{},{},{}\n",
+        bar.timestamp.to_rfc3339(),
+        bar.open,
+        bar.high,
+        bar.low,
+        bar.close,
+        bar.volume,
+        bar.trade_count.map(|count| count.to_string()).unwrap_or_default(),
+        bar.vwap.map(|vwap| vwap.to_string()).unwrap_or_default(),
+      ));
+    }
+    fs::write(path, content)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    history::{
+      BarStore,
+      CsvBarStore,
+    },
+    models::Bar,
+  };
+  use chrono::{
+    NaiveDate,
+    TimeZone,
+    Utc,
+  };
+  use std::collections::BTreeMap;
+
+  fn bar(day: u32, hour: u32, close: f64) -> Bar {
+    Bar {
+      timestamp: Utc.with_ymd_and_hms(2025, 12, day, hour, 0, 0).unwrap(),
+      open: close,
+      high: close,
+      low: close,
+      close,
+      volume: 100.0,
+      trade_count: if hour == 0 { None } else { Some(10) },
+      vwap: Some(close),
+    }
+  }
+
+  #[test]
+  fn test_csv_store_should_replace_days_and_keep_index() {
+    let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    let mut store = CsvBarStore::new(&root);
+    let first = NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+    let second = NaiveDate::from_ymd_opt(2025, 12, 2).unwrap();
+
+    // 00:00 UTC on the 3rd is still the 2nd in New York
+    let days = BTreeMap::from([
+      (first, vec![bar(1, 15, 1.0)]),
+      (second, vec![bar(2, 15, 2.0), bar(3, 0, 2.5)]),
+    ]);
+    store.write_days("AAPL", &days).unwrap();
+    store
+      .write_days("AAPL", &BTreeMap::from([(first, vec![bar(1, 16, 1.5)])]))
+      .unwrap();
+
+    let bars = store.read_bars("AAPL", first, second).unwrap();
+    assert_eq!(
+      bars.iter().map(|bar| bar.close).collect::<Vec<_>>(),
+      vec![1.5, 2.0, 2.5]
+    );
+    assert_eq!(bars[2].trade_count, None);
+    assert_eq!(
+      store.day_index("AAPL").unwrap(),
+      BTreeMap::from([(first, 1), (second, 2)])
+    );
+
+    store.remove_days("AAPL", second, second).unwrap();
+    assert_eq!(store.read_bars("AAPL", first, second).unwrap().len(), 1);
+    assert_eq!(store.day_index("AAPL").unwrap(), BTreeMap::from([(first, 1)]));
+    std::fs::remove_dir_all(root).unwrap();
+  }
+}
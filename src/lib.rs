@@ -1,4 +1,14 @@
+pub mod analytics;
 pub mod api;
+pub mod backtest;
+pub mod dry_run;
+pub mod execution;
+pub mod history;
+pub mod portfolio;
+pub mod profile;
+pub mod risk;
+pub mod sim;
+pub mod stream;
 
 mod client;
 mod models;
@@ -3,6 +3,7 @@ use crate::models::{
   utils::{
     Money,
     deserialize_str_to_u8,
+    serialize_u8_to_str,
   },
 };
 use chrono::{
@@ -41,7 +42,7 @@ pub struct Account {
   pub account_blocked: bool,
   pub trade_suspended_by_user: bool,
   pub shorting_enabled: bool,
-  #[serde(deserialize_with = "deserialize_str_to_u8")]
+  #[serde(serialize_with = "serialize_u8_to_str", deserialize_with = "deserialize_str_to_u8")]
   pub multiplier: u8,
   pub equity: Money,
   pub last_equity: Money,
@@ -55,7 +56,7 @@ pub struct Account {
   pub daytrade_count: u16,
   pub balance_asof: NaiveDate,
   pub crypto_tier: u8,
-  #[serde(deserialize_with = "deserialize_str_to_u8")]
+  #[serde(serialize_with = "serialize_u8_to_str", deserialize_with = "deserialize_str_to_u8")]
   pub intraday_adjustments: u8,
   pub pending_reg_taf_fees: Option<Money>,
   #[serde(skip_serializing_if = "Option::is_none")]
@@ -65,7 +66,7 @@ pub struct Account {
   pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AccountStatus {
   Onboarding,
@@ -76,3 +77,26 @@ pub enum AccountStatus {
   Active,
   Rejected,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountConfigurations {
+  ///
+  ///`both`, `entry` or `exit`
+  pub dtbp_check: String,
+  ///
+  ///`all` or `none`
+  pub trade_confirm_email: String,
+  pub suspend_trade: bool,
+  pub no_shorting: bool,
+  pub fractional_trading: bool,
+  ///
+  ///`1`, `2` or `4`
+  pub max_margin_multiplier: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_options_trading_level: Option<u8>,
+  ///
+  ///`both`, `entry` or `exit`
+  pub pdt_check: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ptp_no_exception_entry: Option<bool>,
+}
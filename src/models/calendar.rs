@@ -2,9 +2,12 @@ use chrono::{
   NaiveDate,
   NaiveTime,
 };
-use serde::Deserialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketCalendar {
   pub date: NaiveDate,
   pub open: NaiveTime,
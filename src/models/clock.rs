@@ -2,9 +2,12 @@ use chrono::{
   DateTime,
   Local,
 };
-use serde::Deserialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MarketClock {
   pub timestamp: DateTime<Local>,
   pub is_open: bool,
@@ -3,17 +3,20 @@ use chrono::{
   DateTime,
   Utc,
 };
-use serde::Deserialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CryptoWalletInfo {
   pub chain: String,
   pub address: String,
   pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CryptoTransfer {
   pub id: Uuid,
   pub tx_hash: String,
@@ -30,14 +33,14 @@ pub struct CryptoTransfer {
   pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum CryptoDirection {
   Incoming,
   Outgoing,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum CryptoStatus {
   Processing,
@@ -45,7 +48,7 @@ pub enum CryptoStatus {
   Complete,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct WhiteListedAddress {
   pub id: String,
   pub chain: String,
@@ -55,14 +58,14 @@ pub struct WhiteListedAddress {
   pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AddressStatus {
   Approved,
   Pending,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GasFee {
   pub fee: Money,
 }
@@ -0,0 +1,107 @@
+use crate::models::Bar;
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CryptoLocation {
+  #[serde(rename = "us")]
+  Us,
+  #[serde(rename = "us-1")]
+  Us1,
+}
+
+impl Display for CryptoLocation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CryptoLocation::Us => write!(f, "us"),
+      CryptoLocation::Us1 => write!(f, "us-1"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TakerSide {
+  #[serde(rename = "B")]
+  Buy,
+  #[serde(rename = "S")]
+  Sell,
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CryptoTrade {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "p")]
+  pub price: f64,
+  #[serde(rename = "s")]
+  pub size: f64,
+  #[serde(rename = "tks")]
+  pub taker_side: TakerSide,
+  #[serde(rename = "i")]
+  pub id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CryptoQuote {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "bp")]
+  pub bid_price: f64,
+  #[serde(rename = "bs")]
+  pub bid_size: f64,
+  #[serde(rename = "ap")]
+  pub ask_price: f64,
+  #[serde(rename = "as")]
+  pub ask_size: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderbookLevel {
+  #[serde(rename = "p")]
+  pub price: f64,
+  #[serde(rename = "s")]
+  pub size: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CryptoOrderbook {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "b")]
+  pub bids: Vec<OrderbookLevel>,
+  #[serde(rename = "a")]
+  pub asks: Vec<OrderbookLevel>,
+}
+
+impl CryptoOrderbook {
+  pub fn best_bid(&self) -> Option<&OrderbookLevel> {
+    self.bids.iter().max_by(|a, b| a.price.total_cmp(&b.price))
+  }
+
+  pub fn best_ask(&self) -> Option<&OrderbookLevel> {
+    self.asks.iter().min_by(|a, b| a.price.total_cmp(&b.price))
+  }
+
+  pub fn spread(&self) -> Option<f64> {
+    Some(self.best_ask()?.price - self.best_bid()?.price)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoSnapshot {
+  pub latest_trade: Option<CryptoTrade>,
+  pub latest_quote: Option<CryptoQuote>,
+  pub minute_bar: Option<Bar>,
+  pub daily_bar: Option<Bar>,
+  pub prev_daily_bar: Option<Bar>,
+}
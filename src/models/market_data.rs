@@ -0,0 +1,46 @@
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bar {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "o")]
+  pub open: f64,
+  #[serde(rename = "h")]
+  pub high: f64,
+  #[serde(rename = "l")]
+  pub low: f64,
+  #[serde(rename = "c")]
+  pub close: f64,
+  #[serde(rename = "v")]
+  pub volume: f64,
+  #[serde(rename = "n", skip_serializing_if = "Option::is_none")]
+  pub trade_count: Option<u64>,
+  #[serde(rename = "vw", skip_serializing_if = "Option::is_none")]
+  pub vwap: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StockFeed {
+  Sip,
+  Iex,
+  Otc,
+  Boats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjustment {
+  Raw,
+  Split,
+  Dividend,
+  All,
+}
@@ -4,12 +4,19 @@ mod calendar;
 mod clock;
 mod corporate_action;
 mod crypto_funding;
+mod crypto_market_data;
 pub mod enums;
 mod error;
+mod market_data;
+mod news;
+mod option_market_data;
 mod options;
 mod orders;
 mod position;
 mod profiles;
+mod screener;
+mod stream;
+mod trade_update;
 pub mod utils;
 mod watch_list;
 
@@ -19,9 +26,16 @@ pub use calendar::*;
 pub use clock::*;
 pub use corporate_action::*;
 pub use crypto_funding::*;
+pub use crypto_market_data::*;
 pub use error::*;
+pub use market_data::*;
+pub use news::*;
+pub use option_market_data::*;
 pub use options::*;
 pub use orders::*;
 pub use position::*;
 pub use profiles::*;
+pub use screener::*;
+pub use stream::*;
+pub use trade_update::*;
 pub use watch_list::*;
@@ -0,0 +1,39 @@
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewsArticle {
+  pub id: u64,
+  pub headline: String,
+  pub summary: String,
+  pub author: String,
+  #[serde(default)]
+  pub content: String,
+  #[serde(default)]
+  pub images: Vec<NewsImage>,
+  pub symbols: Vec<String>,
+  pub source: String,
+  pub url: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewsImage {
+  pub size: NewsImageSize,
+  pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NewsImageSize {
+  Thumb,
+  Small,
+  Large,
+}
@@ -0,0 +1,110 @@
+use crate::models::{
+  Bar,
+  OptionContract,
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionFeed {
+  Indicative,
+  Opra,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionTrade {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "x")]
+  pub exchange: String,
+  #[serde(rename = "p")]
+  pub price: f64,
+  #[serde(rename = "s")]
+  pub size: u32,
+  #[serde(rename = "c")]
+  pub condition: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionQuote {
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "bx")]
+  pub bid_exchange: String,
+  #[serde(rename = "bp")]
+  pub bid_price: f64,
+  #[serde(rename = "bs")]
+  pub bid_size: u32,
+  #[serde(rename = "ax")]
+  pub ask_exchange: String,
+  #[serde(rename = "ap")]
+  pub ask_price: f64,
+  #[serde(rename = "as")]
+  pub ask_size: u32,
+  #[serde(rename = "c")]
+  pub condition: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Greeks {
+  pub delta: f64,
+  pub gamma: f64,
+  pub rho: f64,
+  pub theta: f64,
+  pub vega: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionSnapshot {
+  pub latest_trade: Option<OptionTrade>,
+  pub latest_quote: Option<OptionQuote>,
+  pub minute_bar: Option<Bar>,
+  pub daily_bar: Option<Bar>,
+  pub prev_daily_bar: Option<Bar>,
+  pub implied_volatility: Option<f64>,
+  pub greeks: Option<Greeks>,
+}
+
+#[derive(Debug)]
+pub struct OptionChainEntry {
+  pub contract: OptionContract,
+  pub snapshot: Option<OptionSnapshot>,
+}
+
+impl OptionChainEntry {
+  pub fn delta(&self) -> Option<f64> {
+    self.snapshot.as_ref()?.greeks.as_ref().map(|greeks| greeks.delta)
+  }
+
+  pub fn implied_volatility(&self) -> Option<f64> {
+    self.snapshot.as_ref()?.implied_volatility
+  }
+
+  pub fn open_interest(&self) -> Option<u32> {
+    self.contract.open_interest
+  }
+}
+
+///
+///Pair each contract with the snapshot sharing its symbol, keeping the order of `contracts`
+pub fn join_option_chain(
+  contracts: Vec<OptionContract>,
+  mut snapshots: HashMap<String, OptionSnapshot>,
+) -> Vec<OptionChainEntry> {
+  contracts
+    .into_iter()
+    .map(|contract| {
+      let snapshot = snapshots.remove(&contract.symbol);
+      OptionChainEntry { contract, snapshot }
+    })
+    .collect()
+}
@@ -51,7 +51,7 @@ pub struct OptionContract {
   pub deliverables: Option<Vec<Deliverable>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[derive(Default)]
 pub enum OptionStatus {
@@ -60,7 +60,7 @@ pub struct Order {
   pub position_intent: PositionIntent,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum OrderStatus {
   New,
@@ -79,17 +79,36 @@ pub enum OrderStatus {
   Rejected,
   Suspended,
   Calculated,
+  Held,
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+impl OrderStatus {
+  ///
+  ///Whether the order can no longer fill, `Replaced` orders live on in their replacement
+  pub fn is_final(&self) -> bool {
+    matches!(
+      self,
+      OrderStatus::Filled
+        | OrderStatus::Canceled
+        | OrderStatus::Expired
+        | OrderStatus::Rejected
+        | OrderStatus::DoneForDay
+        | OrderStatus::Replaced
+    )
+  }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderClass {
   Simple,
   Oco,
+  #[serde(rename = "oto")]
   Otc,
   Trigger,
   Bracket,
   Mleg,
+  #[serde(rename = "")]
   Empty,
 }
 
@@ -126,7 +145,7 @@ impl<'de> Visitor<'de> for OrderClassVisitor {
   }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TimeInForce {
   DAY,
@@ -137,7 +156,7 @@ pub enum TimeInForce {
   FOK,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum PositionIntent {
   BuyToOpen,
@@ -83,7 +83,7 @@ pub struct ClosedPosition {
   pub legs: Option<Vec<Order>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum PositionSide {
   Long,
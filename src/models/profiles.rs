@@ -4,6 +4,11 @@ use serde::{
   Deserialize,
   Serialize,
 };
+use std::{
+  collections::BTreeMap,
+  fmt,
+  str::FromStr,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PortfolioHistory {
@@ -17,8 +22,255 @@ pub struct PortfolioHistory {
   pub cashflow: Option<CashFlow>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename(deserialize = "FEE"))]
-pub struct CashFlow {
-  pub fee: Option<Vec<f64>>,
+impl PortfolioHistory {
+  ///
+  ///Values of `activity_type` aligned to [`PortfolioHistory::timestamp`], zero where the activity
+  /// did not occur or Alpaca sent a shorter series
+  pub fn cash_flows(&self, activity_type: &ActivityType) -> Vec<f64> {
+    let values = self.cashflow.as_ref().and_then(|cashflow| cashflow.get(activity_type));
+    let mut aligned: Vec<f64> = values
+      .unwrap_or_default()
+      .iter()
+      .copied()
+      .take(self.timestamp.len())
+      .collect();
+    aligned.resize(self.timestamp.len(), 0.0);
+    aligned
+  }
+
+  ///
+  ///Money moved into (positive) or out of (negative) the account per timestamp, the flows that
+  /// time-weighted and money-weighted returns take out of performance
+  pub fn external_cash_flows(&self) -> Vec<f64> {
+    let mut flows = vec![0.0; self.timestamp.len()];
+    let Some(cashflow) = &self.cashflow else {
+      return flows;
+    };
+    for (_, values) in cashflow
+      .0
+      .iter()
+      .filter(|(activity_type, _)| activity_type.is_external())
+    {
+      for (flow, value) in flows.iter_mut().zip(values) {
+        *flow += value;
+      }
+    }
+    flows
+  }
+}
+
+///
+///Non-trade activity types Alpaca reports in account activities and in the `cashflow` of a
+/// portfolio history, codes without a variant are kept in [`ActivityType::Other`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ActivityType {
+  ///
+  ///ACATS transfer of cash
+  Acatc,
+  ///
+  ///ACATS transfer of securities
+  Acats,
+  ///
+  ///Cash deposit
+  Csd,
+  ///
+  ///Cash withdrawal
+  Csw,
+  Div,
+  ///
+  ///Long term capital gain dividend
+  Divcgl,
+  ///
+  ///Short term capital gain dividend
+  Divcgs,
+  Divfee,
+  ///
+  ///Dividend adjusted for foreign tax withheld
+  Divft,
+  ///
+  ///Dividend adjusted for NRA withholding
+  Divnra,
+  ///
+  ///Dividend return of capital
+  Divroc,
+  ///
+  ///Dividend adjusted for tefra withholding
+  Divtw,
+  Divtxex,
+  Fee,
+  Int,
+  Intnra,
+  Inttw,
+  Jnl,
+  ///
+  ///Cash journal between accounts
+  Jnlc,
+  ///
+  ///Stock journal between accounts
+  Jnls,
+  ///
+  ///Merger or acquisition
+  Ma,
+  ///
+  ///Name change
+  Nc,
+  ///
+  ///Pass-thru charge
+  Ptc,
+  ///
+  ///Pass-thru rebate
+  Ptr,
+  Reorg,
+  ///
+  ///Symbol change
+  Sc,
+  ///
+  ///Stock spinoff
+  Sso,
+  ///
+  ///Stock split
+  Ssp,
+  Other(String),
+}
+
+impl ActivityType {
+  pub fn as_str(&self) -> &str {
+    match self {
+      ActivityType::Acatc => "ACATC",
+      ActivityType::Acats => "ACATS",
+      ActivityType::Csd => "CSD",
+      ActivityType::Csw => "CSW",
+      ActivityType::Div => "DIV",
+      ActivityType::Divcgl => "DIVCGL",
+      ActivityType::Divcgs => "DIVCGS",
+      ActivityType::Divfee => "DIVFEE",
+      ActivityType::Divft => "DIVFT",
+      ActivityType::Divnra => "DIVNRA",
+      ActivityType::Divroc => "DIVROC",
+      ActivityType::Divtw => "DIVTW",
+      ActivityType::Divtxex => "DIVTXEX",
+      ActivityType::Fee => "FEE",
+      ActivityType::Int => "INT",
+      ActivityType::Intnra => "INTNRA",
+      ActivityType::Inttw => "INTTW",
+      ActivityType::Jnl => "JNL",
+      ActivityType::Jnlc => "JNLC",
+      ActivityType::Jnls => "JNLS",
+      ActivityType::Ma => "MA",
+      ActivityType::Nc => "NC",
+      ActivityType::Ptc => "PTC",
+      ActivityType::Ptr => "PTR",
+      ActivityType::Reorg => "REORG",
+      ActivityType::Sc => "SC",
+      ActivityType::Sso => "SSO",
+      ActivityType::Ssp => "SSP",
+      ActivityType::Other(code) => code,
+    }
+  }
+
+  ///
+  ///Deposits, withdrawals, journals and transfers move money across the account boundary, while
+  /// dividends, interest and fees are part of its performance
+  pub fn is_external(&self) -> bool {
+    matches!(
+      self,
+      ActivityType::Csd
+        | ActivityType::Csw
+        | ActivityType::Jnl
+        | ActivityType::Jnlc
+        | ActivityType::Jnls
+        | ActivityType::Acatc
+        | ActivityType::Acats
+    )
+  }
+}
+
+impl FromStr for ActivityType {
+  type Err = std::convert::Infallible;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let activity_type = match s.to_ascii_uppercase().as_str() {
+      "ACATC" => ActivityType::Acatc,
+      "ACATS" => ActivityType::Acats,
+      "CSD" => ActivityType::Csd,
+      "CSW" => ActivityType::Csw,
+      "DIV" => ActivityType::Div,
+      "DIVCGL" => ActivityType::Divcgl,
+      "DIVCGS" => ActivityType::Divcgs,
+      "DIVFEE" => ActivityType::Divfee,
+      "DIVFT" => ActivityType::Divft,
+      "DIVNRA" => ActivityType::Divnra,
+      "DIVROC" => ActivityType::Divroc,
+      "DIVTW" => ActivityType::Divtw,
+      "DIVTXEX" => ActivityType::Divtxex,
+      "FEE" => ActivityType::Fee,
+      "INT" => ActivityType::Int,
+      "INTNRA" => ActivityType::Intnra,
+      "INTTW" => ActivityType::Inttw,
+      "JNL" => ActivityType::Jnl,
+      "JNLC" => ActivityType::Jnlc,
+      "JNLS" => ActivityType::Jnls,
+      "MA" => ActivityType::Ma,
+      "NC" => ActivityType::Nc,
+      "PTC" => ActivityType::Ptc,
+      "PTR" => ActivityType::Ptr,
+      "REORG" => ActivityType::Reorg,
+      "SC" => ActivityType::Sc,
+      "SSO" => ActivityType::Sso,
+      "SSP" => ActivityType::Ssp,
+      other => ActivityType::Other(other.to_string()),
+    };
+    Ok(activity_type)
+  }
+}
+
+impl fmt::Display for ActivityType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl Serialize for ActivityType {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    serializer.serialize_str(self.as_str())
+  }
+}
+
+impl<'de> Deserialize<'de> for ActivityType {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    let Ok(activity_type) = String::deserialize(deserializer)?.parse();
+    Ok(activity_type)
+  }
+}
+
+///
+///Cash flow series of a portfolio history keyed by activity type, e.g.
+/// `{"CSD": [0, 1000, 0], "FEE": [0, 0, -1.5]}`, each aligned to the history timestamps
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CashFlow(pub BTreeMap<ActivityType, Vec<f64>>);
+
+impl CashFlow {
+  pub fn get(&self, activity_type: &ActivityType) -> Option<&[f64]> {
+    self.0.get(activity_type).map(Vec::as_slice)
+  }
+
+  pub fn activity_types(&self) -> impl Iterator<Item = &ActivityType> {
+    self.0.keys()
+  }
+
+  ///
+  ///Sum of one activity type over the whole history
+  pub fn total(&self, activity_type: &ActivityType) -> f64 {
+    self
+      .get(activity_type)
+      .map(|values| values.iter().sum())
+      .unwrap_or_default()
+  }
 }
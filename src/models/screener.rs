@@ -0,0 +1,74 @@
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+
+///
+///A screener row that can be resolved to an `Asset` or added to a watchlist by its symbol
+pub trait ScreenerResult {
+  fn symbol(&self) -> &str;
+}
+
+impl<T: ScreenerResult> ScreenerResult for &T {
+  fn symbol(&self) -> &str {
+    (*self).symbol()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketType {
+  Stocks,
+  Crypto,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MostActive {
+  pub symbol: String,
+  pub volume: f64,
+  pub trade_count: u64,
+}
+
+impl ScreenerResult for MostActive {
+  fn symbol(&self) -> &str {
+    &self.symbol
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MostActives {
+  pub most_actives: Vec<MostActive>,
+  pub last_updated: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mover {
+  pub symbol: String,
+  pub percent_change: f64,
+  pub change: f64,
+  pub price: f64,
+}
+
+impl ScreenerResult for Mover {
+  fn symbol(&self) -> &str {
+    &self.symbol
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketMovers {
+  pub gainers: Vec<Mover>,
+  pub losers: Vec<Mover>,
+  pub market_type: MarketType,
+  pub last_updated: DateTime<Utc>,
+}
+
+impl MarketMovers {
+  pub fn all(&self) -> Vec<&Mover> {
+    self.gainers.iter().chain(self.losers.iter()).collect()
+  }
+}
@@ -0,0 +1,418 @@
+use crate::models::{
+  Bar,
+  NewsArticle,
+  TakerSide,
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Deserializer,
+  Serialize,
+  de::{
+    SeqAccess,
+    Visitor,
+  },
+};
+
+///
+///Channels of a market data stream subscription, `"*"` subscribes to every symbol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub trades: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub quotes: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub bars: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub updated_bars: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub daily_bars: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub statuses: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub lulds: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub corrections: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub news: Vec<String>,
+}
+
+impl Subscription {
+  pub fn is_empty(&self) -> bool {
+    self.channels().iter().all(|channel| channel.is_empty())
+  }
+
+  pub fn add(&mut self, other: &Subscription) {
+    for (channel, symbols) in self.channels_mut().into_iter().zip(other.channels()) {
+      for symbol in symbols {
+        if !channel.contains(symbol) {
+          channel.push(symbol.clone());
+        }
+      }
+    }
+  }
+
+  pub fn remove(&mut self, other: &Subscription) {
+    for (channel, symbols) in self.channels_mut().into_iter().zip(other.channels()) {
+      channel.retain(|symbol| !symbols.contains(symbol));
+    }
+  }
+
+  fn channels(&self) -> [&Vec<String>; 9] {
+    [
+      &self.trades,
+      &self.quotes,
+      &self.bars,
+      &self.updated_bars,
+      &self.daily_bars,
+      &self.statuses,
+      &self.lulds,
+      &self.corrections,
+      &self.news,
+    ]
+  }
+
+  fn channels_mut(&mut self) -> [&mut Vec<String>; 9] {
+    [
+      &mut self.trades,
+      &mut self.quotes,
+      &mut self.bars,
+      &mut self.updated_bars,
+      &mut self.daily_bars,
+      &mut self.statuses,
+      &mut self.lulds,
+      &mut self.corrections,
+      &mut self.news,
+    ]
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "T")]
+pub enum MarketDataMessage {
+  #[serde(rename = "t")]
+  Trade(StreamTrade),
+  #[serde(rename = "q")]
+  Quote(StreamQuote),
+  #[serde(rename = "b")]
+  Bar(StreamBar),
+  #[serde(rename = "u")]
+  UpdatedBar(StreamBar),
+  #[serde(rename = "d")]
+  DailyBar(StreamBar),
+  #[serde(rename = "s")]
+  TradingStatus(TradingStatus),
+  #[serde(rename = "l")]
+  Luld(Luld),
+  #[serde(rename = "c")]
+  Correction(TradeCorrection),
+  #[serde(rename = "x")]
+  CancelError(TradeCancel),
+  #[serde(rename = "n")]
+  News(NewsArticle),
+  #[serde(rename = "success")]
+  Success { msg: String },
+  #[serde(rename = "subscription")]
+  Subscription(Subscription),
+  #[serde(rename = "error")]
+  Error { code: u32, msg: String },
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StreamTrade {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "i")]
+  pub id: Option<u64>,
+  #[serde(rename = "x")]
+  pub exchange: Option<String>,
+  #[serde(rename = "p")]
+  pub price: f64,
+  #[serde(rename = "s")]
+  pub size: f64,
+  #[serde(rename = "c", default, deserialize_with = "deserialize_conditions")]
+  pub conditions: Vec<String>,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+  #[serde(rename = "tks")]
+  pub taker_side: Option<TakerSide>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StreamQuote {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "bx")]
+  pub bid_exchange: Option<String>,
+  #[serde(rename = "bp")]
+  pub bid_price: f64,
+  #[serde(rename = "bs")]
+  pub bid_size: f64,
+  #[serde(rename = "ax")]
+  pub ask_exchange: Option<String>,
+  #[serde(rename = "ap")]
+  pub ask_price: f64,
+  #[serde(rename = "as")]
+  pub ask_size: f64,
+  #[serde(rename = "c", default, deserialize_with = "deserialize_conditions")]
+  pub conditions: Vec<String>,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StreamBar {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(flatten)]
+  pub bar: Bar,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TradingStatus {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "sc")]
+  pub status_code: String,
+  #[serde(rename = "sm")]
+  pub status_message: String,
+  #[serde(rename = "rc")]
+  pub reason_code: String,
+  #[serde(rename = "rm")]
+  pub reason_message: String,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Luld {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "u")]
+  pub limit_up_price: f64,
+  #[serde(rename = "d")]
+  pub limit_down_price: f64,
+  #[serde(rename = "i")]
+  pub indicator: String,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TradeCorrection {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "x")]
+  pub exchange: String,
+  #[serde(rename = "oi")]
+  pub original_id: u64,
+  #[serde(rename = "op")]
+  pub original_price: f64,
+  #[serde(rename = "os")]
+  pub original_size: f64,
+  #[serde(rename = "oc", default, deserialize_with = "deserialize_conditions")]
+  pub original_conditions: Vec<String>,
+  #[serde(rename = "ci")]
+  pub corrected_id: u64,
+  #[serde(rename = "cp")]
+  pub corrected_price: f64,
+  #[serde(rename = "cs")]
+  pub corrected_size: f64,
+  #[serde(rename = "cc", default, deserialize_with = "deserialize_conditions")]
+  pub corrected_conditions: Vec<String>,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TradeCancel {
+  #[serde(rename = "S")]
+  pub symbol: String,
+  #[serde(rename = "i")]
+  pub id: u64,
+  #[serde(rename = "x")]
+  pub exchange: String,
+  #[serde(rename = "p")]
+  pub price: f64,
+  #[serde(rename = "s")]
+  pub size: f64,
+  #[serde(rename = "a")]
+  pub action: String,
+  #[serde(rename = "t", deserialize_with = "deserialize_stream_timestamp")]
+  pub timestamp: DateTime<Utc>,
+  #[serde(rename = "z")]
+  pub tape: Option<String>,
+}
+
+///
+///Stocks send trade conditions as a list, options as a single string
+pub fn deserialize_conditions<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  deserializer.deserialize_any(ConditionsVisitor)
+}
+
+struct ConditionsVisitor;
+
+impl<'de> Visitor<'de> for ConditionsVisitor {
+  type Value = Vec<String>;
+
+  fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    formatter.write_str("a condition string or a list of condition strings")
+  }
+
+  fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    Ok(vec![v.to_string()])
+  }
+
+  fn visit_unit<E>(self) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    Ok(vec![])
+  }
+
+  fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+  where
+    A: SeqAccess<'de>,
+  {
+    let mut conditions = Vec::new();
+    while let Some(condition) = seq.next_element::<String>()? {
+      conditions.push(condition);
+    }
+    Ok(conditions)
+  }
+}
+
+///
+///JSON streams send RFC 3339 strings, msgpack streams send the timestamp extension (type -1)
+pub fn deserialize_stream_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  deserializer.deserialize_any(StreamTimestampVisitor)
+}
+
+struct StreamTimestampVisitor;
+
+impl<'de> Visitor<'de> for StreamTimestampVisitor {
+  type Value = DateTime<Utc>;
+
+  fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    formatter.write_str("an RFC 3339 string or a msgpack timestamp extension")
+  }
+
+  fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    DateTime::parse_from_rfc3339(v)
+      .map(|timestamp| timestamp.with_timezone(&Utc))
+      .map_err(serde::de::Error::custom)
+  }
+
+  fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    deserializer.deserialize_any(self)
+  }
+
+  fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+  where
+    A: SeqAccess<'de>,
+  {
+    let tag: i8 = seq
+      .next_element()?
+      .ok_or_else(|| serde::de::Error::custom("missing msgpack extension type"))?;
+    let data: ExtData = seq
+      .next_element()?
+      .ok_or_else(|| serde::de::Error::custom("missing msgpack extension data"))?;
+    if tag != -1 {
+      return Err(serde::de::Error::custom(format!(
+        "unexpected msgpack extension type {tag}"
+      )));
+    }
+    let (seconds, nanos) = match data.0.as_slice() {
+      [a, b, c, d] => (i64::from(u32::from_be_bytes([*a, *b, *c, *d])), 0),
+      bytes if bytes.len() == 8 => {
+        let value = u64::from_be_bytes(bytes.try_into().unwrap());
+        ((value & 0x0000_0003_ffff_ffff) as i64, (value >> 34) as u32)
+      }
+      bytes if bytes.len() == 12 => {
+        let nanos = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+        let seconds = i64::from_be_bytes(bytes[4..12].try_into().unwrap());
+        (seconds, nanos)
+      }
+      _ => return Err(serde::de::Error::custom("invalid msgpack timestamp length")),
+    };
+    DateTime::from_timestamp(seconds, nanos).ok_or_else(|| serde::de::Error::custom("msgpack timestamp out of range"))
+  }
+}
+
+struct ExtData(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ExtData {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    deserializer.deserialize_bytes(ExtDataVisitor)
+  }
+}
+
+struct ExtDataVisitor;
+
+impl<'de> Visitor<'de> for ExtDataVisitor {
+  type Value = ExtData;
+
+  fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    formatter.write_str("msgpack extension bytes")
+  }
+
+  fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    Ok(ExtData(v.to_vec()))
+  }
+
+  fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    Ok(ExtData(v))
+  }
+
+  fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+  where
+    A: SeqAccess<'de>,
+  {
+    let mut bytes = Vec::new();
+    while let Some(byte) = seq.next_element::<u8>()? {
+      bytes.push(byte);
+    }
+    Ok(ExtData(bytes))
+  }
+}
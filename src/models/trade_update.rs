@@ -0,0 +1,84 @@
+use crate::models::{
+  Order,
+  utils::{
+    Money,
+    NumberAsString,
+  },
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeUpdateEvent {
+  New,
+  Fill,
+  PartialFill,
+  Canceled,
+  Expired,
+  DoneForDay,
+  Replaced,
+  Rejected,
+  PendingNew,
+  Stopped,
+  PendingCancel,
+  PendingReplace,
+  Calculated,
+  Suspended,
+  OrderReplaceRejected,
+  OrderCancelRejected,
+  #[serde(other)]
+  Unknown,
+}
+
+///
+///Change to one of the account's orders, `price`, `qty` and `position_qty` are set on fills
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TradeUpdate {
+  pub event: TradeUpdateEvent,
+  pub order: Order,
+  #[serde(default)]
+  pub timestamp: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub execution_id: Option<String>,
+  #[serde(default)]
+  pub price: Option<Money>,
+  #[serde(default)]
+  pub qty: Option<NumberAsString>,
+  #[serde(default)]
+  pub position_qty: Option<NumberAsString>,
+}
+
+impl TradeUpdate {
+  pub fn is_fill(&self) -> bool {
+    matches!(self.event, TradeUpdateEvent::Fill | TradeUpdateEvent::PartialFill)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationStatus {
+  pub status: String,
+  pub action: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListeningStreams {
+  pub streams: Vec<String>,
+}
+
+///
+///Frame of the trading stream at `/stream`, e.g.
+/// `{"stream":"trade_updates","data":{"event":"fill",...}}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "stream", content = "data", rename_all = "snake_case")]
+pub enum TradingStreamMessage {
+  Authorization(AuthorizationStatus),
+  Listening(ListeningStreams),
+  TradeUpdates(Box<TradeUpdate>),
+}
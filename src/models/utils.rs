@@ -188,6 +188,13 @@ where
     .map_err(serde::de::Error::custom)
 }
 
+pub fn serialize_u8_to_str<S>(value: &u8, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  serializer.serialize_str(&value.to_string())
+}
+
 pub fn deserialize_str_to_u16<'de, D>(deserializer: D) -> Result<u16, D::Error>
 where
   D: Deserializer<'de>,
@@ -3,10 +3,13 @@ use chrono::{
   DateTime,
   Utc,
 };
-use serde::Deserialize;
+use serde::{
+  Deserialize,
+  Serialize,
+};
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct WatchList {
   pub id: Uuid,
   pub account_id: Uuid,
@@ -0,0 +1,412 @@
+use crate::{
+  api::{
+    AssetsApi,
+    OrderApi,
+    OrderRequestBody,
+  },
+  models::{
+    Order,
+    OrderStatus,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+};
+use anyhow::bail;
+use futures_util::{
+  StreamExt,
+  stream,
+};
+use serde::Serialize;
+use std::{
+  collections::BTreeMap,
+  sync::atomic::{
+    AtomicBool,
+    Ordering,
+  },
+  time::Duration,
+};
+use tokio::time::{
+  Instant,
+  sleep,
+};
+use uuid::Uuid;
+
+///
+///What happens to the other legs when one of them fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BasketPolicy {
+  ///
+  ///Keep every leg that went through
+  BestEffort,
+  ///
+  ///Cancel every leg that is still open as soon as one is rejected and send no further leg, legs
+  /// already filled stay filled
+  AllOrCancel,
+}
+
+///
+///One leg of a [`BasketReport`], `error` is set when the leg was never accepted
+///
+///A leg [`BasketPolicy::AllOrCancel`] held back after another one failed has neither an order nor
+/// an error and is `canceled_by_policy`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BasketLegReport {
+  pub symbol: String,
+  ///
+  ///Dollars allotted to the leg
+  pub amount: f64,
+  pub qty: Option<f64>,
+  pub notional: Option<f64>,
+  pub client_order_id: String,
+  pub order_id: Option<Uuid>,
+  pub status: Option<OrderStatus>,
+  pub filled_qty: f64,
+  pub filled_avg_price: Option<f64>,
+  pub filled_value: f64,
+  ///
+  ///Canceled by the basket because another leg failed
+  pub canceled_by_policy: bool,
+  pub error: Option<String>,
+}
+
+impl BasketLegReport {
+  fn failed(&self) -> bool {
+    self.error.is_some() || matches!(self.status, Some(OrderStatus::Rejected))
+  }
+
+  fn is_open(&self) -> bool {
+    self.order_id.is_some() && self.error.is_none() && !self.status.is_some_and(|status| status.is_final())
+  }
+
+  fn unsent(&self) -> bool {
+    self.order_id.is_none() && self.error.is_none()
+  }
+
+  fn update(&mut self, order: &Order) {
+    self.order_id = Some(order.id);
+    self.status = Some(order.status);
+    self.filled_qty = order.filled_qty.as_ref().map(Money::value).unwrap_or_default();
+    self.filled_avg_price = order.filled_avg_price.as_ref().map(Money::value);
+    self.filled_value = self.filled_qty * self.filled_avg_price.unwrap_or_default();
+  }
+}
+
+///
+///Consolidated fills of a basket, `complete` when every leg filled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BasketReport {
+  pub basket_id: Uuid,
+  pub side: Side,
+  pub policy: BasketPolicy,
+  pub amount: f64,
+  pub filled_value: f64,
+  pub filled_legs: usize,
+  pub failed_legs: usize,
+  ///
+  ///Legs still open when tracking timed out
+  pub open_legs: usize,
+  ///
+  ///Legs [`BasketPolicy::AllOrCancel`] never sent because another leg failed first
+  pub unsent_legs: usize,
+  ///
+  ///Whether [`BasketPolicy::AllOrCancel`] canceled the basket
+  pub canceled: bool,
+  pub complete: bool,
+  pub legs: Vec<BasketLegReport>,
+}
+
+///
+///Buys or sells a fixed dollar amount spread over many symbols, one market order per leg
+///
+///Fractionable assets trade by notional, the others by whole shares at the price given with
+/// [`Basket::with_price`]. Legs are submitted a few at a time and tracked until they are final or
+/// the fill timeout passed. Every leg's `client_order_id` starts with the basket id, so the orders
+/// of a basket can be found again after a crash.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::{
+///   portfolio::{
+///     Basket,
+///     BasketPolicy,
+///   },
+///   prelude::Client,
+/// };
+///
+/// # async fn example(client: Client) -> anyhow::Result<()> {
+/// let report = Basket::equal_weight(["AAPL", "MSFT", "NVDA"], 3_000.0)?
+///   .with_policy(BasketPolicy::AllOrCancel)
+///   .submit(&client)
+///   .await?;
+/// println!("filled {:.2} of {:.2}", report.filled_value, report.amount);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Basket {
+  basket_id: Uuid,
+  amount: f64,
+  weights: Vec<(String, f64)>,
+  side: Side,
+  policy: BasketPolicy,
+  prices: BTreeMap<String, f64>,
+  time_in_force: TimeInForce,
+  concurrency: usize,
+  fill_timeout: Duration,
+  poll_interval: Duration,
+}
+
+impl Basket {
+  ///
+  ///Basket of `amount` dollars split by the relative `weights`, which need not add up to one
+  pub fn new<S: Into<String>>(weights: impl IntoIterator<Item = (S, f64)>, amount: f64) -> anyhow::Result<Self> {
+    let weights: Vec<(String, f64)> = weights
+      .into_iter()
+      .map(|(symbol, weight)| (symbol.into(), weight))
+      .collect();
+    if weights.is_empty() {
+      bail!("a basket needs at least one leg");
+    }
+    if let Some((symbol, weight)) = weights.iter().find(|(_, weight)| !weight.is_finite() || *weight <= 0.0) {
+      bail!("weight of {symbol} must be positive, got {weight}");
+    }
+    if !amount.is_finite() || amount <= 0.0 {
+      bail!("basket amount must be positive, got {amount}");
+    }
+    Ok(Basket {
+      basket_id: Uuid::new_v4(),
+      amount,
+      weights,
+      side: Side::Buy,
+      policy: BasketPolicy::BestEffort,
+      prices: BTreeMap::new(),
+      time_in_force: TimeInForce::DAY,
+      concurrency: 5,
+      fill_timeout: Duration::from_secs(30),
+      poll_interval: Duration::from_millis(500),
+    })
+  }
+
+  pub fn equal_weight<S: Into<String>>(symbols: impl IntoIterator<Item = S>, amount: f64) -> anyhow::Result<Self> {
+    Basket::new(symbols.into_iter().map(|symbol| (symbol, 1.0)), amount)
+  }
+
+  pub fn basket_id(&self) -> Uuid {
+    self.basket_id
+  }
+
+  ///
+  ///Buy by default
+  pub fn with_side(mut self, side: Side) -> Self {
+    self.side = side;
+    self
+  }
+
+  ///
+  ///[`BasketPolicy::BestEffort`] by default
+  pub fn with_policy(mut self, policy: BasketPolicy) -> Self {
+    self.policy = policy;
+    self
+  }
+
+  ///
+  ///Price to size whole share legs of assets that are not fractionable
+  pub fn with_price(mut self, symbol: impl Into<String>, price: f64) -> Self {
+    self.prices.insert(symbol.into(), price);
+    self
+  }
+
+  ///
+  ///Orders in flight at once, 5 by default to stay well below the rate limit
+  pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+    self.concurrency = concurrency.max(1);
+    self
+  }
+
+  ///
+  ///How long to track the legs before reporting, 30 seconds by default; zero reports right after
+  /// submitting
+  pub fn with_fill_timeout(mut self, fill_timeout: Duration) -> Self {
+    self.fill_timeout = fill_timeout;
+    self
+  }
+
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+    self.time_in_force = time_in_force;
+    self
+  }
+
+  ///
+  ///Submit every leg, track them and apply the policy
+  pub async fn submit<C>(&self, client: &C) -> anyhow::Result<BasketReport>
+  where
+    C: OrderApi + AssetsApi,
+  {
+    let total_weight: f64 = self.weights.iter().map(|(_, weight)| weight).sum();
+    let failed = &AtomicBool::new(false);
+    let mut legs: Vec<BasketLegReport> = stream::iter(self.weights.iter().enumerate())
+      .map(|(index, (symbol, weight))| async move {
+        let amount = self.amount * weight / total_weight;
+        // legs in flight still complete, the ones not started yet are held back
+        if self.policy == BasketPolicy::AllOrCancel && failed.load(Ordering::SeqCst) {
+          let mut leg = self.leg(index, symbol, amount);
+          leg.canceled_by_policy = true;
+          return leg;
+        }
+        let leg = self.submit_leg(client, index, symbol, amount).await;
+        if leg.failed() {
+          failed.store(true, Ordering::SeqCst);
+        }
+        leg
+      })
+      .buffered(self.concurrency)
+      .collect()
+      .await;
+
+    let mut canceled = false;
+    let deadline = Instant::now() + self.fill_timeout;
+    loop {
+      if self.policy == BasketPolicy::AllOrCancel && !canceled && legs.iter().any(BasketLegReport::failed) {
+        canceled = true;
+        self.cancel_open_legs(client, &mut legs).await;
+      }
+      if !legs.iter().any(BasketLegReport::is_open) || Instant::now() >= deadline {
+        break;
+      }
+      sleep(self.poll_interval).await;
+      let open: Vec<&mut BasketLegReport> = legs.iter_mut().filter(|leg| leg.is_open()).collect();
+      stream::iter(open)
+        .for_each_concurrent(self.concurrency, |leg| async move {
+          if let Some(order_id) = leg.order_id
+            && let Ok(order) = client.get_order_by_id(&order_id).await
+          {
+            leg.update(&order);
+          }
+        })
+        .await;
+    }
+
+    let filled_legs = legs
+      .iter()
+      .filter(|leg| leg.status == Some(OrderStatus::Filled))
+      .count();
+    Ok(BasketReport {
+      basket_id: self.basket_id,
+      side: self.side,
+      policy: self.policy,
+      amount: self.amount,
+      filled_value: legs.iter().map(|leg| leg.filled_value).sum(),
+      filled_legs,
+      failed_legs: legs.iter().filter(|leg| leg.failed()).count(),
+      open_legs: legs.iter().filter(|leg| leg.is_open()).count(),
+      unsent_legs: legs.iter().filter(|leg| leg.unsent()).count(),
+      canceled,
+      complete: filled_legs == legs.len(),
+      legs,
+    })
+  }
+
+  fn leg(&self, index: usize, symbol: &str, amount: f64) -> BasketLegReport {
+    BasketLegReport {
+      symbol: symbol.to_string(),
+      amount,
+      qty: None,
+      notional: None,
+      client_order_id: format!("{}-{index}", self.basket_id),
+      order_id: None,
+      status: None,
+      filled_qty: 0.0,
+      filled_avg_price: None,
+      filled_value: 0.0,
+      canceled_by_policy: false,
+      error: None,
+    }
+  }
+
+  async fn submit_leg<C>(&self, client: &C, index: usize, symbol: &str, amount: f64) -> BasketLegReport
+  where
+    C: OrderApi + AssetsApi,
+  {
+    let mut leg = self.leg(index, symbol, amount);
+    let fractionable = match client.get_asset_by_symbol_or_id(symbol).await {
+      Ok(asset) if !asset.tradable => {
+        leg.error = Some(format!("{symbol} is not tradable"));
+        return leg;
+      }
+      Ok(asset) => asset.fractionable,
+      Err(error) => {
+        leg.error = Some(error.to_string());
+        return leg;
+      }
+    };
+    if fractionable {
+      leg.notional = Some((amount * 100.0).floor() / 100.0);
+    } else {
+      match self.prices.get(symbol) {
+        Some(price) if (amount / price).floor() >= 1.0 => leg.qty = Some((amount / price).floor()),
+        Some(_) => {
+          leg.error = Some(format!("{amount:.2} buys less than one share of {symbol}"));
+          return leg;
+        }
+        None => {
+          leg.error = Some(format!("{symbol} is not fractionable and has no price"));
+          return leg;
+        }
+      }
+    }
+
+    let order = OrderRequestBody {
+      symbol: symbol.to_string(),
+      qty: leg.qty.map(NumberAsString::from_f64),
+      notional: leg.notional.map(Money::from_f64),
+      side: self.side,
+      _type: OrderType::Market,
+      time_in_force: self.time_in_force,
+      limit_price: None,
+      stop_price: None,
+      trail_price: None,
+      trail_percent: None,
+      extended_hours: false,
+      client_order_id: Some(leg.client_order_id.clone()),
+      order_class: None,
+      legs: vec![],
+      take_profit: None,
+      stop_loss: None,
+      position_intent: None,
+    };
+    match client.create_order(&order).await {
+      Ok(order) => leg.update(&order),
+      Err(error) => leg.error = Some(error.to_string()),
+    }
+    leg
+  }
+
+  async fn cancel_open_legs<C: OrderApi>(&self, client: &C, legs: &mut [BasketLegReport]) {
+    let open: Vec<&mut BasketLegReport> = legs.iter_mut().filter(|leg| leg.is_open()).collect();
+    stream::iter(open)
+      .for_each_concurrent(self.concurrency, |leg| async move {
+        let Some(order_id) = leg.order_id else {
+          return;
+        };
+        if client.delete_order_by_id(&order_id).await.is_ok() {
+          leg.canceled_by_policy = true;
+        }
+        if let Ok(order) = client.get_order_by_id(&order_id).await {
+          leg.update(&order);
+        }
+      })
+      .await;
+  }
+}
@@ -0,0 +1,5 @@
+mod basket;
+mod rebalancer;
+
+pub use basket::*;
+pub use rebalancer::*;
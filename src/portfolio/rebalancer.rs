@@ -0,0 +1,549 @@
+use crate::{
+  api::{
+    AccountApi,
+    AssetsApi,
+    OrderApi,
+    OrderRequestBody,
+    PositionApi,
+  },
+  models::{
+    Account,
+    Asset,
+    Position,
+    PositionSide,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+};
+use anyhow::bail;
+use serde::Serialize;
+use std::{
+  collections::BTreeMap,
+  fmt::Write,
+  time::Duration,
+};
+use tokio::time::{
+  Instant,
+  sleep,
+};
+use uuid::Uuid;
+
+///
+///Whether [`Rebalancer::run`] only plans the trades or also submits them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalanceMode {
+  DryRun,
+  Execute,
+}
+
+///
+///One symbol of a [`RebalancePlan`], before and after the trade
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RebalanceLine {
+  pub symbol: String,
+  pub price: f64,
+  pub fractionable: bool,
+  pub current_qty: f64,
+  pub current_value: f64,
+  pub current_weight: f64,
+  ///
+  ///Weight of equity the symbol should have, the requested weight scaled down by the cash buffer
+  pub target_weight: f64,
+  pub target_value: f64,
+  pub side: Option<Side>,
+  ///
+  ///Shares to trade, `None` for notional buys
+  pub qty: Option<f64>,
+  ///
+  ///Dollars to buy of a fractionable asset
+  pub notional: Option<f64>,
+  ///
+  ///Estimated value of the trade at `price`, negative for sells
+  pub trade_value: f64,
+  pub expected_qty: f64,
+  pub expected_value: f64,
+  pub expected_weight: f64,
+  ///
+  ///Why no order is placed although the symbol is off target
+  pub skipped: Option<String>,
+  pub order_id: Option<Uuid>,
+  pub error: Option<String>,
+}
+
+impl RebalanceLine {
+  fn skip(&mut self, reason: &str) {
+    self.side = None;
+    self.qty = None;
+    self.notional = None;
+    self.trade_value = 0.0;
+    self.skipped = Some(reason.to_string());
+  }
+
+  fn order(&self, time_in_force: TimeInForce) -> Option<OrderRequestBody> {
+    Some(OrderRequestBody {
+      symbol: self.symbol.clone(),
+      qty: self.qty.map(NumberAsString::from_f64),
+      notional: self.notional.map(Money::from_f64),
+      side: self.side?,
+      _type: OrderType::Market,
+      time_in_force,
+      limit_price: None,
+      stop_price: None,
+      trail_price: None,
+      trail_percent: None,
+      extended_hours: false,
+      client_order_id: None,
+      order_class: None,
+      legs: vec![],
+      take_profit: None,
+      stop_loss: None,
+      position_intent: None,
+    })
+  }
+}
+
+///
+///Trades taking the account to its target weights, sells before buys
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RebalancePlan {
+  pub mode: RebalanceMode,
+  pub equity: f64,
+  pub cash: f64,
+  pub expected_cash: f64,
+  pub lines: Vec<RebalanceLine>,
+}
+
+impl RebalancePlan {
+  ///
+  ///Market orders of the plan, every sell ahead of every buy
+  pub fn orders(&self, time_in_force: TimeInForce) -> Vec<OrderRequestBody> {
+    let sells = self.lines.iter().filter(|line| line.side == Some(Side::Sell));
+    let buys = self.lines.iter().filter(|line| line.side == Some(Side::Buy));
+    sells.chain(buys).filter_map(|line| line.order(time_in_force)).collect()
+  }
+
+  ///
+  ///Per-symbol before/after table in plain text
+  pub fn to_table(&self) -> String {
+    let percent = |value: f64| format!("{:.2}%", value * 100.0);
+    let mut rows = vec![
+      [
+        "symbol",
+        "price",
+        "qty",
+        "weight",
+        "target",
+        "trade",
+        "qty after",
+        "weight after",
+        "note",
+      ]
+      .map(str::to_string),
+    ];
+    for line in &self.lines {
+      let trade = match (line.side, line.qty, line.notional) {
+        (Some(side), Some(qty), _) => format!("{} {qty}", label(side)),
+        (Some(side), None, Some(notional)) => format!("{} ${notional:.2}", label(side)),
+        _ => "-".to_string(),
+      };
+      let note = match (&line.error, line.order_id, &line.skipped) {
+        (Some(error), _, _) => format!("failed: {error}"),
+        (None, Some(order_id), _) => format!("order {order_id}"),
+        (None, None, Some(skipped)) => skipped.clone(),
+        _ => String::new(),
+      };
+      rows.push([
+        line.symbol.clone(),
+        format!("{:.2}", line.price),
+        format!("{}", line.current_qty),
+        percent(line.current_weight),
+        percent(line.target_weight),
+        trade,
+        format!("{:.4}", line.expected_qty),
+        percent(line.expected_weight),
+        note,
+      ]);
+    }
+    let widths: Vec<usize> = (0..rows[0].len())
+      .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or_default())
+      .collect();
+    let mut table = String::new();
+    for row in &rows {
+      let cells: Vec<String> = row
+        .iter()
+        .zip(&widths)
+        .map(|(cell, width)| format!("{cell:<width$}"))
+        .collect();
+      let _ = writeln!(table, "{}", cells.join("  ").trim_end());
+    }
+    let _ = writeln!(
+      table,
+      "equity {:.2}, cash {:.2} -> {:.2}",
+      self.equity, self.cash, self.expected_cash
+    );
+    table
+  }
+}
+
+fn label(side: Side) -> &'static str {
+  match side {
+    Side::Buy => "buy",
+    Side::Sell => "sell",
+  }
+}
+
+///
+///Moves the account towards target weights of its equity with market orders
+///
+///Fractionable assets are bought by notional and sold by fractional quantity, the others trade
+/// whole shares. Held symbols without a target are sold unless
+/// [`Rebalancer::with_liquidate_untargeted`] turns that off. Prices come from the open positions
+/// and [`Rebalancer::with_price`], a target without either is an error.
+///
+/// ```no_run
+/// use alpaca_trade_api_rust::{
+///   portfolio::{
+///     RebalanceMode,
+///     Rebalancer,
+///   },
+///   prelude::Client,
+/// };
+///
+/// # async fn example(client: Client) -> anyhow::Result<()> {
+/// let rebalancer = Rebalancer::new([("VTI", 0.6), ("BND", 0.4)])?
+///   .with_price("BND", 72.5)
+///   .with_cash_buffer(0.02)
+///   .with_drift_band(0.01);
+/// let plan = rebalancer.run(&client, RebalanceMode::DryRun).await?;
+/// println!("{}", plan.to_table());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rebalancer {
+  targets: BTreeMap<String, f64>,
+  prices: BTreeMap<String, f64>,
+  cash_buffer: f64,
+  drift_band: f64,
+  min_notional: f64,
+  liquidate_untargeted: bool,
+  time_in_force: TimeInForce,
+  fill_timeout: Duration,
+  poll_interval: Duration,
+}
+
+impl Rebalancer {
+  ///
+  ///Rebalancer for non-negative `targets` adding up to at most one, what is left over stays in
+  /// cash
+  pub fn new<S: Into<String>>(targets: impl IntoIterator<Item = (S, f64)>) -> anyhow::Result<Self> {
+    let targets: BTreeMap<String, f64> = targets
+      .into_iter()
+      .map(|(symbol, weight)| (symbol.into(), weight))
+      .collect();
+    if let Some((symbol, weight)) = targets.iter().find(|(_, weight)| !weight.is_finite() || **weight < 0.0) {
+      bail!("target weight of {symbol} must be between 0 and 1, got {weight}");
+    }
+    let total: f64 = targets.values().sum();
+    if total > 1.0 + 1e-9 {
+      bail!("target weights add up to {total}, more than 1");
+    }
+    Ok(Rebalancer {
+      targets,
+      prices: BTreeMap::new(),
+      cash_buffer: 0.0,
+      drift_band: 0.0,
+      min_notional: 1.0,
+      liquidate_untargeted: true,
+      time_in_force: TimeInForce::DAY,
+      fill_timeout: Duration::from_secs(30),
+      poll_interval: Duration::from_millis(500),
+    })
+  }
+
+  ///
+  ///Price of a symbol, overriding the current price of its position
+  pub fn with_price(mut self, symbol: impl Into<String>, price: f64) -> Self {
+    self.prices.insert(symbol.into(), price);
+    self
+  }
+
+  ///
+  ///Fraction of equity always kept in cash, the targets apply to the rest; none by default
+  pub fn with_cash_buffer(mut self, cash_buffer: f64) -> Self {
+    self.cash_buffer = cash_buffer.clamp(0.0, 1.0);
+    self
+  }
+
+  ///
+  ///Leave symbols alone while their weight is within `drift_band` of the target, e.g. `0.02` for
+  /// two percentage points; none by default
+  pub fn with_drift_band(mut self, drift_band: f64) -> Self {
+    self.drift_band = drift_band.max(0.0);
+    self
+  }
+
+  ///
+  ///Smallest trade worth placing in dollars, $1 by default as for Alpaca notional orders
+  pub fn with_min_notional(mut self, min_notional: f64) -> Self {
+    self.min_notional = min_notional.max(0.0);
+    self
+  }
+
+  pub fn with_liquidate_untargeted(mut self, liquidate_untargeted: bool) -> Self {
+    self.liquidate_untargeted = liquidate_untargeted;
+    self
+  }
+
+  ///
+  ///Time in force of the orders, `DAY` by default which fractional orders require
+  pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+    self.time_in_force = time_in_force;
+    self
+  }
+
+  ///
+  ///How long to wait for the sells to fill before submitting the buys, 30 seconds by default
+  pub fn with_fill_timeout(mut self, fill_timeout: Duration) -> Self {
+    self.fill_timeout = fill_timeout;
+    self
+  }
+
+  ///
+  ///Trades from a snapshot of the account, `assets` by symbol telling which ones are fractionable
+  /// and tradable; symbols missing from `assets` trade whole shares
+  pub fn plan(
+    &self,
+    account: &Account,
+    positions: &[Position],
+    assets: &BTreeMap<String, Asset>,
+  ) -> anyhow::Result<RebalancePlan> {
+    let equity = account.equity.value();
+    if equity <= 0.0 {
+      bail!("cannot rebalance an account without equity");
+    }
+    let held: BTreeMap<&str, &Position> = positions
+      .iter()
+      .map(|position| (position.symbol.as_str(), position))
+      .collect();
+    let symbols: Vec<&str> = self
+      .targets
+      .keys()
+      .map(String::as_str)
+      .chain(
+        held
+          .keys()
+          .copied()
+          .filter(|symbol| !self.targets.contains_key(*symbol)),
+      )
+      .collect();
+
+    let mut lines = vec![];
+    for symbol in symbols {
+      let position = held.get(symbol);
+      let price = match (self.prices.get(symbol), position) {
+        (Some(price), _) => *price,
+        (None, Some(position)) => position.current_price.value(),
+        (None, None) => bail!("no price for {symbol}, set one with with_price"),
+      };
+      if price <= 0.0 {
+        bail!("price of {symbol} must be positive, got {price}");
+      }
+      let current_qty = position.map(|position| signed_qty(position)).unwrap_or_default();
+      let current_value = current_qty * price;
+      let targeted = self.targets.get(symbol);
+      let target_weight = targeted.copied().unwrap_or_default() * (1.0 - self.cash_buffer);
+      let asset = assets.get(symbol);
+      let mut line = RebalanceLine {
+        symbol: symbol.to_string(),
+        price,
+        fractionable: asset.is_some_and(|asset| asset.fractionable),
+        current_qty,
+        current_value,
+        current_weight: current_value / equity,
+        target_weight,
+        target_value: target_weight * equity,
+        side: None,
+        qty: None,
+        notional: None,
+        trade_value: 0.0,
+        expected_qty: current_qty,
+        expected_value: current_value,
+        expected_weight: current_value / equity,
+        skipped: None,
+        order_id: None,
+        error: None,
+      };
+      let exit = targeted.is_none() || target_weight == 0.0;
+      if targeted.is_none() && !self.liquidate_untargeted {
+        line.skip("not targeted");
+      } else if exit && current_qty != 0.0 {
+        // exits close the whole position whatever the drift band and order size
+        line.side = Some(if current_qty > 0.0 { Side::Sell } else { Side::Buy });
+        line.qty = Some(current_qty.abs());
+        line.trade_value = -current_value;
+      } else if (line.current_weight - target_weight).abs() <= self.drift_band {
+        line.skip("within drift band");
+      } else {
+        let trade_value = line.target_value - current_value;
+        self.size(&mut line, trade_value);
+      }
+      if asset.is_some_and(|asset| !asset.tradable) && line.side.is_some() {
+        line.skip("not tradable");
+      }
+      lines.push(line);
+    }
+
+    // buys are paid from cash and sale proceeds, scaled down when they would eat into the buffer
+    let cash = account.cash.value();
+    let proceeds: f64 = lines
+      .iter()
+      .filter(|line| line.side == Some(Side::Sell))
+      .map(|line| -line.trade_value)
+      .sum();
+    let available = cash + proceeds - self.cash_buffer * equity;
+    let buys: f64 = lines
+      .iter()
+      .filter(|line| line.side == Some(Side::Buy) && line.current_qty >= 0.0)
+      .map(|line| line.trade_value)
+      .sum();
+    if buys > available.max(0.0) {
+      let scale = available.max(0.0) / buys;
+      for line in lines
+        .iter_mut()
+        .filter(|line| line.side == Some(Side::Buy) && line.current_qty >= 0.0)
+      {
+        let trade_value = line.trade_value * scale;
+        self.size(line, trade_value);
+      }
+    }
+
+    for line in &mut lines {
+      line.expected_qty = line.current_qty + line.trade_value / line.price;
+      line.expected_value = line.expected_qty * line.price;
+      line.expected_weight = line.expected_value / equity;
+    }
+    let expected_cash = cash - lines.iter().map(|line| line.trade_value).sum::<f64>();
+    Ok(RebalancePlan {
+      mode: RebalanceMode::DryRun,
+      equity,
+      cash,
+      expected_cash,
+      lines,
+    })
+  }
+
+  ///
+  ///Turn a dollar amount into an order size, notional buys and fractional sells for fractionable
+  /// assets and whole shares otherwise
+  fn size(&self, line: &mut RebalanceLine, trade_value: f64) {
+    let side = if trade_value > 0.0 { Side::Buy } else { Side::Sell };
+    let (qty, notional, trade_value) = match (line.fractionable, side) {
+      (true, Side::Buy) => {
+        let notional = (trade_value * 100.0).floor() / 100.0;
+        (None, Some(notional), notional)
+      }
+      (true, Side::Sell) => {
+        let qty = ((trade_value.abs() / line.price) * 1e9).floor() / 1e9;
+        (Some(qty), None, -qty * line.price)
+      }
+      (false, _) => {
+        let qty = (trade_value.abs() / line.price).floor();
+        (Some(qty), None, qty * line.price * trade_value.signum())
+      }
+    };
+    line.side = Some(side);
+    line.qty = qty;
+    line.notional = notional;
+    line.trade_value = trade_value;
+    line.skipped = None;
+    if qty == Some(0.0) && !line.fractionable {
+      line.skip("less than one share");
+    } else if trade_value.abs() < self.min_notional {
+      line.skip("below minimum order");
+    }
+  }
+
+  ///
+  ///Read the account, positions and assets, plan the trades and in [`RebalanceMode::Execute`]
+  /// submit them, waiting for the sells to fill before buying
+  ///
+  ///Failed orders are recorded on their line and do not stop the others.
+  pub async fn run<C>(&self, client: &C, mode: RebalanceMode) -> anyhow::Result<RebalancePlan>
+  where
+    C: AccountApi + PositionApi + AssetsApi + OrderApi,
+  {
+    let account = client.get_account().await?;
+    let positions = client.get_all_open_positions().await?;
+    let mut assets = BTreeMap::new();
+    let symbols = self
+      .targets
+      .keys()
+      .chain(positions.iter().map(|position| &position.symbol));
+    for symbol in symbols {
+      if !assets.contains_key(symbol) {
+        assets.insert(symbol.clone(), client.get_asset_by_symbol_or_id(symbol).await?);
+      }
+    }
+    let mut plan = self.plan(&account, &positions, &assets)?;
+    plan.mode = mode;
+    if mode == RebalanceMode::DryRun {
+      return Ok(plan);
+    }
+
+    let mut sells = vec![];
+    for side in [Side::Sell, Side::Buy] {
+      if side == Side::Buy && !sells.is_empty() {
+        self.wait_for_fills(client, &sells).await;
+      }
+      for line in plan.lines.iter_mut().filter(|line| line.side == Some(side)) {
+        let Some(order) = line.order(self.time_in_force) else {
+          continue;
+        };
+        match client.create_order(&order).await {
+          Ok(order) => {
+            line.order_id = Some(order.id);
+            if side == Side::Sell {
+              sells.push(order.id);
+            }
+          }
+          Err(error) => line.error = Some(error.to_string()),
+        }
+      }
+    }
+    Ok(plan)
+  }
+
+  async fn wait_for_fills<C: OrderApi>(&self, client: &C, order_ids: &[Uuid]) {
+    let deadline = Instant::now() + self.fill_timeout;
+    let mut pending = order_ids.to_vec();
+    while !pending.is_empty() && Instant::now() < deadline {
+      let mut still_pending = vec![];
+      for id in pending {
+        match client.get_order_by_id(&id).await {
+          Ok(order) if order.status.is_final() => {}
+          _ => still_pending.push(id),
+        }
+      }
+      pending = still_pending;
+      if !pending.is_empty() {
+        sleep(self.poll_interval).await;
+      }
+    }
+  }
+}
+
+fn signed_qty(position: &Position) -> f64 {
+  match position.side {
+    PositionSide::Short => -position.qty.value().abs(),
+    _ => position.qty.value(),
+  }
+}
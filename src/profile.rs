@@ -0,0 +1,160 @@
+use crate::client::Client;
+use anyhow::{
+  Context,
+  bail,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::{
+  collections::BTreeMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+pub const PAPER_URL: &str = "https://paper-api.alpaca.markets";
+pub const LIVE_URL: &str = "https://api.alpaca.markets";
+pub const DATA_URL: &str = "https://data.alpaca.markets";
+
+///
+///Credentials and endpoints of one account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+  #[serde(default = "paper_url")]
+  pub base_url: String,
+  #[serde(default = "data_url")]
+  pub data_url: String,
+  pub key_id: String,
+  pub secret_key: String,
+  ///
+  ///Whether the profile trades real money, by default whether `base_url` is the live endpoint
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub live: Option<bool>,
+}
+
+fn paper_url() -> String {
+  PAPER_URL.to_string()
+}
+
+fn data_url() -> String {
+  DATA_URL.to_string()
+}
+
+impl Profile {
+  ///
+  ///Profile from `APCA_API_KEY_ID`, `APCA_API_SECRET_KEY` and optionally `APCA_API_BASE_URL` and
+  /// `APCA_API_DATA_URL`
+  pub fn from_env() -> anyhow::Result<Self> {
+    Ok(Profile {
+      base_url: std::env::var("APCA_API_BASE_URL").unwrap_or_else(|_| paper_url()),
+      data_url: std::env::var("APCA_API_DATA_URL").unwrap_or_else(|_| data_url()),
+      key_id: std::env::var("APCA_API_KEY_ID").context("APCA_API_KEY_ID is not set")?,
+      secret_key: std::env::var("APCA_API_SECRET_KEY").context("APCA_API_SECRET_KEY is not set")?,
+      live: None,
+    })
+  }
+
+  ///
+  ///The profile called `name`, or `ALPACA_PROFILE`, from the profiles file; without a name the
+  /// `default` profile if the file has one, else the `APCA_*` environment variables
+  pub fn resolve(name: Option<&str>) -> anyhow::Result<Self> {
+    let name = name
+      .map(str::to_string)
+      .or_else(|| std::env::var("ALPACA_PROFILE").ok());
+    let path = Profiles::default_path();
+    let profiles = match &path {
+      Some(path) if path.exists() => Profiles::load(path)?,
+      _ => Profiles::default(),
+    };
+    match name {
+      Some(name) => match profiles.get(&name) {
+        Some(profile) => Ok(profile.clone()),
+        None => bail!("profile {name} not found in {:?}", path),
+      },
+      None => match profiles.get("default") {
+        Some(profile) => Ok(profile.clone()),
+        None => Profile::from_env(),
+      },
+    }
+  }
+
+  pub fn is_live(&self) -> bool {
+    self
+      .live
+      .unwrap_or_else(|| self.base_url.trim_end_matches('/') == LIVE_URL)
+  }
+
+  pub fn client(&self) -> Client {
+    Client::new(self.base_url.clone(), self.key_id.clone(), self.secret_key.clone())
+      .with_data_url(self.data_url.clone())
+  }
+}
+
+///
+///Named profiles, stored as a JSON object of [`Profile`]s such as
+///
+/// ```json
+/// {
+///   "default": { "key_id": "PK...", "secret_key": "..." },
+///   "live": { "base_url": "https://api.alpaca.markets", "key_id": "AK...", "secret_key": "..." }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Profiles {
+  profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+  ///
+  ///`ALPACA_CONFIG`, else `~/.config/alpaca/profiles.json`
+  pub fn default_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("ALPACA_CONFIG") {
+      return Some(PathBuf::from(path));
+    }
+    std::env::var("HOME")
+      .ok()
+      .map(|home| Path::new(&home).join(".config").join("alpaca").join("profiles.json"))
+  }
+
+  pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Profile> {
+    self.profiles.get(name)
+  }
+
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.profiles.keys().map(String::as_str)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_profiles_should_default_to_paper_and_detect_live() {
+    let profiles: Profiles = serde_json::from_str(
+      r#"{
+        "default": { "key_id": "PK", "secret_key": "secret" },
+        "live": { "base_url": "https://api.alpaca.markets/", "key_id": "AK", "secret_key": "secret" },
+        "local": { "base_url": "http://localhost:8080", "key_id": "K", "secret_key": "S", "live": true }
+      }"#,
+    )
+    .unwrap();
+
+    assert_eq!(profiles.names().collect::<Vec<_>>(), vec!["default", "live", "local"]);
+    let paper = profiles.get("default").unwrap();
+    assert_eq!(paper.base_url, PAPER_URL);
+    assert_eq!(paper.data_url, DATA_URL);
+    assert!(!paper.is_live());
+    assert!(profiles.get("live").unwrap().is_live());
+    assert!(profiles.get("local").unwrap().is_live());
+  }
+}
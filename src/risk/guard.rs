@@ -0,0 +1,472 @@
+use crate::{
+  api::{
+    AccountApi,
+    AllOrdersQueryParameter,
+    ClosePositionInfo,
+    ClosePositionParam,
+    DeleteAllOrdersResponse,
+    OrderApi,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+  },
+  models::{
+    Account,
+    ClosedPosition,
+    Order,
+    Position,
+    StreamQuote,
+    enums::{
+      AssetClass,
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  risk::{
+    RiskRules,
+    RiskViolation,
+  },
+  sim::asset_class,
+};
+use anyhow::bail;
+use std::{
+  collections::{
+    HashMap,
+    VecDeque,
+  },
+  sync::Mutex,
+  time::{
+    Duration,
+    Instant,
+  },
+};
+use uuid::Uuid;
+
+///
+///Order as far as the rules are concerned
+#[derive(Debug)]
+struct ProposedOrder<'a> {
+  symbol: &'a str,
+  side: Side,
+  qty: Option<f64>,
+  notional: Option<f64>,
+  limit_price: Option<f64>,
+  stop_price: Option<f64>,
+  replaces: Option<Uuid>,
+}
+
+impl<'a> ProposedOrder<'a> {
+  fn new(order: &'a OrderRequestBody) -> Self {
+    ProposedOrder {
+      symbol: &order.symbol,
+      side: order.side,
+      qty: order.qty.as_ref().map(NumberAsString::value),
+      notional: order.notional.as_ref().map(Money::value),
+      limit_price: order.limit_price.as_ref().map(Money::value),
+      stop_price: order.stop_price.as_ref().map(Money::value),
+      replaces: None,
+    }
+  }
+}
+
+///
+///Wraps a client and checks every new or replaced order against [`RiskRules`] before it is sent
+///
+///A rejected order fails with a [`RiskViolation`], which can be recovered with
+/// `error.downcast_ref::<RiskViolation>()`. Price bands and the value of market orders rely on the
+/// last quotes fed through [`RiskGuard::update_quote`], falling back to the current price of an
+/// open position; price bands are not checked for symbols without a quote. Positions, account and
+/// the other calls pass straight through to the wrapped client.
+#[derive(Debug)]
+pub struct RiskGuard<C> {
+  client: C,
+  rules: RiskRules,
+  quotes: Mutex<HashMap<String, (f64, f64)>>,
+  submissions: Mutex<VecDeque<Instant>>,
+}
+
+impl<C> RiskGuard<C> {
+  pub fn new(client: C, rules: RiskRules) -> Self {
+    RiskGuard {
+      client,
+      rules,
+      quotes: Mutex::new(HashMap::new()),
+      submissions: Mutex::new(VecDeque::new()),
+    }
+  }
+
+  pub fn inner(&self) -> &C {
+    &self.client
+  }
+
+  pub fn rules(&self) -> &RiskRules {
+    &self.rules
+  }
+
+  ///
+  ///Record the last bid and ask of `symbol`
+  pub fn update_quote(&self, symbol: &str, bid: f64, ask: f64) {
+    self
+      .quotes
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .insert(symbol.to_string(), (bid, ask));
+  }
+
+  pub fn update_stream_quote(&self, quote: &StreamQuote) {
+    self.update_quote(&quote.symbol, quote.bid_price, quote.ask_price);
+  }
+
+  fn quote(&self, symbol: &str) -> Option<(f64, f64)> {
+    self
+      .quotes
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .get(symbol)
+      .copied()
+  }
+
+  ///
+  ///Checks that need neither account nor positions
+  fn check_static(&self, order: &ProposedOrder) -> Result<(), RiskViolation> {
+    let rules = &self.rules;
+    if rules.denied_symbols.contains(order.symbol) {
+      return Err(RiskViolation::SymbolDenied(order.symbol.to_string()));
+    }
+    if let Some(allowed) = &rules.allowed_symbols
+      && !allowed.contains(order.symbol)
+    {
+      return Err(RiskViolation::SymbolNotAllowed(order.symbol.to_string()));
+    }
+    if let (Some(max_deviation_pct), Some((bid, ask))) = (rules.max_price_deviation_pct, self.quote(order.symbol)) {
+      let reference = (bid + ask) / 2.0;
+      for price in [order.limit_price, order.stop_price].into_iter().flatten() {
+        if ((price - reference) / reference).abs() * 100.0 > max_deviation_pct {
+          return Err(RiskViolation::PriceBand {
+            price,
+            reference,
+            max_deviation_pct,
+          });
+        }
+      }
+    }
+    Ok(())
+  }
+
+  ///
+  ///Take one of the submissions allowed per minute, checked and taken under the same lock so
+  /// concurrent orders cannot all pass the check
+  fn reserve_submission(&self) -> Result<Option<Instant>, RiskViolation> {
+    let Some(limit) = self.rules.max_orders_per_minute else {
+      return Ok(None);
+    };
+    let mut submissions = self.submissions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    while let Some(submitted) = submissions.front()
+      && submitted.elapsed() >= Duration::from_secs(60)
+    {
+      submissions.pop_front();
+    }
+    if submissions.len() >= limit {
+      return Err(RiskViolation::OrderRate { limit });
+    }
+    let now = Instant::now();
+    submissions.push_back(now);
+    Ok(Some(now))
+  }
+
+  ///
+  ///Give back a submission that was rejected or never sent
+  fn release_submission(&self, reserved: Option<Instant>) {
+    let Some(reserved) = reserved else {
+      return;
+    };
+    let mut submissions = self.submissions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(index) = submissions.iter().position(|submitted| *submitted == reserved) {
+      submissions.remove(index);
+    }
+  }
+
+  ///
+  ///Checks against the account and the position the order would leave, `open_sells` is the
+  /// quantity the open sell orders of the symbol still have to sell
+  fn check_position(
+    &self,
+    order: &ProposedOrder,
+    account: &Account,
+    positions: &[Position],
+    open_sells: f64,
+  ) -> Result<(), RiskViolation> {
+    let rules = &self.rules;
+    let position = positions
+      .iter()
+      .find(|position| same_symbol(&position.symbol, order.symbol));
+    let current_qty = position.map(|position| position.qty.value()).unwrap_or_default();
+    let quote = self.quote(order.symbol);
+    let price = order
+      .limit_price
+      .or(order.stop_price)
+      .or(quote.map(|(bid, ask)| if order.side == Side::Buy { ask } else { bid }))
+      .or(position.map(|position| position.current_price.value()));
+    let multiplier = match asset_class(order.symbol) {
+      AssetClass::UsOption => 100.0,
+      _ => 1.0,
+    };
+    let qty = match (order.qty, order.notional, price) {
+      (Some(qty), _, _) => qty,
+      (None, Some(notional), Some(price)) => notional / (price * multiplier),
+      // without a price there is no telling whether a notional sell goes short
+      (None, Some(_), None) if order.side == Side::Sell => {
+        return Err(RiskViolation::NoReferencePrice(order.symbol.to_string()));
+      }
+      _ => 0.0,
+    };
+    let signed_qty = if order.side == Side::Buy { qty } else { -qty };
+    let new_qty = current_qty + signed_qty;
+
+    // the open sells take their share of the position first, like a reserved submission slot
+    if !account.shorting_enabled && new_qty - open_sells < -1e-9 {
+      return Err(RiskViolation::ShortingDisabled {
+        symbol: order.symbol.to_string(),
+      });
+    }
+    let needs_price = rules.max_order_notional.is_some()
+      || rules.position_limit(order.symbol).is_some()
+      || rules.max_gross_exposure.is_some();
+    if !needs_price {
+      return Ok(());
+    }
+    let Some(price) = price else {
+      return Err(RiskViolation::NoReferencePrice(order.symbol.to_string()));
+    };
+    let notional = order.notional.unwrap_or(qty * price * multiplier);
+    if let Some(limit) = rules.max_order_notional
+      && notional > limit
+    {
+      return Err(RiskViolation::MaxOrderNotional { notional, limit });
+    }
+    // orders reducing a position are always allowed through the size limits
+    let added = (new_qty.abs() - current_qty.abs()) * price * multiplier;
+    if added <= 0.0 {
+      return Ok(());
+    }
+    if let Some(limit) = rules.position_limit(order.symbol) {
+      let notional = new_qty.abs() * price * multiplier;
+      if notional > limit {
+        return Err(RiskViolation::MaxPosition {
+          symbol: order.symbol.to_string(),
+          notional,
+          limit,
+        });
+      }
+    }
+    if let Some(max_gross_exposure) = rules.max_gross_exposure {
+      let equity = account.equity.value();
+      let gross: f64 = positions
+        .iter()
+        .map(|position| position.market_value.value().abs())
+        .sum();
+      let exposure = gross + added;
+      if exposure > max_gross_exposure * equity {
+        return Err(RiskViolation::MaxGrossExposure {
+          exposure,
+          limit: max_gross_exposure * equity,
+          equity,
+        });
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<C: OrderApi + PositionApi + AccountApi> RiskGuard<C> {
+  ///
+  ///Run every rule against `order` without submitting it
+  pub async fn check_order(&self, order: &OrderRequestBody) -> anyhow::Result<()> {
+    match self.reserve_submission() {
+      Ok(reserved) => self.release_submission(reserved),
+      Err(violation) => bail!(violation),
+    }
+    self.check(&ProposedOrder::new(order)).await
+  }
+
+  ///
+  ///Send an order through `submit` once it passed every rule, its submission slot is given back
+  /// when a rule or the submission fails
+  async fn submit<T>(
+    &self,
+    order: &ProposedOrder<'_>,
+    submit: impl Future<Output = anyhow::Result<T>>,
+  ) -> anyhow::Result<T> {
+    let reserved = match self.reserve_submission() {
+      Ok(reserved) => reserved,
+      Err(violation) => bail!(violation),
+    };
+    let result = match self.check(order).await {
+      Ok(()) => submit.await,
+      Err(error) => Err(error),
+    };
+    if result.is_err() {
+      self.release_submission(reserved);
+    }
+    result
+  }
+
+  async fn check(&self, order: &ProposedOrder<'_>) -> anyhow::Result<()> {
+    if let Err(violation) = self.check_static(order) {
+      bail!(violation);
+    }
+    let account = self.client.get_account().await?;
+    let positions = self.client.get_all_open_positions().await?;
+    let open_sells = if order.side == Side::Sell && !account.shorting_enabled {
+      self.open_sells(order).await?
+    } else {
+      0.0
+    };
+    if let Err(violation) = self.check_position(order, &account, &positions, open_sells) {
+      bail!(violation);
+    }
+    Ok(())
+  }
+
+  ///
+  ///Quantity left to sell by the open sell orders of the symbol, the order being replaced and
+  /// notional orders aside
+  async fn open_sells(&self, order: &ProposedOrder<'_>) -> anyhow::Result<f64> {
+    let open = self
+      .client
+      .get_all_orders(&AllOrdersQueryParameter {
+        status: Some(OrdersFilter::Open),
+        limit: Some(500),
+        after: None,
+        until: None,
+        direction: None,
+        nested: Some(true),
+        symbols: None,
+        side: Some(Side::Sell),
+        asset_class: None,
+        before_order_id: None,
+        after_order_id: None,
+      })
+      .await?;
+    Ok(
+      open
+        .iter()
+        .filter(|open| {
+          open.side == Side::Sell && Some(open.id) != order.replaces && same_symbol(&open.symbol, order.symbol)
+        })
+        .filter_map(|open| {
+          let qty = open.qty.as_ref()?.value();
+          let filled = open.filled_qty.as_ref().map(Money::value).unwrap_or_default();
+          Some(qty - filled)
+        })
+        .sum(),
+    )
+  }
+}
+
+impl<C: OrderApi + PositionApi + AccountApi> OrderApi for RiskGuard<C> {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    self
+      .submit(&ProposedOrder::new(order), self.client.create_order(order))
+      .await
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    self.client.get_all_orders(query_parameter).await
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    self.client.delete_all_orders().await
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    self.client.get_order_by_client_order_id(client_order_id).await
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    self.client.get_order_by_id(id).await
+  }
+
+  ///
+  ///The replacement is checked as a new order of its full quantity
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    let original = self.client.get_order_by_id(order_id).await?;
+    let has_limit = matches!(original._type, OrderType::Limit | OrderType::StopLimit);
+    let has_stop = matches!(original._type, OrderType::Stop | OrderType::StopLimit);
+    let proposed = ProposedOrder {
+      symbol: &original.symbol,
+      side: original.side,
+      qty: Some(order.qty.value()),
+      notional: None,
+      limit_price: has_limit
+        .then(|| replaced_price(&order.limit_price, &original.limit_price))
+        .flatten(),
+      stop_price: has_stop
+        .then(|| replaced_price(&order.stop_price, &original.stop_price))
+        .flatten(),
+      replaces: Some(original.id),
+    };
+    self
+      .submit(&proposed, self.client.replace_order_by_id(order_id, order))
+      .await
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    self.client.delete_order_by_id(order_id).await
+  }
+}
+
+impl<C: AccountApi> AccountApi for RiskGuard<C> {
+  async fn get_account(&self) -> anyhow::Result<Account> {
+    self.client.get_account().await
+  }
+}
+
+impl<C: PositionApi> PositionApi for RiskGuard<C> {
+  async fn get_all_open_positions(&self) -> anyhow::Result<Vec<Position>> {
+    self.client.get_all_open_positions().await
+  }
+
+  async fn get_open_position_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Position> {
+    self.client.get_open_position_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn close_open_position_by_symbol_or_id(
+    &self,
+    symbol_or_id: &str,
+    param: &ClosePositionParam,
+  ) -> anyhow::Result<ClosedPosition> {
+    self
+      .client
+      .close_open_position_by_symbol_or_id(symbol_or_id, param)
+      .await
+  }
+
+  async fn exercise_option_contract_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<()> {
+    self.client.exercise_option_contract_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn clost_all_open_positions(&self, cancel_orders: bool) -> anyhow::Result<Vec<ClosePositionInfo>> {
+    self.client.clost_all_open_positions(cancel_orders).await
+  }
+}
+
+///
+///Whether two symbols name the same asset, positions report crypto pairs without their slash
+fn same_symbol(position_symbol: &str, order_symbol: &str) -> bool {
+  position_symbol.replace('/', "") == order_symbol.replace('/', "")
+}
+
+///
+///Price of a replacement, the original one when the request leaves it out
+fn replaced_price(requested: &Option<Money>, original: &Option<String>) -> Option<f64> {
+  match requested {
+    Some(price) => Some(price.value()),
+    None => original.as_ref().and_then(|price| price.parse().ok()),
+  }
+}
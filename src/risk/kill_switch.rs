@@ -0,0 +1,436 @@
+use crate::{
+  api::{
+    AccountApi,
+    AccountConfigurationsApi,
+    AccountConfigurationsReqBody,
+    AllOrdersQueryParameter,
+    ClosePositionInfo,
+    ClosePositionParam,
+    DeleteAllOrdersResponse,
+    OrderApi,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+  },
+  models::{
+    Account,
+    ClosedPosition,
+    Order,
+    Position,
+  },
+};
+use anyhow::bail;
+use serde::Serialize;
+use std::{
+  collections::BTreeMap,
+  sync::atomic::{
+    AtomicBool,
+    Ordering,
+  },
+  time::Duration,
+};
+use thiserror::Error;
+use tokio::{
+  io::{
+    AsyncReadExt,
+    AsyncWriteExt,
+  },
+  net::{
+    TcpListener,
+    TcpStream,
+  },
+  time::{
+    Instant,
+    sleep,
+    timeout,
+  },
+};
+use uuid::Uuid;
+
+///
+///Error of every new or replaced order while the kill switch is engaged
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+#[error("trading is locked by the kill switch")]
+pub struct KillSwitchEngaged;
+
+///
+///What [`KillSwitch::engage`] did, every step is attempted even when an earlier one failed
+#[derive(Debug, Default, Serialize)]
+pub struct KillReport {
+  pub canceled: Vec<DeleteAllOrdersResponse>,
+  ///
+  ///Orders still open when waiting for the cancels to be confirmed timed out
+  pub unconfirmed: Vec<Uuid>,
+  ///
+  ///The last result of closing each position, the first successful one once it succeeded
+  pub positions: Vec<ClosePositionInfo>,
+  ///
+  ///Symbols whose position could not be closed within the retries
+  pub failed: Vec<String>,
+  pub trade_suspended: bool,
+  ///
+  ///Calls that failed on every attempt
+  pub errors: Vec<String>,
+}
+
+///
+///Wraps a client with an emergency stop that cancels every order, flattens every position and
+/// keeps new orders blocked in-process
+///
+///Once engaged, [`OrderApi::create_order`] and [`OrderApi::replace_order_by_id`] fail with
+/// [`KillSwitchEngaged`] until [`KillSwitch::release`]; cancels, reads and position calls pass
+/// through. The `wait_for_*` triggers lock trading as soon as they fire and return, leaving the
+/// caller to run [`KillSwitch::engage`] or [`KillSwitch::engage_and_suspend`].
+#[derive(Debug)]
+pub struct KillSwitch<C> {
+  client: C,
+  engaged: AtomicBool,
+  attempts: usize,
+  retry_delay: Duration,
+  cancel_timeout: Duration,
+  poll_interval: Duration,
+  request_timeout: Duration,
+}
+
+impl<C> KillSwitch<C> {
+  pub fn new(client: C) -> Self {
+    KillSwitch {
+      client,
+      engaged: AtomicBool::new(false),
+      attempts: 3,
+      retry_delay: Duration::from_secs(1),
+      cancel_timeout: Duration::from_secs(10),
+      poll_interval: Duration::from_millis(250),
+      request_timeout: Duration::from_secs(5),
+    }
+  }
+
+  ///
+  ///Try each call up to `attempts` times, `delay` apart, 3 times a second apart by default
+  pub fn with_retries(mut self, attempts: usize, delay: Duration) -> Self {
+    self.attempts = attempts.max(1);
+    self.retry_delay = delay;
+    self
+  }
+
+  ///
+  ///How long to wait for canceled orders to leave the open orders, 10 seconds by default
+  pub fn with_cancel_timeout(mut self, timeout: Duration) -> Self {
+    self.cancel_timeout = timeout;
+    self
+  }
+
+  ///
+  ///How long [`KillSwitch::wait_for_http`] waits on one connection to read the request and write
+  /// the response, 5 seconds by default
+  pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+    self.request_timeout = timeout;
+    self
+  }
+
+  pub fn inner(&self) -> &C {
+    &self.client
+  }
+
+  pub fn is_engaged(&self) -> bool {
+    self.engaged.load(Ordering::SeqCst)
+  }
+
+  ///
+  ///Block new orders without touching the account
+  pub fn lock(&self) {
+    self.engaged.store(true, Ordering::SeqCst);
+  }
+
+  ///
+  ///Allow new orders again
+  pub fn release(&self) {
+    self.engaged.store(false, Ordering::SeqCst);
+  }
+
+  async fn retry<T, Fut>(&self, mut call: impl FnMut() -> Fut) -> anyhow::Result<T>
+  where
+    Fut: Future<Output = anyhow::Result<T>>,
+  {
+    let mut attempt = 1;
+    loop {
+      match call().await {
+        Ok(value) => return Ok(value),
+        Err(error) if attempt >= self.attempts => return Err(error),
+        Err(_) => {
+          attempt += 1;
+          sleep(self.retry_delay).await;
+        }
+      }
+    }
+  }
+
+  ///
+  ///Lock trading on the next `kind` signal delivered to the process
+  #[cfg(unix)]
+  pub async fn wait_for_signal(&self, kind: tokio::signal::unix::SignalKind) -> anyhow::Result<()> {
+    let mut signal = tokio::signal::unix::signal(kind)?;
+    signal.recv().await;
+    self.lock();
+    Ok(())
+  }
+
+  ///
+  ///Serve `POST /kill` on `listener` and lock trading on the first such request
+  ///
+  ///`GET /status` answers whether trading is locked, anything else is not found. Bind the listener
+  /// to a loopback address, the endpoint has no authentication.
+  pub async fn wait_for_http(&self, listener: TcpListener) -> anyhow::Result<()> {
+    loop {
+      let (stream, _) = listener.accept().await?;
+      // a client that stalls or drops its connection must not keep the endpoint from the next one,
+      // a kill counts once trading is locked even if its response is never delivered
+      let mut killed = false;
+      let _ = timeout(self.request_timeout, self.serve_http(stream, &mut killed)).await;
+      if killed {
+        return Ok(());
+      }
+    }
+  }
+
+  ///
+  ///Answer one request of [`KillSwitch::wait_for_http`], `killed` is set once it locked trading
+  async fn serve_http(&self, mut stream: TcpStream, killed: &mut bool) -> std::io::Result<()> {
+    let mut request = vec![];
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 8192 {
+      let read = stream.read(&mut buffer).await?;
+      if read == 0 {
+        break;
+      }
+      request.extend_from_slice(&buffer[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    let route = (request_line.next(), request_line.next());
+    if route == (Some("POST"), Some("/kill")) {
+      self.lock();
+      *killed = true;
+    }
+    let (status, body) = match route {
+      (Some("POST"), Some("/kill")) => ("202 Accepted", r#"{"engaged":true}"#.to_string()),
+      (Some("GET"), Some("/status")) => ("200 OK", format!(r#"{{"engaged":{}}}"#, self.is_engaged())),
+      _ => ("404 Not Found", r#"{"message":"not found"}"#.to_string()),
+    };
+    let response = format!(
+      "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+  }
+}
+
+///
+///Loss since the previous close as a percentage of `last_equity`, zero when there is no previous
+/// equity
+pub fn drawdown_pct(account: &Account) -> f64 {
+  let last_equity = account.last_equity.value();
+  if last_equity <= 0.0 {
+    return 0.0;
+  }
+  ((last_equity - account.equity.value()) / last_equity * 100.0).max(0.0)
+}
+
+impl<C: AccountApi> KillSwitch<C> {
+  ///
+  ///Lock trading when the drawdown of the day reached `max_drawdown_pct`, returns whether it did
+  pub async fn check_drawdown(&self, max_drawdown_pct: f64) -> anyhow::Result<bool> {
+    let account = self.client.get_account().await?;
+    if drawdown_pct(&account) >= max_drawdown_pct {
+      self.lock();
+      return Ok(true);
+    }
+    Ok(false)
+  }
+
+  ///
+  ///Poll the account every `interval` and lock trading once the drawdown reached
+  /// `max_drawdown_pct`, failed polls are retried on the next tick
+  pub async fn wait_for_drawdown(&self, max_drawdown_pct: f64, interval: Duration) {
+    loop {
+      if let Ok(true) = self.check_drawdown(max_drawdown_pct).await {
+        return;
+      }
+      sleep(interval).await;
+    }
+  }
+}
+
+impl<C: OrderApi + PositionApi> KillSwitch<C> {
+  ///
+  ///Lock trading, cancel every order and wait for the cancels, then close every position
+  ///
+  ///Closing is retried while any position fails to close; each retry cancels the open orders again
+  /// and resubmits the liquidation of every remaining position.
+  pub async fn engage(&self) -> KillReport {
+    self.lock();
+    let mut report = KillReport::default();
+    match self.retry(|| self.client.delete_all_orders()).await {
+      Ok(canceled) => report.canceled = canceled,
+      Err(error) => report.errors.push(format!("delete_all_orders: {error}")),
+    }
+    match self.wait_for_cancels().await {
+      Ok(unconfirmed) => report.unconfirmed = unconfirmed,
+      Err(error) => report.errors.push(format!("get_all_orders: {error}")),
+    }
+
+    let mut positions: BTreeMap<String, ClosePositionInfo> = BTreeMap::new();
+    let succeeded = |info: &ClosePositionInfo| info.status.starts_with('2');
+    for attempt in 1..=self.attempts {
+      match self.client.clost_all_open_positions(true).await {
+        Ok(infos) => {
+          for info in infos {
+            if !positions.get(&info.symbol).is_some_and(succeeded) {
+              positions.insert(info.symbol.clone(), info);
+            }
+          }
+          if positions.values().all(succeeded) {
+            break;
+          }
+        }
+        Err(error) if attempt == self.attempts => {
+          report.errors.push(format!("clost_all_open_positions: {error}"));
+        }
+        Err(_) => {}
+      }
+      if attempt < self.attempts {
+        sleep(self.retry_delay).await;
+      }
+    }
+    report.failed = positions
+      .values()
+      .filter(|info| !succeeded(info))
+      .map(|info| info.symbol.clone())
+      .collect();
+    report.positions = positions.into_values().collect();
+    report
+  }
+
+  ///
+  ///Poll the open orders until none of them is left or the timeout passed, returns the ones left
+  async fn wait_for_cancels(&self) -> anyhow::Result<Vec<Uuid>> {
+    let deadline = Instant::now() + self.cancel_timeout;
+    loop {
+      let open = self
+        .retry(|| {
+          self.client.get_all_orders(&AllOrdersQueryParameter {
+            status: Some(OrdersFilter::Open),
+            limit: Some(500),
+            after: None,
+            until: None,
+            direction: None,
+            nested: Some(false),
+            symbols: None,
+            side: None,
+            asset_class: None,
+            before_order_id: None,
+            after_order_id: None,
+          })
+        })
+        .await?;
+      if open.is_empty() || Instant::now() >= deadline {
+        return Ok(open.into_iter().map(|order| order.id).collect());
+      }
+      sleep(self.poll_interval).await;
+    }
+  }
+}
+
+impl<C: OrderApi + PositionApi + AccountConfigurationsApi> KillSwitch<C> {
+  ///
+  ///[`KillSwitch::engage`], then set `suspend_trade` on the account so nothing can trade until it
+  /// is cleared; suspending comes last because it would also block the liquidation orders
+  pub async fn engage_and_suspend(&self) -> KillReport {
+    let mut report = self.engage().await;
+    let request_body = AccountConfigurationsReqBody {
+      suspend_trade: Some(true),
+      ..Default::default()
+    };
+    match self
+      .retry(|| self.client.update_account_configurations(&request_body))
+      .await
+    {
+      Ok(configurations) => report.trade_suspended = configurations.suspend_trade,
+      Err(error) => report.errors.push(format!("update_account_configurations: {error}")),
+    }
+    report
+  }
+}
+
+impl<C: OrderApi> OrderApi for KillSwitch<C> {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    if self.is_engaged() {
+      bail!(KillSwitchEngaged);
+    }
+    self.client.create_order(order).await
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    self.client.get_all_orders(query_parameter).await
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    self.client.delete_all_orders().await
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    self.client.get_order_by_client_order_id(client_order_id).await
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    self.client.get_order_by_id(id).await
+  }
+
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    if self.is_engaged() {
+      bail!(KillSwitchEngaged);
+    }
+    self.client.replace_order_by_id(order_id, order).await
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    self.client.delete_order_by_id(order_id).await
+  }
+}
+
+impl<C: AccountApi> AccountApi for KillSwitch<C> {
+  async fn get_account(&self) -> anyhow::Result<Account> {
+    self.client.get_account().await
+  }
+}
+
+impl<C: PositionApi> PositionApi for KillSwitch<C> {
+  async fn get_all_open_positions(&self) -> anyhow::Result<Vec<Position>> {
+    self.client.get_all_open_positions().await
+  }
+
+  async fn get_open_position_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Position> {
+    self.client.get_open_position_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn close_open_position_by_symbol_or_id(
+    &self,
+    symbol_or_id: &str,
+    param: &ClosePositionParam,
+  ) -> anyhow::Result<ClosedPosition> {
+    self
+      .client
+      .close_open_position_by_symbol_or_id(symbol_or_id, param)
+      .await
+  }
+
+  async fn exercise_option_contract_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<()> {
+    self.client.exercise_option_contract_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn clost_all_open_positions(&self, cancel_orders: bool) -> anyhow::Result<Vec<ClosePositionInfo>> {
+    self.client.clost_all_open_positions(cancel_orders).await
+  }
+}
@@ -0,0 +1,9 @@
+mod guard;
+mod kill_switch;
+mod pdt;
+mod rules;
+
+pub use guard::*;
+pub use kill_switch::*;
+pub use pdt::*;
+pub use rules::*;
@@ -0,0 +1,531 @@
+use crate::{
+  api::{
+    AccountApi,
+    AllOrdersQueryParameter,
+    ClockApi,
+    ClosePositionInfo,
+    ClosePositionParam,
+    DeleteAllOrdersResponse,
+    OrderApi,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+  },
+  history::{
+    market_time,
+    trading_date,
+  },
+  models::{
+    Account,
+    ClosedPosition,
+    MarketCalendar,
+    Order,
+    Position,
+    enums::{
+      AssetClass,
+      Side,
+    },
+  },
+  sim::asset_class,
+};
+use anyhow::bail;
+use chrono::{
+  DateTime,
+  Datelike,
+  Days,
+  NaiveDate,
+  NaiveTime,
+  Utc,
+  Weekday,
+};
+use std::{
+  collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+  },
+  sync::Mutex,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+///
+///Equity below which pattern day traders are restricted
+pub const PDT_MIN_EQUITY: f64 = 25_000.0;
+
+///
+///Day trades allowed within the rolling window before an account is flagged as pattern day trader
+pub const PDT_MAX_DAY_TRADES: usize = 3;
+
+///
+///Business days of the rolling day trade window
+pub const PDT_WINDOW_DAYS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+struct TrackedFill {
+  symbol: String,
+  side: Side,
+  qty: f64,
+  timestamp: DateTime<Utc>,
+}
+
+///
+///A closing order that closed a position opened the same trading day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayTrade {
+  pub date: NaiveDate,
+  pub symbol: String,
+  pub order_id: Uuid,
+}
+
+#[derive(Debug, Default)]
+struct DayState {
+  position: f64,
+  opened_long: f64,
+  opened_short: f64,
+  day_trades: Vec<Uuid>,
+}
+
+impl DayState {
+  ///
+  ///Apply a fill, returns whether it closed part of a position opened the same day
+  fn apply(&mut self, side: Side, qty: f64) -> bool {
+    let (closing, opened_today) = match side {
+      Side::Buy => (qty.min((-self.position).max(0.0)), &mut self.opened_short),
+      Side::Sell => (qty.min(self.position.max(0.0)), &mut self.opened_long),
+    };
+    let day_trade = closing > 0.0 && *opened_today > 0.0;
+    *opened_today = (*opened_today - closing).max(0.0);
+    match side {
+      Side::Buy => {
+        self.opened_long += qty - closing;
+        self.position += qty;
+      }
+      Side::Sell => {
+        self.opened_short += qty - closing;
+        self.position -= qty;
+      }
+    }
+    day_trade
+  }
+}
+
+///
+///Counts day trades over the rolling window of five business days from order fill history
+///
+///A day trade is a closing order for a position opened earlier the same trading day, so two buys
+/// closed by one sell count once while one buy closed by two sells counts twice. Fills are
+/// attributed to the time their order completed, and the position a day started with is derived
+/// from the current positions and every fill since. Crypto is not subject to the rule and is
+/// ignored.
+#[derive(Debug, Clone, Default)]
+pub struct PdtTracker {
+  trading_days: BTreeSet<NaiveDate>,
+  fills: BTreeMap<Uuid, TrackedFill>,
+  positions: HashMap<String, f64>,
+}
+
+impl PdtTracker {
+  ///
+  ///`calendar` lists the trading days, without it every weekday is one
+  pub fn new(calendar: &[MarketCalendar]) -> Self {
+    PdtTracker {
+      trading_days: calendar.iter().map(|session| session.date).collect(),
+      ..Default::default()
+    }
+  }
+
+  ///
+  ///Record the fills of `order` and its legs, recording an order again updates it
+  pub fn record_order(&mut self, order: &Order) {
+    for leg in order.legs.iter().flatten() {
+      self.record_order(leg);
+    }
+    let qty = order.filled_qty.as_ref().map(|qty| qty.value()).unwrap_or_default();
+    let Some(timestamp) = order.filled_at.or(order.updated_at) else {
+      return;
+    };
+    if qty <= 0.0 || order.asset_class == AssetClass::Crypto {
+      return;
+    }
+    self.fills.insert(
+      order.id,
+      TrackedFill {
+        symbol: order.symbol.clone(),
+        side: order.side,
+        qty,
+        timestamp,
+      },
+    );
+  }
+
+  ///
+  ///Current positions, needed to tell closing from opening fills
+  pub fn set_positions(&mut self, positions: &[Position]) {
+    self.positions = positions
+      .iter()
+      .map(|position| (position.symbol.clone(), position.qty.value()))
+      .collect();
+  }
+
+  ///
+  ///The business days of the window ending with `today`, oldest first
+  pub fn window(&self, today: NaiveDate) -> Vec<NaiveDate> {
+    let mut days: Vec<NaiveDate> = if self.trading_days.is_empty() {
+      today
+        .iter_days()
+        .rev()
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .take(PDT_WINDOW_DAYS)
+        .collect()
+    } else {
+      self
+        .trading_days
+        .range(..=today)
+        .rev()
+        .take(PDT_WINDOW_DAYS)
+        .copied()
+        .collect()
+    };
+    days.reverse();
+    days
+  }
+
+  ///
+  ///Day trades within the window ending with `today`
+  pub fn day_trades(&self, today: NaiveDate) -> Vec<DayTrade> {
+    let window = self.window(today);
+    let symbols: BTreeSet<&str> = self.fills.values().map(|fill| fill.symbol.as_str()).collect();
+    let mut day_trades = vec![];
+    for date in &window {
+      for symbol in &symbols {
+        day_trades.extend(
+          self
+            .replay(symbol, *date)
+            .day_trades
+            .into_iter()
+            .map(|order_id| DayTrade {
+              date: *date,
+              symbol: symbol.to_string(),
+              order_id,
+            }),
+        );
+      }
+    }
+    day_trades
+  }
+
+  ///
+  ///Whether an order for `qty` of `symbol` on `side`, filled now, would be a day trade
+  pub fn would_day_trade(&self, symbol: &str, side: Side, qty: f64, today: NaiveDate) -> bool {
+    if asset_class(symbol) == AssetClass::Crypto {
+      return false;
+    }
+    self.replay(symbol, today).apply(side, qty)
+  }
+
+  ///
+  ///Replay the fills of `symbol` on `date` from the position it started the day with
+  fn replay(&self, symbol: &str, date: NaiveDate) -> DayState {
+    let mut fills: Vec<(&Uuid, &TrackedFill)> = self
+      .fills
+      .iter()
+      .filter(|(_, fill)| fill.symbol == symbol && trading_date(fill.timestamp) >= date)
+      .collect();
+    fills.sort_by_key(|(_, fill)| fill.timestamp);
+    let signed = |fill: &TrackedFill| match fill.side {
+      Side::Buy => fill.qty,
+      Side::Sell => -fill.qty,
+    };
+    let mut state = DayState {
+      position: self.positions.get(symbol).copied().unwrap_or_default()
+        - fills.iter().map(|(_, fill)| signed(fill)).sum::<f64>(),
+      ..Default::default()
+    };
+    for (id, fill) in fills {
+      if trading_date(fill.timestamp) == date && state.apply(fill.side, fill.qty) {
+        state.day_trades.push(*id);
+      }
+    }
+    state
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdtMode {
+  ///
+  ///Reject the order
+  Block,
+  ///
+  ///Submit the order and keep the warning, see [`PdtGuard::take_warnings`]
+  Warn,
+}
+
+///
+///A day trade the account is not allowed to make under the pattern day trader rule
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error(
+  "{side:?} {symbol} would be a day trade after {day_trades} in the last five business days, with equity {equity:.2} \
+   under 25000"
+)]
+pub struct PdtViolation {
+  pub symbol: String,
+  pub side: Side,
+  pub day_trades: usize,
+  pub equity: f64,
+}
+
+///
+///Wraps a client and checks new and replaced orders against the pattern day trader rule
+///
+///Accounts with at least $25,000 of equity pass through untouched. Below that, an order that would
+/// be a day trade is blocked or warned about once the account already made three day trades within
+/// five business days, or any day trade once the account is flagged as pattern day trader. Day
+/// trades are counted from the order history of the window and `Account.daytrade_count`, whichever
+/// is higher, and the clock of the wrapped client decides the trading day.
+#[derive(Debug)]
+pub struct PdtGuard<C> {
+  client: C,
+  calendar: Vec<MarketCalendar>,
+  mode: PdtMode,
+  warnings: Mutex<Vec<PdtViolation>>,
+}
+
+impl<C> PdtGuard<C> {
+  ///
+  ///`calendar` should cover at least the last five trading days, as returned by
+  /// [`crate::api::CalendarApi`]
+  pub fn new(client: C, calendar: Vec<MarketCalendar>, mode: PdtMode) -> Self {
+    PdtGuard {
+      client,
+      calendar,
+      mode,
+      warnings: Mutex::new(vec![]),
+    }
+  }
+
+  pub fn inner(&self) -> &C {
+    &self.client
+  }
+
+  pub fn inner_mut(&mut self) -> &mut C {
+    &mut self.client
+  }
+
+  ///
+  ///Warnings of orders submitted in [`PdtMode::Warn`] since the last call
+  pub fn take_warnings(&self) -> Vec<PdtViolation> {
+    std::mem::take(&mut *self.warnings.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+  }
+}
+
+impl<C: OrderApi + PositionApi + AccountApi + ClockApi> PdtGuard<C> {
+  ///
+  ///Tracker loaded with the current positions and the orders of the window ending today
+  pub async fn tracker(&self) -> anyhow::Result<(PdtTracker, NaiveDate)> {
+    let today = trading_date(self.client.get_market_clock_info().await?.timestamp.to_utc());
+    let mut tracker = PdtTracker::new(&self.calendar);
+    tracker.set_positions(&self.client.get_all_open_positions().await?);
+    let after = tracker
+      .window(today)
+      .first()
+      .and_then(|first| first.checked_sub_days(Days::new(1)))
+      .and_then(|before| market_time(before, NaiveTime::MIN))
+      .map(|after| after.to_rfc3339());
+    let orders = self
+      .client
+      .get_all_orders(&AllOrdersQueryParameter {
+        status: Some(OrdersFilter::All),
+        limit: Some(500),
+        after,
+        until: None,
+        direction: None,
+        nested: Some(true),
+        symbols: None,
+        side: None,
+        asset_class: None,
+        before_order_id: None,
+        after_order_id: None,
+      })
+      .await?;
+    for order in &orders {
+      tracker.record_order(order);
+    }
+    Ok((tracker, today))
+  }
+
+  ///
+  ///The violation `order` would cause, `None` when it is allowed
+  pub async fn check_order(&self, order: &OrderRequestBody) -> anyhow::Result<Option<PdtViolation>> {
+    let qty = match &order.qty {
+      Some(qty) => qty.value(),
+      None => f64::INFINITY,
+    };
+    self.check(&order.symbol, order.side, qty).await
+  }
+
+  async fn check(&self, symbol: &str, side: Side, qty: f64) -> anyhow::Result<Option<PdtViolation>> {
+    let account = self.client.get_account().await?;
+    let equity = account.equity.value();
+    if equity >= PDT_MIN_EQUITY || asset_class(symbol) == AssetClass::Crypto {
+      return Ok(None);
+    }
+    let (tracker, today) = self.tracker().await?;
+    let day_trades = tracker.day_trades(today).len().max(account.daytrade_count as usize);
+    let allowed = if account.pattern_day_trader {
+      0
+    } else {
+      PDT_MAX_DAY_TRADES
+    };
+    if day_trades >= allowed && tracker.would_day_trade(symbol, side, qty, today) {
+      return Ok(Some(PdtViolation {
+        symbol: symbol.to_string(),
+        side,
+        day_trades,
+        equity,
+      }));
+    }
+    Ok(None)
+  }
+
+  ///
+  ///Block or warn about `violation` according to the mode
+  fn enforce(&self, violation: Option<PdtViolation>) -> anyhow::Result<()> {
+    if let Some(violation) = violation {
+      match self.mode {
+        PdtMode::Block => bail!(violation),
+        PdtMode::Warn => self
+          .warnings
+          .lock()
+          .unwrap_or_else(|poisoned| poisoned.into_inner())
+          .push(violation),
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<C: OrderApi + PositionApi + AccountApi + ClockApi> OrderApi for PdtGuard<C> {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    self.enforce(self.check_order(order).await?)?;
+    self.client.create_order(order).await
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    self.client.get_all_orders(query_parameter).await
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    self.client.delete_all_orders().await
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    self.client.get_order_by_client_order_id(client_order_id).await
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    self.client.get_order_by_id(id).await
+  }
+
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    // the replacement is checked like a new order for the same symbol and side
+    let original = self.client.get_order_by_id(order_id).await?;
+    self.enforce(self.check(&original.symbol, original.side, order.qty.value()).await?)?;
+    self.client.replace_order_by_id(order_id, order).await
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    self.client.delete_order_by_id(order_id).await
+  }
+}
+
+impl<C: AccountApi> AccountApi for PdtGuard<C> {
+  async fn get_account(&self) -> anyhow::Result<Account> {
+    self.client.get_account().await
+  }
+}
+
+impl<C: PositionApi> PositionApi for PdtGuard<C> {
+  async fn get_all_open_positions(&self) -> anyhow::Result<Vec<Position>> {
+    self.client.get_all_open_positions().await
+  }
+
+  async fn get_open_position_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<Position> {
+    self.client.get_open_position_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn close_open_position_by_symbol_or_id(
+    &self,
+    symbol_or_id: &str,
+    param: &ClosePositionParam,
+  ) -> anyhow::Result<ClosedPosition> {
+    self
+      .client
+      .close_open_position_by_symbol_or_id(symbol_or_id, param)
+      .await
+  }
+
+  async fn exercise_option_contract_by_symbol_or_id(&self, symbol_or_id: &str) -> anyhow::Result<()> {
+    self.client.exercise_option_contract_by_symbol_or_id(symbol_or_id).await
+  }
+
+  async fn clost_all_open_positions(&self, cancel_orders: bool) -> anyhow::Result<Vec<ClosePositionInfo>> {
+    self.client.clost_all_open_positions(cancel_orders).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  fn fill(symbol: &str, side: Side, qty: f64, day: u32, hour: u32) -> TrackedFill {
+    TrackedFill {
+      symbol: symbol.to_string(),
+      side,
+      qty,
+      timestamp: Utc.with_ymd_and_hms(2025, 12, day, hour, 0, 0).unwrap(),
+    }
+  }
+
+  fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 12, day).unwrap()
+  }
+
+  #[test]
+  fn test_pdt_tracker_should_count_closing_orders_of_same_day_positions() {
+    let mut tracker = PdtTracker::new(&[]);
+    for fill in [
+      // two buys closed by one sell is one day trade
+      fill("AAPL", Side::Buy, 5.0, 1, 15),
+      fill("AAPL", Side::Buy, 5.0, 1, 16),
+      fill("AAPL", Side::Sell, 10.0, 1, 17),
+      // one buy closed by two sells is two
+      fill("MSFT", Side::Buy, 10.0, 2, 15),
+      fill("MSFT", Side::Sell, 5.0, 2, 16),
+      fill("MSFT", Side::Sell, 5.0, 2, 17),
+      // selling yesterday's position and buying it back is none
+      fill("TSLA", Side::Buy, 10.0, 2, 15),
+      fill("TSLA", Side::Sell, 10.0, 3, 15),
+      fill("TSLA", Side::Buy, 10.0, 3, 16),
+    ] {
+      tracker.fills.insert(Uuid::new_v4(), fill);
+    }
+    tracker.positions.insert("TSLA".to_string(), 10.0);
+
+    assert_eq!(
+      tracker.window(date(8)),
+      vec![date(2), date(3), date(4), date(5), date(8)]
+    );
+    let day_trades = tracker.day_trades(date(5));
+    assert_eq!(day_trades.len(), 3);
+    assert_eq!(day_trades[0].symbol, "AAPL");
+    assert_eq!(tracker.day_trades(date(8)).len(), 2);
+
+    assert!(tracker.would_day_trade("TSLA", Side::Sell, 1.0, date(3)));
+    assert!(!tracker.would_day_trade("TSLA", Side::Sell, 1.0, date(4)));
+    assert!(!tracker.would_day_trade("TSLA", Side::Buy, 1.0, date(3)));
+    assert!(!tracker.would_day_trade("BTC/USD", Side::Sell, 1.0, date(3)));
+  }
+}
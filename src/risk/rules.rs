@@ -0,0 +1,104 @@
+use serde::{
+  Deserialize,
+  Serialize,
+};
+use std::{
+  collections::{
+    BTreeMap,
+    BTreeSet,
+  },
+  path::Path,
+};
+use thiserror::Error;
+
+///
+///Pre-trade limits enforced by [`super::RiskGuard`], every rule left out of the config is not
+/// enforced
+///
+///Loaded from a JSON file such as
+///
+/// ```json
+/// {
+///   "max_order_notional": 25000,
+///   "max_position_notional": 50000,
+///   "position_limits": { "TSLA": 10000 },
+///   "max_gross_exposure": 1.5,
+///   "denied_symbols": ["GME"],
+///   "max_price_deviation_pct": 5,
+///   "max_orders_per_minute": 30
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskRules {
+  ///
+  ///Largest value of a single order
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_order_notional: Option<f64>,
+  ///
+  ///Largest absolute position value per symbol after the order fills
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_position_notional: Option<f64>,
+  ///
+  ///Per symbol overrides of `max_position_notional`
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub position_limits: BTreeMap<String, f64>,
+  ///
+  ///Largest sum of absolute position values after the order fills, as a multiple of the account
+  /// equity
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_gross_exposure: Option<f64>,
+  ///
+  ///Only these symbols can be traded when set
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub allowed_symbols: Option<BTreeSet<String>>,
+  #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+  pub denied_symbols: BTreeSet<String>,
+  ///
+  ///Largest distance of limit and stop prices from the last quote, in percent
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_price_deviation_pct: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_orders_per_minute: Option<usize>,
+}
+
+impl RiskRules {
+  pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+  }
+
+  ///
+  ///Position value limit of `symbol`, its override or the general limit
+  pub fn position_limit(&self, symbol: &str) -> Option<f64> {
+    self.position_limits.get(symbol).copied().or(self.max_position_notional)
+  }
+}
+
+///
+///Why [`super::RiskGuard`] rejected an order, returned as the error of the rejected call
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum RiskViolation {
+  #[error("order notional {notional:.2} exceeds the limit of {limit:.2}")]
+  MaxOrderNotional { notional: f64, limit: f64 },
+  #[error("position in {symbol} would reach {notional:.2}, above the limit of {limit:.2}")]
+  MaxPosition { symbol: String, notional: f64, limit: f64 },
+  #[error("gross exposure would reach {exposure:.2}, above {limit:.2} for equity {equity:.2}")]
+  MaxGrossExposure { exposure: f64, limit: f64, equity: f64 },
+  #[error("{0} is not in the allowed symbols")]
+  SymbolNotAllowed(String),
+  #[error("{0} is in the denied symbols")]
+  SymbolDenied(String),
+  #[error("price {price:.4} is more than {max_deviation_pct}% away from the last quote {reference:.4}")]
+  PriceBand {
+    price: f64,
+    reference: f64,
+    max_deviation_pct: f64,
+  },
+  #[error("no last quote or position price for {0} to check the order against")]
+  NoReferencePrice(String),
+  #[error("more than {limit} orders in the last minute")]
+  OrderRate { limit: usize },
+  #[error("selling {symbol} short while shorting is disabled for the account")]
+  ShortingDisabled { symbol: String },
+}
@@ -0,0 +1,1678 @@
+use crate::{
+  api::{
+    ClosePositionBody,
+    ClosePositionInfo,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    ReplaceOrderByIdRequestBody,
+    StopLoss,
+  },
+  models::{
+    Account,
+    AccountStatus,
+    Asset,
+    Bar,
+    ClosedPosition,
+    ErrorResponse,
+    Order,
+    OrderClass,
+    OrderStatus,
+    Position,
+    PositionIntent,
+    PositionSide,
+    TimeInForce,
+    enums::{
+      AssetClass,
+      Currency,
+      Exchange,
+      OrderType,
+      Side,
+      Status,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  sim::{
+    Execution,
+    FeeModel,
+    Fill,
+    FillModel,
+    FillRequest,
+    MarketSnapshot,
+    NextBarOpen,
+  },
+};
+use chrono::{
+  DateTime,
+  Utc,
+};
+use std::{
+  collections::{
+    BTreeMap,
+    HashMap,
+  },
+  sync::Arc,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SimError {
+  #[error("{0} not found")]
+  NotFound(String),
+  #[error("insufficient buying power")]
+  InsufficientBuyingPower,
+  #[error("insufficient qty available for order (requested: {requested}, available: {available})")]
+  InsufficientQty { requested: f64, available: f64 },
+  #[error("{0}")]
+  Unprocessable(String),
+}
+
+impl SimError {
+  ///
+  ///HTTP status the real API answers with for this rejection
+  pub fn status(&self) -> u16 {
+    match self {
+      SimError::NotFound(_) => 404,
+      SimError::InsufficientBuyingPower | SimError::InsufficientQty { .. } => 403,
+      SimError::Unprocessable(_) => 422,
+    }
+  }
+
+  pub fn to_error_response(&self) -> ErrorResponse {
+    let code = match self {
+      SimError::NotFound(_) => 40410000,
+      SimError::InsufficientBuyingPower | SimError::InsufficientQty { .. } => 40310000,
+      SimError::Unprocessable(_) => 42210000,
+    };
+    ErrorResponse::new(code, self.to_string())
+  }
+}
+
+#[derive(Debug, Clone)]
+struct SimAsset {
+  id: Uuid,
+  symbol: String,
+  class: AssetClass,
+  exchange: Exchange,
+}
+
+impl SimAsset {
+  fn to_asset(&self) -> Asset {
+    Asset {
+      id: self.id,
+      class: self.class,
+      cusip: String::new(),
+      exchange: self.exchange,
+      symbol: self.symbol.clone(),
+      name: self.symbol.clone(),
+      status: Status::Active,
+      tradable: true,
+      marginable: false,
+      shortable: false,
+      margin_requirement_long: None,
+      margin_requirement_short: None,
+      easy_to_borrow: false,
+      fractionable: true,
+      attributes: vec![],
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct SimPosition {
+  symbol: String,
+  qty: f64,
+  avg_entry_price: f64,
+}
+
+#[derive(Debug, Clone)]
+struct SimOrder {
+  id: Uuid,
+  client_order_id: String,
+  symbol: String,
+  order_class: OrderClass,
+  side: Side,
+  order_type: OrderType,
+  time_in_force: TimeInForce,
+  qty: Option<f64>,
+  notional: Option<f64>,
+  limit_price: Option<f64>,
+  stop_price: Option<f64>,
+  trail_price: Option<f64>,
+  trail_percent: Option<f64>,
+  hwm: Option<f64>,
+  triggered: bool,
+  extended_hours: bool,
+  position_intent: Option<PositionIntent>,
+  status: OrderStatus,
+  filled_qty: f64,
+  filled_avg_price: Option<f64>,
+  created_at: DateTime<Utc>,
+  updated_at: DateTime<Utc>,
+  filled_at: Option<DateTime<Utc>>,
+  canceled_at: Option<DateTime<Utc>>,
+  expired_at: Option<DateTime<Utc>>,
+  replaced_at: Option<DateTime<Utc>>,
+  replaced_by: Option<Uuid>,
+  replaces: Option<Uuid>,
+  parent: Option<Uuid>,
+  legs: Vec<Uuid>,
+  oco_group: Option<Uuid>,
+}
+
+impl SimOrder {
+  fn new(symbol: &str, side: Side, order_type: OrderType, time_in_force: TimeInForce, now: DateTime<Utc>) -> Self {
+    SimOrder {
+      id: Uuid::new_v4(),
+      client_order_id: Uuid::new_v4().to_string(),
+      symbol: symbol.to_string(),
+      order_class: OrderClass::Simple,
+      side,
+      order_type,
+      time_in_force,
+      qty: None,
+      notional: None,
+      limit_price: None,
+      stop_price: None,
+      trail_price: None,
+      trail_percent: None,
+      hwm: None,
+      triggered: false,
+      extended_hours: false,
+      position_intent: None,
+      status: OrderStatus::New,
+      filled_qty: 0.0,
+      filled_avg_price: None,
+      created_at: now,
+      updated_at: now,
+      filled_at: None,
+      canceled_at: None,
+      expired_at: None,
+      replaced_at: None,
+      replaced_by: None,
+      replaces: None,
+      parent: None,
+      legs: vec![],
+      oco_group: None,
+    }
+  }
+
+  ///
+  ///Open as listed by `GET /v2/orders?status=open`, including held bracket legs
+  fn is_open(&self) -> bool {
+    self.is_working() || self.status == OrderStatus::Held
+  }
+
+  ///
+  ///Open and eligible to execute
+  fn is_working(&self) -> bool {
+    matches!(
+      self.status,
+      OrderStatus::New | OrderStatus::Accepted | OrderStatus::PartiallyFilled
+    )
+  }
+
+  fn is_buy(&self) -> bool {
+    self.side == Side::Buy
+  }
+
+  ///
+  ///Current stop of a trailing stop order, trailing its high (sell) or low (buy) water mark
+  fn trailing_stop(&self) -> Option<f64> {
+    let hwm = self.hwm?;
+    match (self.trail_price, self.trail_percent, self.is_buy()) {
+      (Some(trail), _, true) => Some(hwm + trail),
+      (Some(trail), _, false) => Some(hwm - trail),
+      (None, Some(percent), true) => Some(hwm * (1.0 + percent / 100.0)),
+      (None, Some(percent), false) => Some(hwm * (1.0 - percent / 100.0)),
+      (None, None, _) => None,
+    }
+  }
+
+  ///
+  ///Execute up to `qty` of the order within `market`, latching stop triggers and moving trailing
+  /// stops
+  fn execute(&mut self, model: &dyn FillModel, market: &MarketSnapshot, qty: f64, resting: bool) -> Option<Fill> {
+    let buy = self.is_buy();
+    let stop_price = match self.order_type {
+      OrderType::Stop | OrderType::StopLimit => self.stop_price,
+      OrderType::TrailingStop => self.trailing_stop(),
+      OrderType::Market | OrderType::Limit => None,
+    };
+    let untriggered = !self.triggered && !matches!(self.order_type, OrderType::Market | OrderType::Limit);
+    let mut trigger_price = None;
+    if untriggered {
+      match model.trigger(self.side, stop_price?, market) {
+        Some(price) => {
+          self.triggered = true;
+          trigger_price = Some(price);
+        }
+        None => {
+          if self.order_type == OrderType::TrailingStop {
+            let bar = market.bar;
+            self.hwm = self
+              .hwm
+              .map(|hwm| if buy { hwm.min(bar.low) } else { hwm.max(bar.high) });
+          }
+          return None;
+        }
+      }
+    }
+    let limit_price = match self.order_type {
+      OrderType::Limit | OrderType::StopLimit => Some(self.limit_price?),
+      _ => None,
+    };
+    let request = FillRequest {
+      side: self.side,
+      qty,
+      limit_price,
+      trigger_price,
+      resting: resting && trigger_price.is_none(),
+    };
+    model.fill(&request, market).filter(|fill| fill.qty > 0.0)
+  }
+}
+
+///
+///In-memory brokerage account executing orders against supplied prices or bars
+///
+///By default orders are checked against the last price as soon as they are submitted, which suits
+/// the live simulator. With [`Broker::with_immediate_fills`] disabled orders only execute on the
+/// next [`Broker::process_bar`], which is what a backtest needs to avoid acting on prices it has
+/// already seen.
+#[derive(Debug, Clone)]
+pub struct Broker {
+  account_id: Uuid,
+  created_at: DateTime<Utc>,
+  now: DateTime<Utc>,
+  immediate_fills: bool,
+  fill_model: Arc<dyn FillModel>,
+  fee_model: Option<Arc<dyn FeeModel>>,
+  cash: f64,
+  last_equity: f64,
+  fees: f64,
+  assets: BTreeMap<String, SimAsset>,
+  prices: HashMap<String, f64>,
+  quotes: HashMap<String, (f64, f64)>,
+  positions: BTreeMap<String, SimPosition>,
+  orders: Vec<SimOrder>,
+}
+
+impl Broker {
+  pub fn new(cash: f64) -> Self {
+    let now = Utc::now();
+    Broker {
+      account_id: Uuid::new_v4(),
+      created_at: now,
+      now,
+      immediate_fills: true,
+      fill_model: Arc::new(NextBarOpen),
+      fee_model: None,
+      cash,
+      last_equity: cash,
+      fees: 0.0,
+      assets: BTreeMap::new(),
+      prices: HashMap::new(),
+      quotes: HashMap::new(),
+      positions: BTreeMap::new(),
+      orders: vec![],
+    }
+  }
+
+  pub fn with_immediate_fills(mut self, immediate_fills: bool) -> Self {
+    self.immediate_fills = immediate_fills;
+    self
+  }
+
+  ///
+  ///How orders execute against bars and quotes, [`NextBarOpen`] by default
+  pub fn with_fill_model(mut self, fill_model: impl FillModel + 'static) -> Self {
+    self.fill_model = Arc::new(fill_model);
+    self
+  }
+
+  ///
+  ///Commissions and fees charged on every fill, none by default
+  pub fn with_fee_model(mut self, fee_model: impl FeeModel + 'static) -> Self {
+    self.fee_model = Some(Arc::new(fee_model));
+    self
+  }
+
+  pub fn account_id(&self) -> Uuid {
+    self.account_id
+  }
+
+  pub fn now(&self) -> DateTime<Utc> {
+    self.now
+  }
+
+  ///
+  ///Move the broker clock, used for every order timestamp
+  pub fn set_time(&mut self, now: DateTime<Utc>) {
+    self.now = now;
+  }
+
+  pub fn add_asset(&mut self, symbol: &str, class: AssetClass) {
+    let exchange = match class {
+      AssetClass::Crypto => Exchange::CRYPTO,
+      _ => Exchange::NASDAQ,
+    };
+    self.assets.entry(symbol.to_string()).or_insert_with(|| SimAsset {
+      id: Uuid::new_v4(),
+      symbol: symbol.to_string(),
+      class,
+      exchange,
+    });
+  }
+
+  pub fn assets(&self) -> Vec<Asset> {
+    self.assets.values().map(SimAsset::to_asset).collect()
+  }
+
+  pub fn asset(&self, symbol_or_id: &str) -> Result<Asset, SimError> {
+    self
+      .find_asset(symbol_or_id)
+      .map(SimAsset::to_asset)
+      .ok_or_else(|| SimError::NotFound(format!("asset {}", symbol_or_id)))
+  }
+
+  pub fn price(&self, symbol: &str) -> Option<f64> {
+    self.prices.get(symbol).copied()
+  }
+
+  ///
+  ///Last bid and ask of `symbol`
+  pub fn quote(&self, symbol: &str) -> Option<(f64, f64)> {
+    self.quotes.get(symbol).copied()
+  }
+
+  ///
+  ///Commissions and fees charged so far
+  pub fn fees(&self) -> f64 {
+    self.fees
+  }
+
+  ///
+  ///Record a new price, unknown symbols become tradable assets, returns the orders it filled
+  pub fn set_price(&mut self, symbol: &str, price: f64) -> Vec<Order> {
+    let bar = Bar {
+      timestamp: self.now,
+      open: price,
+      high: price,
+      low: price,
+      close: price,
+      volume: 0.0,
+      trade_count: None,
+      vwap: None,
+    };
+    self.process_bar(symbol, &bar, true)
+  }
+
+  ///
+  ///Record a new quote, the midpoint becomes the last price; returns the orders it filled
+  ///
+  ///Fill models that execute against quotes use it until the next quote replaces it.
+  pub fn set_quote(&mut self, symbol: &str, bid: f64, ask: f64) -> Vec<Order> {
+    self.quotes.insert(symbol.to_string(), (bid, ask));
+    self.set_price(symbol, (bid + ask) / 2.0)
+  }
+
+  ///
+  ///Execute the working orders of `symbol` against `bar`, returns the orders it filled
+  ///
+  ///Outside `regular_hours` only extended hours orders and crypto orders execute. Bracket legs
+  /// released by a fill start working on the next bar.
+  pub fn process_bar(&mut self, symbol: &str, bar: &Bar, regular_hours: bool) -> Vec<Order> {
+    self.add_asset(symbol, asset_class(symbol));
+    let class = self.assets[symbol].class;
+    let market = self.market_snapshot(symbol, bar);
+
+    let candidates: Vec<usize> = (0..self.orders.len())
+      .filter(|index| self.orders[*index].is_working() && self.orders[*index].symbol == symbol)
+      .collect();
+    let mut filled = vec![];
+    for index in candidates {
+      let order = &self.orders[index];
+      if !order.is_working() || (class != AssetClass::Crypto && !regular_hours && !order.extended_hours) {
+        continue;
+      }
+      if self.execute(index, &market, true) {
+        filled.push(index);
+      }
+    }
+    self.prices.insert(symbol.to_string(), bar.close);
+    filled
+      .into_iter()
+      .map(|index| self.render_order(&self.orders[index]))
+      .collect()
+  }
+
+  ///
+  ///Expire the open DAY orders submitted before `created_before`, returns the expired orders
+  pub fn expire_day_orders(&mut self, created_before: DateTime<Utc>) -> Vec<Order> {
+    let expiring: Vec<usize> = (0..self.orders.len())
+      .filter(|index| {
+        let order = &self.orders[*index];
+        order.is_open() && order.time_in_force == TimeInForce::DAY && order.created_at < created_before
+      })
+      .collect();
+    expiring
+      .into_iter()
+      .map(|index| {
+        self.close_order(index, OrderStatus::Expired);
+        self.render_order(&self.orders[index])
+      })
+      .collect()
+  }
+
+  ///
+  ///Apply a forward (`ratio` > 1) or reverse split to the position, open orders and last price of
+  /// `symbol`
+  pub fn apply_split(&mut self, symbol: &str, ratio: f64) {
+    if let Some(position) = self.positions.get_mut(symbol) {
+      position.qty *= ratio;
+      position.avg_entry_price /= ratio;
+    }
+    if let Some(price) = self.prices.get_mut(symbol) {
+      *price /= ratio;
+    }
+    for order in self
+      .orders
+      .iter_mut()
+      .filter(|order| order.is_open() && order.symbol == symbol)
+    {
+      order.qty = order.qty.map(|qty| qty * ratio);
+      order.filled_qty *= ratio;
+      for price in [
+        &mut order.limit_price,
+        &mut order.stop_price,
+        &mut order.trail_price,
+        &mut order.hwm,
+      ] {
+        *price = price.map(|price| price / ratio);
+      }
+    }
+  }
+
+  ///
+  ///Signed quantity held in `symbol`, zero without a position
+  pub fn position_qty(&self, symbol: &str) -> f64 {
+    self
+      .positions
+      .get(symbol)
+      .map(|position| position.qty)
+      .unwrap_or_default()
+  }
+
+  ///
+  ///Add `amount` to the cash balance, negative amounts withdraw
+  pub fn credit_cash(&mut self, amount: f64) {
+    self.cash += amount;
+  }
+
+  pub fn cash(&self) -> f64 {
+    self.cash
+  }
+
+  pub fn equity(&self) -> f64 {
+    self.cash
+      + self
+        .positions
+        .values()
+        .map(|position| self.market_value(position))
+        .sum::<f64>()
+  }
+
+  ///
+  ///Cash not reserved by open buy orders, the account trades without margin
+  pub fn buying_power(&self) -> f64 {
+    let reserved: f64 = self
+      .orders
+      .iter()
+      .filter(|order| order.is_working() && order.is_buy())
+      .map(|order| self.reserve_cost(order))
+      .sum();
+    self.cash - reserved
+  }
+
+  ///
+  ///Start a new trading day, `last_equity` becomes the current equity
+  pub fn roll_day(&mut self) {
+    self.last_equity = self.equity();
+  }
+
+  pub fn account(&self) -> Account {
+    let long_market_value: f64 = self
+      .positions
+      .values()
+      .map(|position| self.market_value(position))
+      .filter(|value| *value > 0.0)
+      .sum();
+    let short_market_value: f64 = self
+      .positions
+      .values()
+      .map(|position| self.market_value(position))
+      .filter(|value| *value < 0.0)
+      .sum();
+    let buying_power = self.buying_power();
+    Account {
+      id: self.account_id,
+      account_number: format!("SIM{}", &self.account_id.simple().to_string()[..9].to_uppercase()),
+      status: AccountStatus::Active,
+      crypto_status: AccountStatus::Active,
+      currency: Currency::USD,
+      buying_power: Money::from_f64(buying_power),
+      regt_buying_power: Money::from_f64(buying_power),
+      daytrading_buying_power: Money::from_f64(0.0),
+      effective_buying_power: Money::from_f64(buying_power),
+      options_buying_power: Money::from_f64(buying_power),
+      options_approved_level: 0,
+      options_trading_level: 0,
+      non_marginable_buying_power: Money::from_f64(buying_power),
+      bod_dtbp: Money::from_f64(0.0),
+      cash: Money::from_f64(self.cash),
+      accrued_fees: Money::from_f64(0.0),
+      portfolio_value: Money::from_f64(self.equity()),
+      pattern_day_trader: false,
+      trading_blocked: false,
+      transfers_blocked: false,
+      account_blocked: false,
+      trade_suspended_by_user: false,
+      shorting_enabled: false,
+      multiplier: 1,
+      equity: Money::from_f64(self.equity()),
+      last_equity: Money::from_f64(self.last_equity),
+      long_market_value: Money::from_f64(long_market_value),
+      short_market_value: Money::from_f64(short_market_value),
+      position_market_value: Money::from_f64(long_market_value + short_market_value),
+      initial_margin: Money::from_f64(0.0),
+      maintenance_margin: Money::from_f64(0.0),
+      last_maintenance_margin: Money::from_f64(0.0),
+      sma: Money::from_f64(0.0),
+      daytrade_count: 0,
+      balance_asof: self.now.date_naive(),
+      crypto_tier: 0,
+      intraday_adjustments: 0,
+      pending_reg_taf_fees: None,
+      pending_transfer_in: None,
+      pending_transfer_out: None,
+      created_at: self.created_at,
+    }
+  }
+
+  ///
+  ///Validate and accept an order, bracket, OTO and OCO orders create their legs as well
+  pub fn submit_order(&mut self, request: &OrderRequestBody) -> Result<Order, SimError> {
+    let asset = match self.assets.get(&request.symbol) {
+      Some(asset) => asset.clone(),
+      None => return Err(SimError::Unprocessable(format!("asset {} not found", request.symbol))),
+    };
+    if let Some(client_order_id) = &request.client_order_id
+      && self
+        .orders
+        .iter()
+        .any(|order| &order.client_order_id == client_order_id)
+    {
+      return Err(SimError::Unprocessable("client_order_id must be unique".to_string()));
+    }
+    let mut order = SimOrder::new(
+      &asset.symbol,
+      request.side,
+      request._type,
+      request.time_in_force,
+      self.now,
+    );
+    if let Some(client_order_id) = &request.client_order_id {
+      order.client_order_id = client_order_id.clone();
+    }
+    order.order_class = match request.order_class {
+      None | Some(OrderClass::Empty) => OrderClass::Simple,
+      Some(order_class) => order_class,
+    };
+    order.qty = request.qty.as_ref().map(NumberAsString::value);
+    order.notional = request.notional.as_ref().map(Money::value);
+    order.limit_price = request.limit_price.as_ref().map(Money::value);
+    order.stop_price = request.stop_price.as_ref().map(Money::value);
+    order.trail_price = request.trail_price.as_ref().map(Money::value);
+    order.trail_percent = request.trail_percent.as_ref().map(Money::value);
+    order.extended_hours = request.extended_hours;
+    order.position_intent = request.position_intent;
+
+    let take_profit = request
+      .take_profit
+      .as_ref()
+      .map(|take_profit| take_profit.limit_price.value());
+    let mut legs = vec![];
+    match (order.order_class, take_profit, &request.stop_loss) {
+      (OrderClass::Simple, _, _) => {}
+      (OrderClass::Bracket, Some(take_profit), Some(stop_loss)) => {
+        let group = Uuid::new_v4();
+        legs.push(self.take_profit_leg(&order, take_profit, Some(group)));
+        legs.push(self.stop_loss_leg(&order, stop_loss, Some(group)));
+      }
+      (OrderClass::Bracket, _, _) => {
+        return Err(SimError::Unprocessable(
+          "bracket orders require take_profit and stop_loss".to_string(),
+        ));
+      }
+      (OrderClass::Otc, Some(take_profit), None) => legs.push(self.take_profit_leg(&order, take_profit, None)),
+      (OrderClass::Otc, None, Some(stop_loss)) => legs.push(self.stop_loss_leg(&order, stop_loss, None)),
+      (OrderClass::Otc, _, _) => {
+        return Err(SimError::Unprocessable(
+          "oto orders require either take_profit or stop_loss".to_string(),
+        ));
+      }
+      (OrderClass::Oco, Some(take_profit), Some(stop_loss)) if order.order_type == OrderType::Limit => {
+        // the parent is the take-profit limit, its single leg the stop-loss, both working from the start
+        order.limit_price = Some(take_profit);
+        order.oco_group = Some(order.id);
+        let mut leg = self.stop_loss_leg(&order, stop_loss, Some(order.id));
+        leg.side = order.side;
+        leg.status = OrderStatus::New;
+        legs.push(leg);
+      }
+      (OrderClass::Oco, _, _) => {
+        return Err(SimError::Unprocessable(
+          "oco orders must be limit orders with take_profit and stop_loss".to_string(),
+        ));
+      }
+      _ => {
+        return Err(SimError::Unprocessable(
+          "order class not supported by the simulator".to_string(),
+        ));
+      }
+    }
+    if !legs.is_empty() && !matches!(order.order_type, OrderType::Market | OrderType::Limit) {
+      return Err(SimError::Unprocessable(
+        "advanced orders must be market or limit orders".to_string(),
+      ));
+    }
+    self.validate(&order, asset.class)?;
+    for leg in &legs {
+      self.validate(leg, asset.class)?;
+    }
+    self.accept(order, legs, None)
+  }
+
+  pub fn replace_order(&mut self, id: &Uuid, request: &ReplaceOrderByIdRequestBody) -> Result<Order, SimError> {
+    let index = self.order_index(id)?;
+    if !self.orders[index].is_open() {
+      return Err(SimError::Unprocessable("order is not replaceable".to_string()));
+    }
+    let mut order = self.orders[index].clone();
+    order.id = Uuid::new_v4();
+    order.client_order_id = request.client_order_id.clone();
+    order.qty = Some(request.qty.value());
+    order.notional = None;
+    order.time_in_force = request.time_in_force;
+    // prices left out of the request keep their current value
+    let limit_price = request.limit_price.as_ref().map(Money::value);
+    let stop_price = request.stop_price.as_ref().map(Money::value);
+    let trail = request.trail.as_ref().map(Money::value);
+    match order.order_type {
+      OrderType::Market => {}
+      OrderType::Limit => order.limit_price = limit_price.or(order.limit_price),
+      OrderType::Stop => order.stop_price = stop_price.or(order.stop_price),
+      OrderType::StopLimit => {
+        order.limit_price = limit_price.or(order.limit_price);
+        order.stop_price = stop_price.or(order.stop_price);
+      }
+      OrderType::TrailingStop if order.trail_price.is_some() => order.trail_price = trail.or(order.trail_price),
+      OrderType::TrailingStop => order.trail_percent = trail.or(order.trail_percent),
+    }
+    order.created_at = self.now;
+    order.updated_at = self.now;
+    order.replaces = Some(self.orders[index].id);
+    if order.oco_group == Some(self.orders[index].id) {
+      order.oco_group = Some(order.id);
+    }
+    let class = self.assets[&order.symbol].class;
+    self.validate(&order, class)?;
+    self.accept(order, vec![], Some(index))
+  }
+
+  ///
+  ///Cancel an open order, its held legs and the other side of its OCO group
+  pub fn cancel_order(&mut self, id: &Uuid) -> Result<(), SimError> {
+    let index = self.order_index(id)?;
+    if !self.orders[index].is_open() {
+      return Err(SimError::Unprocessable("order is not cancelable".to_string()));
+    }
+    let legs = self.orders[index].legs.clone();
+    let group = self.orders[index].oco_group;
+    for related in 0..self.orders.len() {
+      let order = &self.orders[related];
+      let is_related = related == index || legs.contains(&order.id) || (group.is_some() && order.oco_group == group);
+      if is_related && order.is_open() {
+        self.close_order(related, OrderStatus::Canceled);
+      }
+    }
+    Ok(())
+  }
+
+  ///
+  ///Cancel every open order, returns the ids of the canceled orders
+  pub fn cancel_all_orders(&mut self) -> Vec<Uuid> {
+    let open: Vec<usize> = (0..self.orders.len())
+      .filter(|index| self.orders[*index].is_open())
+      .collect();
+    open
+      .into_iter()
+      .map(|index| {
+        self.close_order(index, OrderStatus::Canceled);
+        self.orders[index].id
+      })
+      .collect()
+  }
+
+  pub fn order(&self, id: &Uuid) -> Result<Order, SimError> {
+    let index = self.order_index(id)?;
+    Ok(self.render_order(&self.orders[index]))
+  }
+
+  pub fn order_by_client_order_id(&self, client_order_id: &str) -> Result<Order, SimError> {
+    match self
+      .orders
+      .iter()
+      .find(|order| order.client_order_id == client_order_id)
+    {
+      Some(order) => Ok(self.render_order(order)),
+      None => Err(SimError::NotFound(format!("order {}", client_order_id))),
+    }
+  }
+
+  ///
+  ///Orders newest first, filtered like `GET /v2/orders`
+  ///
+  ///Most recent orders first, `nested` leaves out legs since they are rendered inside their parent
+  pub fn orders(&self, status: &OrdersFilter, symbols: &[String], nested: bool, limit: usize) -> Vec<Order> {
+    self
+      .orders
+      .iter()
+      .rev()
+      .filter(|order| !nested || order.parent.is_none())
+      .filter(|order| match status {
+        OrdersFilter::Open => order.is_open(),
+        OrdersFilter::Closed => !order.is_open(),
+        OrdersFilter::All => true,
+      })
+      .filter(|order| symbols.is_empty() || symbols.contains(&order.symbol))
+      .take(limit)
+      .map(|order| self.render_order(order))
+      .collect()
+  }
+
+  pub fn positions(&self) -> Vec<Position> {
+    self
+      .positions
+      .values()
+      .map(|position| self.render_position(position))
+      .collect()
+  }
+
+  pub fn position(&self, symbol_or_id: &str) -> Result<Position, SimError> {
+    match self
+      .find_asset(symbol_or_id)
+      .and_then(|asset| self.positions.get(&asset.symbol))
+    {
+      Some(position) => Ok(self.render_position(position)),
+      None => Err(SimError::NotFound(format!("position {}", symbol_or_id))),
+    }
+  }
+
+  ///
+  ///Liquidate `qty` shares, or `percentage` of the position, or all of it with a market order
+  pub fn close_position(
+    &mut self,
+    symbol_or_id: &str,
+    qty: Option<f64>,
+    percentage: Option<f64>,
+  ) -> Result<Order, SimError> {
+    let position = match self
+      .find_asset(symbol_or_id)
+      .and_then(|asset| self.positions.get(&asset.symbol))
+    {
+      Some(position) => position.clone(),
+      None => return Err(SimError::NotFound(format!("position {}", symbol_or_id))),
+    };
+    let long = position.qty > 0.0;
+    let mut order = SimOrder::new(
+      &position.symbol,
+      if long { Side::Sell } else { Side::Buy },
+      OrderType::Market,
+      TimeInForce::DAY,
+      self.now,
+    );
+    order.qty = Some(match (qty, percentage) {
+      (Some(qty), _) => qty,
+      (None, Some(percentage)) => position.qty.abs() * percentage / 100.0,
+      (None, None) => position.qty.abs(),
+    });
+    order.position_intent = Some(if long {
+      PositionIntent::SellToClose
+    } else {
+      PositionIntent::BuyToClose
+    });
+    self.accept(order, vec![], None)
+  }
+
+  ///
+  ///Liquidate every position, canceling open orders first when asked to
+  pub fn close_all_positions(&mut self, cancel_orders: bool) -> Vec<(String, Result<Order, SimError>)> {
+    if cancel_orders {
+      self.cancel_all_orders();
+    }
+    let symbols: Vec<String> = self.positions.keys().cloned().collect();
+    symbols
+      .into_iter()
+      .map(|symbol| {
+        let result = self.close_position(&symbol, None, None);
+        (symbol, result)
+      })
+      .collect()
+  }
+
+  fn take_profit_leg(&self, parent: &SimOrder, limit_price: f64, group: Option<Uuid>) -> SimOrder {
+    let mut leg = self.exit_leg(parent, OrderType::Limit, group);
+    leg.limit_price = Some(limit_price);
+    leg
+  }
+
+  fn stop_loss_leg(&self, parent: &SimOrder, stop_loss: &StopLoss, group: Option<Uuid>) -> SimOrder {
+    let order_type = match stop_loss.limit_price {
+      Some(_) => OrderType::StopLimit,
+      None => OrderType::Stop,
+    };
+    let mut leg = self.exit_leg(parent, order_type, group);
+    leg.stop_price = Some(stop_loss.stop_price.value());
+    leg.limit_price = stop_loss.limit_price.as_ref().map(Money::value);
+    leg
+  }
+
+  ///
+  ///Held leg closing the position `parent` opens, released once the parent fills
+  fn exit_leg(&self, parent: &SimOrder, order_type: OrderType, group: Option<Uuid>) -> SimOrder {
+    let side = match parent.side {
+      Side::Buy => Side::Sell,
+      Side::Sell => Side::Buy,
+    };
+    let mut leg = SimOrder::new(&parent.symbol, side, order_type, parent.time_in_force, self.now);
+    leg.order_class = parent.order_class;
+    leg.qty = parent.qty;
+    leg.status = OrderStatus::Held;
+    leg.parent = Some(parent.id);
+    leg.oco_group = group;
+    leg
+  }
+
+  fn validate(&self, order: &SimOrder, class: AssetClass) -> Result<(), SimError> {
+    let unprocessable = |message: &str| Err(SimError::Unprocessable(message.to_string()));
+    if matches!(order.time_in_force, TimeInForce::OPG | TimeInForce::CLS) {
+      return unprocessable("opg and cls orders are not supported by the simulator");
+    }
+    match (order.qty, order.notional) {
+      (Some(qty), None) if qty > 0.0 => {}
+      (None, Some(notional)) if notional > 0.0 && order.order_type == OrderType::Market => {}
+      (None, Some(_)) if order.order_type != OrderType::Market => {
+        return unprocessable("notional orders must be market orders");
+      }
+      _ => return unprocessable("either a positive qty or notional is required"),
+    }
+    match order.order_type {
+      OrderType::Limit if order.limit_price.is_none() => return unprocessable("limit orders require a limit_price"),
+      OrderType::Stop if order.stop_price.is_none() => return unprocessable("stop orders require a stop_price"),
+      OrderType::StopLimit if order.limit_price.is_none() || order.stop_price.is_none() => {
+        return unprocessable("stop limit orders require a stop_price and a limit_price");
+      }
+      OrderType::TrailingStop if order.trail_price.is_some() == order.trail_percent.is_some() => {
+        return unprocessable("trailing stop orders require either trail_price or trail_percent");
+      }
+      _ => {}
+    }
+    let needs_price = matches!(order.order_type, OrderType::Market | OrderType::TrailingStop);
+    if needs_price && !self.prices.contains_key(&order.symbol) {
+      return Err(SimError::Unprocessable(format!(
+        "no price available for {}",
+        order.symbol
+      )));
+    }
+    if order.extended_hours
+      && class != AssetClass::Crypto
+      && (order.order_type != OrderType::Limit || order.time_in_force != TimeInForce::DAY)
+    {
+      return unprocessable("extended hours orders must be DAY limit orders");
+    }
+    Ok(())
+  }
+
+  ///
+  ///Check funds for a new order, or the replacement of the order at `replaces`, then book it with
+  /// its legs
+  fn accept(&mut self, mut order: SimOrder, legs: Vec<SimOrder>, replaces: Option<usize>) -> Result<Order, SimError> {
+    let price = self.prices.get(&order.symbol).copied();
+    if order.order_type == OrderType::TrailingStop && order.hwm.is_none() {
+      order.hwm = price;
+    }
+
+    // the order being replaced frees its reservation before the new one is checked
+    let (released_cost, released_qty) = match replaces.map(|index| &self.orders[index]) {
+      Some(replaced) if replaced.is_buy() && replaced.is_working() => (self.reserve_cost(replaced), 0.0),
+      Some(replaced) if replaced.is_working() && replaced.parent.is_none() => (0.0, self.remaining_qty(replaced)),
+      _ => (0.0, 0.0),
+    };
+    let qty = self.remaining_qty(&order);
+    if order.status != OrderStatus::Held {
+      match order.side {
+        Side::Buy => {
+          if self.reserve_cost(&order) > self.buying_power() + released_cost + 1e-9 {
+            return Err(SimError::InsufficientBuyingPower);
+          }
+        }
+        Side::Sell if order.parent.is_none() => {
+          let available = self.available_qty(&order.symbol) + released_qty;
+          if qty > available + 1e-9 {
+            return Err(SimError::InsufficientQty {
+              requested: qty,
+              available,
+            });
+          }
+        }
+        Side::Sell => {}
+      }
+    }
+
+    if let Some(index) = replaces {
+      let replaced_id = self.orders[index].id;
+      self.close_order(index, OrderStatus::Replaced);
+      self.orders[index].replaced_at = Some(self.now);
+      self.orders[index].replaced_by = Some(order.id);
+      for other in self.orders.iter_mut() {
+        if other.parent == Some(replaced_id) {
+          other.parent = Some(order.id);
+        }
+        for leg in other.legs.iter_mut().filter(|leg| **leg == replaced_id) {
+          *leg = order.id;
+        }
+      }
+    }
+    order.legs = legs.iter().map(|leg| leg.id).collect();
+    self.orders.push(order);
+    let index = self.orders.len() - 1;
+    self.orders.extend(legs);
+
+    if self.immediate_fills
+      && let Some(price) = price
+    {
+      let bar = Bar {
+        timestamp: self.now,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+        volume: 0.0,
+        trade_count: None,
+        vwap: None,
+      };
+      let market = self.market_snapshot(&self.orders[index].symbol, &bar);
+      for working_index in index..self.orders.len() {
+        if self.orders[working_index].is_working() {
+          self.execute(working_index, &market, false);
+        }
+      }
+    }
+    Ok(self.render_order(&self.orders[index]))
+  }
+
+  fn market_snapshot<'a>(&self, symbol: &str, bar: &'a Bar) -> MarketSnapshot<'a> {
+    let quote = self.quotes.get(symbol);
+    MarketSnapshot {
+      bar,
+      bid: quote.map(|(bid, _)| *bid),
+      ask: quote.map(|(_, ask)| *ask),
+    }
+  }
+
+  ///
+  ///Execute the working order at `index` against `market`, then cancel whatever an IOC or FOK
+  /// order left unfilled; returns whether it traded
+  fn execute(&mut self, index: usize, market: &MarketSnapshot, resting: bool) -> bool {
+    let qty = self.remaining_qty(&self.orders[index]);
+    let fill_model = self.fill_model.clone();
+    let order = &mut self.orders[index];
+    let fill = order
+      .execute(fill_model.as_ref(), market, qty, resting)
+      .filter(|fill| order.time_in_force != TimeInForce::FOK || fill.qty >= qty - 1e-9);
+    let traded = fill.is_some();
+    if let Some(fill) = fill {
+      self.fill(index, fill);
+    }
+    let order = &self.orders[index];
+    if order.is_working() && matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK) {
+      self.close_order(index, OrderStatus::Canceled);
+    }
+    traded
+  }
+
+  ///
+  ///Book `fill` against the order, a complete fill releases its held legs; any fill cancels the
+  /// other side of its OCO group
+  fn fill(&mut self, index: usize, fill: Fill) {
+    let remaining = self.remaining_qty(&self.orders[index]);
+    let qty = fill.qty.min(remaining);
+    let price = fill.price;
+    let now = self.now;
+    let order = &self.orders[index];
+    let class = self.assets[&order.symbol].class;
+    let fee = match &self.fee_model {
+      Some(fee_model) => fee_model.fee(&Execution {
+        symbol: &order.symbol,
+        class,
+        side: order.side,
+        qty,
+        price,
+        liquidity: fill.liquidity,
+      }),
+      None => 0.0,
+    };
+
+    let order = &mut self.orders[index];
+    let signed_qty = if order.is_buy() { qty } else { -qty };
+    let complete = qty >= remaining - 1e-9;
+    order.filled_avg_price = Some(match order.filled_avg_price {
+      Some(average) => (average * order.filled_qty + price * qty) / (order.filled_qty + qty),
+      None => price,
+    });
+    order.filled_qty += qty;
+    order.updated_at = now;
+    if complete {
+      order.status = OrderStatus::Filled;
+      order.filled_at = Some(now);
+    } else {
+      order.status = OrderStatus::PartiallyFilled;
+    }
+    self.cash -= signed_qty * price * contract_multiplier(class) + fee;
+    self.fees += fee;
+
+    let position = self.positions.entry(order.symbol.clone()).or_insert(SimPosition {
+      symbol: order.symbol.clone(),
+      qty: 0.0,
+      avg_entry_price: price,
+    });
+    let new_qty = position.qty + signed_qty;
+    if position.qty == 0.0 || position.qty.signum() != new_qty.signum() {
+      position.avg_entry_price = price;
+    } else if new_qty.abs() > position.qty.abs() {
+      position.avg_entry_price = (position.avg_entry_price * position.qty + price * signed_qty) / new_qty;
+    }
+    position.qty = new_qty;
+    if position.qty.abs() < 1e-9 {
+      self.positions.remove(&order.symbol);
+    }
+
+    let (id, legs, group, filled_qty) = (order.id, order.legs.clone(), order.oco_group, order.filled_qty);
+    for other in self.orders.iter_mut() {
+      if complete && legs.contains(&other.id) && other.status == OrderStatus::Held {
+        other.status = OrderStatus::New;
+        other.qty = Some(filled_qty);
+        other.updated_at = now;
+        if other.order_type == OrderType::TrailingStop {
+          other.hwm = Some(price);
+        }
+      } else if group.is_some() && other.oco_group == group && other.id != id && other.is_open() {
+        other.status = OrderStatus::Canceled;
+        other.canceled_at = Some(now);
+        other.updated_at = now;
+      }
+    }
+  }
+
+  fn close_order(&mut self, index: usize, status: OrderStatus) {
+    let order = &mut self.orders[index];
+    order.status = status;
+    order.updated_at = self.now;
+    match status {
+      OrderStatus::Canceled => order.canceled_at = Some(self.now),
+      OrderStatus::Expired => order.expired_at = Some(self.now),
+      _ => {}
+    }
+  }
+
+  fn remaining_qty(&self, order: &SimOrder) -> f64 {
+    match (order.qty, order.notional) {
+      (Some(qty), _) => qty - order.filled_qty,
+      (None, Some(notional)) => match self.prices.get(&order.symbol) {
+        Some(price) => {
+          let spent = order.filled_qty * order.filled_avg_price.unwrap_or_default();
+          ((notional - spent) / price).max(0.0)
+        }
+        None => 0.0,
+      },
+      (None, None) => 0.0,
+    }
+  }
+
+  ///
+  ///Buying power an open buy order reserves for its unfilled quantity
+  fn reserve_cost(&self, order: &SimOrder) -> f64 {
+    self.remaining_qty(order) * self.reserve_price(order) * self.multiplier(&order.symbol)
+  }
+
+  ///
+  ///Price buy orders reserve buying power at, the worst price they can execute at when known
+  fn reserve_price(&self, order: &SimOrder) -> f64 {
+    order
+      .limit_price
+      .or(order.stop_price)
+      .or_else(|| order.trailing_stop())
+      .or_else(|| self.prices.get(&order.symbol).copied())
+      .unwrap_or_default()
+  }
+
+  ///
+  ///Long quantity not already committed to open sell orders, OCO groups commit their quantity once
+  fn available_qty(&self, symbol: &str) -> f64 {
+    let mut groups: HashMap<Uuid, f64> = HashMap::new();
+    let mut held = 0.0;
+    for order in &self.orders {
+      if !order.is_working() || order.is_buy() || order.symbol != symbol {
+        continue;
+      }
+      let qty = self.remaining_qty(order);
+      match order.oco_group {
+        Some(group) => {
+          let committed = groups.entry(group).or_default();
+          *committed = committed.max(qty);
+        }
+        None => held += qty,
+      }
+    }
+    self.position_qty(symbol).max(0.0) - held - groups.values().sum::<f64>()
+  }
+
+  fn order_index(&self, id: &Uuid) -> Result<usize, SimError> {
+    match self.orders.iter().position(|order| &order.id == id) {
+      Some(index) => Ok(index),
+      None => Err(SimError::NotFound(format!("order {}", id))),
+    }
+  }
+
+  fn find_asset(&self, symbol_or_id: &str) -> Option<&SimAsset> {
+    self.assets.get(symbol_or_id).or_else(|| {
+      self
+        .assets
+        .values()
+        .find(|asset| asset.id.to_string() == symbol_or_id || asset.symbol.replace('/', "") == symbol_or_id)
+    })
+  }
+
+  fn multiplier(&self, symbol: &str) -> f64 {
+    match self.assets.get(symbol) {
+      Some(asset) => contract_multiplier(asset.class),
+      None => 1.0,
+    }
+  }
+
+  fn market_value(&self, position: &SimPosition) -> f64 {
+    self.multiplier(&position.symbol)
+      * position.qty
+      * self
+        .prices
+        .get(&position.symbol)
+        .copied()
+        .unwrap_or(position.avg_entry_price)
+  }
+
+  fn render_order(&self, order: &SimOrder) -> Order {
+    let asset = &self.assets[&order.symbol];
+    let legs: Vec<Order> = order
+      .legs
+      .iter()
+      .filter_map(|leg| self.orders.iter().find(|other| &other.id == leg))
+      .map(|leg| self.render_order(leg))
+      .collect();
+    let stop_price = match order.order_type {
+      OrderType::TrailingStop => order.trailing_stop(),
+      _ => order.stop_price,
+    };
+    Order {
+      id: order.id,
+      client_order_id: order.client_order_id.clone(),
+      created_at: Some(order.created_at),
+      updated_at: Some(order.updated_at),
+      submitted_at: Some(order.created_at),
+      filled_at: order.filled_at,
+      expired_at: order.expired_at,
+      canceled_at: order.canceled_at,
+      failed_at: None,
+      replaced_at: order.replaced_at,
+      replaced_by: order.replaced_by,
+      replaces: order.replaces,
+      asset_id: asset.id,
+      symbol: order.symbol.clone(),
+      asset_class: asset.class,
+      national: order.notional.map(|notional| notional.to_string()),
+      qty: order.qty.map(NumberAsString::from_f64),
+      filled_qty: Some(Money::from_f64(order.filled_qty)),
+      filled_avg_price: order.filled_avg_price.map(Money::from_f64),
+      order_class: order.order_class,
+      _type: order.order_type,
+      side: order.side,
+      time_in_force: order.time_in_force,
+      limit_price: order.limit_price.map(|price| price.to_string()),
+      stop_price: stop_price.map(|price| price.to_string()),
+      status: order.status,
+      extended_hours: order.extended_hours,
+      legs: if legs.is_empty() { None } else { Some(legs) },
+      trail_price: order.trail_price.map(Money::from_f64),
+      trail_percent: order.trail_percent.map(Money::from_f64),
+      hwm: order.hwm.map(Money::from_f64),
+      position_intent: order.position_intent.unwrap_or(match order.side {
+        Side::Buy => PositionIntent::BuyToOpen,
+        Side::Sell => PositionIntent::SellToClose,
+      }),
+    }
+  }
+
+  fn render_position(&self, position: &SimPosition) -> Position {
+    let asset = &self.assets[&position.symbol];
+    let current_price = self
+      .prices
+      .get(&position.symbol)
+      .copied()
+      .unwrap_or(position.avg_entry_price);
+    let multiplier = contract_multiplier(asset.class);
+    let market_value = position.qty * current_price * multiplier;
+    let cost_basis = position.qty * position.avg_entry_price * multiplier;
+    let unrealized_pl = market_value - cost_basis;
+    let unrealized_plpc = if cost_basis == 0.0 {
+      0.0
+    } else {
+      unrealized_pl / cost_basis.abs()
+    };
+    Position {
+      asset_id: asset.id,
+      // the API reports crypto positions without the slash of the pair, BTCUSD for BTC/USD
+      symbol: position.symbol.replace('/', ""),
+      exchange: asset.exchange,
+      asset_class: asset.class,
+      avg_entry_price: Money::from_f64(position.avg_entry_price),
+      qty: NumberAsString::from_f64(position.qty),
+      qty_available: Some(NumberAsString::from_f64(self.available_qty(&position.symbol))),
+      side: if position.qty > 0.0 {
+        PositionSide::Long
+      } else {
+        PositionSide::Short
+      },
+      market_value: Money::from_f64(market_value),
+      cost_basis: Money::from_f64(cost_basis),
+      unrealized_pl: Money::from_f64(unrealized_pl),
+      unrealized_plpc: NumberAsString::from_f64(unrealized_plpc),
+      unrealized_intraday_pl: Money::from_f64(unrealized_pl),
+      unrealized_intraday_plpc: NumberAsString::from_f64(unrealized_plpc),
+      current_price: Money::from_f64(current_price),
+      lastday_price: Money::from_f64(position.avg_entry_price),
+      change_today: NumberAsString::from_f64(0.0),
+      asset_marginable: false,
+    }
+  }
+}
+
+///
+///Asset class of a symbol first seen in market data: crypto pairs contain a slash and option
+/// contracts follow the OCC format, root symbol then `YYMMDD`, `C` or `P` and the strike in
+/// thousandths
+pub(crate) fn asset_class(symbol: &str) -> AssetClass {
+  let bytes = symbol.as_bytes();
+  let option = bytes.len() > 15 && {
+    let (expiration, rest) = bytes[bytes.len() - 15..].split_at(6);
+    expiration.iter().all(u8::is_ascii_digit)
+      && matches!(rest[0], b'C' | b'P')
+      && rest[1..].iter().all(u8::is_ascii_digit)
+  };
+  match (symbol.contains('/'), option) {
+    (true, _) => AssetClass::Crypto,
+    (false, true) => AssetClass::UsOption,
+    (false, false) => AssetClass::UsEquity,
+  }
+}
+
+///
+///Shares per unit of quantity, 100 for option contracts
+fn contract_multiplier(class: AssetClass) -> f64 {
+  match class {
+    AssetClass::UsOption => 100.0,
+    _ => 1.0,
+  }
+}
+
+///
+///One entry of the `DELETE /v2/positions` answer, a failed symbol carries its error and status
+pub(crate) fn close_position_info(symbol: String, result: Result<Order, SimError>) -> ClosePositionInfo {
+  match result {
+    Ok(order) => ClosePositionInfo {
+      symbol,
+      status: "200".to_string(),
+      body: ClosePositionBody::Order(Box::new(order)),
+    },
+    Err(error) => ClosePositionInfo {
+      symbol,
+      status: error.status().to_string(),
+      body: ClosePositionBody::Error(error.to_error_response()),
+    },
+  }
+}
+
+///
+///The liquidation order of `DELETE /v2/positions/{symbol_or_id}` in the shape that route answers
+/// with
+pub(crate) fn closed_position(order: Order) -> ClosedPosition {
+  ClosedPosition {
+    id: order.id,
+    client_order_id: order.client_order_id,
+    created_at: order.created_at,
+    updated_at: order.updated_at,
+    submitted_at: order.submitted_at,
+    filled_at: order.filled_at,
+    expired_at: order.expired_at,
+    canceled_at: order.canceled_at,
+    failed_at: order.failed_at,
+    replaced_at: order.replaced_at,
+    replaced_by: order.replaced_by,
+    replaces: order.replaces,
+    asset_id: Some(order.asset_id),
+    symbol: Some(order.symbol),
+    asset_class: Some(order.asset_class),
+    notional: String::new(),
+    qty: order.qty,
+    filled_qty: order.filled_qty.map(|qty| NumberAsString::from_f64(qty.value())),
+    filled_avg_price: order.filled_avg_price,
+    order_class: Some(order.order_class),
+    _type: order._type,
+    side: order.side,
+    time_in_force: order.time_in_force,
+    limit_price: Money::from_f64(0.0),
+    stop_price: Money::from_f64(0.0),
+    status: order.status,
+    extended_hours: order.extended_hours,
+    trail_percent: NumberAsString::from_f64(0.0),
+    trail_price: None,
+    hwm: None,
+    position_intent: serde_json::to_value(order.position_intent)
+      .ok()
+      .and_then(|intent| intent.as_str().map(str::to_string))
+      .unwrap_or_default(),
+    legs: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    api::{
+      OrderRequestBody,
+      OrderStatus as OrdersFilter,
+      StopLoss,
+      TakeProfit,
+    },
+    models::{
+      Bar,
+      OrderClass,
+      OrderStatus,
+      TimeInForce,
+      enums::{
+        OrderType,
+        Side,
+      },
+      utils::{
+        Money,
+        NumberAsString,
+      },
+    },
+    sim::{
+      Broker,
+      CryptoFees,
+      FeeSchedule,
+      NextBarOpen,
+      OptionFees,
+      SimError,
+      VolumeParticipation,
+    },
+  };
+  use chrono::Utc;
+
+  fn order(side: Side, qty: f64, limit_price: Option<f64>) -> OrderRequestBody {
+    OrderRequestBody {
+      symbol: "AAPL".to_string(),
+      qty: Some(NumberAsString::from_f64(qty)),
+      notional: None,
+      side,
+      _type: if limit_price.is_some() {
+        OrderType::Limit
+      } else {
+        OrderType::Market
+      },
+      time_in_force: TimeInForce::DAY,
+      limit_price: limit_price.map(Money::from_f64),
+      stop_price: None,
+      trail_price: None,
+      trail_percent: None,
+      extended_hours: false,
+      client_order_id: None,
+      order_class: None,
+      legs: vec![],
+      take_profit: None,
+      stop_loss: None,
+      position_intent: None,
+    }
+  }
+
+  fn bar(open: f64, high: f64, low: f64, close: f64) -> Bar {
+    Bar {
+      timestamp: Utc::now(),
+      open,
+      high,
+      low,
+      close,
+      volume: 1_000.0,
+      trade_count: None,
+      vwap: None,
+    }
+  }
+
+  #[test]
+  fn test_broker_should_fill_market_and_resting_limit_orders() {
+    let mut broker = Broker::new(10_000.0);
+    broker.set_price("AAPL", 100.0);
+
+    let filled = broker.submit_order(&order(Side::Buy, 10.0, None)).unwrap();
+    assert_eq!(filled.status, OrderStatus::Filled);
+    assert_eq!(broker.cash(), 9_000.0);
+
+    let resting = broker.submit_order(&order(Side::Buy, 20.0, Some(95.0))).unwrap();
+    assert_eq!(resting.status, OrderStatus::New);
+    assert_eq!(broker.buying_power(), 7_100.0);
+    assert!(broker.set_price("AAPL", 96.0).is_empty());
+
+    let fills = broker.set_price("AAPL", 94.0);
+    assert_eq!(fills[0].id, resting.id);
+    let position = broker.position("AAPL").unwrap();
+    assert_eq!(position.qty.value(), 30.0);
+    assert_eq!(position.avg_entry_price.value(), 96.0);
+    assert_eq!(broker.equity(), 7_120.0 + 30.0 * 94.0);
+    assert_eq!(broker.orders(&OrdersFilter::Open, &[], false, 50).len(), 0);
+  }
+
+  #[test]
+  fn test_broker_should_reject_orders_it_cannot_cover() {
+    let mut broker = Broker::new(1_000.0);
+    broker.set_price("AAPL", 100.0);
+
+    assert_eq!(
+      broker.submit_order(&order(Side::Buy, 11.0, None)).unwrap_err(),
+      SimError::InsufficientBuyingPower
+    );
+    broker.submit_order(&order(Side::Buy, 5.0, None)).unwrap();
+    broker.submit_order(&order(Side::Sell, 3.0, Some(120.0))).unwrap();
+    assert_eq!(
+      broker.submit_order(&order(Side::Sell, 3.0, None)).unwrap_err(),
+      SimError::InsufficientQty {
+        requested: 3.0,
+        available: 2.0
+      }
+    );
+  }
+
+  #[test]
+  fn test_broker_should_trigger_stops_and_trail_the_high_water_mark() {
+    let mut broker = Broker::new(10_000.0).with_immediate_fills(false);
+    broker.process_bar("AAPL", &bar(100.0, 100.0, 100.0, 100.0), true);
+    let entry = broker.submit_order(&order(Side::Buy, 10.0, None)).unwrap();
+    broker.process_bar("AAPL", &bar(101.0, 102.0, 100.0, 101.0), true);
+    assert_eq!(
+      broker.order(&entry.id).unwrap().filled_avg_price.unwrap().value(),
+      101.0
+    );
+
+    let mut trailing = order(Side::Sell, 5.0, None);
+    trailing._type = OrderType::TrailingStop;
+    trailing.trail_price = Some(Money::from_f64(2.0));
+    let trailing = broker.submit_order(&trailing).unwrap();
+    let mut stop_limit = order(Side::Sell, 5.0, Some(99.0));
+    stop_limit._type = OrderType::StopLimit;
+    stop_limit.stop_price = Some(Money::from_f64(98.0));
+    let stop_limit = broker.submit_order(&stop_limit).unwrap();
+
+    // the high of 110 lifts the trailing stop to 108, the next bar gaps through it
+    assert!(
+      broker
+        .process_bar("AAPL", &bar(101.0, 110.0, 100.0, 109.0), true)
+        .is_empty()
+    );
+    assert_eq!(broker.order(&trailing.id).unwrap().stop_price.unwrap(), "108");
+    let fills = broker.process_bar("AAPL", &bar(107.0, 107.0, 99.0, 99.0), true);
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].filled_avg_price.as_ref().unwrap().value(), 107.0);
+
+    // stopped at 98 but the limit of 99 is only reached on the following bar
+    assert!(
+      broker
+        .process_bar("AAPL", &bar(98.5, 98.5, 98.0, 98.0), true)
+        .is_empty()
+    );
+    assert_eq!(broker.process_bar("AAPL", &bar(97.5, 99.5, 96.0, 99.0), true).len(), 1);
+    assert_eq!(
+      broker.order(&stop_limit.id).unwrap().filled_avg_price.unwrap().value(),
+      99.0
+    );
+    assert!(broker.position("AAPL").is_err());
+  }
+
+  #[test]
+  fn test_broker_should_release_bracket_legs_and_cancel_the_other_side() {
+    let mut broker = Broker::new(10_000.0).with_immediate_fills(false);
+    broker.process_bar("AAPL", &bar(100.0, 100.0, 100.0, 100.0), true);
+    let mut bracket = order(Side::Buy, 10.0, Some(99.0));
+    bracket.order_class = Some(OrderClass::Bracket);
+    bracket.take_profit = Some(TakeProfit {
+      limit_price: Money::from_f64(105.0),
+    });
+    bracket.stop_loss = Some(StopLoss {
+      stop_price: Money::from_f64(95.0),
+      limit_price: None,
+    });
+    let parent = broker.submit_order(&bracket).unwrap();
+    let legs = parent.legs.unwrap();
+    assert_eq!(legs[0].status, OrderStatus::Held);
+    assert_eq!(legs[1]._type, OrderType::Stop);
+
+    // the take profit is in range of the fill bar but legs only work from the next bar
+    broker.process_bar("AAPL", &bar(100.0, 106.0, 98.0, 104.0), true);
+    assert_eq!(broker.order(&parent.id).unwrap().status, OrderStatus::Filled);
+    assert_eq!(broker.order(&legs[0].id).unwrap().status, OrderStatus::New);
+    assert_eq!(broker.position("AAPL").unwrap().qty_available.unwrap().value(), 0.0);
+
+    broker.process_bar("AAPL", &bar(104.0, 106.0, 103.0, 105.5), true);
+    assert_eq!(broker.order(&legs[0].id).unwrap().status, OrderStatus::Filled);
+    assert_eq!(broker.order(&legs[1].id).unwrap().status, OrderStatus::Canceled);
+    assert_eq!(broker.cash(), 10_000.0 - 990.0 + 1_050.0);
+  }
+
+  #[test]
+  fn test_broker_should_keep_regular_orders_out_of_extended_hours() {
+    let mut broker = Broker::new(10_000.0).with_immediate_fills(false);
+    broker.process_bar("AAPL", &bar(100.0, 100.0, 100.0, 100.0), true);
+    let regular = broker.submit_order(&order(Side::Buy, 1.0, Some(100.0))).unwrap();
+    let mut extended = order(Side::Buy, 1.0, Some(100.0));
+    extended.extended_hours = true;
+    let extended = broker.submit_order(&extended).unwrap();
+    let mut market = order(Side::Buy, 1.0, None);
+    market.extended_hours = true;
+    assert!(broker.submit_order(&market).is_err());
+
+    let fills = broker.process_bar("AAPL", &bar(99.0, 99.0, 99.0, 99.0), false);
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].id, extended.id);
+    assert_eq!(broker.expire_day_orders(Utc::now()).len(), 1);
+    assert_eq!(broker.order(&regular.id).unwrap().status, OrderStatus::Expired);
+  }
+
+  #[test]
+  fn test_broker_should_fill_partially_and_charge_fees() {
+    let mut broker = Broker::new(100_000.0)
+      .with_immediate_fills(false)
+      .with_fill_model(VolumeParticipation {
+        model: NextBarOpen,
+        max_participation: 0.1,
+      })
+      .with_fee_model(FeeSchedule {
+        option: OptionFees { per_contract: 0.5 },
+        crypto: CryptoFees {
+          maker_bps: 10.0,
+          taker_bps: 20.0,
+        },
+        ..Default::default()
+      });
+    broker.process_bar("AAPL", &bar(100.0, 100.0, 100.0, 100.0), true);
+
+    let large = broker.submit_order(&order(Side::Buy, 150.0, None)).unwrap();
+    let filled = broker.process_bar("AAPL", &bar(100.0, 102.0, 100.0, 102.0), true);
+    assert_eq!(filled[0].status, OrderStatus::PartiallyFilled);
+    assert_eq!(broker.position_qty("AAPL"), 100.0);
+    broker.process_bar("AAPL", &bar(104.0, 104.0, 104.0, 104.0), true);
+    let large = broker.order(&large.id).unwrap();
+    assert_eq!(large.status, OrderStatus::Filled);
+    assert!((large.filled_avg_price.unwrap().value() - (100.0 * 100.0 + 50.0 * 104.0) / 150.0).abs() < 1e-9);
+    assert_eq!(broker.cash(), 100_000.0 - 10_000.0 - 5_200.0);
+
+    let mut ioc = order(Side::Buy, 300.0, None);
+    ioc.time_in_force = TimeInForce::IOC;
+    let ioc = broker.submit_order(&ioc).unwrap();
+    broker.process_bar("AAPL", &bar(104.0, 104.0, 104.0, 104.0), true);
+    let ioc = broker.order(&ioc.id).unwrap();
+    assert_eq!(
+      (ioc.status, ioc.filled_qty.unwrap().value()),
+      (OrderStatus::Canceled, 100.0)
+    );
+
+    let cash = broker.cash();
+    let mut crypto = order(Side::Buy, 1.0, Some(1_000.0));
+    crypto.symbol = "BTC/USD".to_string();
+    broker.process_bar("BTC/USD", &bar(1_010.0, 1_010.0, 1_010.0, 1_010.0), false);
+    broker.submit_order(&crypto).unwrap();
+    broker.process_bar("BTC/USD", &bar(1_005.0, 1_005.0, 995.0, 1_000.0), false);
+    assert!((broker.cash() - (cash - 1_000.0 - 1.0)).abs() < 1e-9);
+
+    let cash = broker.cash();
+    let mut contract = order(Side::Buy, 2.0, None);
+    contract.symbol = "AAPL250117C00150000".to_string();
+    broker.process_bar(&contract.symbol, &bar(1.5, 1.5, 1.5, 1.5), true);
+    broker.submit_order(&contract).unwrap();
+    broker.process_bar(&contract.symbol, &bar(1.5, 1.5, 1.5, 1.5), true);
+    assert!((broker.cash() - (cash - 300.0 - 1.0)).abs() < 1e-9);
+    assert!((broker.fees() - 2.0).abs() < 1e-9);
+    assert_eq!(broker.position(&contract.symbol).unwrap().market_value.value(), 300.0);
+  }
+}
@@ -0,0 +1,171 @@
+use crate::{
+  models::enums::{
+    AssetClass,
+    Side,
+  },
+  sim::Liquidity,
+};
+use std::fmt::Debug;
+
+///
+///One simulated execution a fee is charged for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Execution<'a> {
+  pub symbol: &'a str,
+  pub class: AssetClass,
+  pub side: Side,
+  ///
+  ///Shares, contracts or coins
+  pub qty: f64,
+  pub price: f64,
+  pub liquidity: Liquidity,
+}
+
+impl Execution<'_> {
+  ///
+  ///Traded value, options count 100 shares per contract
+  pub fn notional(&self) -> f64 {
+    match self.class {
+      AssetClass::UsOption => self.qty * self.price * 100.0,
+      _ => self.qty * self.price,
+    }
+  }
+}
+
+///
+///Commissions and fees deducted from cash on every simulated execution
+pub trait FeeModel: Debug + Send + Sync {
+  fn fee(&self, execution: &Execution) -> f64;
+}
+
+///
+///Commission free equity trading with the regulatory fees charged on sales
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityFees {
+  pub commission_per_share: f64,
+  ///
+  ///SEC fee per dollar sold
+  pub sec_fee_rate: f64,
+  ///
+  ///FINRA trading activity fee per share sold, capped at `taf_max` per execution
+  pub taf_per_share: f64,
+  pub taf_max: f64,
+}
+
+impl Default for EquityFees {
+  fn default() -> Self {
+    EquityFees {
+      commission_per_share: 0.0,
+      sec_fee_rate: 0.0000278,
+      taf_per_share: 0.000166,
+      taf_max: 8.3,
+    }
+  }
+}
+
+impl FeeModel for EquityFees {
+  fn fee(&self, execution: &Execution) -> f64 {
+    let commission = execution.qty * self.commission_per_share;
+    match execution.side {
+      Side::Sell => {
+        commission + execution.notional() * self.sec_fee_rate + (execution.qty * self.taf_per_share).min(self.taf_max)
+      }
+      Side::Buy => commission,
+    }
+  }
+}
+
+///
+///Flat fee per option contract, both sides
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OptionFees {
+  pub per_contract: f64,
+}
+
+impl FeeModel for OptionFees {
+  fn fee(&self, execution: &Execution) -> f64 {
+    execution.qty * self.per_contract
+  }
+}
+
+///
+///Crypto fees in basis points of the traded value, depending on whether the order added or took
+/// liquidity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CryptoFees {
+  pub maker_bps: f64,
+  pub taker_bps: f64,
+}
+
+impl Default for CryptoFees {
+  ///
+  ///Lowest volume tier of the Alpaca crypto fee schedule
+  fn default() -> Self {
+    CryptoFees {
+      maker_bps: 15.0,
+      taker_bps: 25.0,
+    }
+  }
+}
+
+impl FeeModel for CryptoFees {
+  fn fee(&self, execution: &Execution) -> f64 {
+    let bps = match execution.liquidity {
+      Liquidity::Maker => self.maker_bps,
+      Liquidity::Taker => self.taker_bps,
+    };
+    execution.notional() * bps / 10_000.0
+  }
+}
+
+///
+///Charges each asset class with its own fee model
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FeeSchedule {
+  pub equity: EquityFees,
+  pub option: OptionFees,
+  pub crypto: CryptoFees,
+}
+
+impl FeeModel for FeeSchedule {
+  fn fee(&self, execution: &Execution) -> f64 {
+    match execution.class {
+      AssetClass::UsEquity => self.equity.fee(execution),
+      AssetClass::UsOption => self.option.fee(execution),
+      AssetClass::Crypto => self.crypto.fee(execution),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_fee_schedule_should_charge_each_asset_class() {
+    let schedule = FeeSchedule {
+      option: OptionFees { per_contract: 0.65 },
+      ..Default::default()
+    };
+    let fee = |class, side, qty, price, liquidity| {
+      schedule.fee(&Execution {
+        symbol: "SYM",
+        class,
+        side,
+        qty,
+        price,
+        liquidity,
+      })
+    };
+
+    assert_eq!(fee(AssetClass::UsEquity, Side::Buy, 100.0, 50.0, Liquidity::Taker), 0.0);
+    let sale = fee(AssetClass::UsEquity, Side::Sell, 100.0, 50.0, Liquidity::Taker);
+    assert!((sale - (5_000.0 * 0.0000278 + 0.0166)).abs() < 1e-9);
+    let large_sale = fee(AssetClass::UsEquity, Side::Sell, 100_000.0, 1.0, Liquidity::Taker);
+    assert!((large_sale - (100_000.0 * 0.0000278 + 8.3)).abs() < 1e-9);
+
+    assert!((fee(AssetClass::UsOption, Side::Buy, 3.0, 1.2, Liquidity::Taker) - 1.95).abs() < 1e-9);
+    assert_eq!(fee(AssetClass::Crypto, Side::Buy, 2.0, 1_000.0, Liquidity::Maker), 3.0);
+    assert_eq!(fee(AssetClass::Crypto, Side::Buy, 2.0, 1_000.0, Liquidity::Taker), 5.0);
+  }
+}
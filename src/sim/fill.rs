@@ -0,0 +1,428 @@
+use crate::models::{
+  Bar,
+  enums::Side,
+};
+use std::fmt::Debug;
+
+///
+///Market data an order executes against: a bar, and the quote at its open when one is known
+#[derive(Debug, Clone, Copy)]
+pub struct MarketSnapshot<'a> {
+  pub bar: &'a Bar,
+  pub bid: Option<f64>,
+  pub ask: Option<f64>,
+}
+
+impl MarketSnapshot<'_> {
+  fn quote(&self) -> Option<(f64, f64)> {
+    Some((self.bid?, self.ask?))
+  }
+}
+
+///
+///An order, or the rest of one, that is ready to execute
+///
+///Stops have already triggered by the time a [`FillModel`] sees them: stop and trailing stop
+/// orders arrive as market orders and stop limit orders as limit orders, with the price they
+/// triggered at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillRequest {
+  pub side: Side,
+  pub qty: f64,
+  pub limit_price: Option<f64>,
+  ///
+  ///Price the stop triggered at within this bar, execution starts there instead of at the open
+  pub trigger_price: Option<f64>,
+  ///
+  ///Whether the order was already working before this bar, as opposed to just submitted or
+  /// triggered
+  pub resting: bool,
+}
+
+impl FillRequest {
+  fn is_buy(&self) -> bool {
+    self.side == Side::Buy
+  }
+
+  ///
+  ///Whether `price` satisfies the limit price, always true for market orders
+  fn accepts(&self, price: f64) -> bool {
+    match (self.limit_price, self.is_buy()) {
+      (Some(limit), true) => price <= limit + 1e-9,
+      (Some(limit), false) => price >= limit - 1e-9,
+      (None, _) => true,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+  ///
+  ///A resting limit order filled at its limit price
+  Maker,
+  Taker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+  pub price: f64,
+  ///
+  ///At most the requested quantity, less for a partial fill
+  pub qty: f64,
+  pub liquidity: Liquidity,
+}
+
+///
+///Decides when and at what price simulated orders execute
+pub trait FillModel: Debug + Send + Sync {
+  ///
+  ///Price at which a stop at `stop_price` triggers within the bar, `None` when it does not
+  fn trigger(&self, side: Side, stop_price: f64, market: &MarketSnapshot) -> Option<f64> {
+    stop_trigger(side, stop_price, market.bar)
+  }
+
+  ///
+  ///Execution of `order` within the bar, `None` when it does not fill
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill>;
+}
+
+///
+///Buy stops trigger once the price trades at or above the stop, sell stops at or below; a bar
+/// opening beyond the stop triggers at the open
+pub fn stop_trigger(side: Side, stop_price: f64, bar: &Bar) -> Option<f64> {
+  match side {
+    Side::Buy if bar.open >= stop_price => Some(bar.open),
+    Side::Buy if bar.high >= stop_price => Some(stop_price),
+    Side::Sell if bar.open <= stop_price => Some(bar.open),
+    Side::Sell if bar.low <= stop_price => Some(stop_price),
+    _ => None,
+  }
+}
+
+///
+///Limit orders fill at their limit price when the bar trades through it, or at `reference` when
+/// that is already better
+fn limit_fill(order: &FillRequest, bar: &Bar, reference: f64) -> Option<Fill> {
+  let limit = order.limit_price?;
+  if order.accepts(reference) {
+    let liquidity = match order.resting && reference == limit {
+      true => Liquidity::Maker,
+      false => Liquidity::Taker,
+    };
+    return Some(Fill {
+      price: reference,
+      qty: order.qty,
+      liquidity,
+    });
+  }
+  // a stop limit that triggered beyond its limit waits for the next bar
+  if order.trigger_price.is_some() {
+    return None;
+  }
+  let touched = if order.is_buy() {
+    bar.low <= limit
+  } else {
+    bar.high >= limit
+  };
+  touched.then_some(Fill {
+    price: limit,
+    qty: order.qty,
+    liquidity: match order.resting {
+      true => Liquidity::Maker,
+      false => Liquidity::Taker,
+    },
+  })
+}
+
+///
+///Market orders fill at the open of the first bar after submission, limit orders at the open when
+/// it is marketable and at their limit when the bar trades through it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NextBarOpen;
+
+impl FillModel for NextBarOpen {
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill> {
+    let reference = order.trigger_price.unwrap_or(market.bar.open);
+    match order.limit_price {
+      Some(_) => limit_fill(order, market.bar, reference),
+      None => Some(Fill {
+        price: reference,
+        qty: order.qty,
+        liquidity: Liquidity::Taker,
+      }),
+    }
+  }
+}
+
+///
+///Orders fill at the volume weighted average price of the bar, the typical price `(high + low +
+/// close) / 3` when the bar has none
+///
+///Limit orders fill at the average when it is within the limit and at the limit when only part of
+/// the bar traded through it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BarVwap;
+
+impl FillModel for BarVwap {
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill> {
+    let bar = market.bar;
+    let vwap = bar.vwap.unwrap_or((bar.high + bar.low + bar.close) / 3.0);
+    // a stop cannot execute at a better average than where it triggered
+    let reference = match (order.trigger_price, order.is_buy()) {
+      (Some(trigger), true) => vwap.max(trigger),
+      (Some(trigger), false) => vwap.min(trigger),
+      (None, _) => vwap,
+    };
+    match order.limit_price {
+      Some(_) => limit_fill(order, bar, reference),
+      None => Some(Fill {
+        price: reference,
+        qty: order.qty,
+        liquidity: Liquidity::Taker,
+      }),
+    }
+  }
+}
+
+///
+///Orders execute against the quote: buys at the ask and sells at the bid, limit orders once the
+/// quote crosses their limit
+///
+///Resting limit orders fill at their limit price as makers. Stops trigger when the side of the
+/// quote they would execute against reaches the stop. Without a quote the bar decides, as with
+/// [`NextBarOpen`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuoteCross;
+
+impl FillModel for QuoteCross {
+  fn trigger(&self, side: Side, stop_price: f64, market: &MarketSnapshot) -> Option<f64> {
+    match (market.quote(), side) {
+      (Some((_, ask)), Side::Buy) => (ask >= stop_price).then_some(ask),
+      (Some((bid, _)), Side::Sell) => (bid <= stop_price).then_some(bid),
+      (None, _) => stop_trigger(side, stop_price, market.bar),
+    }
+  }
+
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill> {
+    let Some((bid, ask)) = market.quote() else {
+      return NextBarOpen.fill(order, market);
+    };
+    let quote = if order.is_buy() { ask } else { bid };
+    if !order.accepts(quote) {
+      return None;
+    }
+    match (order.limit_price, order.resting) {
+      (Some(limit), true) => Some(Fill {
+        price: limit,
+        qty: order.qty,
+        liquidity: Liquidity::Maker,
+      }),
+      _ => Some(Fill {
+        price: quote,
+        qty: order.qty,
+        liquidity: Liquidity::Taker,
+      }),
+    }
+  }
+}
+
+///
+///Caps every fill of the wrapped model at a share of the bar volume, the rest of the order stays
+/// working
+///
+///Bars without volume, like the flat bars of [`super::Broker::set_price`], are not capped.
+#[derive(Debug, Clone, Copy)]
+pub struct VolumeParticipation<M> {
+  pub model: M,
+  ///
+  ///Largest share of the bar volume one order can take, `0.1` for 10%
+  pub max_participation: f64,
+}
+
+impl<M: FillModel> FillModel for VolumeParticipation<M> {
+  fn trigger(&self, side: Side, stop_price: f64, market: &MarketSnapshot) -> Option<f64> {
+    self.model.trigger(side, stop_price, market)
+  }
+
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill> {
+    let mut fill = self.model.fill(order, market)?;
+    if market.bar.volume > 0.0 {
+      fill.qty = fill.qty.min(market.bar.volume * self.max_participation);
+    }
+    (fill.qty > 0.0).then_some(fill)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlippageAmount {
+  ///
+  ///Price units per share
+  Fixed(f64),
+  ///
+  ///Basis points of the price
+  Bps(f64),
+}
+
+///
+///Moves every taker fill of the wrapped model against the order, without going past its limit
+/// price
+#[derive(Debug, Clone, Copy)]
+pub struct Slippage<M> {
+  pub model: M,
+  pub amount: SlippageAmount,
+}
+
+impl<M: FillModel> FillModel for Slippage<M> {
+  fn trigger(&self, side: Side, stop_price: f64, market: &MarketSnapshot) -> Option<f64> {
+    self.model.trigger(side, stop_price, market)
+  }
+
+  fn fill(&self, order: &FillRequest, market: &MarketSnapshot) -> Option<Fill> {
+    let mut fill = self.model.fill(order, market)?;
+    if fill.liquidity == Liquidity::Taker {
+      let slippage = match self.amount {
+        SlippageAmount::Fixed(amount) => amount,
+        SlippageAmount::Bps(bps) => fill.price * bps / 10_000.0,
+      };
+      fill.price = match (order.is_buy(), order.limit_price) {
+        (true, Some(limit)) => (fill.price + slippage).min(limit.max(fill.price)),
+        (true, None) => fill.price + slippage,
+        (false, Some(limit)) => (fill.price - slippage).max(limit.min(fill.price)),
+        (false, None) => fill.price - slippage,
+      };
+    }
+    Some(fill)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Utc;
+
+  fn bar(open: f64, high: f64, low: f64, close: f64, volume: f64, vwap: Option<f64>) -> Bar {
+    Bar {
+      timestamp: Utc::now(),
+      open,
+      high,
+      low,
+      close,
+      volume,
+      trade_count: None,
+      vwap,
+    }
+  }
+
+  fn request(side: Side, qty: f64, limit_price: Option<f64>) -> FillRequest {
+    FillRequest {
+      side,
+      qty,
+      limit_price,
+      trigger_price: None,
+      resting: true,
+    }
+  }
+
+  #[test]
+  fn test_fill_models_should_price_market_and_limit_orders() {
+    let bar = bar(100.0, 104.0, 98.0, 102.0, 1_000.0, Some(101.0));
+    let market = MarketSnapshot {
+      bar: &bar,
+      bid: None,
+      ask: None,
+    };
+
+    let open = NextBarOpen.fill(&request(Side::Buy, 10.0, None), &market).unwrap();
+    assert_eq!((open.price, open.liquidity), (100.0, Liquidity::Taker));
+    let limit = NextBarOpen
+      .fill(&request(Side::Buy, 10.0, Some(99.0)), &market)
+      .unwrap();
+    assert_eq!((limit.price, limit.liquidity), (99.0, Liquidity::Maker));
+    assert!(
+      NextBarOpen
+        .fill(&request(Side::Sell, 10.0, Some(105.0)), &market)
+        .is_none()
+    );
+
+    assert_eq!(
+      BarVwap.fill(&request(Side::Buy, 10.0, None), &market).unwrap().price,
+      101.0
+    );
+    assert_eq!(
+      BarVwap
+        .fill(&request(Side::Sell, 10.0, Some(103.0)), &market)
+        .unwrap()
+        .price,
+      103.0
+    );
+    let triggered = FillRequest {
+      trigger_price: Some(103.0),
+      ..request(Side::Buy, 10.0, None)
+    };
+    assert_eq!(BarVwap.fill(&triggered, &market).unwrap().price, 103.0);
+  }
+
+  #[test]
+  fn test_quote_cross_should_execute_against_the_spread() {
+    let bar = bar(100.0, 100.0, 100.0, 100.0, 0.0, None);
+    let market = MarketSnapshot {
+      bar: &bar,
+      bid: Some(99.9),
+      ask: Some(100.1),
+    };
+
+    assert_eq!(
+      QuoteCross.fill(&request(Side::Buy, 1.0, None), &market).unwrap().price,
+      100.1
+    );
+    assert_eq!(
+      QuoteCross.fill(&request(Side::Sell, 1.0, None), &market).unwrap().price,
+      99.9
+    );
+    assert!(
+      QuoteCross
+        .fill(&request(Side::Buy, 1.0, Some(100.0)), &market)
+        .is_none()
+    );
+    let resting = QuoteCross.fill(&request(Side::Sell, 1.0, Some(99.5)), &market).unwrap();
+    assert_eq!((resting.price, resting.liquidity), (99.5, Liquidity::Maker));
+    assert_eq!(QuoteCross.trigger(Side::Sell, 100.0, &market), Some(99.9));
+    assert_eq!(QuoteCross.trigger(Side::Buy, 100.5, &market), None);
+  }
+
+  #[test]
+  fn test_wrappers_should_cap_quantity_and_add_slippage() {
+    let bar = bar(100.0, 101.0, 99.0, 100.0, 500.0, None);
+    let market = MarketSnapshot {
+      bar: &bar,
+      bid: None,
+      ask: None,
+    };
+
+    let capped = VolumeParticipation {
+      model: NextBarOpen,
+      max_participation: 0.1,
+    };
+    assert_eq!(capped.fill(&request(Side::Buy, 80.0, None), &market).unwrap().qty, 50.0);
+    assert_eq!(capped.fill(&request(Side::Buy, 20.0, None), &market).unwrap().qty, 20.0);
+
+    let bps = Slippage {
+      model: NextBarOpen,
+      amount: SlippageAmount::Bps(10.0),
+    };
+    assert!((bps.fill(&request(Side::Buy, 1.0, None), &market).unwrap().price - 100.1).abs() < 1e-9);
+    let fixed = Slippage {
+      model: NextBarOpen,
+      amount: SlippageAmount::Fixed(0.5),
+    };
+    assert_eq!(
+      fixed.fill(&request(Side::Sell, 1.0, None), &market).unwrap().price,
+      99.5
+    );
+    let marketable = FillRequest {
+      resting: false,
+      ..request(Side::Buy, 1.0, Some(100.2))
+    };
+    assert_eq!(fixed.fill(&marketable, &market).unwrap().price, 100.2);
+  }
+}
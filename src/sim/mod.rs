@@ -0,0 +1,11 @@
+mod broker;
+mod fees;
+mod fill;
+#[cfg(feature = "sim")]
+mod server;
+
+pub use broker::*;
+pub use fees::*;
+pub use fill::*;
+#[cfg(feature = "sim")]
+pub use server::*;
@@ -0,0 +1,918 @@
+use crate::{
+  api::{
+    AddAssetReqBody,
+    BasicWatchListInfo,
+    ClosePositionInfo,
+    DeleteAllOrdersResponse,
+    OrderRequestBody,
+    OrderStatus as OrdersFilter,
+    ReplaceOrderByIdRequestBody,
+    WatchListReqBody,
+  },
+  history::{
+    MARKET_TIMEZONE,
+    market_time,
+  },
+  models::{
+    Account,
+    Asset,
+    ClosedPosition,
+    CryptoTransfer,
+    CryptoWalletInfo,
+    ErrorResponse,
+    MarketCalendar,
+    MarketClock,
+    OptionContract,
+    Order,
+    OrderStatus,
+    Position,
+    TradeUpdate,
+    TradeUpdateEvent,
+    TradingStreamMessage,
+    WatchList,
+    enums::AssetClass,
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  sim::{
+    Broker,
+    SimError,
+    close_position_info,
+    closed_position,
+  },
+};
+use axum::{
+  Json,
+  Router,
+  extract::{
+    Path,
+    Query,
+    State,
+    WebSocketUpgrade,
+    ws::{
+      Message,
+      WebSocket,
+    },
+  },
+  http::StatusCode,
+  response::{
+    IntoResponse,
+    Response,
+  },
+  routing::{
+    delete,
+    get,
+  },
+};
+use chrono::{
+  DateTime,
+  Datelike,
+  Days,
+  Local,
+  NaiveDate,
+  NaiveTime,
+  Utc,
+  Weekday,
+};
+use serde::Deserialize;
+use serde_json::{
+  Value,
+  json,
+};
+use std::{
+  collections::HashMap,
+  net::SocketAddr,
+  ops::{
+    Deref,
+    DerefMut,
+  },
+  sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+  },
+};
+use tokio::{
+  net::TcpListener,
+  sync::broadcast,
+  task::JoinHandle,
+};
+use uuid::Uuid;
+
+#[derive(Debug)]
+struct SimWatchList {
+  id: Uuid,
+  name: String,
+  created_at: DateTime<Utc>,
+  updated_at: DateTime<Utc>,
+  symbols: Vec<String>,
+}
+
+#[derive(Debug)]
+struct SimState {
+  broker: Broker,
+  market_open: Option<bool>,
+  watch_lists: Vec<SimWatchList>,
+  option_contracts: Vec<OptionContract>,
+  wallets: Vec<CryptoWalletInfo>,
+  ///
+  ///Status, filled quantity and average fill price of every order as last published
+  published: HashMap<Uuid, (OrderStatus, f64, f64)>,
+}
+
+///
+///Serves the trading routes of the `/v2` API from a [`Broker`], prices are pushed with `POST
+/// /sim/prices`
+///
+///Order changes are published as trade updates on the `/stream` WebSocket, which accepts any key.
+#[derive(Debug, Clone)]
+pub struct SimServer {
+  state: Arc<Mutex<SimState>>,
+  trade_updates: broadcast::Sender<String>,
+}
+
+///
+///State locked for a change, publishing the trade updates it caused when released
+struct StateGuard<'a> {
+  server: &'a SimServer,
+  state: MutexGuard<'a, SimState>,
+}
+
+impl Deref for StateGuard<'_> {
+  type Target = SimState;
+
+  fn deref(&self) -> &SimState {
+    &self.state
+  }
+}
+
+impl DerefMut for StateGuard<'_> {
+  fn deref_mut(&mut self) -> &mut SimState {
+    &mut self.state
+  }
+}
+
+impl Drop for StateGuard<'_> {
+  fn drop(&mut self) {
+    for update in self.state.take_trade_updates() {
+      let message = TradingStreamMessage::TradeUpdates(Box::new(update));
+      if let Ok(text) = serde_json::to_string(&message) {
+        let _ = self.server.trade_updates.send(text);
+      }
+    }
+  }
+}
+
+///
+///A [`SimServer`] running in the background, stopped when dropped
+#[derive(Debug)]
+pub struct SimHandle {
+  local_addr: SocketAddr,
+  task: JoinHandle<()>,
+}
+
+impl SimHandle {
+  pub fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+
+  ///
+  ///Base url to build a [`crate::prelude::Client`] against
+  pub fn base_url(&self) -> String {
+    format!("http://{}", self.local_addr)
+  }
+}
+
+impl Drop for SimHandle {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+impl SimServer {
+  pub fn new(broker: Broker) -> Self {
+    SimServer {
+      state: Arc::new(Mutex::new(SimState {
+        broker,
+        market_open: None,
+        watch_lists: vec![],
+        option_contracts: vec![],
+        wallets: vec![],
+        published: HashMap::new(),
+      })),
+      trade_updates: broadcast::channel(1_024).0,
+    }
+  }
+
+  ///
+  ///Force `GET /v2/clock` open or closed instead of following the weekday 9:30-16:00 calendar
+  pub fn with_market_open(self, market_open: bool) -> Self {
+    self.lock().market_open = Some(market_open);
+    self
+  }
+
+  ///
+  ///List `contract` under `/v2/options/contracts` and make it tradable
+  pub fn with_option_contract(self, contract: OptionContract) -> Self {
+    let mut state = self.lock();
+    state.broker.add_asset(&contract.symbol, AssetClass::UsOption);
+    state.option_contracts.push(contract);
+    drop(state);
+    self
+  }
+
+  pub fn with_wallet(self, wallet: CryptoWalletInfo) -> Self {
+    self.lock().wallets.push(wallet);
+    self
+  }
+
+  ///
+  ///Feed a new price, returns the orders it filled
+  pub fn set_price(&self, symbol: &str, price: f64) -> Vec<Order> {
+    self.lock_now().broker.set_price(symbol, price)
+  }
+
+  ///
+  ///Feed a new bid and ask, returns the orders it filled
+  pub fn set_quote(&self, symbol: &str, bid: f64, ask: f64) -> Vec<Order> {
+    self.lock_now().broker.set_quote(symbol, bid, ask)
+  }
+
+  ///
+  ///Run `f` with exclusive access to the simulated broker
+  pub fn with_broker<R>(&self, f: impl FnOnce(&mut Broker) -> R) -> R {
+    f(&mut self.lock_changes().broker)
+  }
+
+  pub fn router(&self) -> Router {
+    Router::new()
+      .route("/v2/account", get(get_account))
+      .route("/v2/clock", get(get_clock))
+      .route("/v2/calendar", get(get_calendar))
+      .route("/v2/assets", get(get_assets))
+      .route("/v2/assets/{symbol_or_id}", get(get_asset))
+      .route(
+        "/v2/orders",
+        get(get_orders).post(create_order).delete(delete_all_orders),
+      )
+      .route("/v2/orders:by_client_order_id", get(get_order_by_client_order_id))
+      .route(
+        "/v2/orders/{id}",
+        get(get_order).patch(replace_order).delete(delete_order),
+      )
+      .route("/v2/positions", get(get_positions).delete(close_all_positions))
+      .route("/v2/positions/{symbol_or_id}", get(get_position).delete(close_position))
+      .route("/v2/watchlists", get(get_watch_lists).post(create_watch_list))
+      .route(
+        "/v2/watchlists:by_name",
+        get(get_watch_list_by_name)
+          .put(update_watch_list_by_name)
+          .post(add_asset_by_name)
+          .delete(delete_watch_list_by_name),
+      )
+      .route(
+        "/v2/watchlists/{id}",
+        get(get_watch_list)
+          .put(update_watch_list)
+          .post(add_asset)
+          .delete(delete_watch_list),
+      )
+      .route("/v2/watchlists/{id}/{symbol}", delete(remove_asset))
+      .route("/v2/options/contracts", get(get_option_contracts))
+      .route("/v2/options/contracts/{symbol_or_id}", get(get_option_contract))
+      .route("/v2/wallets", get(get_wallets))
+      .route("/v2/wallets/transfers", get(get_wallet_transfers))
+      .route("/stream", get(trading_stream))
+      .route("/sim/prices", get(get_prices).post(set_prices))
+      .fallback(not_found)
+      .with_state(self.clone())
+  }
+
+  pub async fn serve(self, listener: TcpListener) -> anyhow::Result<()> {
+    axum::serve(listener, self.router()).await?;
+    Ok(())
+  }
+
+  ///
+  ///Bind `addr`, port 0 picks a free one, and serve in a background task
+  pub async fn spawn(self, addr: &str) -> anyhow::Result<SimHandle> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    let task = tokio::spawn(async move {
+      let _ = self.serve(listener).await;
+    });
+    Ok(SimHandle { local_addr, task })
+  }
+
+  fn lock(&self) -> MutexGuard<'_, SimState> {
+    self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  fn lock_changes(&self) -> StateGuard<'_> {
+    StateGuard {
+      server: self,
+      state: self.lock(),
+    }
+  }
+
+  ///
+  ///Lock the state for a change with the broker clock moved to now
+  fn lock_now(&self) -> StateGuard<'_> {
+    let mut state = self.lock_changes();
+    state.broker.set_time(Utc::now());
+    state
+  }
+}
+
+struct ApiError(StatusCode, ErrorResponse);
+
+impl From<SimError> for ApiError {
+  fn from(error: SimError) -> Self {
+    ApiError(
+      StatusCode::from_u16(error.status()).unwrap_or(StatusCode::UNPROCESSABLE_ENTITY),
+      error.to_error_response(),
+    )
+  }
+}
+
+impl IntoResponse for ApiError {
+  fn into_response(self) -> Response {
+    (self.0, Json(self.1)).into_response()
+  }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+async fn not_found() -> ApiError {
+  SimError::NotFound("route".to_string()).into()
+}
+
+async fn get_account(State(server): State<SimServer>) -> Json<Account> {
+  Json(server.lock_now().broker.account())
+}
+
+///
+///Weekday sessions from 9:30 to 16:00 New York time, settling the next session
+fn weekday_sessions(start: NaiveDate, end: NaiveDate) -> Vec<MarketCalendar> {
+  let is_session = |date: &NaiveDate| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+  start
+    .iter_days()
+    .take_while(|date| *date <= end)
+    .filter(is_session)
+    .map(|date| MarketCalendar {
+      date,
+      open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+      settlement_date: date.iter_days().skip(1).find(is_session).unwrap(),
+    })
+    .collect()
+}
+
+async fn get_clock(State(server): State<SimServer>) -> Json<MarketClock> {
+  let state = server.lock_now();
+  let now = state.broker.now().with_timezone(&Local);
+  let today = now.with_timezone(&MARKET_TIMEZONE).date_naive();
+  let sessions = weekday_sessions(today, today + Days::new(7));
+  let next_open = sessions
+    .iter()
+    .filter_map(|session| market_time(session.date, session.open))
+    .map(|open| open.with_timezone(&Local))
+    .find(|open| *open > now)
+    .unwrap();
+  let next_close = sessions
+    .iter()
+    .filter_map(|session| market_time(session.date, session.close))
+    .map(|close| close.with_timezone(&Local))
+    .find(|close| *close > now)
+    .unwrap();
+  Json(MarketClock {
+    timestamp: now,
+    is_open: state.market_open.unwrap_or(next_close < next_open),
+    next_open,
+    next_close,
+  })
+}
+
+#[derive(Deserialize)]
+struct CalendarQuery {
+  start: Option<NaiveDate>,
+  end: Option<NaiveDate>,
+}
+
+async fn get_calendar(Query(query): Query<CalendarQuery>) -> Json<Vec<MarketCalendar>> {
+  let start = query.start.unwrap_or_else(|| Utc::now().date_naive());
+  let end = query.end.unwrap_or(start + Days::new(30));
+  Json(weekday_sessions(start, end))
+}
+
+async fn get_assets(State(server): State<SimServer>) -> Json<Vec<Asset>> {
+  Json(server.lock().broker.assets())
+}
+
+async fn get_asset(State(server): State<SimServer>, Path(symbol_or_id): Path<String>) -> ApiResult<Asset> {
+  Ok(Json(server.lock().broker.asset(&symbol_or_id)?))
+}
+
+async fn create_order(State(server): State<SimServer>, Json(request): Json<OrderRequestBody>) -> ApiResult<Order> {
+  Ok(Json(server.lock_now().broker.submit_order(&request)?))
+}
+
+#[derive(Deserialize)]
+struct OrdersQuery {
+  status: Option<String>,
+  limit: Option<usize>,
+  symbols: Option<String>,
+  nested: Option<bool>,
+}
+
+async fn get_orders(State(server): State<SimServer>, Query(query): Query<OrdersQuery>) -> ApiResult<Vec<Order>> {
+  let status = match query.status.as_deref() {
+    None | Some("open") => OrdersFilter::Open,
+    Some("closed") => OrdersFilter::Closed,
+    Some("all") => OrdersFilter::All,
+    Some(status) => return Err(SimError::Unprocessable(format!("invalid status {}", status)).into()),
+  };
+  let symbols: Vec<String> = match query.symbols {
+    Some(symbols) => symbols.split(',').map(str::to_string).collect(),
+    None => vec![],
+  };
+  let limit = query.limit.unwrap_or(50);
+  Ok(Json(server.lock().broker.orders(
+    &status,
+    &symbols,
+    query.nested.unwrap_or(false),
+    limit,
+  )))
+}
+
+async fn delete_all_orders(State(server): State<SimServer>) -> (StatusCode, Json<Vec<DeleteAllOrdersResponse>>) {
+  let canceled = server.lock_now().broker.cancel_all_orders();
+  (
+    StatusCode::MULTI_STATUS,
+    Json(
+      canceled
+        .into_iter()
+        .map(|id| DeleteAllOrdersResponse { id, status: 200 })
+        .collect(),
+    ),
+  )
+}
+
+#[derive(Deserialize)]
+struct ClientOrderIdQuery {
+  client_order_id: String,
+}
+
+async fn get_order_by_client_order_id(
+  State(server): State<SimServer>,
+  Query(query): Query<ClientOrderIdQuery>,
+) -> ApiResult<Order> {
+  Ok(Json(
+    server.lock().broker.order_by_client_order_id(&query.client_order_id)?,
+  ))
+}
+
+async fn get_order(State(server): State<SimServer>, Path(id): Path<Uuid>) -> ApiResult<Order> {
+  Ok(Json(server.lock().broker.order(&id)?))
+}
+
+async fn replace_order(
+  State(server): State<SimServer>,
+  Path(id): Path<Uuid>,
+  Json(request): Json<ReplaceOrderByIdRequestBody>,
+) -> ApiResult<Order> {
+  Ok(Json(server.lock_now().broker.replace_order(&id, &request)?))
+}
+
+async fn delete_order(State(server): State<SimServer>, Path(id): Path<Uuid>) -> Result<StatusCode, ApiError> {
+  server.lock_now().broker.cancel_order(&id)?;
+  Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_positions(State(server): State<SimServer>) -> Json<Vec<Position>> {
+  Json(server.lock().broker.positions())
+}
+
+#[derive(Deserialize)]
+struct CloseAllQuery {
+  cancel_orders: Option<bool>,
+}
+
+async fn close_all_positions(
+  State(server): State<SimServer>,
+  Query(query): Query<CloseAllQuery>,
+) -> (StatusCode, Json<Vec<ClosePositionInfo>>) {
+  let closed = server
+    .lock_now()
+    .broker
+    .close_all_positions(query.cancel_orders.unwrap_or_default());
+  let infos = closed
+    .into_iter()
+    .map(|(symbol, result)| close_position_info(symbol, result))
+    .collect();
+  (StatusCode::MULTI_STATUS, Json(infos))
+}
+
+async fn get_position(State(server): State<SimServer>, Path(symbol_or_id): Path<String>) -> ApiResult<Position> {
+  Ok(Json(server.lock().broker.position(&symbol_or_id)?))
+}
+
+#[derive(Deserialize)]
+struct ClosePositionQuery {
+  qty: Option<f64>,
+  percentage: Option<f64>,
+}
+
+async fn close_position(
+  State(server): State<SimServer>,
+  Path(symbol_or_id): Path<String>,
+  Query(query): Query<ClosePositionQuery>,
+) -> ApiResult<ClosedPosition> {
+  let order = server
+    .lock_now()
+    .broker
+    .close_position(&symbol_or_id, query.qty, query.percentage)?;
+  Ok(Json(closed_position(order)))
+}
+
+impl SimState {
+  ///
+  ///Trade updates for the orders whose status or fills moved since the last call, oldest first
+  fn take_trade_updates(&mut self) -> Vec<TradeUpdate> {
+    let mut updates = vec![];
+    let orders = self.broker.orders(&OrdersFilter::All, &[], false, usize::MAX);
+    for order in orders.into_iter().rev() {
+      let filled_qty = order.filled_qty.as_ref().map(Money::value).unwrap_or_default();
+      let avg_price = order.filled_avg_price.as_ref().map(Money::value).unwrap_or_default();
+      let current = (order.status, filled_qty, avg_price);
+      let previous = self.published.insert(order.id, current);
+      if previous == Some(current) {
+        continue;
+      }
+      let event = match order.status {
+        OrderStatus::Held => continue,
+        OrderStatus::New | OrderStatus::Accepted | OrderStatus::AcceptedForBidding | OrderStatus::PendingNew => {
+          TradeUpdateEvent::New
+        }
+        OrderStatus::PartiallyFilled => TradeUpdateEvent::PartialFill,
+        OrderStatus::Filled => TradeUpdateEvent::Fill,
+        OrderStatus::DoneForDay => TradeUpdateEvent::DoneForDay,
+        OrderStatus::Canceled => TradeUpdateEvent::Canceled,
+        OrderStatus::Expired => TradeUpdateEvent::Expired,
+        OrderStatus::Replaced => TradeUpdateEvent::Replaced,
+        OrderStatus::PendingCancel => TradeUpdateEvent::PendingCancel,
+        OrderStatus::PendingReplace => TradeUpdateEvent::PendingReplace,
+        OrderStatus::Stopped => TradeUpdateEvent::Stopped,
+        OrderStatus::Rejected => TradeUpdateEvent::Rejected,
+        OrderStatus::Suspended => TradeUpdateEvent::Suspended,
+        OrderStatus::Calculated => TradeUpdateEvent::Calculated,
+      };
+      let (previous_qty, previous_avg_price) = previous.map(|(_, qty, price)| (qty, price)).unwrap_or_default();
+      let fill_qty = filled_qty - previous_qty;
+      let mut update = TradeUpdate {
+        event,
+        timestamp: Some(self.broker.now()),
+        execution_id: None,
+        price: None,
+        qty: None,
+        position_qty: None,
+        order,
+      };
+      if update.is_fill() && fill_qty > 0.0 {
+        update.execution_id = Some(Uuid::new_v4().to_string());
+        let price = (avg_price * filled_qty - previous_avg_price * previous_qty) / fill_qty;
+        update.price = Some(Money::from_f64(price));
+        update.qty = Some(NumberAsString::from_f64(fill_qty));
+        update.position_qty = Some(NumberAsString::from_f64(self.broker.position_qty(&update.order.symbol)));
+      }
+      updates.push(update);
+    }
+    updates
+  }
+
+  fn render_watch_list(&self, watch_list: &SimWatchList) -> WatchList {
+    WatchList {
+      id: watch_list.id,
+      account_id: self.broker.account_id(),
+      created_at: watch_list.created_at,
+      updated_at: watch_list.updated_at,
+      name: watch_list.name.clone(),
+      assets: watch_list
+        .symbols
+        .iter()
+        .filter_map(|symbol| self.broker.asset(symbol).ok())
+        .collect(),
+    }
+  }
+
+  fn watch_list_index(&self, id: Option<&Uuid>, name: Option<&str>) -> Result<usize, SimError> {
+    self
+      .watch_lists
+      .iter()
+      .position(|watch_list| Some(&watch_list.id) == id || Some(watch_list.name.as_str()) == name)
+      .ok_or_else(|| SimError::NotFound("watchlist".to_string()))
+  }
+
+  fn check_symbols(&self, symbols: &[String]) -> Result<(), SimError> {
+    for symbol in symbols {
+      if self.broker.asset(symbol).is_err() {
+        return Err(SimError::Unprocessable(format!("asset {} not found", symbol)));
+      }
+    }
+    Ok(())
+  }
+
+  fn update_watch_list(&mut self, index: usize, request: WatchListReqBody) -> Result<WatchList, SimError> {
+    self.check_symbols(&request.symbols)?;
+    let now = self.broker.now();
+    let watch_list = &mut self.watch_lists[index];
+    watch_list.name = request.name;
+    watch_list.symbols = request.symbols;
+    watch_list.updated_at = now;
+    Ok(self.render_watch_list(&self.watch_lists[index]))
+  }
+
+  fn add_asset(&mut self, index: usize, symbol: String) -> Result<WatchList, SimError> {
+    self.check_symbols(std::slice::from_ref(&symbol))?;
+    let now = self.broker.now();
+    let watch_list = &mut self.watch_lists[index];
+    if !watch_list.symbols.contains(&symbol) {
+      watch_list.symbols.push(symbol);
+    }
+    watch_list.updated_at = now;
+    Ok(self.render_watch_list(&self.watch_lists[index]))
+  }
+}
+
+async fn get_watch_lists(State(server): State<SimServer>) -> Json<Vec<BasicWatchListInfo>> {
+  let state = server.lock();
+  Json(
+    state
+      .watch_lists
+      .iter()
+      .map(|watch_list| BasicWatchListInfo {
+        id: watch_list.id,
+        account_id: state.broker.account_id(),
+        created_at: watch_list.created_at,
+        updated_at: watch_list.updated_at,
+        name: watch_list.name.clone(),
+      })
+      .collect(),
+  )
+}
+
+async fn create_watch_list(
+  State(server): State<SimServer>,
+  Json(request): Json<WatchListReqBody>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  if state.watch_list_index(None, Some(&request.name)).is_ok() {
+    return Err(SimError::Unprocessable("watchlist name must be unique".to_string()).into());
+  }
+  state.check_symbols(&request.symbols)?;
+  let now = state.broker.now();
+  state.watch_lists.push(SimWatchList {
+    id: Uuid::new_v4(),
+    name: request.name,
+    created_at: now,
+    updated_at: now,
+    symbols: request.symbols,
+  });
+  Ok(Json(state.render_watch_list(state.watch_lists.last().unwrap())))
+}
+
+async fn get_watch_list(State(server): State<SimServer>, Path(id): Path<Uuid>) -> ApiResult<WatchList> {
+  let state = server.lock();
+  let index = state.watch_list_index(Some(&id), None)?;
+  Ok(Json(state.render_watch_list(&state.watch_lists[index])))
+}
+
+async fn update_watch_list(
+  State(server): State<SimServer>,
+  Path(id): Path<Uuid>,
+  Json(request): Json<WatchListReqBody>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  let index = state.watch_list_index(Some(&id), None)?;
+  Ok(Json(state.update_watch_list(index, request)?))
+}
+
+async fn add_asset(
+  State(server): State<SimServer>,
+  Path(id): Path<Uuid>,
+  Json(request): Json<AddAssetReqBody>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  let index = state.watch_list_index(Some(&id), None)?;
+  Ok(Json(state.add_asset(index, request.symbol)?))
+}
+
+async fn delete_watch_list(State(server): State<SimServer>, Path(id): Path<Uuid>) -> Result<StatusCode, ApiError> {
+  let mut state = server.lock();
+  let index = state.watch_list_index(Some(&id), None)?;
+  state.watch_lists.remove(index);
+  Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove_asset(
+  State(server): State<SimServer>,
+  Path((id, symbol)): Path<(Uuid, String)>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  let index = state.watch_list_index(Some(&id), None)?;
+  let now = state.broker.now();
+  let watch_list = &mut state.watch_lists[index];
+  watch_list.symbols.retain(|held| held != &symbol);
+  watch_list.updated_at = now;
+  Ok(Json(state.render_watch_list(&state.watch_lists[index])))
+}
+
+#[derive(Deserialize)]
+struct WatchListNameQuery {
+  name: String,
+}
+
+async fn get_watch_list_by_name(
+  State(server): State<SimServer>,
+  Query(query): Query<WatchListNameQuery>,
+) -> ApiResult<WatchList> {
+  let state = server.lock();
+  let index = state.watch_list_index(None, Some(&query.name))?;
+  Ok(Json(state.render_watch_list(&state.watch_lists[index])))
+}
+
+async fn update_watch_list_by_name(
+  State(server): State<SimServer>,
+  Query(query): Query<WatchListNameQuery>,
+  Json(request): Json<WatchListReqBody>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  let index = state.watch_list_index(None, Some(&query.name))?;
+  Ok(Json(state.update_watch_list(index, request)?))
+}
+
+async fn add_asset_by_name(
+  State(server): State<SimServer>,
+  Query(query): Query<WatchListNameQuery>,
+  Json(request): Json<AddAssetReqBody>,
+) -> ApiResult<WatchList> {
+  let mut state = server.lock_now();
+  let index = state.watch_list_index(None, Some(&query.name))?;
+  Ok(Json(state.add_asset(index, request.symbol)?))
+}
+
+async fn delete_watch_list_by_name(
+  State(server): State<SimServer>,
+  Query(query): Query<WatchListNameQuery>,
+) -> Result<StatusCode, ApiError> {
+  let mut state = server.lock();
+  let index = state.watch_list_index(None, Some(&query.name))?;
+  state.watch_lists.remove(index);
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct OptionContractsQuery {
+  underlying_symbols: Option<String>,
+}
+
+async fn get_option_contracts(
+  State(server): State<SimServer>,
+  Query(query): Query<OptionContractsQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+  let state = server.lock();
+  let underlying: Option<Vec<&str>> = query
+    .underlying_symbols
+    .as_deref()
+    .map(|symbols| symbols.split(',').collect());
+  let contracts: Vec<&OptionContract> = state
+    .option_contracts
+    .iter()
+    .filter(|contract| match &underlying {
+      Some(symbols) => symbols.contains(&contract.underlying_symbol.as_str()),
+      None => true,
+    })
+    .collect();
+  let response = serde_json::json!({
+    "option_contracts": contracts,
+    "next_page_token": Option::<String>::None,
+  });
+  Ok(Json(response))
+}
+
+async fn get_option_contract(
+  State(server): State<SimServer>,
+  Path(symbol_or_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+  let state = server.lock();
+  match state
+    .option_contracts
+    .iter()
+    .find(|contract| contract.symbol == symbol_or_id || contract.id.to_string() == symbol_or_id)
+  {
+    Some(contract) => Ok(Json(serde_json::to_value(contract).unwrap_or_default())),
+    None => Err(SimError::NotFound(format!("option contract {}", symbol_or_id)).into()),
+  }
+}
+
+async fn get_wallets(State(server): State<SimServer>) -> Json<Vec<CryptoWalletInfo>> {
+  let state = server.lock();
+  Json(
+    state
+      .wallets
+      .iter()
+      .map(|wallet| CryptoWalletInfo {
+        chain: wallet.chain.clone(),
+        address: wallet.address.clone(),
+        created_at: wallet.created_at,
+      })
+      .collect(),
+  )
+}
+
+async fn get_wallet_transfers() -> Json<Vec<CryptoTransfer>> {
+  Json(vec![])
+}
+
+async fn get_prices(State(server): State<SimServer>) -> Json<HashMap<String, f64>> {
+  let state = server.lock();
+  Json(
+    state
+      .broker
+      .assets()
+      .into_iter()
+      .filter_map(|asset| state.broker.price(&asset.symbol).map(|price| (asset.symbol, price)))
+      .collect(),
+  )
+}
+
+async fn set_prices(State(server): State<SimServer>, Json(prices): Json<HashMap<String, f64>>) -> Json<Vec<Order>> {
+  let mut state = server.lock_now();
+  Json(
+    prices
+      .iter()
+      .flat_map(|(symbol, price)| state.broker.set_price(symbol, *price))
+      .collect(),
+  )
+}
+
+async fn trading_stream(State(server): State<SimServer>, upgrade: WebSocketUpgrade) -> Response {
+  upgrade.on_upgrade(move |socket| serve_trading_stream(server, socket))
+}
+
+///
+///Answers `auth` and `listen` actions like the trading stream, then forwards trade updates
+async fn serve_trading_stream(server: SimServer, mut socket: WebSocket) {
+  let mut trade_updates = server.trade_updates.subscribe();
+  let mut authorized = false;
+  let mut listening = false;
+  loop {
+    tokio::select! {
+      frame = socket.recv() => {
+        let action: Value = match frame {
+          Some(Ok(Message::Text(text))) => serde_json::from_str(&text).unwrap_or_default(),
+          Some(Ok(Message::Binary(bytes))) => serde_json::from_slice(&bytes).unwrap_or_default(),
+          Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+          Some(Ok(_)) => continue,
+        };
+        let reply = match action["action"].as_str() {
+          Some("auth") | Some("authenticate") => {
+            authorized = true;
+            json!({ "stream": "authorization", "data": { "status": "authorized", "action": "authenticate" } })
+          }
+          Some("listen") if authorized => {
+            let streams: Vec<&str> = action["data"]["streams"]
+              .as_array()
+              .into_iter()
+              .flatten()
+              .filter_map(Value::as_str)
+              .filter(|stream| *stream == "trade_updates")
+              .collect();
+            if !listening && !streams.is_empty() {
+              // skip whatever happened before listening
+              trade_updates = trade_updates.resubscribe();
+            }
+            listening = !streams.is_empty();
+            json!({ "stream": "listening", "data": { "streams": streams } })
+          }
+          Some(action) => json!({ "stream": "authorization", "data": { "status": "unauthorized", "action": action } }),
+          None => continue,
+        };
+        if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+          return;
+        }
+      }
+      update = trade_updates.recv() => match update {
+        Ok(text) if listening => {
+          if socket.send(Message::Text(text.into())).await.is_err() {
+            return;
+          }
+        }
+        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+        Err(broadcast::error::RecvError::Closed) => return,
+      },
+    }
+  }
+}
@@ -0,0 +1,388 @@
+use crate::models::{
+  CryptoLocation,
+  ErrorResponse,
+  MarketDataMessage,
+  OptionFeed,
+  Subscription,
+};
+use anyhow::bail;
+use futures_util::{
+  SinkExt,
+  StreamExt,
+};
+use serde::{
+  Deserialize,
+  Serialize,
+  de::IgnoredAny,
+};
+use std::{
+  sync::{
+    Arc,
+    atomic::{
+      AtomicU64,
+      Ordering,
+    },
+  },
+  time::Duration,
+};
+use tokio::{
+  net::TcpStream,
+  sync::mpsc,
+  task::JoinHandle,
+};
+use tokio_tungstenite::{
+  MaybeTlsStream,
+  WebSocketStream,
+  connect_async,
+  tungstenite::{
+    Message,
+    client::IntoClientRequest,
+    http::HeaderValue,
+  },
+};
+
+pub const MARKET_DATA_STREAM_URL: &str = "wss://stream.data.alpaca.markets";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketDataFeed {
+  Iex,
+  Sip,
+  Test,
+  Crypto(CryptoLocation),
+  Options(OptionFeed),
+  News,
+}
+
+impl MarketDataFeed {
+  pub fn path(&self) -> String {
+    match self {
+      MarketDataFeed::Iex => "v2/iex".to_string(),
+      MarketDataFeed::Sip => "v2/sip".to_string(),
+      MarketDataFeed::Test => "v2/test".to_string(),
+      MarketDataFeed::Crypto(location) => format!("v1beta3/crypto/{location}"),
+      MarketDataFeed::Options(OptionFeed::Indicative) => "v1beta1/indicative".to_string(),
+      MarketDataFeed::Options(OptionFeed::Opra) => "v1beta1/opra".to_string(),
+      MarketDataFeed::News => "v1beta1/news".to_string(),
+    }
+  }
+
+  ///
+  ///The options stream only speaks msgpack, every other feed uses JSON
+  pub fn encoding(&self) -> StreamEncoding {
+    match self {
+      MarketDataFeed::Options(_) => StreamEncoding::MsgPack,
+      _ => StreamEncoding::Json,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEncoding {
+  Json,
+  MsgPack,
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+  pub url: String,
+  pub api_key_id: String,
+  pub api_secret_key: String,
+  pub encoding: StreamEncoding,
+  pub buffer_size: usize,
+  pub reconnect_delay: Duration,
+  pub max_reconnect_delay: Duration,
+  pub auth_timeout: Duration,
+}
+
+impl StreamConfig {
+  ///
+  ///Build a config for `feed` under `base_url`, e.g. [`MARKET_DATA_STREAM_URL`] or a local server
+  ///
+  /// ```
+  /// use alpaca_trade_api_rust::stream::{
+  ///   MARKET_DATA_STREAM_URL,
+  ///   MarketDataFeed,
+  ///   StreamConfig,
+  /// };
+  ///
+  /// let config = StreamConfig::new(
+  ///   MARKET_DATA_STREAM_URL.to_string(),
+  ///   MarketDataFeed::Iex,
+  ///   "testApiKey".to_string(),
+  ///   "testApiSecretKey".to_string(),
+  /// );
+  /// assert_eq!(config.url, "wss://stream.data.alpaca.markets/v2/iex");
+  /// ```
+  pub fn new(base_url: String, feed: MarketDataFeed, api_key_id: String, api_secret_key: String) -> Self {
+    StreamConfig {
+      url: format!("{}/{}", base_url.trim_end_matches('/'), feed.path()),
+      api_key_id,
+      api_secret_key,
+      encoding: feed.encoding(),
+      buffer_size: 10_000,
+      reconnect_delay: Duration::from_millis(500),
+      max_reconnect_delay: Duration::from_secs(30),
+      auth_timeout: Duration::from_secs(10),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum StreamAction<'a> {
+  Auth { key: &'a str, secret: &'a str },
+  Subscribe(&'a Subscription),
+  Unsubscribe(&'a Subscription),
+}
+
+#[derive(Debug)]
+enum StreamCommand {
+  Subscribe(Subscription),
+  Unsubscribe(Subscription),
+}
+
+///
+///Real-time market data over Alpaca's WebSocket stream.
+///
+///A background task reconnects with exponential backoff and re-sends the current subscription.
+///
+///Drop policy: decoded messages wait in a queue of `StreamConfig::buffer_size` entries. When the
+///consumer falls behind and the queue is full, newly received messages are dropped instead of
+///blocking the socket, and each drop is counted in [`MarketDataStream::dropped_messages`]. Each
+///message of a frame is decoded on its own, one that cannot be decoded is skipped and counted in
+///[`MarketDataStream::malformed_messages`] while the rest of the frame is delivered.
+pub struct MarketDataStream {
+  commands: mpsc::UnboundedSender<StreamCommand>,
+  messages: mpsc::Receiver<MarketDataMessage>,
+  dropped: Arc<AtomicU64>,
+  malformed: Arc<AtomicU64>,
+  worker: JoinHandle<()>,
+}
+
+impl MarketDataStream {
+  pub async fn connect(config: StreamConfig) -> anyhow::Result<Self> {
+    let socket = connect_and_authenticate(&config).await?;
+    let (commands, command_receiver) = mpsc::unbounded_channel();
+    let (message_sender, messages) = mpsc::channel(config.buffer_size.max(1));
+    let dropped = Arc::new(AtomicU64::new(0));
+    let malformed = Arc::new(AtomicU64::new(0));
+    let worker = tokio::spawn(run(
+      config,
+      socket,
+      command_receiver,
+      message_sender,
+      dropped.clone(),
+      malformed.clone(),
+    ));
+    Ok(MarketDataStream {
+      commands,
+      messages,
+      dropped,
+      malformed,
+      worker,
+    })
+  }
+
+  pub fn subscribe(&self, subscription: Subscription) -> anyhow::Result<()> {
+    if self.commands.send(StreamCommand::Subscribe(subscription)).is_err() {
+      bail!("market data stream is closed")
+    }
+    Ok(())
+  }
+
+  pub fn unsubscribe(&self, subscription: Subscription) -> anyhow::Result<()> {
+    if self.commands.send(StreamCommand::Unsubscribe(subscription)).is_err() {
+      bail!("market data stream is closed")
+    }
+    Ok(())
+  }
+
+  pub async fn next(&mut self) -> Option<MarketDataMessage> {
+    self.messages.recv().await
+  }
+
+  pub fn dropped_messages(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+
+  ///
+  ///Messages skipped because they could not be decoded, a frame that is not a list counts once
+  pub fn malformed_messages(&self) -> u64 {
+    self.malformed.load(Ordering::Relaxed)
+  }
+}
+
+///
+///One message of a frame, anything that is not a [`MarketDataMessage`] is kept as malformed so it
+/// does not fail the whole frame
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FrameMessage {
+  Decoded(Box<MarketDataMessage>),
+  Malformed(IgnoredAny),
+}
+
+impl Drop for MarketDataStream {
+  fn drop(&mut self) {
+    self.worker.abort();
+  }
+}
+
+async fn run(
+  config: StreamConfig,
+  mut socket: Socket,
+  mut commands: mpsc::UnboundedReceiver<StreamCommand>,
+  messages: mpsc::Sender<MarketDataMessage>,
+  dropped: Arc<AtomicU64>,
+  malformed: Arc<AtomicU64>,
+) {
+  let mut subscription = Subscription::default();
+  loop {
+    loop {
+      tokio::select! {
+        command = commands.recv() => {
+          let Some(command) = command else {
+            let _ = socket.close(None).await;
+            return;
+          };
+          let sent = match &command {
+            StreamCommand::Subscribe(added) => {
+              subscription.add(added);
+              send_action(&mut socket, config.encoding, &StreamAction::Subscribe(added)).await
+            }
+            StreamCommand::Unsubscribe(removed) => {
+              subscription.remove(removed);
+              send_action(&mut socket, config.encoding, &StreamAction::Unsubscribe(removed)).await
+            }
+          };
+          if sent.is_err() {
+            break;
+          }
+        }
+        frame = socket.next() => {
+          let decoded = match frame {
+            Some(Ok(Message::Text(text))) => serde_json::from_slice::<Vec<FrameMessage>>(text.as_bytes()).ok(),
+            Some(Ok(Message::Binary(bytes))) => rmp_serde::from_slice::<Vec<FrameMessage>>(&bytes).ok(),
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+            Some(Ok(_)) => Some(vec![]),
+          };
+          let Some(decoded) = decoded else {
+            malformed.fetch_add(1, Ordering::Relaxed);
+            continue;
+          };
+          for message in decoded {
+            let message = match message {
+              FrameMessage::Decoded(message) => *message,
+              FrameMessage::Malformed(_) => {
+                malformed.fetch_add(1, Ordering::Relaxed);
+                continue;
+              }
+            };
+            match messages.try_send(message) {
+              Ok(()) => {}
+              Err(mpsc::error::TrySendError::Full(_)) => {
+                dropped.fetch_add(1, Ordering::Relaxed);
+              }
+              Err(mpsc::error::TrySendError::Closed(_)) => return,
+            }
+          }
+        }
+      }
+    }
+
+    let mut delay = config.reconnect_delay;
+    socket = loop {
+      tokio::time::sleep(delay).await;
+      if messages.is_closed() {
+        return;
+      }
+      if let Ok(reconnected) = reconnect(&config, &subscription).await {
+        break reconnected;
+      }
+      delay = (delay * 2).min(config.max_reconnect_delay);
+    };
+  }
+}
+
+async fn reconnect(config: &StreamConfig, subscription: &Subscription) -> anyhow::Result<Socket> {
+  let mut socket = connect_and_authenticate(config).await?;
+  if !subscription.is_empty() {
+    send_action(&mut socket, config.encoding, &StreamAction::Subscribe(subscription)).await?;
+  }
+  Ok(socket)
+}
+
+async fn connect_and_authenticate(config: &StreamConfig) -> anyhow::Result<Socket> {
+  let mut request = config.url.as_str().into_client_request()?;
+  if config.encoding == StreamEncoding::MsgPack {
+    request
+      .headers_mut()
+      .insert("Content-Type", HeaderValue::from_static("application/msgpack"));
+  }
+  let (mut socket, _) = connect_async(request).await?;
+  match tokio::time::timeout(config.auth_timeout, authenticate(&mut socket, config)).await {
+    Ok(result) => result.map(|_| socket),
+    Err(_) => bail!("timed out authenticating market data stream"),
+  }
+}
+
+async fn authenticate(socket: &mut Socket, config: &StreamConfig) -> anyhow::Result<()> {
+  let auth = StreamAction::Auth {
+    key: &config.api_key_id,
+    secret: &config.api_secret_key,
+  };
+  while let Some(frame) = socket.next().await {
+    let decoded = match frame? {
+      Message::Text(text) => serde_json::from_slice::<Vec<MarketDataMessage>>(text.as_bytes())?,
+      Message::Binary(bytes) => rmp_serde::from_slice::<Vec<MarketDataMessage>>(&bytes)?,
+      Message::Close(_) => break,
+      _ => continue,
+    };
+    for message in decoded {
+      match message {
+        MarketDataMessage::Success { msg } if msg == "connected" => {
+          send_action(socket, config.encoding, &auth).await?;
+        }
+        MarketDataMessage::Success { msg } if msg == "authenticated" => return Ok(()),
+        MarketDataMessage::Error { code, msg } => bail!(ErrorResponse::new(code, msg)),
+        _ => {}
+      }
+    }
+  }
+  bail!("market data stream closed before authentication")
+}
+
+async fn send_action(socket: &mut Socket, encoding: StreamEncoding, action: &StreamAction<'_>) -> anyhow::Result<()> {
+  let message = match encoding {
+    StreamEncoding::Json => Message::text(serde_json::to_string(action)?),
+    StreamEncoding::MsgPack => Message::binary(rmp_serde::to_vec_named(action)?),
+  };
+  socket.send(message).await?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    models::Subscription,
+    stream::market_data_stream::StreamAction,
+  };
+
+  #[test]
+  fn test_subscribe_action_serialization() {
+    let subscription = Subscription {
+      trades: vec!["AAPL".to_string()],
+      updated_bars: vec!["*".to_string()],
+      lulds: vec!["TSLA".to_string()],
+      ..Default::default()
+    };
+
+    let json = serde_json::to_string(&StreamAction::Subscribe(&subscription)).unwrap();
+    assert_eq!(
+      json,
+      r#"{"action":"subscribe","trades":["AAPL"],"updatedBars":["*"],"lulds":["TSLA"]}"#
+    )
+  }
+}
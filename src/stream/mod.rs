@@ -0,0 +1,7 @@
+mod market_data_stream;
+mod news_stream;
+mod trade_updates_stream;
+
+pub use market_data_stream::*;
+pub use news_stream::*;
+pub use trade_updates_stream::*;
@@ -0,0 +1,60 @@
+use crate::{
+  models::{
+    MarketDataMessage,
+    NewsArticle,
+    Subscription,
+  },
+  stream::{
+    MarketDataStream,
+    StreamConfig,
+  },
+};
+
+///
+///Yields news articles as they are published, `"*"` follows every symbol
+pub struct NewsStream {
+  stream: MarketDataStream,
+}
+
+impl NewsStream {
+  ///
+  ///Connect with a config built for [`crate::stream::MarketDataFeed::News`] and subscribe to
+  /// `symbols`
+  pub async fn connect(config: StreamConfig, symbols: Vec<String>) -> anyhow::Result<Self> {
+    let stream = MarketDataStream::connect(config).await?;
+    let news_stream = NewsStream { stream };
+    news_stream.subscribe(symbols)?;
+    Ok(news_stream)
+  }
+
+  pub fn subscribe(&self, symbols: Vec<String>) -> anyhow::Result<()> {
+    self.stream.subscribe(Subscription {
+      news: symbols,
+      ..Default::default()
+    })
+  }
+
+  pub fn unsubscribe(&self, symbols: Vec<String>) -> anyhow::Result<()> {
+    self.stream.unsubscribe(Subscription {
+      news: symbols,
+      ..Default::default()
+    })
+  }
+
+  pub async fn next(&mut self) -> Option<NewsArticle> {
+    while let Some(message) = self.stream.next().await {
+      if let MarketDataMessage::News(article) = message {
+        return Some(article);
+      }
+    }
+    None
+  }
+
+  pub fn dropped_messages(&self) -> u64 {
+    self.stream.dropped_messages()
+  }
+
+  pub fn malformed_messages(&self) -> u64 {
+    self.stream.malformed_messages()
+  }
+}
@@ -0,0 +1,211 @@
+use crate::{
+  models::{
+    TradeUpdate,
+    TradingStreamMessage,
+  },
+  stream::{
+    StreamConfig,
+    StreamEncoding,
+  },
+};
+use anyhow::bail;
+use futures_util::{
+  SinkExt,
+  StreamExt,
+};
+use serde_json::json;
+use std::{
+  sync::{
+    Arc,
+    atomic::{
+      AtomicU64,
+      Ordering,
+    },
+  },
+  time::Duration,
+};
+use tokio::{
+  net::TcpStream,
+  sync::mpsc,
+  task::JoinHandle,
+};
+use tokio_tungstenite::{
+  MaybeTlsStream,
+  WebSocketStream,
+  connect_async,
+  tungstenite::Message,
+};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+impl StreamConfig {
+  ///
+  ///Build a config for the trade updates of the account behind `base_url`, the trading url such as
+  /// [`crate::profile::PAPER_URL`] or a local simulator
+  ///
+  /// ```
+  /// use alpaca_trade_api_rust::stream::StreamConfig;
+  ///
+  /// let config = StreamConfig::trade_updates(
+  ///   "https://paper-api.alpaca.markets".to_string(),
+  ///   "testApiKey".to_string(),
+  ///   "testApiSecretKey".to_string(),
+  /// );
+  /// assert_eq!(config.url, "wss://paper-api.alpaca.markets/stream");
+  /// ```
+  pub fn trade_updates(base_url: String, api_key_id: String, api_secret_key: String) -> Self {
+    let base_url = base_url.trim_end_matches('/');
+    let url = match base_url.split_once("://") {
+      Some(("https", host)) => format!("wss://{host}/stream"),
+      Some(("http", host)) => format!("ws://{host}/stream"),
+      _ => format!("{base_url}/stream"),
+    };
+    StreamConfig {
+      url,
+      api_key_id,
+      api_secret_key,
+      encoding: StreamEncoding::Json,
+      buffer_size: 10_000,
+      reconnect_delay: Duration::from_millis(500),
+      max_reconnect_delay: Duration::from_secs(30),
+      auth_timeout: Duration::from_secs(10),
+    }
+  }
+}
+
+///
+///Order updates of the account over the trading WebSocket stream.
+///
+///Reconnects with exponential backoff like [`crate::stream::MarketDataStream`] and has the same
+///drop policy. Updates sent while disconnected are lost, so consumers that keep order state should
+///reload it over REST when [`TradeUpdatesStream::reconnects`] changes.
+pub struct TradeUpdatesStream {
+  updates: mpsc::Receiver<TradeUpdate>,
+  dropped: Arc<AtomicU64>,
+  reconnects: Arc<AtomicU64>,
+  worker: JoinHandle<()>,
+}
+
+impl TradeUpdatesStream {
+  pub async fn connect(config: StreamConfig) -> anyhow::Result<Self> {
+    let socket = connect_and_listen(&config).await?;
+    let (update_sender, updates) = mpsc::channel(config.buffer_size.max(1));
+    let dropped = Arc::new(AtomicU64::new(0));
+    let reconnects = Arc::new(AtomicU64::new(0));
+    let worker = tokio::spawn(run(config, socket, update_sender, dropped.clone(), reconnects.clone()));
+    Ok(TradeUpdatesStream {
+      updates,
+      dropped,
+      reconnects,
+      worker,
+    })
+  }
+
+  pub async fn next(&mut self) -> Option<TradeUpdate> {
+    self.updates.recv().await
+  }
+
+  ///
+  ///An update if one is already waiting
+  pub fn try_next(&mut self) -> Option<TradeUpdate> {
+    self.updates.try_recv().ok()
+  }
+
+  pub fn dropped_messages(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+
+  pub fn reconnects(&self) -> u64 {
+    self.reconnects.load(Ordering::Relaxed)
+  }
+}
+
+impl Drop for TradeUpdatesStream {
+  fn drop(&mut self) {
+    self.worker.abort();
+  }
+}
+
+async fn run(
+  config: StreamConfig,
+  mut socket: Socket,
+  updates: mpsc::Sender<TradeUpdate>,
+  dropped: Arc<AtomicU64>,
+  reconnects: Arc<AtomicU64>,
+) {
+  loop {
+    while let Some(frame) = socket.next().await {
+      let message = match frame {
+        Ok(Message::Text(text)) => decode(text.as_bytes()),
+        Ok(Message::Binary(bytes)) => decode(&bytes),
+        Ok(Message::Close(_)) | Err(_) => break,
+        Ok(_) => None,
+      };
+      if let Some(TradingStreamMessage::TradeUpdates(update)) = message {
+        match updates.try_send(*update) {
+          Ok(()) => {}
+          Err(mpsc::error::TrySendError::Full(_)) => {
+            dropped.fetch_add(1, Ordering::Relaxed);
+          }
+          Err(mpsc::error::TrySendError::Closed(_)) => return,
+        }
+      }
+    }
+
+    let mut delay = config.reconnect_delay;
+    socket = loop {
+      tokio::time::sleep(delay).await;
+      if updates.is_closed() {
+        return;
+      }
+      if let Ok(reconnected) = connect_and_listen(&config).await {
+        reconnects.fetch_add(1, Ordering::Relaxed);
+        break reconnected;
+      }
+      delay = (delay * 2).min(config.max_reconnect_delay);
+    };
+  }
+}
+
+///
+///Alpaca sends the trading stream as JSON in text or binary frames
+fn decode(bytes: &[u8]) -> Option<TradingStreamMessage> {
+  serde_json::from_slice(bytes).ok()
+}
+
+async fn connect_and_listen(config: &StreamConfig) -> anyhow::Result<Socket> {
+  let (mut socket, _) = connect_async(config.url.as_str()).await?;
+  match tokio::time::timeout(config.auth_timeout, authenticate_and_listen(&mut socket, config)).await {
+    Ok(result) => result.map(|_| socket),
+    Err(_) => bail!("timed out authenticating trade updates stream"),
+  }
+}
+
+async fn authenticate_and_listen(socket: &mut Socket, config: &StreamConfig) -> anyhow::Result<()> {
+  let auth = json!({ "action": "auth", "key": config.api_key_id, "secret": config.api_secret_key });
+  socket.send(Message::text(auth.to_string())).await?;
+  while let Some(frame) = socket.next().await {
+    let message = match frame? {
+      Message::Text(text) => decode(text.as_bytes()),
+      Message::Binary(bytes) => decode(&bytes),
+      Message::Close(_) => break,
+      _ => continue,
+    };
+    match message {
+      Some(TradingStreamMessage::Authorization(authorization)) if authorization.status == "authorized" => {
+        let listen = json!({ "action": "listen", "data": { "streams": ["trade_updates"] } });
+        socket.send(Message::text(listen.to_string())).await?;
+      }
+      Some(TradingStreamMessage::Authorization(authorization)) => {
+        bail!("trade updates stream {}", authorization.status)
+      }
+      Some(TradingStreamMessage::Listening(listening))
+        if listening.streams.iter().any(|stream| stream == "trade_updates") =>
+      {
+        return Ok(());
+      }
+      _ => {}
+    }
+  }
+  bail!("trade updates stream closed before listening")
+}
@@ -0,0 +1,84 @@
+use alpaca_trade_api_rust::{
+  api::{
+    AccountConfigurationsApi,
+    AccountConfigurationsReqBody,
+  },
+  prelude::Client,
+};
+use httpmock::{
+  Method::{
+    GET,
+    PATCH,
+  },
+  MockServer,
+};
+
+const CONFIGURATIONS: &str = r#"{
+  "dtbp_check": "entry",
+  "trade_confirm_email": "all",
+  "suspend_trade": false,
+  "no_shorting": false,
+  "fractional_trading": true,
+  "max_margin_multiplier": "4",
+  "max_options_trading_level": 2,
+  "pdt_check": "entry",
+  "ptp_no_exception_entry": false
+}"#;
+
+#[tokio::test]
+async fn test_get_account_configurations_should_return_configurations() {
+  let server = MockServer::start();
+  let configurations_mock = server.mock(|when, then| {
+    when
+      .method(GET)
+      .header("Content-Type", "application/json")
+      .header("Accept", "application/json")
+      .header("APCA-API-KEY-ID", "test_key")
+      .header("APCA-API-SECRET-KEY", "test_secret")
+      .path("/v2/account/configurations");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(CONFIGURATIONS);
+  });
+
+  let api = Client::new(server.base_url(), "test_key".to_string(), "test_secret".to_string());
+  match api.get_account_configurations().await {
+    Ok(configurations) => {
+      assert_eq!(configurations.dtbp_check, "entry");
+      assert!(!configurations.suspend_trade);
+      assert_eq!(configurations.max_options_trading_level, Some(2));
+    }
+    Err(e) => {
+      configurations_mock.assert();
+      panic!("API call failed: {:?}", e)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_update_account_configurations_should_send_only_the_changes() {
+  let server = MockServer::start();
+  let configurations_mock = server.mock(|when, then| {
+    when
+      .method(PATCH)
+      .header("Content-Type", "application/json")
+      .header("APCA-API-KEY-ID", "test_key")
+      .header("APCA-API-SECRET-KEY", "test_secret")
+      .path("/v2/account/configurations")
+      .body(r#"{"suspend_trade":true}"#);
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(CONFIGURATIONS.replace(r#""suspend_trade": false"#, r#""suspend_trade": true"#));
+  });
+
+  let api = Client::new(server.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let request_body = AccountConfigurationsReqBody {
+    suspend_trade: Some(true),
+    ..Default::default()
+  };
+  let configurations = api.update_account_configurations(&request_body).await.unwrap();
+  configurations_mock.assert();
+  assert!(configurations.suspend_trade);
+}
@@ -0,0 +1,242 @@
+use alpaca_trade_api_rust::{
+  api::{
+    AccountApi,
+    AllOrdersQueryParameter,
+    ClockApi,
+    OrderApi,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    StopLoss,
+    TakeProfit,
+    TimeFrame,
+  },
+  backtest::Backtest,
+  prelude::{
+    Bar,
+    CorporateAction,
+    MarketCalendar,
+    OrderClass,
+    OrderStatus,
+    TimeInForce,
+    enums::Side,
+    utils::Money,
+  },
+};
+use chrono::{
+  DateTime,
+  NaiveDate,
+  NaiveTime,
+  TimeZone,
+  Utc,
+};
+use uuid::Uuid;
+
+pub mod shared;
+use shared::order;
+
+fn bar(timestamp: DateTime<Utc>, open: f64, high: f64, low: f64, close: f64) -> Bar {
+  Bar {
+    timestamp,
+    open,
+    high,
+    low,
+    close,
+    volume: 1_000.0,
+    trade_count: None,
+    vwap: None,
+  }
+}
+
+fn at(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+  Utc.with_ymd_and_hms(2025, month, day, hour, 0, 0).unwrap()
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+}
+
+fn corporate_action(ca_type: &str, ca_sub_type: &str, effective_date: NaiveDate) -> CorporateAction {
+  CorporateAction {
+    id: Uuid::new_v4(),
+    corporate_action_id: String::new(),
+    ca_type: ca_type.to_string(),
+    ca_sub_type: ca_sub_type.to_string(),
+    initiating_symbol: "XYZ".to_string(),
+    initiating_original_cusip: String::new(),
+    target_symbol: "XYZ".to_string(),
+    target_original_cusip: String::new(),
+    declaration_date: None,
+    expiration_date: None,
+    effective_date: Some(effective_date),
+    record_date: None,
+    payable_date: None,
+    cash: "0".to_string(),
+    old_rate: "1".to_string(),
+    new_rate: "1".to_string(),
+  }
+}
+
+///
+///Buys into the first dip with a bracket order, strategy code only sees the trading API traits
+async fn enter_bracket<C: OrderApi + PositionApi + AccountApi>(client: &C, symbol: &str) -> anyhow::Result<()> {
+  if client.get_all_open_positions().await?.is_empty() && client.get_account().await?.cash.value() > 1_000.0 {
+    let mut order = order(symbol, Side::Buy, 10.0, None);
+    order.time_in_force = TimeInForce::GTC;
+    order.order_class = Some(OrderClass::Bracket);
+    order.take_profit = Some(TakeProfit {
+      limit_price: Money::from_f64(105.5),
+    });
+    order.stop_loss = Some(StopLoss {
+      stop_price: Money::from_f64(96.0),
+      limit_price: None,
+    });
+    client.create_order(&order).await?;
+  }
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_backtest_should_run_bracket_strategy() {
+  let mut backtest = Backtest::new(100_000.0, TimeFrame::Day(1)).with_bars(
+    "AAPL",
+    vec![
+      bar(at(12, 1, 15), 100.0, 100.0, 100.0, 100.0),
+      bar(at(12, 2, 15), 102.0, 102.0, 102.0, 102.0),
+      bar(at(12, 3, 15), 104.0, 106.0, 104.0, 105.0),
+      bar(at(12, 4, 15), 103.0, 103.0, 95.0, 96.0),
+      bar(at(12, 5, 15), 97.0, 97.0, 97.0, 97.0),
+    ],
+  );
+
+  assert_eq!(backtest.step(), Some(at(12, 1, 15)));
+  enter_bracket(&backtest, "AAPL").await.unwrap();
+  let orders = backtest
+    .get_all_orders(&AllOrdersQueryParameter {
+      status: Some(OrdersFilter::All),
+      limit: None,
+      after: None,
+      until: None,
+      direction: None,
+      nested: Some(true),
+      symbols: None,
+      side: None,
+      asset_class: None,
+      before_order_id: None,
+      after_order_id: None,
+    })
+    .await
+    .unwrap();
+  assert_eq!(orders.len(), 1);
+  let parent = orders[0].id;
+
+  backtest.step();
+  let filled = backtest.get_order_by_id(&parent).await.unwrap();
+  assert_eq!(filled.status, OrderStatus::Filled);
+  assert_eq!(filled.filled_avg_price.unwrap().value(), 102.0);
+  assert_eq!(
+    backtest
+      .get_open_position_by_symbol_or_id("AAPL")
+      .await
+      .unwrap()
+      .qty
+      .value(),
+    10.0
+  );
+
+  backtest.step();
+  assert!(backtest.get_all_open_positions().await.unwrap().is_empty());
+  let legs = backtest.get_order_by_id(&parent).await.unwrap().legs.unwrap();
+  let statuses: Vec<OrderStatus> = legs.iter().map(|leg| leg.status).collect();
+  assert!(statuses.contains(&OrderStatus::Filled));
+  assert!(statuses.contains(&OrderStatus::Canceled));
+
+  backtest.run_to_end();
+  assert_eq!(backtest.step(), None);
+  assert_eq!(backtest.get_account().await.unwrap().cash.value(), 100_035.0);
+  let history = backtest.portfolio_history();
+  assert_eq!(history.timestamp.len(), 5);
+  assert_eq!(history.timeframe, "1D");
+  assert_eq!(history.equity[1], 100_000.0);
+  assert_eq!(history.profit_loss[4], 35.0);
+}
+
+#[tokio::test]
+async fn test_backtest_should_apply_sessions_and_corporate_actions() {
+  let sessions: Vec<MarketCalendar> = [date(11, 28), date(12, 1), date(12, 2), date(12, 3), date(12, 4)]
+    .into_iter()
+    .map(|date| MarketCalendar {
+      date,
+      open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+      settlement_date: date,
+    })
+    .collect();
+  let split = CorporateAction {
+    old_rate: "1".to_string(),
+    new_rate: "2".to_string(),
+    ..corporate_action("split", "stock_split", date(12, 2))
+  };
+  let dividend = CorporateAction {
+    cash: "0.1".to_string(),
+    payable_date: Some(date(12, 4)),
+    ..corporate_action("dividend", "cash", date(12, 2))
+  };
+  let mut backtest = Backtest::new(100_000.0, TimeFrame::Minute(15))
+    .with_bars(
+      "XYZ",
+      vec![
+        bar(at(11, 28, 15), 50.0, 50.0, 50.0, 50.0),
+        bar(at(12, 1, 15), 50.0, 51.0, 50.0, 51.0),
+        bar(at(12, 1, 22), 49.0, 49.0, 49.0, 49.0),
+        bar(at(12, 2, 15), 25.0, 25.0, 25.0, 25.0),
+        bar(at(12, 3, 15), 25.0, 25.0, 25.0, 25.0),
+        bar(at(12, 4, 15), 25.0, 25.0, 25.0, 25.0),
+      ],
+    )
+    .with_sessions(&sessions)
+    .with_corporate_actions(vec![split, dividend]);
+
+  backtest.step();
+  assert!(backtest.get_market_clock_info().await.unwrap().is_open);
+  // submitted after the last bar of the day, the DAY order carries over to the next session
+  let market = backtest
+    .create_order(&order("XYZ", Side::Buy, 10.0, None))
+    .await
+    .unwrap();
+
+  backtest.step();
+  let market = backtest.get_order_by_id(&market.id).await.unwrap();
+  assert_eq!(market.filled_avg_price.unwrap().value(), 50.0);
+  let regular = backtest
+    .create_order(&order("XYZ", Side::Buy, 1.0, Some(49.5)))
+    .await
+    .unwrap();
+  let mut extended = order("XYZ", Side::Buy, 1.0, Some(49.5));
+  extended.extended_hours = true;
+  let extended = backtest.create_order(&extended).await.unwrap();
+
+  backtest.step();
+  assert!(!backtest.get_market_clock_info().await.unwrap().is_open);
+  assert_eq!(
+    backtest.get_order_by_id(&regular.id).await.unwrap().status,
+    OrderStatus::New
+  );
+  let extended = backtest.get_order_by_id(&extended.id).await.unwrap();
+  assert_eq!(extended.status, OrderStatus::Filled);
+  assert_eq!(extended.filled_avg_price.unwrap().value(), 49.0);
+
+  backtest.step();
+  assert_eq!(
+    backtest.get_order_by_id(&regular.id).await.unwrap().status,
+    OrderStatus::Expired
+  );
+  let position = backtest.get_open_position_by_symbol_or_id("XYZ").await.unwrap();
+  assert_eq!(position.qty.value(), 22.0);
+  assert_eq!(backtest.get_account().await.unwrap().cash.value(), 99_451.0);
+
+  backtest.run_to_end();
+  let cash = backtest.get_account().await.unwrap().cash.value();
+  assert!((cash - 99_453.2).abs() < 1e-9);
+  assert_eq!(backtest.bars("XYZ").len(), 6);
+  assert_eq!(backtest.portfolio_history().timeframe, "15Min");
+}
@@ -0,0 +1,189 @@
+use alpaca_trade_api_rust::{
+  api::TimeFrame,
+  history::{
+    BarDownloadConfig,
+    BarDownloader,
+    BarGap,
+    BarStore,
+    CsvBarStore,
+    SymbolSync,
+    trading_date,
+  },
+  prelude::Client,
+};
+use chrono::NaiveDate;
+use httpmock::{
+  Method::GET,
+  MockServer,
+};
+
+const CALENDAR_RESPONSE: &str = r#"[
+  { "close": "16:00", "date": "2025-12-01", "open": "09:30", "settlement_date": "2025-12-02" },
+  { "close": "13:00", "date": "2025-12-02", "open": "09:30", "settlement_date": "2025-12-03" },
+  { "close": "16:00", "date": "2025-12-03", "open": "09:30", "settlement_date": "2025-12-04" }
+]"#;
+
+fn date(day: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(2025, 12, day).unwrap()
+}
+
+#[tokio::test]
+async fn test_bar_downloader_should_only_fetch_missing_days() {
+  let ms = MockServer::start();
+  let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  ms.mock(|when, then| {
+    when.method(GET).path("/v2/calendar");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(CALENDAR_RESPONSE);
+  });
+  let first_page = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v2/stocks/bars")
+      .query_param("symbols", "AAPL")
+      .query_param("timeframe", "1Hour")
+      .query_param("start", "2025-12-01T05:00:00Z")
+      .query_param("end", "2025-12-03T04:59:59.999999Z")
+      .query_param_missing("page_token");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"{
+        "bars": { "AAPL": [
+          { "t": "2025-12-01T15:00:00Z", "o": 280.1, "h": 281.0, "l": 279.8, "c": 280.5, "v": 1200000, "n": 15000, "vw": 280.4 },
+          { "t": "2025-12-02T00:00:00Z", "o": 281.0, "h": 281.2, "l": 280.9, "c": 281.1, "v": 3000, "n": 120, "vw": 281.0 }
+        ] },
+        "next_page_token": "QUFQTHxNfDIwMjUtMTItMDJUMDA6MDA6MDBa"
+      }"#,
+    );
+  });
+  let second_page = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v2/stocks/bars")
+      .query_param("page_token", "QUFQTHxNfDIwMjUtMTItMDJUMDA6MDA6MDBa");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"{
+        "bars": { "AAPL": [
+          { "t": "2025-12-02T15:00:00Z", "o": 282.0, "h": 283.5, "l": 281.7, "c": 283.0, "v": 900000, "n": 11000, "vw": 282.8 }
+        ] },
+        "next_page_token": null
+      }"#,
+    );
+  });
+
+  let config = BarDownloadConfig::new(vec!["AAPL".to_string()], TimeFrame::Hour(1), date(1), date(2));
+  let mut downloader = BarDownloader::new(&api_client, CsvBarStore::new(&root), config).unwrap();
+  let summary = downloader.sync().await.unwrap();
+  first_page.assert();
+  second_page.assert();
+  assert_eq!(
+    summary,
+    vec![SymbolSync {
+      symbol: "AAPL".to_string(),
+      days: 2,
+      bars: 3
+    }]
+  );
+  // the after-hours bar at 00:00 UTC belongs to the 1st in New York
+  let index = downloader.store().day_index("AAPL").unwrap();
+  assert_eq!(index.get(&date(1)), Some(&2));
+  assert!(downloader.sync().await.unwrap()[0].days == 0);
+
+  let third_day = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v2/stocks/bars")
+      .query_param("start", "2025-12-03T05:00:00Z");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(r#"{ "bars": {}, "next_page_token": null }"#);
+  });
+  let config = BarDownloadConfig::new(vec!["AAPL".to_string()], TimeFrame::Hour(1), date(1), date(3));
+  let mut downloader = BarDownloader::new(&api_client, CsvBarStore::new(&root), config).unwrap();
+  assert_eq!(
+    downloader.find_gaps().await.unwrap(),
+    vec![BarGap {
+      symbol: "AAPL".to_string(),
+      missing: vec![date(3)],
+      empty: vec![],
+    }]
+  );
+  downloader.sync().await.unwrap();
+  third_day.assert();
+  first_page.assert_calls(1);
+  assert_eq!(downloader.find_gaps().await.unwrap()[0].empty, vec![date(3)]);
+  std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn test_bar_downloader_should_redownload_window() {
+  let ms = MockServer::start();
+  let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  ms.mock(|when, then| {
+    when.method(GET).path("/v2/calendar");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(CALENDAR_RESPONSE);
+  });
+  let raw = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v2/stocks/bars")
+      .query_param("start", "2025-12-01T05:00:00Z");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"{ "bars": { "NVDA": [
+        { "t": "2025-12-01T05:00:00Z", "o": 1400.0, "h": 1410.0, "l": 1390.0, "c": 1405.0, "v": 100 },
+        { "t": "2025-12-02T05:00:00Z", "o": 1405.0, "h": 1420.0, "l": 1400.0, "c": 1415.0, "v": 100 },
+        { "t": "2025-12-03T05:00:00Z", "o": 1415.0, "h": 1430.0, "l": 1410.0, "c": 1420.0, "v": 100 }
+      ] }, "next_page_token": null }"#,
+    );
+  });
+  let adjusted = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v2/stocks/bars")
+      .query_param("start", "2025-12-02T05:00:00Z")
+      .query_param("end", "2025-12-03T04:59:59.999999Z");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"{ "bars": { "NVDA": [
+        { "t": "2025-12-02T05:00:00Z", "o": 140.5, "h": 142.0, "l": 140.0, "c": 141.5, "v": 1000 }
+      ] }, "next_page_token": null }"#,
+    );
+  });
+
+  let config = BarDownloadConfig::new(vec!["NVDA".to_string()], TimeFrame::Day(1), date(1), date(3));
+  let mut downloader = BarDownloader::new(&api_client, CsvBarStore::new(&root), config).unwrap();
+  downloader.sync().await.unwrap();
+  raw.assert();
+
+  let sync = downloader.redownload("NVDA", date(2), date(2)).await.unwrap();
+  adjusted.assert();
+  assert_eq!(sync.bars, 1);
+  let closes: Vec<f64> = downloader
+    .store()
+    .read_bars("NVDA", date(1), date(3))
+    .unwrap()
+    .iter()
+    .map(|bar| bar.close)
+    .collect();
+  assert_eq!(closes, vec![1405.0, 141.5, 1420.0]);
+
+  // a narrower range leaves the stored days past its end alone
+  let config = BarDownloadConfig::new(vec!["NVDA".to_string()], TimeFrame::Day(1), date(1), date(2));
+  let mut downloader = BarDownloader::new(&api_client, CsvBarStore::new(&root), config).unwrap();
+  downloader.redownload("NVDA", date(2), date(3)).await.unwrap();
+  let dates: Vec<NaiveDate> = downloader
+    .store()
+    .read_bars("NVDA", date(1), date(3))
+    .unwrap()
+    .iter()
+    .map(|bar| trading_date(bar.timestamp))
+    .collect();
+  assert_eq!(dates, vec![date(1), date(2), date(3)]);
+  std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    PositionApi,
+  },
+  portfolio::{
+    Basket,
+    BasketPolicy,
+  },
+  prelude::{
+    Client,
+    OrderStatus,
+  },
+  sim::{
+    Broker,
+    SimHandle,
+    SimServer,
+  },
+};
+use std::time::Duration;
+
+pub mod shared;
+
+async fn simulator(broker: Broker) -> (SimHandle, Client) {
+  let server = SimServer::new(broker).with_market_open(true);
+  let prices = [("AAPL", 200.0), ("MSFT", 400.0), ("NVDA", 125.0)];
+  let (handle, _, client) = shared::simulator(server, &prices).await;
+  (handle, client)
+}
+
+#[tokio::test]
+async fn test_basket_should_split_amount_into_notional_legs() {
+  let (_handle, client) = simulator(Broker::new(10_000.0)).await;
+  let basket = Basket::new([("AAPL", 2.0), ("MSFT", 1.0), ("NVDA", 1.0)], 1_000.0)
+    .unwrap()
+    .with_concurrency(2)
+    .with_poll_interval(Duration::from_millis(10));
+
+  let report = basket.submit(&client).await.unwrap();
+
+  assert!(report.complete);
+  assert_eq!((report.filled_legs, report.failed_legs, report.open_legs), (3, 0, 0));
+  assert!((report.filled_value - 1_000.0).abs() < 1e-6);
+  let notionals: Vec<Option<f64>> = report.legs.iter().map(|leg| leg.notional).collect();
+  assert_eq!(notionals, vec![Some(500.0), Some(250.0), Some(250.0)]);
+  assert_eq!(report.legs[0].filled_qty, 2.5);
+  assert_eq!(report.legs[2].client_order_id, format!("{}-2", basket.basket_id()));
+  let order = client
+    .get_order_by_client_order_id(&report.legs[1].client_order_id)
+    .await
+    .unwrap();
+  assert_eq!(Some(order.id), report.legs[1].order_id);
+  assert_eq!(client.get_all_open_positions().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_basket_should_cancel_open_legs_when_all_or_cancel_leg_fails() {
+  let (_handle, client) = simulator(Broker::new(10_000.0).with_immediate_fills(false)).await;
+  let symbols = ["AAPL", "UNKNOWN", "MSFT"];
+
+  let report = Basket::equal_weight(symbols, 900.0)
+    .unwrap()
+    .with_policy(BasketPolicy::AllOrCancel)
+    .with_poll_interval(Duration::from_millis(10))
+    .submit(&client)
+    .await
+    .unwrap();
+
+  assert!(report.canceled && !report.complete);
+  assert_eq!((report.filled_legs, report.failed_legs, report.open_legs), (0, 1, 0));
+  assert!(report.legs[1].error.is_some() && report.legs[1].order_id.is_none());
+  for leg in [&report.legs[0], &report.legs[2]] {
+    assert!(leg.canceled_by_policy);
+    assert_eq!(leg.status, Some(OrderStatus::Canceled));
+  }
+
+  // best effort keeps the accepted legs working
+  let report = Basket::equal_weight(symbols, 900.0)
+    .unwrap()
+    .with_fill_timeout(Duration::ZERO)
+    .submit(&client)
+    .await
+    .unwrap();
+  assert!(!report.canceled);
+  assert_eq!((report.failed_legs, report.open_legs), (1, 2));
+  assert_eq!(report.legs[0].status, Some(OrderStatus::New));
+}
+
+#[tokio::test]
+async fn test_basket_should_not_send_legs_after_an_all_or_cancel_leg_failed() {
+  let (_handle, client) = simulator(Broker::new(10_000.0).with_immediate_fills(false)).await;
+
+  let report = Basket::equal_weight(["UNKNOWN", "AAPL", "MSFT"], 900.0)
+    .unwrap()
+    .with_policy(BasketPolicy::AllOrCancel)
+    .with_concurrency(1)
+    .with_poll_interval(Duration::from_millis(10))
+    .submit(&client)
+    .await
+    .unwrap();
+
+  assert!(report.canceled && !report.complete);
+  assert_eq!((report.failed_legs, report.open_legs, report.unsent_legs), (1, 0, 2));
+  for leg in &report.legs[1..] {
+    assert!(leg.canceled_by_policy && leg.error.is_none());
+    assert_eq!((leg.order_id, leg.status), (None, None));
+    assert!(client.get_order_by_client_order_id(&leg.client_order_id).await.is_err());
+  }
+}
@@ -10,7 +10,7 @@ use httpmock::{
   MockServer,
 };
 
-mod shared;
+pub mod shared;
 
 #[tokio::test]
 async fn test_get_market_calendar_info_should_return_good_1() {
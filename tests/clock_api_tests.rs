@@ -8,7 +8,7 @@ use httpmock::{
   MockServer,
 };
 
-mod shared;
+pub mod shared;
 
 #[tokio::test]
 async fn test_get_market_calendar_info_should_return_good_1() {
@@ -30,7 +30,7 @@ async fn test_get_market_calendar_info_should_return_good_1() {
     .setup_endpoint(GET, "/v2/clock", 200, response_body, |client| async move {
       match client.get_market_clock_info().await {
         Ok(result) => {
-          assert_eq!(result.is_open, true);
+          assert!(result.is_open);
           assert_eq!(result.next_open.year(), 2025);
           assert_eq!(result.next_open.month(), 11);
           assert_eq!(result.next_open.day(), 17);
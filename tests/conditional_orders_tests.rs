@@ -0,0 +1,352 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+  },
+  execution::{
+    Condition,
+    ConditionalOrder,
+    ConditionalOrderStatus,
+    ConditionalOrders,
+  },
+  history::market_time,
+  prelude::{
+    Client,
+    OrderStatus,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::Money,
+  },
+  sim::{
+    Broker,
+    SimHandle,
+    SimServer,
+  },
+};
+use chrono::{
+  DateTime,
+  NaiveDate,
+  NaiveTime,
+  TimeDelta,
+  Utc,
+};
+use uuid::Uuid;
+
+pub mod shared;
+use shared::{
+  order,
+  state_file,
+};
+
+fn buy(qty: f64) -> OrderRequestBody {
+  order("AAPL", Side::Buy, qty, None)
+}
+
+async fn simulator(market_open: bool) -> (SimHandle, Client) {
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(market_open);
+  let (handle, _, client) = shared::simulator(server, &[("AAPL", 200.0)]).await;
+  (handle, client)
+}
+
+fn new_york(date: (i32, u32, u32), hour: u32, minute: u32) -> DateTime<Utc> {
+  market_time(
+    NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+  )
+  .unwrap()
+}
+
+#[tokio::test]
+async fn test_price_condition_should_fire_when_another_symbol_crosses_its_level() {
+  let (_handle, client) = simulator(true).await;
+  let path = state_file("conditional_orders");
+  let mut conditional = ConditionalOrders::open(&path).unwrap();
+  let id = conditional
+    .register(ConditionalOrder::new(Condition::price_above("SPY", 500.0), buy(2.0)))
+    .unwrap();
+  assert_eq!(conditional.subscription().trades, vec!["SPY".to_string()]);
+  let now = Utc::now();
+
+  // already above the level, it has to trade below it first
+  assert!(
+    conditional
+      .on_price(&client, "SPY", 505.0, now)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  assert!(
+    conditional
+      .on_price(&client, "SPY", 495.0, now)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  drop(conditional);
+
+  let mut conditional = ConditionalOrders::open(&path).unwrap();
+  let order = conditional.order(&id).unwrap();
+  assert_eq!((order.status, order.armed), (ConditionalOrderStatus::Pending, true));
+  assert!(
+    conditional
+      .on_price(&client, "AAPL", 600.0, now)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  assert_eq!(
+    conditional.on_price(&client, "SPY", 501.0, now).await.unwrap(),
+    vec![id]
+  );
+  let order = conditional.order(&id).unwrap();
+  assert_eq!(order.status, ConditionalOrderStatus::Submitted);
+  let submitted = client.get_order_by_client_order_id(&id.to_string()).await.unwrap();
+  assert_eq!(Some(submitted.id), order.order_id);
+  assert_eq!(
+    (submitted.symbol.as_str(), submitted.status),
+    ("AAPL", OrderStatus::Filled)
+  );
+  assert!(conditional.subscription().trades.is_empty());
+  assert!(conditional.cancel(&client, &id).await.is_err());
+  assert!(
+    conditional
+      .register(ConditionalOrder::new(Condition::price_below("SPY", -1.0), buy(1.0)))
+      .is_err()
+  );
+
+  let conditional = ConditionalOrders::open(&path).unwrap();
+  assert_eq!(conditional.list().len(), 1);
+  assert_eq!(conditional.list()[0].status, ConditionalOrderStatus::Submitted);
+  std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_time_condition_should_fire_at_the_new_york_time_of_day() {
+  let (_handle, client) = simulator(true).await;
+  let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+  let mut conditional = ConditionalOrders::new();
+  let id = conditional
+    .register(ConditionalOrder::new(
+      Condition::market_time(date, NaiveTime::from_hms_opt(15, 55, 0).unwrap()).unwrap(),
+      buy(1.0),
+    ))
+    .unwrap();
+  let expires_at = new_york((2025, 6, 2), 15, 58);
+  let late = conditional
+    .register(ConditionalOrder::new(Condition::price_below("AAPL", 150.0), buy(1.0)).with_expiry(expires_at))
+    .unwrap();
+
+  assert!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 2), 15, 54))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  assert_eq!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 2), 15, 55))
+      .await
+      .unwrap(),
+    vec![id]
+  );
+  assert_eq!(
+    conditional.order(&id).unwrap().submitted_at,
+    Some(new_york((2025, 6, 2), 15, 55))
+  );
+  assert!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 2), 16, 0))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  let order = conditional.order(&late).unwrap();
+  assert_eq!((order.status, order.order_id), (ConditionalOrderStatus::Expired, None));
+  assert!(!conditional.has_active_orders());
+
+  assert!(
+    conditional
+      .register(
+        ConditionalOrder::new(Condition::At { time: Utc::now() }, buy(1.0))
+          .with_expiry(Utc::now() - TimeDelta::minutes(1))
+      )
+      .is_err()
+  );
+}
+
+#[tokio::test]
+async fn test_good_till_date_should_cancel_the_order_at_its_expiry() {
+  let (_handle, client) = simulator(true).await;
+  let mut conditional = ConditionalOrders::new();
+  let mut limit = buy(1.0);
+  limit._type = OrderType::Limit;
+  limit.time_in_force = TimeInForce::GTC;
+  limit.limit_price = Some(Money::from_f64(150.0));
+  let now = new_york((2025, 6, 2), 10, 0);
+  let expires_at = now + TimeDelta::days(3);
+  let id = conditional
+    .register(ConditionalOrder::new(Condition::Immediately, limit).with_expiry(expires_at))
+    .unwrap();
+  let pending = conditional
+    .register(ConditionalOrder::new(Condition::price_below("AAPL", 100.0), buy(1.0)))
+    .unwrap();
+
+  assert_eq!(conditional.on_time(&client, now).await.unwrap(), vec![id]);
+  assert!(conditional.on_time(&client, now).await.unwrap().is_empty());
+  let order_id = conditional.order(&id).unwrap().order_id.unwrap();
+  assert_eq!(conditional.order(&id).unwrap().status, ConditionalOrderStatus::Working);
+  assert_eq!(
+    client.get_order_by_id(&order_id).await.unwrap().status,
+    OrderStatus::New
+  );
+
+  conditional.on_time(&client, expires_at).await.unwrap();
+  assert_eq!(conditional.order(&id).unwrap().status, ConditionalOrderStatus::Expired);
+  assert_eq!(
+    client.get_order_by_id(&order_id).await.unwrap().status,
+    OrderStatus::Canceled
+  );
+
+  conditional.cancel(&client, &pending).await.unwrap();
+  assert_eq!(
+    conditional.order(&pending).unwrap().status,
+    ConditionalOrderStatus::Canceled
+  );
+  assert!(conditional.cancel(&client, &Uuid::new_v4()).await.is_err());
+  conditional.prune().unwrap();
+  assert!(conditional.list().is_empty());
+}
+
+#[tokio::test]
+async fn test_time_conditions_should_wait_for_a_trading_session() {
+  let (_handle, client) = simulator(false).await;
+  let mut conditional = ConditionalOrders::new();
+  let saturday = NaiveDate::from_ymd_opt(2025, 6, 7).unwrap();
+  let weekend = conditional
+    .register(ConditionalOrder::new(
+      Condition::market_time(saturday, NaiveTime::from_hms_opt(15, 55, 0).unwrap()).unwrap(),
+      buy(1.0),
+    ))
+    .unwrap();
+  let immediate = conditional
+    .register(ConditionalOrder::new(Condition::Immediately, buy(1.0)))
+    .unwrap();
+  let mut pre_market = buy(1.0);
+  pre_market._type = OrderType::Limit;
+  pre_market.limit_price = Some(Money::from_f64(150.0));
+  pre_market.extended_hours = true;
+  let extended = conditional
+    .register(ConditionalOrder::new(
+      Condition::At {
+        time: new_york((2025, 6, 9), 8, 0),
+      },
+      pre_market,
+    ))
+    .unwrap();
+
+  // the weekend time never comes, the immediate order waits for the open
+  assert!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 7), 15, 55))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  let order = conditional.order(&weekend).unwrap();
+  assert_eq!(order.status, ConditionalOrderStatus::Expired);
+  assert!(order.error.as_deref().unwrap().contains("outside a trading session"));
+  assert!(client.get_order_by_client_order_id(&weekend.to_string()).await.is_err());
+  assert_eq!(
+    conditional.order(&immediate).unwrap().status,
+    ConditionalOrderStatus::Pending
+  );
+
+  // pre-market on Monday only suits the order that allows extended hours
+  assert_eq!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 9), 8, 0))
+      .await
+      .unwrap(),
+    vec![extended]
+  );
+  assert_eq!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 9), 9, 30))
+      .await
+      .unwrap(),
+    vec![immediate]
+  );
+}
+
+#[tokio::test]
+async fn test_opening_auction_should_wait_until_the_next_session_accepts_it() {
+  let (_handle, client) = simulator(false).await;
+  let mut conditional = ConditionalOrders::new();
+
+  // Friday after the close, Monday's auction accepts orders from Sunday evening
+  let friday = conditional
+    .opening_auction(&client, buy(1.0), new_york((2025, 6, 6), 17, 0))
+    .await
+    .unwrap();
+  let order = conditional.order(&friday).unwrap();
+  assert_eq!(order.order.time_in_force, TimeInForce::OPG);
+  assert_eq!(
+    order.condition,
+    Condition::At {
+      time: new_york((2025, 6, 8), 19, 0)
+    }
+  );
+
+  let monday = conditional
+    .opening_auction(&client, buy(1.0), new_york((2025, 6, 9), 8, 0))
+    .await
+    .unwrap();
+  assert_eq!(
+    conditional.order(&monday).unwrap().condition,
+    Condition::At {
+      time: new_york((2025, 6, 9), 8, 0)
+    }
+  );
+
+  // too late for Monday's auction
+  let tuesday = conditional
+    .opening_auction(&client, buy(1.0), new_york((2025, 6, 9), 9, 29))
+    .await
+    .unwrap();
+  assert_eq!(
+    conditional.order(&tuesday).unwrap().condition,
+    Condition::At {
+      time: new_york((2025, 6, 9), 19, 0)
+    }
+  );
+
+  assert!(
+    conditional
+      .on_time(&client, new_york((2025, 6, 8), 18, 59))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  // the simulator refuses auction orders, the submission is retried before giving up
+  let at = new_york((2025, 6, 9), 8, 0);
+  assert_eq!(conditional.on_time(&client, at).await.unwrap(), vec![friday, monday]);
+  assert_eq!(
+    conditional.order(&monday).unwrap().status,
+    ConditionalOrderStatus::Submitting
+  );
+  conditional.on_time(&client, at).await.unwrap();
+  conditional.on_time(&client, at).await.unwrap();
+  let order = conditional.order(&monday).unwrap();
+  assert_eq!((order.status, order.attempts), (ConditionalOrderStatus::Failed, 3));
+  assert!(order.error.as_deref().unwrap().contains("opg"));
+  assert_eq!(
+    conditional.order(&tuesday).unwrap().status,
+    ConditionalOrderStatus::Pending
+  );
+}
@@ -11,7 +11,7 @@ use httpmock::{
   MockServer,
 };
 
-mod shared;
+pub mod shared;
 
 #[tokio::test]
 async fn test_get_all_crypto_funding_wallet_should_return_good() {
@@ -0,0 +1,265 @@
+use alpaca_trade_api_rust::{
+  api::{
+    CryptoBarsQueryParameter,
+    CryptoHistoricalQueryParameter,
+    CryptoMarketDataApi,
+    CryptoSymbolsQueryParameter,
+    TimeFrame,
+  },
+  prelude::{
+    Client,
+    CryptoLocation,
+    TakerSide,
+  },
+};
+use httpmock::{
+  Method::GET,
+  MockServer,
+};
+
+#[tokio::test]
+async fn test_get_crypto_bars_should_return_bars_by_symbol() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "bars": {
+      "BTC/USD": [
+        {
+          "c": 101250.5,
+          "h": 101400,
+          "l": 101100.25,
+          "n": 42,
+          "o": 101200,
+          "t": "2025-01-03T00:00:00Z",
+          "v": 1.2345,
+          "vw": 101260.1
+        },
+        {
+          "c": 101300,
+          "h": 101350,
+          "l": 101200,
+          "n": 17,
+          "o": 101250.5,
+          "t": "2025-01-03T00:01:00Z",
+          "v": 0.5,
+          "vw": 101290.4
+        }
+      ]
+    },
+    "next_page_token": null
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .header("APCA-API-KEY-ID", "test_key")
+      .header("APCA-API-SECRET-KEY", "test_secret")
+      .path("/v1beta3/crypto/us/bars")
+      .query_param("symbols", "BTC/USD")
+      .query_param("timeframe", "1Min");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = CryptoBarsQueryParameter {
+    symbols: vec!["BTC/USD".to_string()],
+    timeframe: TimeFrame::Minute(1),
+    start: None,
+    end: None,
+    limit: None,
+    page_token: None,
+    sort: None,
+  };
+
+  match api_client.get_crypto_bars(CryptoLocation::Us, &parameter).await {
+    Ok(response) => {
+      let bars = response.bars.get("BTC/USD").unwrap();
+      assert_eq!(bars.len(), 2);
+      assert_eq!(bars[0].open, 101200.0);
+      assert_eq!(bars[0].volume, 1.2345);
+      assert_eq!(bars[1].trade_count, Some(17));
+      assert_eq!(response.next_page_token, None);
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_crypto_trades_should_use_us_1_location() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "trades": {
+      "ETH/USD": [
+        {
+          "i": 7513465,
+          "p": 3605.1,
+          "s": 0.02,
+          "t": "2025-01-03T00:00:01.512Z",
+          "tks": "S"
+        }
+      ]
+    },
+    "next_page_token": "RVRIL1VTRHw="
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when.method(GET).path("/v1beta3/crypto/us-1/trades");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = CryptoHistoricalQueryParameter {
+    symbols: vec!["ETH/USD".to_string()],
+    start: None,
+    end: None,
+    limit: Some(1),
+    page_token: None,
+    sort: None,
+  };
+
+  match api_client.get_crypto_trades(CryptoLocation::Us1, &parameter).await {
+    Ok(response) => {
+      let trades = response.trades.get("ETH/USD").unwrap();
+      assert_eq!(trades[0].taker_side, TakerSide::Sell);
+      assert_eq!(trades[0].id, 7513465);
+      assert_eq!(response.next_page_token, Some("RVRIL1VTRHw=".to_string()));
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_crypto_latest_orderbooks_should_return_levels() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "orderbooks": {
+      "BTC/USD": {
+        "a": [
+          { "p": 101310.2, "s": 0.25 },
+          { "p": 101300.0, "s": 0.1 }
+        ],
+        "b": [
+          { "p": 101280.0, "s": 0.3 },
+          { "p": 101290.5, "s": 0.05 }
+        ],
+        "t": "2025-01-03T00:00:02.01Z"
+      }
+    }
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta3/crypto/us/latest/orderbooks")
+      .query_param("symbols", "BTC/USD");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = CryptoSymbolsQueryParameter {
+    symbols: vec!["BTC/USD".to_string()],
+  };
+
+  match api_client
+    .get_crypto_latest_orderbooks(CryptoLocation::Us, &parameter)
+    .await
+  {
+    Ok(response) => {
+      let orderbook = response.orderbooks.get("BTC/USD").unwrap();
+      assert_eq!(orderbook.asks.len(), 2);
+      assert_eq!(orderbook.best_bid().unwrap().price, 101290.5);
+      assert_eq!(orderbook.best_ask().unwrap().price, 101300.0);
+      assert!((orderbook.spread().unwrap() - 9.5).abs() < 1e-9);
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_crypto_snapshots_should_return_snapshot() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "snapshots": {
+      "BTC/USD": {
+        "dailyBar": { "c": 101250.5, "h": 102000, "l": 99800, "n": 5120, "o": 100100, "t": "2025-01-03T00:00:00Z", "v": 310.5, "vw": 100900.2 },
+        "latestQuote": { "ap": 101300, "as": 0.1, "bp": 101290.5, "bs": 0.05, "t": "2025-01-03T12:00:00.5Z" },
+        "latestTrade": { "i": 42, "p": 101295, "s": 0.001, "t": "2025-01-03T12:00:00.4Z", "tks": "B" },
+        "minuteBar": { "c": 101295, "h": 101300, "l": 101280, "n": 12, "o": 101290, "t": "2025-01-03T11:59:00Z", "v": 0.8, "vw": 101291.3 },
+        "prevDailyBar": null
+      }
+    }
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when.method(GET).path("/v1beta3/crypto/us/snapshots");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = CryptoSymbolsQueryParameter {
+    symbols: vec!["BTC/USD".to_string()],
+  };
+
+  match api_client.get_crypto_snapshots(CryptoLocation::Us, &parameter).await {
+    Ok(response) => {
+      let snapshot = response.snapshots.get("BTC/USD").unwrap();
+      assert_eq!(snapshot.latest_trade.as_ref().unwrap().taker_side, TakerSide::Buy);
+      assert_eq!(snapshot.latest_quote.as_ref().unwrap().ask_price, 101300.0);
+      assert_eq!(snapshot.daily_bar.as_ref().unwrap().trade_count, Some(5120));
+      assert!(snapshot.prev_daily_bar.is_none());
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_crypto_latest_quotes_should_return_error() {
+  let ms = MockServer::start();
+  ms.mock(|when, then| {
+    when.method(GET).path("/v1beta3/crypto/us/latest/quotes");
+    then
+      .status(400)
+      .header("Content-Type", "application/json")
+      .body(r#"{"message":"invalid symbol: BTCUSD"}"#);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = CryptoSymbolsQueryParameter {
+    symbols: vec!["BTCUSD".to_string()],
+  };
+
+  match api_client
+    .get_crypto_latest_quotes(CryptoLocation::Us, &parameter)
+    .await
+  {
+    Ok(_) => panic!("expected an error response"),
+    Err(error) => assert!(error.to_string().contains("400")),
+  }
+}
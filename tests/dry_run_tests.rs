@@ -0,0 +1,243 @@
+use alpaca_trade_api_rust::{
+  api::{
+    ClosePositionParam,
+    OrderApi,
+    OrderRequestBody,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+  },
+  dry_run::{
+    DryRun,
+    Journal,
+  },
+  prelude::{
+    Client,
+    OrderStatus,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+};
+use httpmock::{
+  Method::{
+    DELETE,
+    GET,
+    PATCH,
+    POST,
+  },
+  MockServer,
+};
+
+pub mod shared;
+use shared::order;
+
+const ACCOUNT: &str = r#"{
+  "id": "fff0e281-2a5a-4b97-8dcc-790a439a49b2",
+  "account_number": "PA39J45DA4AZ",
+  "status": "ACTIVE",
+  "crypto_status": "ACTIVE",
+  "options_approved_level": 3,
+  "options_trading_level": 3,
+  "currency": "USD",
+  "buying_power": "2000",
+  "regt_buying_power": "2000",
+  "daytrading_buying_power": "0",
+  "effective_buying_power": "2000",
+  "non_marginable_buying_power": "2000",
+  "options_buying_power": "2000",
+  "bod_dtbp": "0",
+  "cash": "2000",
+  "accrued_fees": "0",
+  "portfolio_value": "4043.19",
+  "pattern_day_trader": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2024-10-31T15:46:03.666425Z",
+  "trade_suspended_by_user": false,
+  "multiplier": "1",
+  "shorting_enabled": false,
+  "equity": "4043.19",
+  "last_equity": "4043.19",
+  "long_market_value": "2043.19",
+  "short_market_value": "0",
+  "position_market_value": "2043.19",
+  "initial_margin": "0",
+  "maintenance_margin": "0",
+  "last_maintenance_margin": "0",
+  "sma": "0",
+  "daytrade_count": 0,
+  "balance_asof": "2025-10-31",
+  "crypto_tier": 1,
+  "intraday_adjustments": "0",
+  "pending_reg_taf_fees": "0"
+}"#;
+
+const ASSET: &str = r#"{
+  "id": "fc6a5dcd-4a70-4b8d-b64f-d83a6dae9ba4",
+  "class": "us_equity",
+  "cusip": "30303M102",
+  "exchange": "NASDAQ",
+  "symbol": "META",
+  "name": "Meta Platforms, Inc. Class A Common Stock",
+  "status": "active",
+  "tradable": true,
+  "marginable": true,
+  "margin_requirement_long": "30",
+  "margin_requirement_short": "30",
+  "shortable": true,
+  "easy_to_borrow": true,
+  "fractionable": true,
+  "attributes": []
+}"#;
+
+const POSITION: &str = r#"{
+  "asset_id": "fc6a5dcd-4a70-4b8d-b64f-d83a6dae9ba4",
+  "symbol": "META",
+  "exchange": "NASDAQ",
+  "asset_class": "us_equity",
+  "asset_marginable": true,
+  "qty": "3",
+  "avg_entry_price": "634.25",
+  "side": "long",
+  "market_value": "1943.85",
+  "cost_basis": "1902.75",
+  "unrealized_pl": "41.1",
+  "unrealized_plpc": "0.0216",
+  "unrealized_intraday_pl": "0",
+  "unrealized_intraday_plpc": "0",
+  "current_price": "647.95",
+  "lastday_price": "647.95",
+  "change_today": "0",
+  "qty_available": "3"
+}"#;
+
+fn limit_order(qty: f64, limit_price: Option<f64>) -> OrderRequestBody {
+  OrderRequestBody {
+    _type: OrderType::Limit,
+    client_order_id: Some(format!("dry-run-{qty}")),
+    ..order("META", Side::Buy, qty, limit_price)
+  }
+}
+
+#[tokio::test]
+async fn test_dry_run_should_answer_mutating_calls_without_sending_them() {
+  let server = MockServer::start();
+  for (path, body) in [
+    ("/v2/account", ACCOUNT),
+    ("/v2/assets/META", ASSET),
+    ("/v2/positions/META", POSITION),
+  ] {
+    server.mock(|when, then| {
+      when.method(GET).path(path);
+      then.status(200).header("Content-Type", "application/json").body(body);
+    });
+  }
+  let create_mock = server.mock(|when, then| {
+    when.method(POST).path("/v2/orders");
+    then.status(500);
+  });
+  let replace_mock = server.mock(|when, then| {
+    when.method(PATCH).path_prefix("/v2/orders/");
+    then.status(500);
+  });
+  let close_mock = server.mock(|when, then| {
+    when.method(DELETE).path("/v2/positions/META");
+    then.status(500);
+  });
+
+  let path = std::env::temp_dir().join(format!("{}.jsonl", uuid::Uuid::new_v4()));
+  let client = Client::new(server.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let dry_run = DryRun::new(client).with_journal_file(&path);
+
+  let order = dry_run.create_order(&limit_order(1.0, Some(600.0))).await.unwrap();
+  assert_eq!(order.status, OrderStatus::Accepted);
+  assert_eq!(order.client_order_id, "dry-run-1");
+  assert_eq!(order.limit_price.as_deref(), Some("600"));
+  assert_eq!(dry_run.get_order_by_id(&order.id).await.unwrap().symbol, "META");
+
+  let error = dry_run.create_order(&limit_order(10.0, Some(600.0))).await.unwrap_err();
+  assert!(error.to_string().contains("insufficient buying power"));
+  let error = dry_run.create_order(&limit_order(2.0, None)).await.unwrap_err();
+  assert!(error.to_string().contains("limit orders require a limit_price"));
+
+  let replacement = dry_run
+    .replace_order_by_id(
+      &order.id,
+      &ReplaceOrderByIdRequestBody {
+        qty: NumberAsString::from_f64(2.0),
+        time_in_force: TimeInForce::GTC,
+        limit_price: Some(Money::from_f64(610.0)),
+        stop_price: None,
+        trail: None,
+        client_order_id: "dry-run-replacement".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(replacement.replaces, Some(order.id));
+  let replaced = dry_run.get_order_by_id(&order.id).await.unwrap();
+  assert_eq!(replaced.status, OrderStatus::Replaced);
+  assert_eq!(replaced.replaced_by, Some(replacement.id));
+
+  let closed = dry_run
+    .close_open_position_by_symbol_or_id("META", &ClosePositionParam::Percentage(50.0))
+    .await
+    .unwrap();
+  assert_eq!(closed.side, Side::Sell);
+  assert_eq!(closed.qty.unwrap().value(), 1.5);
+
+  create_mock.assert_calls(0);
+  replace_mock.assert_calls(0);
+  close_mock.assert_calls(0);
+
+  let journal = dry_run.journal();
+  let calls: Vec<&str> = journal.entries().iter().map(|entry| entry.call.as_str()).collect();
+  assert_eq!(
+    calls,
+    vec![
+      "create_order",
+      "create_order",
+      "create_order",
+      "replace_order_by_id",
+      "close_open_position_by_symbol_or_id",
+    ]
+  );
+  assert!(journal.entries()[1].response.is_none());
+  assert_eq!(Journal::load(&path).unwrap(), journal);
+  std::fs::remove_file(&path).unwrap();
+
+  // live trading placed the first order only
+  let diff = journal.diff_orders(&[order]);
+  assert!(diff.live_only.is_empty());
+  assert_eq!(diff.dry_run_only.len(), 2);
+  assert_eq!(diff.dry_run_only[0].limit_price, Some(610.0));
+  assert_eq!(diff.dry_run_only[1].order_type, OrderType::Market);
+}
+
+#[tokio::test]
+async fn test_dry_run_should_count_open_synthetic_buys_against_buying_power() {
+  let server = MockServer::start();
+  for (path, body) in [("/v2/account", ACCOUNT), ("/v2/assets/META", ASSET)] {
+    server.mock(|when, then| {
+      when.method(GET).path(path);
+      then.status(200).header("Content-Type", "application/json").body(body);
+    });
+  }
+  let client = Client::new(server.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let dry_run = DryRun::new(client);
+
+  // each buy fits the 2000 of buying power, both together do not
+  let first = dry_run.create_order(&limit_order(2.0, Some(600.0))).await.unwrap();
+  let error = dry_run.create_order(&limit_order(3.0, Some(600.0))).await.unwrap_err();
+  assert!(error.to_string().contains("insufficient buying power"));
+
+  dry_run.delete_order_by_id(&first.id).await.unwrap();
+  dry_run.create_order(&limit_order(3.0, Some(600.0))).await.unwrap();
+}
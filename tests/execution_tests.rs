@@ -0,0 +1,533 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    AllOrdersQueryParameter,
+    DeleteAllOrdersResponse,
+    OrderApi,
+    OrderRequestBody,
+    ReplaceOrderByIdRequestBody,
+  },
+  execution::{
+    ExecutionAlgo,
+    ExecutionStatus,
+    ExecutionStrategy,
+    VolumeProfile,
+  },
+  history::market_time,
+  prelude::{
+    Bar,
+    Client,
+    Order,
+    OrderStatus,
+    enums::Side,
+  },
+  sim::{
+    Broker,
+    NextBarOpen,
+    SimHandle,
+    SimServer,
+    VolumeParticipation,
+  },
+  stream::{
+    MarketDataFeed,
+    MarketDataStream,
+    StreamConfig,
+  },
+};
+use chrono::{
+  DateTime,
+  NaiveDate,
+  NaiveTime,
+  TimeDelta,
+  Utc,
+};
+use futures_util::{
+  SinkExt,
+  StreamExt,
+};
+use std::{
+  collections::HashSet,
+  sync::Mutex,
+  time::Duration,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::{
+  accept_async,
+  tungstenite::Message,
+};
+use uuid::Uuid;
+
+pub mod shared;
+use shared::close;
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+  market_time(
+    NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+  )
+  .unwrap()
+}
+
+fn bar(price: f64, volume: f64) -> Bar {
+  Bar {
+    timestamp: Utc::now(),
+    open: price,
+    high: price,
+    low: price,
+    close: price,
+    volume,
+    trade_count: None,
+    vwap: None,
+  }
+}
+
+///
+///Simulator filling at most 10% of each bar's volume, orders only execute on bars
+async fn simulator() -> (SimHandle, SimServer, Client) {
+  let broker = Broker::new(1_000_000.0)
+    .with_immediate_fills(false)
+    .with_fill_model(VolumeParticipation {
+      model: NextBarOpen,
+      max_participation: 0.1,
+    });
+  shared::simulator(SimServer::new(broker).with_market_open(true), &[("AAPL", 100.0)]).await
+}
+
+fn trade(server: &SimServer, price: f64, volume: f64) {
+  server.with_broker(|broker| broker.process_bar("AAPL", &bar(price, volume), true));
+}
+
+///
+///Client whose cancels stay pending while the order keeps working, as the API reports them until
+/// the exchange confirms
+struct PendingCancels {
+  client: Client,
+  canceled: Mutex<HashSet<Uuid>>,
+}
+
+impl OrderApi for PendingCancels {
+  async fn create_order(&self, order: &OrderRequestBody) -> anyhow::Result<Order> {
+    self.client.create_order(order).await
+  }
+
+  async fn get_all_orders(&self, query_parameter: &AllOrdersQueryParameter) -> anyhow::Result<Vec<Order>> {
+    self.client.get_all_orders(query_parameter).await
+  }
+
+  async fn delete_all_orders(&self) -> anyhow::Result<Vec<DeleteAllOrdersResponse>> {
+    self.client.delete_all_orders().await
+  }
+
+  async fn get_order_by_client_order_id(&self, client_order_id: &str) -> anyhow::Result<Order> {
+    self.client.get_order_by_client_order_id(client_order_id).await
+  }
+
+  async fn get_order_by_id(&self, id: &Uuid) -> anyhow::Result<Order> {
+    let mut order = self.client.get_order_by_id(id).await?;
+    if self.canceled.lock().unwrap().contains(id) && !order.status.is_final() {
+      order.status = OrderStatus::PendingCancel;
+    }
+    Ok(order)
+  }
+
+  async fn replace_order_by_id(&self, order_id: &Uuid, order: &ReplaceOrderByIdRequestBody) -> anyhow::Result<Order> {
+    self.client.replace_order_by_id(order_id, order).await
+  }
+
+  async fn delete_order_by_id(&self, order_id: &Uuid) -> anyhow::Result<()> {
+    self.canceled.lock().unwrap().insert(*order_id);
+    Ok(())
+  }
+}
+
+#[test]
+fn test_volume_profile_should_weight_the_window_by_historical_volume() {
+  let minute = |hour: u32, minute: u32| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+  let profile = VolumeProfile::new(
+    [(minute(9, 30), 300.0), (minute(9, 45), 100.0), (minute(10, 0), 100.0)],
+    Duration::from_secs(15 * 60),
+  )
+  .unwrap();
+  assert!(close(profile.weight(minute(9, 40)), 0.6));
+  assert_eq!(profile.weight(minute(15, 0)), 0.0);
+
+  let (start, end) = (at(9, 30), at(10, 0));
+  assert_eq!(profile.fraction(start, end, start), 0.0);
+  assert!(close(profile.fraction(start, end, at(9, 45)), 0.75));
+  assert!(close(profile.fraction(start, end, at(9, 50)), 0.75 + 0.25 / 3.0));
+  assert_eq!(profile.fraction(start, end, at(11, 0)), 1.0);
+  // no history after hours, so the window is spread evenly
+  assert!(close(profile.fraction(at(18, 0), at(19, 0), at(18, 15)), 0.25));
+
+  let bars: Vec<Bar> = [(9, 30, 50.0), (9, 31, 150.0), (9, 45, 200.0)]
+    .into_iter()
+    .map(|(hour, minute, volume)| Bar {
+      timestamp: at(hour, minute),
+      ..bar(100.0, volume)
+    })
+    .collect();
+  let profile = VolumeProfile::from_bars(&bars, Duration::from_secs(15 * 60)).unwrap();
+  assert!(close(profile.weight(minute(9, 44)), 0.5));
+  assert!(VolumeProfile::new([(minute(9, 30), 0.0)], Duration::from_secs(60)).is_err());
+  assert!(VolumeProfile::new([(minute(9, 30), 1.0)], Duration::from_secs(90)).is_err());
+}
+
+#[tokio::test]
+async fn test_twap_should_roll_partial_fills_into_the_next_slice() {
+  let (_handle, server, client) = simulator().await;
+  let mut algo = ExecutionAlgo::new(ExecutionStrategy::Twap, "AAPL", Side::Buy, 100.0, at(10, 0), at(10, 5))
+    .unwrap()
+    .with_client_order_id_prefix("twap-test")
+    .with_arrival_price(100.0);
+
+  assert_eq!(algo.step(&client, at(9, 59)).await.unwrap(), ExecutionStatus::Working);
+  assert!(algo.report().children.is_empty());
+  algo.step(&client, at(10, 0)).await.unwrap();
+  // a second step within the slice submits nothing
+  algo.step(&client, at(10, 0) + TimeDelta::seconds(30)).await.unwrap();
+  assert_eq!(algo.report().children.len(), 1);
+  trade(&server, 100.0, 100.0);
+  algo.step(&client, at(10, 1)).await.unwrap();
+  trade(&server, 101.0, 1_000.0);
+  for minute in 2..5 {
+    algo.step(&client, at(10, minute)).await.unwrap();
+    trade(&server, 101.0, 1_000.0);
+  }
+  assert_eq!(
+    algo.step(&client, at(10, 4) + TimeDelta::seconds(30)).await.unwrap(),
+    ExecutionStatus::Completed
+  );
+
+  let report = algo.report();
+  let children: Vec<(&str, f64, f64)> = report
+    .children
+    .iter()
+    .map(|child| (child.client_order_id.as_str(), child.qty, child.filled_qty))
+    .collect();
+  assert_eq!(
+    children,
+    vec![
+      ("twap-test-0", 20.0, 10.0),
+      ("twap-test-1", 30.0, 30.0),
+      ("twap-test-2", 20.0, 20.0),
+      ("twap-test-3", 20.0, 20.0),
+      ("twap-test-4", 20.0, 20.0),
+    ]
+  );
+  assert_eq!(report.children[0].status, Some(OrderStatus::Canceled));
+  assert_eq!(report.filled_qty, 100.0);
+  assert!(close(report.avg_fill_price.unwrap(), 100.9));
+  assert!(close(report.slippage_bps.unwrap(), 90.0));
+  assert!(close(report.slippage.unwrap(), 90.0));
+}
+
+#[tokio::test]
+async fn test_twap_should_not_oversize_a_child_while_the_previous_one_is_pending_cancel() {
+  let (_handle, server, client) = simulator().await;
+  let client = PendingCancels {
+    client,
+    canceled: Mutex::new(HashSet::new()),
+  };
+  let mut algo = ExecutionAlgo::new(ExecutionStrategy::Twap, "AAPL", Side::Buy, 100.0, at(10, 0), at(10, 2)).unwrap();
+
+  algo.step(&client, at(10, 0)).await.unwrap();
+  // the first child is still working when the second slice starts
+  algo.step(&client, at(10, 1)).await.unwrap();
+  let report = algo.report();
+  assert_eq!(report.children[0].status, Some(OrderStatus::PendingCancel));
+  assert_eq!(report.children[1].qty, 50.0);
+
+  // the pending cancel loses the race and the first child fills as well
+  trade(&server, 100.0, 10_000.0);
+  assert_eq!(
+    algo.step(&client, at(10, 1) + TimeDelta::seconds(30)).await.unwrap(),
+    ExecutionStatus::Completed
+  );
+  let report = algo.report();
+  let children: Vec<(f64, Option<OrderStatus>)> =
+    report.children.iter().map(|child| (child.qty, child.status)).collect();
+  assert_eq!(
+    children,
+    vec![(50.0, Some(OrderStatus::Filled)), (50.0, Some(OrderStatus::Filled))]
+  );
+  assert_eq!(report.filled_qty, 100.0);
+}
+
+#[tokio::test]
+async fn test_vwap_should_front_load_slices_by_volume_profile() {
+  let (_handle, server, client) = simulator().await;
+  let profile = VolumeProfile::new(
+    [
+      (NaiveTime::from_hms_opt(10, 0, 0).unwrap(), 3.0),
+      (NaiveTime::from_hms_opt(10, 1, 0).unwrap(), 1.0),
+    ],
+    Duration::from_secs(60),
+  )
+  .unwrap();
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Vwap(profile),
+    "AAPL",
+    Side::Buy,
+    40.0,
+    at(10, 0),
+    at(10, 2),
+  )
+  .unwrap()
+  .with_arrival_price(100.0);
+
+  algo.step(&client, at(10, 0)).await.unwrap();
+  trade(&server, 99.0, 10_000.0);
+  algo.step(&client, at(10, 1)).await.unwrap();
+  trade(&server, 98.0, 10_000.0);
+  assert_eq!(algo.step(&client, at(10, 2)).await.unwrap(), ExecutionStatus::Completed);
+
+  let report = algo.report();
+  let qtys: Vec<f64> = report.children.iter().map(|child| child.qty).collect();
+  assert_eq!(qtys, vec![30.0, 10.0]);
+  assert!(
+    report.children[0]
+      .client_order_id
+      .starts_with(algo.client_order_id_prefix())
+  );
+  assert!(close(report.avg_fill_price.unwrap(), 98.75));
+  // buying below arrival is a gain
+  assert!(close(report.slippage_bps.unwrap(), -125.0));
+}
+
+#[tokio::test]
+async fn test_pov_should_follow_market_volume_and_expire_at_the_end_of_the_window() {
+  let (_handle, server, client) = simulator().await;
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Pov { participation: 0.2 },
+    "AAPL",
+    Side::Buy,
+    100.0,
+    at(10, 0),
+    at(10, 3),
+  )
+  .unwrap();
+
+  algo.record_market_volume(100.0);
+  algo.step(&client, at(10, 0)).await.unwrap();
+  trade(&server, 100.0, 1_000.0);
+  algo.record_market_volume(150.0);
+  algo.step(&client, at(10, 1)).await.unwrap();
+  // no more volume, the next slice shows the same shortfall again
+  algo.step(&client, at(10, 2)).await.unwrap();
+  assert_eq!(algo.step(&client, at(10, 3)).await.unwrap(), ExecutionStatus::Expired);
+
+  let report = algo.report();
+  let children: Vec<(f64, Option<OrderStatus>)> =
+    report.children.iter().map(|child| (child.qty, child.status)).collect();
+  assert_eq!(
+    children,
+    vec![
+      (20.0, Some(OrderStatus::Filled)),
+      (30.0, Some(OrderStatus::Canceled)),
+      (30.0, Some(OrderStatus::Canceled))
+    ]
+  );
+  assert_eq!((report.filled_qty, report.remaining_qty), (20.0, 80.0));
+  // without an arrival price the first fill is the benchmark
+  assert_eq!(report.slippage_bps, Some(0.0));
+  assert!(
+    ExecutionAlgo::new(
+      ExecutionStrategy::Pov { participation: 1.5 },
+      "AAPL",
+      Side::Buy,
+      100.0,
+      at(10, 0),
+      at(10, 3),
+    )
+    .is_err()
+  );
+}
+
+#[tokio::test]
+async fn test_iceberg_should_show_one_child_at_a_time() {
+  let (_handle, server, client) = simulator().await;
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Iceberg { display_qty: 10.0 },
+    "AAPL",
+    Side::Buy,
+    25.0,
+    at(10, 0),
+    at(11, 0),
+  )
+  .unwrap()
+  .with_limit_price(100.0)
+  .with_client_order_id_prefix("ice");
+
+  algo.step(&client, at(10, 0)).await.unwrap();
+  // above the limit, nothing fills and nothing more is shown
+  trade(&server, 101.0, 10_000.0);
+  algo.step(&client, at(10, 1)).await.unwrap();
+  assert_eq!(algo.report().children.len(), 1);
+  // half the displayed size fills, the child keeps working
+  trade(&server, 100.0, 50.0);
+  algo.step(&client, at(10, 2)).await.unwrap();
+  assert_eq!(algo.report().children.len(), 1);
+  for minute in 3..6 {
+    trade(&server, 99.5, 10_000.0);
+    algo.step(&client, at(10, minute)).await.unwrap();
+  }
+  trade(&server, 99.5, 10_000.0);
+  assert_eq!(algo.step(&client, at(10, 6)).await.unwrap(), ExecutionStatus::Completed);
+
+  let report = algo.report();
+  let children: Vec<(&str, f64, Option<f64>)> = report
+    .children
+    .iter()
+    .map(|child| (child.client_order_id.as_str(), child.qty, child.limit_price))
+    .collect();
+  assert_eq!(
+    children,
+    vec![
+      ("ice-0", 10.0, Some(100.0)),
+      ("ice-1", 10.0, Some(100.0)),
+      ("ice-2", 5.0, Some(100.0)),
+    ]
+  );
+  assert_eq!(report.filled_qty, 25.0);
+}
+
+#[tokio::test]
+async fn test_run_should_stop_at_market_close() {
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(false);
+  server.set_price("AAPL", 100.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let start = Utc::now() - TimeDelta::minutes(1);
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Twap,
+    "AAPL",
+    Side::Buy,
+    10.0,
+    start,
+    start + TimeDelta::hours(1),
+  )
+  .unwrap();
+
+  let report = algo.run(&client, None).await.unwrap();
+  assert_eq!(report.status, ExecutionStatus::MarketClosed);
+  assert!(report.children.is_empty());
+}
+
+#[tokio::test]
+async fn test_run_should_step_from_the_clock_until_filled() {
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(true);
+  server.set_price("AAPL", 100.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let start = Utc::now() - TimeDelta::minutes(1);
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Twap,
+    "AAPL",
+    Side::Buy,
+    10.0,
+    start,
+    start + TimeDelta::hours(1),
+  )
+  .unwrap()
+  .with_slice_interval(Duration::from_secs(2 * 60 * 60))
+  .with_poll_interval(Duration::from_millis(10));
+
+  let report = algo.run(&client, None).await.unwrap();
+  assert_eq!(report.status, ExecutionStatus::Completed);
+  assert_eq!(report.children.len(), 1);
+  assert_eq!(report.avg_fill_price, Some(100.0));
+}
+
+#[tokio::test]
+async fn test_run_should_fail_after_repeated_child_rejections() {
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(true);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let start = Utc::now() - TimeDelta::minutes(1);
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Iceberg { display_qty: 10.0 },
+    "UNKNOWN",
+    Side::Buy,
+    100.0,
+    start,
+    start + TimeDelta::hours(1),
+  )
+  .unwrap()
+  .with_poll_interval(Duration::from_millis(10));
+
+  let report = tokio::time::timeout(Duration::from_secs(5), algo.run(&client, None))
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(report.status, ExecutionStatus::Failed);
+  assert_eq!(report.children.len(), 3);
+  assert!(
+    report
+      .children
+      .iter()
+      .all(|child| child.error.is_some() && child.order_id.is_none())
+  );
+  assert_eq!(algo.step(&client, Utc::now()).await.unwrap(), ExecutionStatus::Failed);
+  assert_eq!(algo.report().children.len(), 3);
+}
+
+#[tokio::test]
+async fn test_run_should_follow_market_volume_from_the_trade_stream() {
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(true);
+  server.set_price("AAPL", 100.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let config = StreamConfig::new(
+    format!("ws://{}", listener.local_addr().unwrap()),
+    MarketDataFeed::Iex,
+    "key".to_string(),
+    "secret".to_string(),
+  );
+  tokio::spawn(async move {
+    let (tcp, _) = listener.accept().await.unwrap();
+    let mut socket = accept_async(tcp).await.unwrap();
+    socket
+      .send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+      .await
+      .unwrap();
+    socket.next().await.unwrap().unwrap();
+    socket
+      .send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+      .await
+      .unwrap();
+    let subscribe = socket.next().await.unwrap().unwrap().into_text().unwrap();
+    assert!(subscribe.contains(r#""trades":["AAPL"]"#));
+    socket
+      .send(Message::text(
+        r#"[
+          {"T":"t","S":"AAPL","i":1,"x":"V","p":100.0,"s":300,"c":["@"],"t":"2025-11-14T15:30:00Z","z":"C"},
+          {"T":"t","S":"MSFT","i":2,"x":"V","p":400.0,"s":10000,"c":["@"],"t":"2025-11-14T15:30:00Z","z":"C"}
+        ]"#,
+      ))
+      .await
+      .unwrap();
+    // keep the connection open until the algo is done
+    socket.next().await;
+  });
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  let start = Utc::now() - TimeDelta::seconds(1);
+  let mut algo = ExecutionAlgo::new(
+    ExecutionStrategy::Pov { participation: 0.2 },
+    "AAPL",
+    Side::Buy,
+    100.0,
+    start,
+    start + TimeDelta::seconds(4),
+  )
+  .unwrap()
+  .with_slice_interval(Duration::from_secs(1))
+  .with_poll_interval(Duration::from_millis(10));
+
+  assert!(algo.run(&client, None).await.is_err());
+  let report = algo.run(&client, Some(&mut stream)).await.unwrap();
+  // only the AAPL trades count
+  assert_eq!(report.status, ExecutionStatus::Expired);
+  assert_eq!(report.filled_qty, 60.0);
+}
@@ -0,0 +1,131 @@
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+    PositionApi,
+    TimeFrame,
+  },
+  backtest::Backtest,
+  prelude::{
+    OrderStatus,
+    TimeInForce,
+    enums::Side,
+  },
+  risk::{
+    KillSwitch,
+    KillSwitchEngaged,
+  },
+};
+use chrono::TimeDelta;
+use std::time::Duration;
+use tokio::{
+  io::AsyncWriteExt,
+  net::{
+    TcpListener,
+    TcpStream,
+  },
+};
+
+pub mod shared;
+use shared::{
+  bars,
+  order,
+};
+
+fn backtest() -> Backtest {
+  shared::backtest(
+    100_000.0,
+    TimeFrame::Day(1),
+    bars(TimeDelta::days(1), &[100.0, 100.0, 80.0, 80.0]),
+  )
+}
+
+#[tokio::test]
+async fn test_kill_switch_should_flatten_and_lock_on_drawdown() {
+  let mut backtest = backtest();
+  backtest
+    .create_order(&order("AAPL", Side::Buy, 500.0, None))
+    .await
+    .unwrap();
+  let resting = backtest
+    .create_order(&OrderRequestBody {
+      time_in_force: TimeInForce::GTC,
+      ..order("AAPL", Side::Buy, 10.0, Some(50.0))
+    })
+    .await
+    .unwrap();
+  backtest.step();
+  backtest.step();
+  let switch = KillSwitch::new(backtest).with_retries(2, Duration::ZERO);
+
+  assert!(!switch.check_drawdown(15.0).await.unwrap());
+  assert!(!switch.is_engaged());
+  assert!(switch.check_drawdown(5.0).await.unwrap());
+  let error = switch
+    .create_order(&order("AAPL", Side::Buy, 1.0, None))
+    .await
+    .unwrap_err();
+  assert_eq!(error.downcast_ref::<KillSwitchEngaged>(), Some(&KillSwitchEngaged));
+
+  let report = switch.engage().await;
+  assert_eq!(report.canceled.len(), 1);
+  assert_eq!(report.canceled[0].id, resting.id);
+  assert!(report.unconfirmed.is_empty());
+  assert_eq!(report.positions.len(), 1);
+  assert_eq!(report.positions[0].symbol, "AAPL");
+  assert_eq!(report.positions[0].order().unwrap().side, Side::Sell);
+  assert!(report.failed.is_empty());
+  assert!(report.errors.is_empty());
+  assert_eq!(
+    switch.get_order_by_id(&resting.id).await.unwrap().status,
+    OrderStatus::Canceled
+  );
+  assert!(switch.create_order(&order("AAPL", Side::Buy, 1.0, None)).await.is_err());
+
+  switch.release();
+  switch.create_order(&order("AAPL", Side::Buy, 1.0, None)).await.unwrap();
+  assert!(switch.get_open_position_by_symbol_or_id("AAPL").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_kill_switch_should_lock_from_the_http_endpoint() {
+  let switch = KillSwitch::new(backtest());
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+
+  let (served, _) = tokio::join!(switch.wait_for_http(listener), async {
+    let client = reqwest::Client::new();
+    let status = client.get(format!("{url}/status")).send().await.unwrap();
+    assert_eq!(status.status(), 200);
+    assert_eq!(status.text().await.unwrap(), r#"{"engaged":false}"#);
+    let missing = client.post(format!("{url}/other")).send().await.unwrap();
+    assert_eq!(missing.status(), 404);
+    let kill = client.post(format!("{url}/kill")).send().await.unwrap();
+    assert_eq!(kill.status(), 202);
+  });
+  served.unwrap();
+  assert!(switch.is_engaged());
+}
+
+#[tokio::test]
+async fn test_kill_switch_should_serve_the_kill_after_a_silent_connection() {
+  let switch = KillSwitch::new(backtest()).with_request_timeout(Duration::from_millis(100));
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let address = listener.local_addr().unwrap();
+
+  let (served, _) = tokio::join!(switch.wait_for_http(listener), async {
+    // connects without ever sending a request, then one that goes away mid request
+    let _silent = TcpStream::connect(address).await.unwrap();
+    let mut dropped = TcpStream::connect(address).await.unwrap();
+    dropped.write_all(b"POST /ki").await.unwrap();
+    drop(dropped);
+    let kill = reqwest::Client::new()
+      .post(format!("http://{address}/kill"))
+      .send()
+      .await
+      .unwrap();
+    assert_eq!(kill.status(), 202);
+  });
+  served.unwrap();
+  assert!(switch.is_engaged());
+}
@@ -0,0 +1,350 @@
+use alpaca_trade_api_rust::{
+  prelude::{
+    MarketDataMessage,
+    OptionFeed,
+    Subscription,
+  },
+  stream::{
+    MarketDataFeed,
+    MarketDataStream,
+    StreamConfig,
+  },
+};
+use futures_util::{
+  SinkExt,
+  StreamExt,
+};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::net::{
+  TcpListener,
+  TcpStream,
+};
+use tokio_tungstenite::{
+  WebSocketStream,
+  accept_async,
+  tungstenite::Message,
+};
+
+const IEX_SESSION: &str = r#"[
+  {"T":"t","S":"AAPL","i":52983525029461,"x":"V","p":230.12,"s":100,"c":["@","I"],"t":"2025-11-14T15:30:00.123456789Z","z":"C"},
+  {"T":"q","S":"AAPL","bx":"V","bp":230.1,"bs":2,"ax":"V","ap":230.15,"as":3,"c":["R"],"t":"2025-11-14T15:30:00.2Z","z":"C"},
+  {"T":"b","S":"AAPL","o":230.0,"h":230.3,"l":229.9,"c":230.12,"v":15432,"t":"2025-11-14T15:29:00Z","n":211,"vw":230.08},
+  {"T":"u","S":"AAPL","o":230.0,"h":230.35,"l":229.9,"c":230.2,"v":15500,"t":"2025-11-14T15:29:00Z","n":214,"vw":230.09},
+  {"T":"d","S":"AAPL","o":228.5,"h":231.0,"l":228.1,"c":230.2,"v":3123456,"t":"2025-11-14T05:00:00Z","n":40210,"vw":229.81},
+  {"T":"s","S":"AAPL","sc":"H","sm":"Trading Halt","rc":"T12","rm":"Trading Halted; For information requested by NASDAQ","t":"2025-11-14T15:31:00Z","z":"C"},
+  {"T":"l","S":"AAPL","u":241.62,"d":218.61,"i":"B","t":"2025-11-14T15:31:05Z","z":"C"},
+  {"T":"c","S":"AAPL","x":"V","oi":52983525029461,"op":230.12,"os":100,"oc":["@","I"],"ci":52983525029470,"cp":230.11,"cs":100,"cc":["@","I"],"t":"2025-11-14T15:31:10Z","z":"C"}
+]"#;
+
+async fn accept(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+  let (tcp, _) = listener.accept().await.unwrap();
+  accept_async(tcp).await.unwrap()
+}
+
+async fn receive_json(socket: &mut WebSocketStream<TcpStream>) -> Value {
+  loop {
+    match socket.next().await.unwrap().unwrap() {
+      Message::Text(text) => return serde_json::from_str(text.as_str()).unwrap(),
+      Message::Binary(bytes) => return rmp_serde::from_slice(&bytes).unwrap(),
+      _ => continue,
+    }
+  }
+}
+
+async fn handshake(socket: &mut WebSocketStream<TcpStream>) {
+  socket
+    .send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+    .await
+    .unwrap();
+  let auth = receive_json(socket).await;
+  assert_eq!(auth["action"], "auth");
+  assert_eq!(auth["key"], "test_key");
+  assert_eq!(auth["secret"], "test_secret");
+  socket
+    .send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+    .await
+    .unwrap();
+}
+
+fn config(listener: &TcpListener, feed: MarketDataFeed) -> StreamConfig {
+  let base_url = format!("ws://{}", listener.local_addr().unwrap());
+  let mut config = StreamConfig::new(base_url, feed, "test_key".to_string(), "test_secret".to_string());
+  config.reconnect_delay = Duration::from_millis(10);
+  config
+}
+
+#[tokio::test]
+async fn test_stream_should_replay_recorded_iex_session() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let config = config(&listener, MarketDataFeed::Iex);
+  assert!(config.url.ends_with("/v2/iex"));
+
+  let server = tokio::spawn(async move {
+    let mut socket = accept(&listener).await;
+    handshake(&mut socket).await;
+    let subscribe = receive_json(&mut socket).await;
+    assert_eq!(subscribe["action"], "subscribe");
+    assert_eq!(subscribe["trades"][0], "AAPL");
+    assert_eq!(subscribe["updatedBars"][0], "AAPL");
+    socket
+      .send(Message::text(
+        r#"[{"T":"subscription","trades":["AAPL"],"quotes":["AAPL"],"bars":["AAPL"],"updatedBars":["AAPL"],"dailyBars":["AAPL"],"statuses":["AAPL"],"lulds":["AAPL"],"corrections":["AAPL"],"cancelErrors":["AAPL"]}]"#,
+      ))
+      .await
+      .unwrap();
+    socket.send(Message::text(IEX_SESSION)).await.unwrap();
+    let unsubscribe = receive_json(&mut socket).await;
+    assert_eq!(unsubscribe["action"], "unsubscribe");
+    assert_eq!(unsubscribe["quotes"][0], "AAPL");
+  });
+
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  let symbols = vec!["AAPL".to_string()];
+  stream
+    .subscribe(Subscription {
+      trades: symbols.clone(),
+      quotes: symbols.clone(),
+      bars: symbols.clone(),
+      updated_bars: symbols.clone(),
+      daily_bars: symbols.clone(),
+      statuses: symbols.clone(),
+      lulds: symbols.clone(),
+      corrections: symbols.clone(),
+      ..Default::default()
+    })
+    .unwrap();
+
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Subscription(subscription) => assert_eq!(subscription.lulds, symbols),
+    other => panic!("unexpected message {:?}", other),
+  }
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Trade(trade) => {
+      assert_eq!(trade.price, 230.12);
+      assert_eq!(trade.conditions, vec!["@", "I"]);
+      assert_eq!(trade.tape.as_deref(), Some("C"));
+    }
+    other => panic!("unexpected message {:?}", other),
+  }
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Quote(quote) => assert_eq!(quote.ask_size, 3.0),
+    other => panic!("unexpected message {:?}", other),
+  }
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Bar(bar) => assert_eq!(bar.bar.trade_count, Some(211)),
+    other => panic!("unexpected message {:?}", other),
+  }
+  assert!(matches!(stream.next().await.unwrap(), MarketDataMessage::UpdatedBar(_)));
+  assert!(matches!(stream.next().await.unwrap(), MarketDataMessage::DailyBar(_)));
+  match stream.next().await.unwrap() {
+    MarketDataMessage::TradingStatus(status) => assert_eq!(status.status_code, "H"),
+    other => panic!("unexpected message {:?}", other),
+  }
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Luld(luld) => assert_eq!(luld.limit_down_price, 218.61),
+    other => panic!("unexpected message {:?}", other),
+  }
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Correction(correction) => assert_eq!(correction.corrected_price, 230.11),
+    other => panic!("unexpected message {:?}", other),
+  }
+
+  stream
+    .unsubscribe(Subscription {
+      quotes: symbols,
+      ..Default::default()
+    })
+    .unwrap();
+  server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_should_resubscribe_after_reconnect() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let config = config(&listener, MarketDataFeed::Test);
+
+  let server = tokio::spawn(async move {
+    let mut first = accept(&listener).await;
+    handshake(&mut first).await;
+    let subscribe = receive_json(&mut first).await;
+    assert_eq!(subscribe["trades"][0], "FAKEPACA");
+    drop(first);
+
+    let mut second = accept(&listener).await;
+    handshake(&mut second).await;
+    let resubscribe = receive_json(&mut second).await;
+    assert_eq!(resubscribe["action"], "subscribe");
+    assert_eq!(resubscribe["trades"][0], "FAKEPACA");
+    second
+      .send(Message::text(
+        r#"[{"T":"t","S":"FAKEPACA","i":1,"x":"V","p":10.5,"s":1,"c":["@"],"t":"2025-11-14T15:30:00Z","z":"A"}]"#,
+      ))
+      .await
+      .unwrap();
+    second.next().await;
+  });
+
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  stream
+    .subscribe(Subscription {
+      trades: vec!["FAKEPACA".to_string()],
+      ..Default::default()
+    })
+    .unwrap();
+
+  match tokio::time::timeout(Duration::from_secs(5), stream.next())
+    .await
+    .unwrap()
+  {
+    Some(MarketDataMessage::Trade(trade)) => assert_eq!(trade.symbol, "FAKEPACA"),
+    other => panic!("unexpected message {:?}", other),
+  }
+  drop(stream);
+  server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_should_drop_newest_messages_when_buffer_is_full() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let mut config = config(&listener, MarketDataFeed::Iex);
+  config.buffer_size = 2;
+
+  let server = tokio::spawn(async move {
+    let mut socket = accept(&listener).await;
+    handshake(&mut socket).await;
+    let trades: Vec<String> = (1..=5)
+      .map(|id| {
+        format!(
+          r#"{{"T":"t","S":"AAPL","i":{id},"x":"V","p":230.0,"s":1,"c":["@"],"t":"2025-11-14T15:30:00Z","z":"C"}}"#
+        )
+      })
+      .collect();
+    socket
+      .send(Message::text(format!("[{}]", trades.join(","))))
+      .await
+      .unwrap();
+    socket.next().await;
+  });
+
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  tokio::time::sleep(Duration::from_millis(200)).await;
+
+  for expected_id in 1..=2 {
+    match stream.next().await.unwrap() {
+      MarketDataMessage::Trade(trade) => assert_eq!(trade.id, Some(expected_id)),
+      other => panic!("unexpected message {:?}", other),
+    }
+  }
+  assert_eq!(stream.dropped_messages(), 3);
+  drop(stream);
+  server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_should_skip_only_the_malformed_messages_of_a_frame() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let config = config(&listener, MarketDataFeed::Iex);
+
+  let server = tokio::spawn(async move {
+    let mut socket = accept(&listener).await;
+    handshake(&mut socket).await;
+    let trade = |id: u64, price: &str| {
+      format!(
+        r#"{{"T":"t","S":"AAPL","i":{id},"x":"V","p":{price},"s":1,"c":["@"],"t":"2025-11-14T15:30:00Z","z":"C"}}"#
+      )
+    };
+    let frame = format!(
+      "[{},{},{}]",
+      trade(1, "230.0"),
+      trade(2, r#""oops""#),
+      trade(3, "230.1")
+    );
+    socket.send(Message::text(frame)).await.unwrap();
+    socket.send(Message::text("not a list")).await.unwrap();
+    socket
+      .send(Message::text(format!("[{}]", trade(4, "230.2"))))
+      .await
+      .unwrap();
+    socket.next().await;
+  });
+
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  for expected_id in [1, 3, 4] {
+    match tokio::time::timeout(Duration::from_secs(5), stream.next())
+      .await
+      .unwrap()
+    {
+      Some(MarketDataMessage::Trade(trade)) => assert_eq!(trade.id, Some(expected_id)),
+      other => panic!("unexpected message {:?}", other),
+    }
+  }
+  assert_eq!(stream.malformed_messages(), 2);
+  assert_eq!(stream.dropped_messages(), 0);
+  drop(stream);
+  server.await.unwrap();
+}
+
+fn msgpack_str(buffer: &mut Vec<u8>, value: &str) {
+  buffer.push(0xa0 | value.len() as u8);
+  buffer.extend_from_slice(value.as_bytes());
+}
+
+fn msgpack_f64(buffer: &mut Vec<u8>, value: f64) {
+  buffer.push(0xcb);
+  buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+fn msgpack_timestamp(buffer: &mut Vec<u8>, seconds: u64, nanos: u64) {
+  buffer.extend_from_slice(&[0xd7, 0xff]);
+  buffer.extend_from_slice(&((nanos << 34) | seconds).to_be_bytes());
+}
+
+#[tokio::test]
+async fn test_options_stream_should_decode_msgpack_frames() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let config = config(&listener, MarketDataFeed::Options(OptionFeed::Indicative));
+  assert!(config.url.ends_with("/v1beta1/indicative"));
+
+  let server = tokio::spawn(async move {
+    let mut socket = accept(&listener).await;
+    let connected = rmp_serde::to_vec_named(&vec![serde_json::json!({"T": "success", "msg": "connected"})]).unwrap();
+    socket.send(Message::binary(connected)).await.unwrap();
+    let auth = receive_json(&mut socket).await;
+    assert_eq!(auth["action"], "auth");
+    let authenticated =
+      rmp_serde::to_vec_named(&vec![serde_json::json!({"T": "success", "msg": "authenticated"})]).unwrap();
+    socket.send(Message::binary(authenticated)).await.unwrap();
+
+    let mut frame = vec![0x91, 0x87];
+    msgpack_str(&mut frame, "T");
+    msgpack_str(&mut frame, "t");
+    msgpack_str(&mut frame, "S");
+    msgpack_str(&mut frame, "AAPL251219C00200000");
+    msgpack_str(&mut frame, "t");
+    msgpack_timestamp(&mut frame, 1_763_134_200, 500_000_000);
+    msgpack_str(&mut frame, "p");
+    msgpack_f64(&mut frame, 28.15);
+    msgpack_str(&mut frame, "s");
+    frame.push(0x05);
+    msgpack_str(&mut frame, "x");
+    msgpack_str(&mut frame, "C");
+    msgpack_str(&mut frame, "c");
+    msgpack_str(&mut frame, "I");
+    socket.send(Message::binary(frame)).await.unwrap();
+    socket.next().await;
+  });
+
+  let mut stream = MarketDataStream::connect(config).await.unwrap();
+  match stream.next().await.unwrap() {
+    MarketDataMessage::Trade(trade) => {
+      assert_eq!(trade.symbol, "AAPL251219C00200000");
+      assert_eq!(trade.size, 5.0);
+      assert_eq!(trade.conditions, vec!["I"]);
+      assert_eq!(trade.timestamp.to_rfc3339(), "2025-11-14T15:30:00.500+00:00");
+    }
+    other => panic!("unexpected message {:?}", other),
+  }
+  drop(stream);
+  server.await.unwrap();
+}
@@ -0,0 +1,166 @@
+use alpaca_trade_api_rust::{
+  api::{
+    NewsApi,
+    NewsQueryParameter,
+  },
+  prelude::{
+    Client,
+    NewsImageSize,
+  },
+  stream::{
+    MarketDataFeed,
+    NewsStream,
+    StreamConfig,
+  },
+};
+use futures_util::{
+  SinkExt,
+  StreamExt,
+};
+use httpmock::{
+  Method::GET,
+  MockServer,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::{
+  accept_async,
+  tungstenite::Message,
+};
+
+#[tokio::test]
+async fn test_get_news_should_follow_pages() {
+  let ms = MockServer::start();
+  let first_page = r#"
+  {
+    "news": [
+      {
+        "id": 24843171,
+        "headline": "Apple Leader in Phone Sales in China for Second Straight Month in November",
+        "author": "Charles Gross",
+        "created_at": "2025-11-14T14:03:12Z",
+        "updated_at": "2025-11-14T14:03:13Z",
+        "summary": "",
+        "content": "<p>According to data</p>",
+        "url": "https://www.benzinga.com/news/25/11/24843171/apple-leader",
+        "images": [
+          { "size": "large", "url": "https://cdn.benzinga.com/files/imagecache/2048x1536xUP/images/story/2025/apple.jpeg" },
+          { "size": "thumb", "url": "https://cdn.benzinga.com/files/imagecache/250x187xUP/images/story/2025/apple.jpeg" }
+        ],
+        "symbols": ["AAPL"],
+        "source": "benzinga"
+      }
+    ],
+    "next_page_token": "MTcwMjQ2NjE5MjAwMDAwMDAwMHwyNDg0MzE3MQ=="
+  }
+  "#;
+  let second_page = r#"
+  {
+    "news": [
+      {
+        "id": 24843150,
+        "headline": "Tesla Recalls Cybertruck",
+        "author": "Benzinga Newsdesk",
+        "created_at": "2025-11-14T13:01:00Z",
+        "updated_at": "2025-11-14T13:01:00Z",
+        "summary": "Tesla is recalling vehicles",
+        "url": null,
+        "images": [],
+        "symbols": ["TSLA"],
+        "source": "benzinga"
+      }
+    ],
+    "next_page_token": null
+  }
+  "#;
+  let first_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/news")
+      .query_param("symbols", "AAPL,TSLA")
+      .query_param("exclude_contentless", "true")
+      .query_param_missing("page_token");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(first_page);
+  });
+  let second_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/news")
+      .query_param("page_token", "MTcwMjQ2NjE5MjAwMDAwMDAwMHwyNDg0MzE3MQ==");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(second_page);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let mut parameter = NewsQueryParameter {
+    symbols: vec!["AAPL".to_string(), "TSLA".to_string()],
+    exclude_contentless: Some(true),
+    ..Default::default()
+  };
+
+  let mut articles = vec![];
+  loop {
+    let response = api_client.get_news(&parameter).await.unwrap();
+    articles.extend(response.news);
+    match response.next_page_token {
+      Some(token) => parameter.page_token = Some(token),
+      None => break,
+    }
+  }
+
+  first_mock.assert();
+  second_mock.assert();
+  assert_eq!(articles.len(), 2);
+  assert_eq!(articles[0].symbols, vec!["AAPL"]);
+  assert_eq!(articles[0].images[1].size, NewsImageSize::Thumb);
+  assert_eq!(articles[1].url, None);
+  assert_eq!(articles[1].content, "");
+}
+
+#[tokio::test]
+async fn test_news_stream_should_yield_articles() {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let base_url = format!("ws://{}", listener.local_addr().unwrap());
+  let config = StreamConfig::new(
+    base_url,
+    MarketDataFeed::News,
+    "test_key".to_string(),
+    "test_secret".to_string(),
+  );
+  assert!(config.url.ends_with("/v1beta1/news"));
+
+  let server = tokio::spawn(async move {
+    let (tcp, _) = listener.accept().await.unwrap();
+    let mut socket = accept_async(tcp).await.unwrap();
+    socket
+      .send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+      .await
+      .unwrap();
+    socket.next().await.unwrap().unwrap();
+    socket
+      .send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+      .await
+      .unwrap();
+    let subscribe = socket.next().await.unwrap().unwrap();
+    assert_eq!(subscribe.to_text().unwrap(), r#"{"action":"subscribe","news":["*"]}"#);
+    socket
+      .send(Message::text(
+        r#"[{"T":"subscription","news":["*"]},{"T":"n","id":24918784,"headline":"Corsair Reports Purchase Of Majority Ownership In iDisplay","summary":"Corsair Gaming, Inc. (NASDAQ:CRSR)","author":"Benzinga Newsdesk","created_at":"2025-11-14T14:57:55Z","updated_at":"2025-11-14T14:57:56Z","url":"https://www.benzinga.com/m-a/22/01/24918784/corsair","content":"<p>Corsair Gaming</p>","symbols":["CRSR"],"source":"benzinga"}]"#,
+      ))
+      .await
+      .unwrap();
+    socket.next().await;
+  });
+
+  let mut stream = NewsStream::connect(config, vec!["*".to_string()]).await.unwrap();
+  let article = stream.next().await.unwrap();
+  assert_eq!(article.id, 24918784);
+  assert_eq!(article.symbols, vec!["CRSR"]);
+  assert!(article.images.is_empty());
+  drop(stream);
+  server.await.unwrap();
+}
@@ -0,0 +1,225 @@
+use alpaca_trade_api_rust::{
+  api::{
+    OptionBarsQueryParameter,
+    OptionChainQueryParameter,
+    OptionLatestQueryParameter,
+    OptionMarketDataApi,
+    TimeFrame,
+  },
+  prelude::{
+    Client,
+    OptionContract,
+    OptionFeed,
+    join_option_chain,
+  },
+};
+use httpmock::{
+  Method::GET,
+  MockServer,
+};
+
+#[tokio::test]
+async fn test_get_option_bars_should_return_bars() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "bars": {
+      "AAPL251219C00200000": [
+        { "c": 28.15, "h": 28.4, "l": 27.9, "n": 18, "o": 28.0, "t": "2025-11-14T05:00:00Z", "v": 64, "vw": 28.12 }
+      ]
+    },
+    "next_page_token": null
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/options/bars")
+      .query_param("symbols", "AAPL251219C00200000")
+      .query_param("timeframe", "1Day");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = OptionBarsQueryParameter {
+    symbols: vec!["AAPL251219C00200000".to_string()],
+    timeframe: TimeFrame::Day(1),
+    start: None,
+    end: None,
+    limit: None,
+    page_token: None,
+    sort: None,
+  };
+
+  match api_client.get_option_bars(&parameter).await {
+    Ok(response) => {
+      let bars = response.bars.get("AAPL251219C00200000").unwrap();
+      assert_eq!(bars.len(), 1);
+      assert_eq!(bars[0].volume, 64.0);
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_option_latest_quotes_should_use_opra_feed() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "quotes": {
+      "AAPL251219C00200000": {
+        "ap": 28.3, "as": 12, "ax": "C", "bp": 28.05, "bs": 9, "bx": "X", "c": "A", "t": "2025-11-14T20:59:59.9Z"
+      }
+    }
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/options/quotes/latest")
+      .query_param("feed", "opra");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = OptionLatestQueryParameter {
+    symbols: vec!["AAPL251219C00200000".to_string()],
+    feed: Some(OptionFeed::Opra),
+  };
+
+  match api_client.get_option_latest_quotes(&parameter).await {
+    Ok(response) => {
+      let quote = response.quotes.get("AAPL251219C00200000").unwrap();
+      assert_eq!(quote.bid_price, 28.05);
+      assert_eq!(quote.ask_size, 12);
+      assert_eq!(quote.ask_exchange, "C");
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_get_option_chain_should_join_with_contracts() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "snapshots": {
+      "AA251114C00020000": {
+        "greeks": { "delta": 0.8123, "gamma": 0.0412, "rho": 0.0101, "theta": -0.0311, "vega": 0.0152 },
+        "impliedVolatility": 0.4821,
+        "latestQuote": { "ap": 13.9, "as": 10, "ax": "C", "bp": 13.4, "bs": 8, "bx": "N", "c": " ", "t": "2025-11-07T20:59:59Z" },
+        "latestTrade": { "c": "I", "p": 13.65, "s": 1, "t": "2025-11-07T19:30:00Z", "x": "C" }
+      },
+      "AA251114C00024000": {
+        "greeks": { "delta": 0.3512, "gamma": 0.0833, "rho": 0.0051, "theta": -0.0412, "vega": 0.0221 },
+        "impliedVolatility": 0.5102
+      }
+    },
+    "next_page_token": null
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/options/snapshots/AA")
+      .query_param("feed", "indicative");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let contracts: Vec<OptionContract> = serde_json::from_str(
+    r#"
+  [
+    {
+      "id": "0c7826be-8606-4100-9e0a-94a1b6f5aaad",
+      "symbol": "AA251114C00020000",
+      "name": "AA Nov 14 2025 20 Call",
+      "tradable": true,
+      "expiration_date": "2025-11-14",
+      "underlying_symbol": "AA",
+      "underlying_asset_id": "3ca0202f-01f4-41a0-bb0c-c8864e767ebd",
+      "type": "call",
+      "style": "american",
+      "strike_price": "20",
+      "multiplier": "100",
+      "size": "100",
+      "open_interest": 1520,
+      "deliverables": null
+    },
+    {
+      "id": "f8df3699-b0a4-4666-9bd9-ebf129dcdab3",
+      "symbol": "AA251114C00024000",
+      "name": "AA Nov 14 2025 24 Call",
+      "tradable": true,
+      "expiration_date": "2025-11-14",
+      "underlying_symbol": "AA",
+      "underlying_asset_id": "3ca0202f-01f4-41a0-bb0c-c8864e767ebd",
+      "type": "call",
+      "style": "american",
+      "strike_price": "24",
+      "multiplier": "100",
+      "size": "100",
+      "open_interest": 80,
+      "deliverables": null
+    },
+    {
+      "id": "1b2f9c4e-7d6a-4c1e-9f7b-2a3c4d5e6f70",
+      "symbol": "AA251114C00030000",
+      "name": "AA Nov 14 2025 30 Call",
+      "tradable": true,
+      "expiration_date": "2025-11-14",
+      "underlying_symbol": "AA",
+      "underlying_asset_id": "3ca0202f-01f4-41a0-bb0c-c8864e767ebd",
+      "type": "call",
+      "style": "american",
+      "strike_price": "30",
+      "multiplier": "100",
+      "size": "100",
+      "deliverables": null
+    }
+  ]
+  "#,
+  )
+  .unwrap();
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = OptionChainQueryParameter {
+    feed: Some(OptionFeed::Indicative),
+    ..Default::default()
+  };
+
+  match api_client.get_option_chain("AA", &parameter).await {
+    Ok(response) => {
+      let chain = join_option_chain(contracts, response.snapshots);
+      assert_eq!(chain.len(), 3);
+      assert!(chain[2].snapshot.is_none());
+      assert_eq!(chain[0].implied_volatility(), Some(0.4821));
+
+      let screened: Vec<&str> = chain
+        .iter()
+        .filter(|entry| entry.delta().is_some_and(|delta| delta > 0.5))
+        .filter(|entry| entry.open_interest().is_some_and(|open_interest| open_interest >= 100))
+        .map(|entry| entry.contract.symbol.as_str())
+        .collect();
+      assert_eq!(screened, vec!["AA251114C00020000"]);
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
@@ -37,7 +37,7 @@ use httpmock::{
 use std::str::FromStr;
 use uuid::Uuid;
 
-mod shared;
+pub mod shared;
 
 #[tokio::test]
 async fn test_get_orders_should_return_order_list() {
@@ -216,7 +216,7 @@ async fn test_create_order_should_return_ok() {
     }),
     stop_loss: Some(StopLoss {
       stop_price: Money::from_f64(20.43),
-      limit_price: Money::from_f64(23.23),
+      limit_price: Some(Money::from_f64(23.23)),
     }),
     position_intent: Some(PositionIntent::BuyToClose),
   };
@@ -480,9 +480,9 @@ async fn test_replace_order_by_id_should_return_ok() {
   let request_body = &ReplaceOrderByIdRequestBody {
     qty: NumberAsString::from_f64(4.0),
     time_in_force: TimeInForce::DAY,
-    limit_price: Money::from_f64(100.0),
-    stop_price: Money::from_f64(90.0),
-    trail: Money::from_f64(10.0),
+    limit_price: Some(Money::from_f64(100.0)),
+    stop_price: Some(Money::from_f64(90.0)),
+    trail: Some(Money::from_f64(10.0)),
     client_order_id: String::from("test_client_order_id"),
   };
 
@@ -532,3 +532,19 @@ async fn test_delete_order_by_id_should_return_ok() {
     )
     .await;
 }
+
+#[test]
+fn test_order_class_should_serialize_to_the_names_it_deserializes_from() {
+  for (order_class, name) in [
+    (OrderClass::Simple, "simple"),
+    (OrderClass::Oco, "oco"),
+    (OrderClass::Otc, "oto"),
+    (OrderClass::Bracket, "bracket"),
+    (OrderClass::Mleg, "mleg"),
+    (OrderClass::Empty, ""),
+  ] {
+    let json = serde_json::to_string(&order_class).unwrap();
+    assert_eq!(json, format!("\"{name}\""));
+    assert_eq!(serde_json::from_str::<OrderClass>(&json).unwrap(), order_class);
+  }
+}
@@ -0,0 +1,310 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+    PositionApi,
+  },
+  execution::{
+    EmulatedOrderStatus,
+    ExitLeg,
+    OrderEmulator,
+    Trail,
+  },
+  prelude::{
+    Bar,
+    Client,
+    MarketDataMessage,
+    OrderStatus,
+    enums::Side,
+  },
+  sim::{
+    Broker,
+    NextBarOpen,
+    SimHandle,
+    SimServer,
+    VolumeParticipation,
+  },
+};
+use chrono::{
+  DateTime,
+  TimeDelta,
+  TimeZone,
+  Utc,
+};
+
+pub mod shared;
+use shared::{
+  order,
+  state_file,
+};
+
+const PAIR: &str = "BTC/USD";
+
+fn buy(qty: f64) -> OrderRequestBody {
+  order(PAIR, Side::Buy, qty, None)
+}
+
+async fn simulator(broker: Broker) -> (SimHandle, SimServer, Client) {
+  shared::simulator(SimServer::new(broker), &[(PAIR, 50_000.0)]).await
+}
+
+fn at(seconds: i64) -> DateTime<Utc> {
+  Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap() + TimeDelta::seconds(seconds)
+}
+
+fn trade(price: f64, seconds: i64) -> MarketDataMessage {
+  serde_json::from_value(serde_json::json!({
+    "T": "t",
+    "S": PAIR,
+    "i": seconds,
+    "p": price,
+    "s": 0.1,
+    "t": at(seconds).to_rfc3339(),
+    "tks": "B",
+  }))
+  .unwrap()
+}
+
+#[tokio::test]
+async fn test_trailing_stop_should_keep_its_high_water_mark_across_restarts() {
+  let (_handle, server, client) = simulator(Broker::new(100_000.0)).await;
+  client.create_order(&buy(1.0)).await.unwrap();
+  let path = state_file("emulated_orders");
+  let mut emulator = OrderEmulator::open(&path).unwrap();
+  let id = emulator
+    .trailing_stop(PAIR, Side::Sell, 1.0, Trail::Percent(2.0))
+    .unwrap();
+  assert_eq!(emulator.subscription().trades, vec![PAIR.to_string()]);
+
+  for (price, seconds) in [(50_000.0, 0), (51_000.0, 1), (50_500.0, 2)] {
+    assert!(
+      emulator
+        .on_message(&client, &trade(price, seconds))
+        .await
+        .unwrap()
+        .is_empty()
+    );
+  }
+  assert_eq!(emulator.order(&id).unwrap().stop_price(), Some(49_980.0));
+  drop(emulator);
+
+  let mut emulator = OrderEmulator::open(&path).unwrap();
+  emulator.recover(&client).await.unwrap();
+  let order = emulator.order(&id).unwrap();
+  assert_eq!((order.status, order.hwm), (EmulatedOrderStatus::Armed, Some(51_000.0)));
+
+  server.set_price(PAIR, 49_900.0);
+  let triggered = emulator.on_price(&client, PAIR, 49_900.0, at(3)).await.unwrap();
+  assert_eq!(triggered, vec![id]);
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(order.status, EmulatedOrderStatus::Triggered);
+  assert_eq!(
+    (order.exit_leg, order.trigger_price, order.triggered_at),
+    (Some(ExitLeg::TrailingStop), Some(49_900.0), Some(at(3)))
+  );
+  let exit = client
+    .get_order_by_client_order_id(&format!("{id}-trail"))
+    .await
+    .unwrap();
+  assert_eq!(Some(exit.id), order.exit_order_id);
+  assert_eq!((exit.side, exit.status), (Side::Sell, OrderStatus::Filled));
+  assert!(client.get_all_open_positions().await.unwrap().is_empty());
+  assert!(!emulator.has_active_orders());
+
+  // triggered orders stay triggered after a restart
+  let emulator = OrderEmulator::open(&path).unwrap();
+  assert_eq!(emulator.order(&id).unwrap().status, EmulatedOrderStatus::Triggered);
+  std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_oco_should_trigger_one_leg_only() {
+  let (_handle, server, client) = simulator(Broker::new(100_000.0)).await;
+  client.create_order(&buy(1.0)).await.unwrap();
+  let mut emulator = OrderEmulator::new();
+  let id = emulator.oco(PAIR, Side::Sell, 1.0, 52_000.0, 48_000.0).unwrap();
+
+  assert!(
+    emulator
+      .on_price(&client, PAIR, 51_999.0, at(0))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  assert!(
+    emulator
+      .on_price(&client, "ETH/USD", 60_000.0, at(1))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  server.set_price(PAIR, 52_100.0);
+  assert_eq!(
+    emulator.on_price(&client, PAIR, 52_100.0, at(2)).await.unwrap(),
+    vec![id]
+  );
+  server.set_price(PAIR, 47_000.0);
+  assert!(
+    emulator
+      .on_price(&client, PAIR, 47_000.0, at(3))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(order.exit_leg, Some(ExitLeg::TakeProfit));
+  assert!(client.get_order_by_client_order_id(&format!("{id}-sl")).await.is_err());
+  assert!(client.get_all_open_positions().await.unwrap().is_empty());
+
+  assert!(emulator.oco(PAIR, Side::Sell, 1.0, 48_000.0, 52_000.0).is_err());
+  assert!(emulator.oco(PAIR, Side::Buy, 1.0, 52_000.0, 48_000.0).is_err());
+  assert!(emulator.oco("AAPL", Side::Sell, 1.0, 52_000.0, 48_000.0).is_err());
+  assert!(
+    emulator
+      .trailing_stop(PAIR, Side::Sell, 1.0, Trail::Percent(100.0))
+      .is_err()
+  );
+}
+
+#[tokio::test]
+async fn test_bracket_should_arm_exits_for_the_filled_entry() {
+  let (_handle, server, client) = simulator(Broker::new(100_000.0).with_immediate_fills(false)).await;
+  let mut emulator = OrderEmulator::new();
+  let id = emulator.bracket(&client, buy(0.5), 55_000.0, 47_500.0).await.unwrap();
+  assert_eq!(emulator.order(&id).unwrap().status, EmulatedOrderStatus::AwaitingEntry);
+
+  // the entry has not filled yet, so a price beyond the stop triggers nothing
+  assert!(
+    emulator
+      .on_price(&client, PAIR, 47_000.0, at(0))
+      .await
+      .unwrap()
+      .is_empty()
+  );
+  server.set_price(PAIR, 50_000.0);
+  // fetched again only once the poll interval passed
+  emulator.on_price(&client, PAIR, 50_000.0, at(0)).await.unwrap();
+  assert_eq!(emulator.order(&id).unwrap().status, EmulatedOrderStatus::AwaitingEntry);
+  emulator.on_price(&client, PAIR, 50_000.0, at(1)).await.unwrap();
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(
+    (order.status, order.side, order.qty),
+    (EmulatedOrderStatus::Armed, Side::Sell, 0.5)
+  );
+
+  assert_eq!(
+    emulator.on_price(&client, PAIR, 47_400.0, at(2)).await.unwrap(),
+    vec![id]
+  );
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(order.exit_leg, Some(ExitLeg::StopLoss));
+  let entry = client
+    .get_order_by_client_order_id(&format!("{id}-entry"))
+    .await
+    .unwrap();
+  assert_eq!(Some(entry.id), order.entry_order_id);
+  let exit = client.get_order_by_client_order_id(&format!("{id}-sl")).await.unwrap();
+  assert_eq!((exit.side, exit.qty.unwrap().value()), (Side::Sell, 0.5));
+
+  let pending = emulator.bracket(&client, buy(0.5), 55_000.0, 47_500.0).await.unwrap();
+  emulator.cancel(&client, &pending).await.unwrap();
+  let entry_id = emulator.order(&pending).unwrap().entry_order_id.unwrap();
+  assert_eq!(
+    client.get_order_by_id(&entry_id).await.unwrap().status,
+    OrderStatus::Canceled
+  );
+  assert!(emulator.cancel(&client, &pending).await.is_err());
+  emulator.prune().unwrap();
+  assert!(emulator.orders().is_empty());
+}
+
+#[tokio::test]
+async fn test_bracket_should_cover_each_partial_fill_of_its_entry() {
+  let broker = Broker::new(100_000.0)
+    .with_immediate_fills(false)
+    .with_fill_model(VolumeParticipation {
+      model: NextBarOpen,
+      max_participation: 0.1,
+    });
+  let (_handle, server, client) = simulator(broker).await;
+  // each bar fills 0.2 of the entry
+  let fill_bar = || {
+    server.with_broker(|broker| {
+      let bar = Bar {
+        timestamp: Utc::now(),
+        open: 50_000.0,
+        high: 50_000.0,
+        low: 50_000.0,
+        close: 50_000.0,
+        volume: 2.0,
+        trade_count: None,
+        vwap: None,
+      };
+      broker.process_bar(PAIR, &bar, true);
+    })
+  };
+  let mut emulator = OrderEmulator::new();
+  let id = emulator.bracket(&client, buy(0.5), 55_000.0, 47_500.0).await.unwrap();
+
+  fill_bar();
+  emulator.on_price(&client, PAIR, 50_000.0, at(0)).await.unwrap();
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(
+    (order.status, order.qty, order.entry_open),
+    (EmulatedOrderStatus::Armed, 0.2, true)
+  );
+  fill_bar();
+  emulator.on_price(&client, PAIR, 50_000.0, at(1)).await.unwrap();
+  assert!((emulator.order(&id).unwrap().qty - 0.4).abs() < 1e-9);
+
+  // the stop loss covers what filled and the rest of the entry is canceled
+  assert_eq!(
+    emulator.on_price(&client, PAIR, 47_400.0, at(2)).await.unwrap(),
+    vec![id]
+  );
+  let order = emulator.order(&id).unwrap();
+  assert!(!order.entry_open);
+  let entry = client.get_order_by_id(&order.entry_order_id.unwrap()).await.unwrap();
+  assert_eq!(entry.status, OrderStatus::Canceled);
+  let exit = client.get_order_by_client_order_id(&format!("{id}-sl")).await.unwrap();
+  assert_eq!(exit.side, Side::Sell);
+  assert!((exit.qty.unwrap().value() - 0.4).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_recover_should_submit_exits_that_failed_before_a_restart() {
+  let (_handle, _server, client) = simulator(Broker::new(100_000.0)).await;
+  client.create_order(&buy(1.0)).await.unwrap();
+  let unreachable = Client::new(
+    "http://127.0.0.1:1".to_string(),
+    "key".to_string(),
+    "secret".to_string(),
+  );
+  let path = state_file("emulated_orders");
+  let mut emulator = OrderEmulator::open(&path).unwrap();
+  let id = emulator.oco(PAIR, Side::Sell, 1.0, 52_000.0, 48_000.0).unwrap();
+
+  assert_eq!(
+    emulator.on_price(&unreachable, PAIR, 47_900.0, at(0)).await.unwrap(),
+    vec![id]
+  );
+  let order = emulator.order(&id).unwrap();
+  assert_eq!((order.status, order.attempts), (EmulatedOrderStatus::Triggering, 1));
+  assert!(order.error.is_some());
+  drop(emulator);
+
+  let mut emulator = OrderEmulator::open(&path).unwrap();
+  emulator.recover(&client).await.unwrap();
+  let order = emulator.order(&id).unwrap();
+  assert_eq!(
+    (order.status, order.error.as_deref()),
+    (EmulatedOrderStatus::Triggered, None)
+  );
+  let exit = client.get_order_by_client_order_id(&format!("{id}-sl")).await.unwrap();
+  assert_eq!(order.exit_order_id, Some(exit.id));
+  assert!(client.get_all_open_positions().await.unwrap().is_empty());
+  std::fs::remove_file(path).unwrap();
+}
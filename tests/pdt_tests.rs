@@ -0,0 +1,125 @@
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+    ReplaceOrderByIdRequestBody,
+    TimeFrame,
+  },
+  backtest::Backtest,
+  prelude::{
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  risk::{
+    PdtGuard,
+    PdtMode,
+    PdtViolation,
+  },
+};
+use chrono::TimeDelta;
+
+pub mod shared;
+use shared::{
+  bars,
+  order,
+};
+
+///
+///AAPL at 100 in 15 minute bars over the morning of 2025-12-01
+fn backtest(equity: f64) -> Backtest {
+  shared::backtest(
+    equity,
+    TimeFrame::Minute(15),
+    bars(TimeDelta::minutes(15), &[100.0; 12]),
+  )
+}
+
+///
+///Submit `order` and step the backtest so it fills
+async fn fill(guard: &mut PdtGuard<Backtest>, order: &OrderRequestBody) -> anyhow::Result<()> {
+  guard.create_order(order).await?;
+  guard.inner_mut().step();
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_pdt_guard_should_block_the_fourth_day_trade() {
+  let mut guard = PdtGuard::new(backtest(20_000.0), vec![], PdtMode::Block);
+  for _ in 0..3 {
+    fill(&mut guard, &order("AAPL", Side::Buy, 10.0, None)).await.unwrap();
+    fill(&mut guard, &order("AAPL", Side::Sell, 10.0, None)).await.unwrap();
+  }
+  let (tracker, today) = guard.tracker().await.unwrap();
+  assert_eq!(tracker.day_trades(today).len(), 3);
+
+  // opening is still allowed, closing the same day is not
+  fill(&mut guard, &order("AAPL", Side::Buy, 10.0, None)).await.unwrap();
+  let error = guard
+    .create_order(&order("AAPL", Side::Sell, 5.0, None))
+    .await
+    .unwrap_err();
+  assert_eq!(
+    error.downcast_ref::<PdtViolation>().unwrap(),
+    &PdtViolation {
+      symbol: "AAPL".to_string(),
+      side: Side::Sell,
+      day_trades: 3,
+      equity: 20_000.0,
+    }
+  );
+}
+
+#[tokio::test]
+async fn test_pdt_guard_should_warn_or_pass_through() {
+  let mut guard = PdtGuard::new(backtest(20_000.0), vec![], PdtMode::Warn);
+  for _ in 0..4 {
+    fill(&mut guard, &order("AAPL", Side::Buy, 10.0, None)).await.unwrap();
+    fill(&mut guard, &order("AAPL", Side::Sell, 10.0, None)).await.unwrap();
+  }
+  let warnings = guard.take_warnings();
+  assert_eq!(warnings.len(), 1);
+  assert_eq!(warnings[0].day_trades, 3);
+  assert!(guard.take_warnings().is_empty());
+
+  let mut guard = PdtGuard::new(backtest(30_000.0), vec![], PdtMode::Block);
+  for _ in 0..4 {
+    fill(&mut guard, &order("AAPL", Side::Buy, 10.0, None)).await.unwrap();
+    fill(&mut guard, &order("AAPL", Side::Sell, 10.0, None)).await.unwrap();
+  }
+}
+
+#[tokio::test]
+async fn test_pdt_guard_should_check_replacements() {
+  let mut guard = PdtGuard::new(backtest(20_000.0), vec![], PdtMode::Block);
+  for _ in 0..2 {
+    fill(&mut guard, &order("AAPL", Side::Buy, 10.0, None)).await.unwrap();
+    fill(&mut guard, &order("AAPL", Side::Sell, 10.0, None)).await.unwrap();
+  }
+  fill(&mut guard, &order("AAPL", Side::Buy, 20.0, None)).await.unwrap();
+
+  // the resting sell is allowed before the third day trade, its replacement after it is not
+  let sell = OrderRequestBody {
+    _type: OrderType::Limit,
+    limit_price: Some(Money::from_f64(200.0)),
+    ..order("AAPL", Side::Sell, 5.0, None)
+  };
+  let sell = guard.create_order(&sell).await.unwrap();
+  fill(&mut guard, &order("AAPL", Side::Sell, 10.0, None)).await.unwrap();
+  let replacement = ReplaceOrderByIdRequestBody {
+    qty: NumberAsString::from_f64(10.0),
+    time_in_force: TimeInForce::DAY,
+    limit_price: Some(Money::from_f64(190.0)),
+    stop_price: None,
+    trail: None,
+    client_order_id: "pdt-replacement".to_string(),
+  };
+  let error = guard.replace_order_by_id(&sell.id, &replacement).await.unwrap_err();
+  assert_eq!(error.downcast_ref::<PdtViolation>().unwrap().day_trades, 3);
+}
@@ -0,0 +1,378 @@
+use alpaca_trade_api_rust::{
+  analytics::{
+    Benchmark,
+    BenchmarkComparison,
+    PerformanceMetrics,
+    PerformanceReport,
+    PortfolioPoint,
+    PortfolioSeries,
+    session_dates,
+  },
+  prelude::{
+    ActivityType,
+    Bar,
+    Order,
+    PortfolioHistory,
+    Position,
+  },
+};
+use chrono::{
+  NaiveDate,
+  TimeDelta,
+  TimeZone,
+  Utc,
+};
+use serde_json::json;
+
+pub mod shared;
+use shared::close;
+
+///
+///Daily history starting unfunded, funded with 10,000 and topped up with 2,000 on the fourth day
+fn history() -> PortfolioHistory {
+  let days = [6, 7, 8, 9, 10, 13];
+  serde_json::from_value(json!({
+    "timestamp": days.map(|day| Utc.with_ymd_and_hms(2025, 1, day, 5, 0, 0).unwrap().timestamp()),
+    "equity": [0.0, 10_000.0, 10_500.0, 10_000.0, 12_600.0, 11_970.0, 12_700.0],
+    "profit_loss": [0.0, 0.0, 500.0, 0.0, 600.0, -30.0, 700.0],
+    "profit_loss_pct": [0.0, 0.0, 0.05, 0.0, 0.06, -0.0025, 0.0575],
+    "base_value": "0",
+    "base_value_asof": "2025-01-03",
+    "timeframe": "1D",
+    "cashflow": null
+  }))
+  .unwrap()
+}
+
+#[test]
+fn test_portfolio_series_should_zip_history_and_adjust_for_cash_flows() {
+  let series = PortfolioSeries::from(&history()).with_cash_flows(&[0.0, 10_000.0, 0.0, 0.0, 2_000.0, 0.0, 0.0]);
+
+  // the seventh equity has no timestamp
+  assert_eq!(series.points.len(), 6);
+  assert_eq!(series.start(), Some(Utc.with_ymd_and_hms(2025, 1, 6, 5, 0, 0).unwrap()));
+  assert_eq!(series.points[4].cash_flow, 2_000.0);
+
+  let returns = series.period_returns();
+  assert_eq!(returns.len(), 4);
+  assert!(close(returns[0].1, 0.05));
+  // the 2,000 deposit is not performance
+  assert!(close(returns[2].1, 0.06));
+  assert!(close(series.total_return(), 11_970.0 / 10_000.0 - 1.0));
+  assert!(close(series.net_cash_flow(), 12_000.0));
+  assert!(close(
+    series.time_weighted_return(),
+    1.05 * (10_000.0 / 10_500.0) * 1.06 * (11_970.0 / 12_600.0) - 1.0
+  ));
+
+  let drawdowns = series.drawdowns();
+  assert_eq!(drawdowns.len(), 2);
+  assert!(close(drawdowns[0].depth, 10_000.0 / 10_500.0 - 1.0));
+  assert_eq!(
+    drawdowns[0].recovered,
+    Some(Utc.with_ymd_and_hms(2025, 1, 10, 5, 0, 0).unwrap())
+  );
+  assert_eq!(drawdowns[0].duration, TimeDelta::days(2));
+  let max_drawdown = series.max_drawdown().unwrap();
+  assert!(close(max_drawdown.depth, -0.05));
+  assert_eq!(max_drawdown.recovered, None);
+  assert_eq!(max_drawdown.duration, TimeDelta::days(3));
+}
+
+#[test]
+fn test_performance_metrics_should_annualize_daily_returns() {
+  let mut history = history();
+  history
+    .timestamp
+    .push(Utc.with_ymd_and_hms(2025, 1, 14, 5, 0, 0).unwrap().timestamp() as u64);
+  let series = PortfolioSeries::from(&history).with_cash_flows(&[0.0, 10_000.0, 0.0, 0.0, 2_000.0, 0.0, 0.0]);
+  let metrics = PerformanceMetrics::compute(&series, 0.04);
+
+  assert_eq!(metrics.trading_days, 5);
+  assert!(close(metrics.time_weighted_return, 0.06841269841269826));
+  assert!((metrics.volatility - 0.9225774922538409).abs() < 1e-9);
+  assert!((metrics.sharpe.unwrap() - 3.965477670786616).abs() < 1e-9);
+  assert!((metrics.sortino.unwrap() - 7.43964956276724).abs() < 1e-9);
+  assert!((metrics.calmar.unwrap() - 541.6653950786545).abs() < 1e-6);
+  assert_eq!(
+    metrics.best_day.unwrap().0,
+    NaiveDate::from_ymd_opt(2025, 1, 14).unwrap()
+  );
+  assert_eq!(
+    metrics.worst_day,
+    Some((NaiveDate::from_ymd_opt(2025, 1, 13).unwrap(), 11_970.0 / 12_600.0 - 1.0))
+  );
+  assert_eq!((metrics.positive_days, metrics.negative_days), (3, 2));
+
+  // recovered on the last day, the longest drawdown ran from Friday to Tuesday
+  let longest = metrics.longest_drawdown.unwrap();
+  assert_eq!(longest.duration, TimeDelta::days(4));
+  assert_eq!(serde_json::to_value(longest).unwrap()["duration"], json!(4.0));
+
+  // intraday samples collapse to the last one of each trading day
+  let intraday = PortfolioSeries::new(
+    [(14, 10_000.0), (15, 10_100.0), (20, 10_200.0), (39, 10_000.0)]
+      .into_iter()
+      .map(|(hour, equity)| PortfolioPoint {
+        timestamp: Utc.with_ymd_and_hms(2025, 1, 6, 0, 0, 0).unwrap() + TimeDelta::hours(hour),
+        equity,
+        profit_loss: 0.0,
+        profit_loss_pct: 0.0,
+        cash_flow: 0.0,
+      })
+      .collect(),
+  );
+  assert_eq!(intraday.daily().points.len(), 2);
+  assert!(close(intraday.daily_returns()[0].1, 10_000.0 / 10_200.0 - 1.0));
+}
+
+#[test]
+fn test_portfolio_history_should_decode_cash_flows_by_activity_type() {
+  let history: PortfolioHistory = serde_json::from_value(json!({
+    "timestamp": [
+      Utc.with_ymd_and_hms(2025, 1, 1, 5, 0, 0).unwrap().timestamp(),
+      Utc.with_ymd_and_hms(2025, 7, 2, 5, 0, 0).unwrap().timestamp(),
+      Utc.with_ymd_and_hms(2026, 1, 1, 5, 0, 0).unwrap().timestamp(),
+    ],
+    "equity": [10_000.0, 20_500.0, 22_000.0],
+    "profit_loss": [0.0, 500.0, 1_500.0],
+    "profit_loss_pct": [0.0, 0.05, 0.0732],
+    "base_value": "10000",
+    "base_value_asof": "2024-12-31",
+    "timeframe": "1D",
+    "cashflow": {
+      "CSD": [0.0, 10_000.0],
+      "DIV": [0.0, 200.0, 0.0],
+      "FEE": [0.0, 0.0, -5.0],
+      "CFEE": [0.0, 0.0, -1.0]
+    }
+  }))
+  .unwrap();
+
+  let cashflow = history.cashflow.as_ref().unwrap();
+  assert_eq!(
+    cashflow.activity_types().cloned().collect::<Vec<_>>(),
+    vec![
+      ActivityType::Csd,
+      ActivityType::Div,
+      ActivityType::Fee,
+      ActivityType::Other("CFEE".to_string())
+    ]
+  );
+  assert_eq!(cashflow.total(&ActivityType::Fee), -5.0);
+  assert_eq!(history.cash_flows(&ActivityType::Csd), vec![0.0, 10_000.0, 0.0]);
+  assert_eq!(history.cash_flows(&ActivityType::Csw), vec![0.0, 0.0, 0.0]);
+  // dividends and fees are performance, only the deposit crosses the account boundary
+  assert_eq!(history.external_cash_flows(), vec![0.0, 10_000.0, 0.0]);
+
+  let series = PortfolioSeries::from(&history);
+  assert!(close(series.time_weighted_return(), 1.05 * (22_000.0 / 20_500.0) - 1.0));
+  assert!((series.money_weighted_return().unwrap() - 0.13462697984706973).abs() < 1e-9);
+  assert_eq!(serde_json::to_value(cashflow).unwrap()["CFEE"], json!([0.0, 0.0, -1.0]));
+}
+
+#[test]
+fn test_benchmark_comparison_should_align_sessions_and_compute_relative_risk() {
+  // closing samples at 16:00 New York with a weekend sample and a 1,000 deposit on Monday
+  let dates = [(1, 27), (1, 28), (1, 29), (1, 30), (1, 31), (2, 1), (2, 3), (2, 4)];
+  let series = PortfolioSeries::new(
+    dates
+      .iter()
+      .zip([
+        10_000.0, 10_100.0, 10_050.0, 10_250.0, 10_200.0, 10_210.0, 11_300.0, 11_400.0,
+      ])
+      .zip([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1_000.0, 0.0])
+      .map(|((&(month, day), equity), cash_flow)| PortfolioPoint {
+        timestamp: Utc.with_ymd_and_hms(2025, month, day, 21, 0, 0).unwrap(),
+        equity,
+        profit_loss: 0.0,
+        profit_loss_pct: 0.0,
+        cash_flow,
+      })
+      .collect(),
+  );
+  // daily bars stamped at midnight New York
+  let bars: Vec<Bar> = [(1, 27), (1, 28), (1, 29), (1, 30), (1, 31), (2, 3), (2, 4)]
+    .iter()
+    .zip([600.0, 606.0, 603.0, 609.0, 606.0, 612.0, 610.0])
+    .map(|(&(month, day), close)| Bar {
+      timestamp: Utc.with_ymd_and_hms(2025, month, day, 5, 0, 0).unwrap(),
+      open: close,
+      high: close,
+      low: close,
+      close,
+      volume: 1_000.0,
+      trade_count: None,
+      vwap: None,
+    })
+    .collect();
+  let comparison = BenchmarkComparison::compute(&series, &Benchmark::from_bars("SPY", &bars), 0.04);
+
+  assert_eq!(comparison.start, NaiveDate::from_ymd_opt(2025, 1, 27));
+  assert_eq!(comparison.end, NaiveDate::from_ymd_opt(2025, 2, 4));
+  // Saturday is folded into Monday
+  assert_eq!(comparison.periods, 6);
+  assert!(close(comparison.portfolio_return, 0.03911504424778767));
+  assert!(close(comparison.benchmark_return, 610.0 / 600.0 - 1.0));
+  assert!(close(comparison.beta.unwrap(), 0.9899985345515234));
+  assert!(close(comparison.alpha.unwrap(), 0.9314139213212521));
+  assert!(close(comparison.correlation.unwrap(), 0.8049932177759042));
+  assert!(close(comparison.tracking_error, 0.09120574629368715));
+  assert!(close(comparison.information_ratio.unwrap(), 10.139584707096187));
+  assert!(close(comparison.up_capture.unwrap(), 1.3289765428756262));
+  assert!(close(comparison.down_capture.unwrap(), 0.0792638605233438));
+
+  assert_eq!(comparison.monthly.len(), 2);
+  assert!(close(comparison.monthly[0].portfolio, 0.02));
+  assert!(close(comparison.monthly[1].benchmark, 610.0 / 606.0 - 1.0));
+
+  let markdown = comparison.to_markdown();
+  assert!(markdown.starts_with("## Performance against SPY\n\n2025-01-27 to 2025-02-04, 6 sessions\n"));
+  assert!(markdown.contains("| Return | 3.91% | 1.67% |\n"));
+  assert!(markdown.contains("| Beta | 0.99 |\n"));
+  assert!(markdown.contains("| 2025-02 | 1.87% | 0.66% | 1.21% |\n"));
+}
+
+#[test]
+fn test_session_dates_should_keep_utc_midnight_daily_series_on_their_date() {
+  let midnights = [
+    Utc.with_ymd_and_hms(2025, 3, 7, 0, 0, 0).unwrap(),
+    Utc.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap(),
+  ];
+  assert_eq!(
+    session_dates(&midnights),
+    vec![
+      NaiveDate::from_ymd_opt(2025, 3, 7).unwrap(),
+      NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()
+    ]
+  );
+
+  // across the DST change daily bars move from 05:00 to 04:00 UTC and stay on their session
+  let equity_bars = [
+    Utc.with_ymd_and_hms(2025, 3, 7, 5, 0, 0).unwrap(),
+    Utc.with_ymd_and_hms(2025, 3, 10, 4, 0, 0).unwrap(),
+    Utc.with_ymd_and_hms(2025, 3, 11, 0, 0, 0).unwrap(),
+  ];
+  assert_eq!(
+    session_dates(&equity_bars),
+    vec![
+      NaiveDate::from_ymd_opt(2025, 3, 7).unwrap(),
+      NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(),
+      NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()
+    ]
+  );
+}
+
+fn filled_order(symbol: &str, side: &str, qty: &str, price: Option<&str>, filled_at: Option<&str>) -> Order {
+  serde_json::from_value(json!({
+    "id": "bff50af3-8fb4-4a7e-8ffe-6527cdf6b453",
+    "client_order_id": "1df8303c-e84c-402c-85db-819008f479de",
+    "created_at": "2025-01-07T14:59:48Z",
+    "updated_at": "2025-01-07T14:59:49Z",
+    "submitted_at": "2025-01-07T14:59:48Z",
+    "filled_at": filled_at,
+    "expired_at": null,
+    "canceled_at": null,
+    "failed_at": null,
+    "replaced_at": null,
+    "replaced_by": null,
+    "replaces": null,
+    "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+    "symbol": symbol,
+    "asset_class": "us_equity",
+    "notional": null,
+    "qty": qty,
+    "filled_qty": if filled_at.is_some() { qty } else { "0" },
+    "filled_avg_price": price,
+    "order_class": "",
+    "type": "market",
+    "side": side,
+    "position_intent": "buy_to_open",
+    "time_in_force": "day",
+    "limit_price": null,
+    "stop_price": null,
+    "status": if filled_at.is_some() { "filled" } else { "canceled" },
+    "extended_hours": false,
+    "legs": null,
+    "trail_percent": null,
+    "trail_price": null,
+    "hwm": null
+  }))
+  .unwrap()
+}
+
+fn position(symbol: &str, side: &str, market_value: &str) -> Position {
+  serde_json::from_value(json!({
+    "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+    "symbol": symbol,
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "asset_marginable": true,
+    "qty": "10",
+    "avg_entry_price": "100",
+    "side": side,
+    "market_value": market_value,
+    "cost_basis": market_value,
+    "unrealized_pl": "0",
+    "unrealized_plpc": "0",
+    "unrealized_intraday_pl": "0",
+    "unrealized_intraday_plpc": "0",
+    "current_price": "100",
+    "lastday_price": "100",
+    "change_today": "0",
+    "qty_available": "10"
+  }))
+  .unwrap()
+}
+
+#[test]
+fn test_performance_report_should_render_self_contained_html() {
+  let orders = [
+    filled_order("AAPL", "buy", "10", Some("150.5"), Some("2025-01-07T15:00:00Z")),
+    filled_order("AAPL", "sell", "4", None, None),
+    filled_order("<MSFT>", "sell", "2", Some("400"), Some("2025-01-09T15:00:00Z")),
+  ];
+  let positions = [position("AAPL", "long", "6000"), position("TSLA", "short", "-2000")];
+  let series = PortfolioSeries::from(&history()).with_cash_flows(&[0.0, 10_000.0, 0.0, 0.0, 2_000.0, 0.0]);
+  let html = PerformanceReport::from_series("Q1 & archive", series)
+    .with_orders(&orders)
+    .with_positions(&positions)
+    .with_cash(2_000.0)
+    .with_generated_at(Utc.with_ymd_and_hms(2025, 1, 14, 12, 0, 0).unwrap())
+    .render();
+
+  assert!(html.starts_with("<!DOCTYPE html>"));
+  assert!(html.contains("<h1>Q1 &amp; archive</h1>"));
+  assert!(html.contains("2025-01-06 to 2025-01-13, generated 2025-01-14 12:00:00 UTC"));
+  // equity, drawdown, heatmap and allocation charts
+  assert_eq!(html.matches("<svg ").count(), 4);
+  assert!(!html.contains("<script") && !html.contains("src=") && !html.contains("<link"));
+  assert!(html.contains("<tr><td>Max drawdown</td><td class=\"number\">-5.00%</td></tr>"));
+  assert!(html.contains(">Jan</text>") && html.contains(">2025</text>"));
+  assert!(html.contains("AAPL 60.0%") && html.contains("TSLA (short) 20.0%") && html.contains("Cash 20.0%"));
+
+  // newest fill first, the canceled order is left out
+  let trades = &html[html.find("<h2>Trades</h2>").unwrap()..];
+  assert_eq!(trades.matches("<tr><td>").count(), 2);
+  assert!(trades.find("&lt;MSFT&gt;").unwrap() < trades.find("AAPL").unwrap());
+  assert!(trades.contains(
+    "<td>2025-01-07 15:00</td><td>AAPL</td><td>buy</td><td>market</td><td class=\"number\">10</td><td \
+     class=\"number\">150.50</td><td class=\"number\">1505.00</td>"
+  ));
+}
+
+#[test]
+fn test_performance_report_should_fit_every_holding_in_the_allocation_legend() {
+  let positions: Vec<Position> = (0..20)
+    .map(|index| position(&format!("S{index:02}"), "long", &(1_000 - index).to_string()))
+    .collect();
+  let html = PerformanceReport::new("many holdings", &history())
+    .with_positions(&positions)
+    .with_cash(500.0)
+    .render();
+
+  // 21 legend rows of 18 between margins of 12
+  let allocation = &html[html.rfind("<svg ").unwrap()..];
+  assert!(allocation.contains(r#"viewBox="0 0 720 402""#));
+  assert!(allocation.contains(r#"y="382.0">Cash "#));
+}
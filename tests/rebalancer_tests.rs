@@ -0,0 +1,158 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    AccountApi,
+    AssetsApi,
+    OrderApi,
+    PositionApi,
+  },
+  portfolio::{
+    RebalanceMode,
+    Rebalancer,
+  },
+  prelude::{
+    Client,
+    TimeInForce,
+    enums::Side,
+  },
+  sim::{
+    Broker,
+    SimHandle,
+    SimServer,
+  },
+};
+use std::collections::BTreeMap;
+
+pub mod shared;
+use shared::{
+  close,
+  order,
+  simulator,
+};
+
+///
+///Account of 10,000 with 60% VTI, 27% BRK, 3% XYZ and 10% cash
+async fn account() -> (SimHandle, Client) {
+  let (handle, _, client) = simulator(
+    SimServer::new(Broker::new(10_000.0)).with_market_open(true),
+    &[("VTI", 120.0), ("XYZ", 30.0), ("BRK", 540.0), ("BND", 72.5)],
+  )
+  .await;
+  for (symbol, qty) in [("VTI", 50.0), ("XYZ", 10.0), ("BRK", 5.0)] {
+    client.create_order(&order(symbol, Side::Buy, qty, None)).await.unwrap();
+  }
+  (handle, client)
+}
+
+#[tokio::test]
+async fn test_rebalancer_should_plan_sells_before_buys_within_the_cash_buffer() {
+  let (_handle, client) = account().await;
+  let account = client.get_account().await.unwrap();
+  let positions = client.get_all_open_positions().await.unwrap();
+  let mut assets = BTreeMap::new();
+  for symbol in ["VTI", "XYZ", "BRK", "BND"] {
+    let mut asset = client.get_asset_by_symbol_or_id(symbol).await.unwrap();
+    asset.fractionable = symbol != "BRK";
+    assets.insert(symbol.to_string(), asset);
+  }
+  let rebalancer = Rebalancer::new([("VTI", 0.5), ("BND", 0.3), ("BRK", 0.2)])
+    .unwrap()
+    .with_price("BND", 72.5)
+    .with_cash_buffer(0.02)
+    .with_drift_band(0.01);
+
+  let plan = rebalancer.plan(&account, &positions, &assets).unwrap();
+  let line = |symbol: &str| plan.lines.iter().find(|line| line.symbol == symbol).unwrap();
+
+  assert_eq!(plan.equity, 10_000.0);
+  let vti = line("VTI");
+  assert!(close(vti.target_weight, 0.49));
+  assert_eq!((vti.side, vti.qty), (Some(Side::Sell), Some(9.166666666)));
+  // whole shares only, 740 of drift is one share
+  let brk = line("BRK");
+  assert_eq!((brk.side, brk.qty), (Some(Side::Sell), Some(1.0)));
+  assert_eq!(brk.expected_qty, 4.0);
+  let xyz = line("XYZ");
+  assert_eq!(
+    (xyz.side, xyz.qty, xyz.target_weight),
+    (Some(Side::Sell), Some(10.0), 0.0)
+  );
+  assert_eq!(xyz.expected_qty, 0.0);
+  // 2,940 wanted but only 1,000 cash and 1,940 of sales minus the 200 buffer are available
+  let bnd = line("BND");
+  assert_eq!(
+    (bnd.side, bnd.qty, bnd.notional),
+    (Some(Side::Buy), None, Some(2_739.99))
+  );
+  assert!(close(plan.expected_cash, 200.00999992));
+
+  let orders = plan.orders(TimeInForce::DAY);
+  let sides: Vec<(&str, Side)> = orders.iter().map(|order| (order.symbol.as_str(), order.side)).collect();
+  assert_eq!(
+    sides,
+    vec![
+      ("BRK", Side::Sell),
+      ("VTI", Side::Sell),
+      ("XYZ", Side::Sell),
+      ("BND", Side::Buy)
+    ]
+  );
+
+  let table = plan.to_table();
+  assert!(table.starts_with("symbol  price"));
+  assert!(table.contains("buy $2739.99"));
+
+  let wide_band = Rebalancer::new([("VTI", 0.5), ("BND", 0.3), ("BRK", 0.2)])
+    .unwrap()
+    .with_price("BND", 72.5)
+    .with_cash_buffer(0.02)
+    .with_drift_band(0.12)
+    .with_liquidate_untargeted(false);
+  let plan = wide_band.plan(&account, &positions, &assets).unwrap();
+  let line = |symbol: &str| plan.lines.iter().find(|line| line.symbol == symbol).unwrap();
+  assert_eq!(line("VTI").skipped.as_deref(), Some("within drift band"));
+  assert_eq!(line("XYZ").skipped.as_deref(), Some("not targeted"));
+  assert_eq!(line("BND").notional, Some(800.0));
+
+  assert!(Rebalancer::new([("VTI", 0.7), ("BND", 0.4)]).is_err());
+  let unpriced = Rebalancer::new([("QQQ", 0.5)]).unwrap();
+  assert!(unpriced.plan(&account, &positions, &assets).is_err());
+}
+
+#[tokio::test]
+async fn test_rebalancer_should_submit_orders_only_in_execute_mode() {
+  let (_handle, client) = account().await;
+  let rebalancer = Rebalancer::new([("VTI", 0.5), ("BND", 0.3), ("BRK", 0.2)])
+    .unwrap()
+    .with_price("BND", 72.5)
+    .with_cash_buffer(0.02);
+
+  let dry_run = rebalancer.run(&client, RebalanceMode::DryRun).await.unwrap();
+  assert!(dry_run.lines.iter().all(|line| line.order_id.is_none()));
+  assert_eq!(client.get_all_open_positions().await.unwrap().len(), 3);
+
+  let executed = rebalancer.run(&client, RebalanceMode::Execute).await.unwrap();
+  assert_eq!(executed.mode, RebalanceMode::Execute);
+  assert!(
+    executed
+      .lines
+      .iter()
+      .all(|line| line.order_id.is_some() && line.error.is_none())
+  );
+
+  let positions: BTreeMap<String, f64> = client
+    .get_all_open_positions()
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|position| (position.symbol.clone(), position.qty.value()))
+    .collect();
+  assert_eq!(positions.len(), 3);
+  assert!(close(positions["VTI"], 50.0 - 9.166666666));
+  // the simulator lists every asset as fractionable
+  assert!(close(positions["BRK"], 5.0 - 1.37037037));
+  assert!(close(positions["BND"] * 72.5, executed.lines[0].notional.unwrap()));
+  let account = client.get_account().await.unwrap();
+  assert!(close(account.cash.value(), executed.expected_cash));
+}
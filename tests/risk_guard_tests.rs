@@ -0,0 +1,252 @@
+use alpaca_trade_api_rust::{
+  api::{
+    OrderApi,
+    OrderRequestBody,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+    TimeFrame,
+  },
+  backtest::Backtest,
+  prelude::{
+    TimeInForce,
+    enums::Side,
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  risk::{
+    RiskGuard,
+    RiskRules,
+    RiskViolation,
+  },
+};
+use chrono::TimeDelta;
+
+pub mod shared;
+use shared::{
+  bars,
+  order,
+};
+
+fn backtest() -> Backtest {
+  shared::backtest(100_000.0, TimeFrame::Day(1), bars(TimeDelta::days(1), &[100.0; 3]))
+}
+
+async fn violation<C>(guard: &RiskGuard<C>, order: &OrderRequestBody) -> RiskViolation
+where
+  RiskGuard<C>: OrderApi,
+{
+  let error = guard.create_order(order).await.unwrap_err();
+  error.downcast_ref::<RiskViolation>().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_risk_guard_should_reject_orders_breaking_the_rules() {
+  let rules = RiskRules {
+    max_order_notional: Some(5_000.0),
+    max_position_notional: Some(6_000.0),
+    denied_symbols: ["GME".to_string()].into(),
+    max_price_deviation_pct: Some(5.0),
+    ..Default::default()
+  };
+  let guard = RiskGuard::new(backtest(), rules);
+  guard.update_quote("AAPL", 99.9, 100.1);
+
+  assert_eq!(
+    violation(&guard, &order("GME", Side::Buy, 1.0, None)).await,
+    RiskViolation::SymbolDenied("GME".to_string())
+  );
+  assert!(matches!(
+    violation(&guard, &order("AAPL", Side::Buy, 60.0, None)).await,
+    RiskViolation::MaxOrderNotional { limit: 5_000.0, .. }
+  ));
+  assert!(matches!(
+    violation(&guard, &order("AAPL", Side::Buy, 1.0, Some(90.0))).await,
+    RiskViolation::PriceBand { price: 90.0, .. }
+  ));
+  assert_eq!(
+    violation(&guard, &order("AAPL", Side::Sell, 1.0, None)).await,
+    RiskViolation::ShortingDisabled {
+      symbol: "AAPL".to_string()
+    }
+  );
+
+  guard.create_order(&order("AAPL", Side::Buy, 40.0, None)).await.unwrap();
+  guard.inner().with_broker(|broker| broker.set_price("AAPL", 100.0));
+  assert_eq!(
+    guard
+      .get_open_position_by_symbol_or_id("AAPL")
+      .await
+      .unwrap()
+      .qty
+      .value(),
+    40.0
+  );
+  assert_eq!(
+    violation(&guard, &order("AAPL", Side::Buy, 30.0, None)).await,
+    RiskViolation::MaxPosition {
+      symbol: "AAPL".to_string(),
+      notional: 70.0 * 100.1,
+      limit: 6_000.0,
+    }
+  );
+  // reducing the position is always allowed
+  guard
+    .create_order(&order("AAPL", Side::Sell, 30.0, None))
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_risk_guard_should_count_open_sells_against_the_position() {
+  let guard = RiskGuard::new(backtest(), RiskRules::default());
+  guard.create_order(&order("AAPL", Side::Buy, 40.0, None)).await.unwrap();
+  guard.inner().with_broker(|broker| broker.set_price("AAPL", 100.0));
+  let resting = guard
+    .create_order(&order("AAPL", Side::Sell, 30.0, Some(200.0)))
+    .await
+    .unwrap();
+
+  // 30 of the 40 shares are already on their way out
+  assert_eq!(
+    violation(&guard, &order("AAPL", Side::Sell, 20.0, None)).await,
+    RiskViolation::ShortingDisabled {
+      symbol: "AAPL".to_string()
+    }
+  );
+  guard
+    .create_order(&order("AAPL", Side::Sell, 10.0, None))
+    .await
+    .unwrap();
+  guard.inner().with_broker(|broker| broker.set_price("AAPL", 100.0));
+  // a replacement takes the place of the order it replaces
+  guard
+    .replace_order_by_id(
+      &resting.id,
+      &ReplaceOrderByIdRequestBody {
+        qty: NumberAsString::from_f64(30.0),
+        time_in_force: TimeInForce::DAY,
+        limit_price: Some(Money::from_f64(190.0)),
+        stop_price: None,
+        trail: None,
+        client_order_id: "risk-replacement".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_risk_guard_should_limit_exposure_and_order_rate() {
+  let path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+  std::fs::write(
+    &path,
+    r#"{"max_gross_exposure": 0.05, "allowed_symbols": ["AAPL"], "max_orders_per_minute": 2}"#,
+  )
+  .unwrap();
+  let rules = RiskRules::from_file(&path).unwrap();
+  std::fs::remove_file(&path).unwrap();
+  let guard = RiskGuard::new(backtest(), rules);
+
+  assert_eq!(
+    violation(&guard, &order("MSFT", Side::Buy, 1.0, None)).await,
+    RiskViolation::SymbolNotAllowed("MSFT".to_string())
+  );
+  assert_eq!(
+    violation(&guard, &order("AAPL", Side::Buy, 51.0, Some(100.0))).await,
+    RiskViolation::MaxGrossExposure {
+      exposure: 5_100.0,
+      limit: 5_000.0,
+      equity: 100_000.0,
+    }
+  );
+  guard
+    .create_order(&order("AAPL", Side::Buy, 10.0, Some(100.0)))
+    .await
+    .unwrap();
+  guard
+    .create_order(&order("AAPL", Side::Buy, 10.0, Some(100.0)))
+    .await
+    .unwrap();
+  assert_eq!(
+    violation(&guard, &order("AAPL", Side::Buy, 10.0, Some(100.0))).await,
+    RiskViolation::OrderRate { limit: 2 }
+  );
+}
+
+#[tokio::test]
+async fn test_risk_guard_should_match_crypto_positions_and_price_notional_sells() {
+  let mut backtest = Backtest::new(100_000.0, TimeFrame::Day(1))
+    .with_bars("AAPL", bars(TimeDelta::days(1), &[100.0; 3]))
+    .with_bars("BTC/USD", bars(TimeDelta::days(1), &[1_000.0; 3]));
+  backtest.step();
+  let rules = RiskRules {
+    max_position_notional: Some(2_500.0),
+    ..Default::default()
+  };
+  let guard = RiskGuard::new(backtest, rules);
+  guard.update_quote("BTC/USD", 999.0, 1_001.0);
+
+  guard
+    .create_order(&order("BTC/USD", Side::Buy, 2.0, None))
+    .await
+    .unwrap();
+  guard.inner().with_broker(|broker| broker.set_price("BTC/USD", 1_000.0));
+  // the position is reported as BTCUSD and still counts for the pair
+  assert_eq!(guard.get_all_open_positions().await.unwrap()[0].symbol, "BTCUSD");
+  assert!(matches!(
+    violation(&guard, &order("BTC/USD", Side::Buy, 1.0, None)).await,
+    RiskViolation::MaxPosition { .. }
+  ));
+  guard
+    .create_order(&order("BTC/USD", Side::Sell, 1.0, None))
+    .await
+    .unwrap();
+
+  let mut notional_sell = order("AAPL", Side::Sell, 1.0, None);
+  notional_sell.qty = None;
+  notional_sell.notional = Some(Money::from_f64(500.0));
+  assert_eq!(
+    violation(&guard, &notional_sell).await,
+    RiskViolation::NoReferencePrice("AAPL".to_string())
+  );
+}
+
+#[cfg(feature = "sim")]
+#[tokio::test]
+async fn test_risk_guard_should_not_let_concurrent_orders_exceed_the_order_rate() {
+  use alpaca_trade_api_rust::{
+    prelude::Client,
+    sim::{
+      Broker,
+      SimServer,
+    },
+  };
+
+  let server = SimServer::new(Broker::new(100_000.0)).with_market_open(true);
+  server.set_price("AAPL", 100.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let rules = RiskRules {
+    max_orders_per_minute: Some(2),
+    max_order_notional: Some(5_000.0),
+    ..Default::default()
+  };
+  let guard = RiskGuard::new(client, rules);
+
+  // a rejected order gives its submission back
+  assert!(matches!(
+    violation(&guard, &order("AAPL", Side::Buy, 60.0, Some(100.0))).await,
+    RiskViolation::MaxOrderNotional { .. }
+  ));
+  let orders: Vec<OrderRequestBody> = (0..5).map(|_| order("AAPL", Side::Buy, 1.0, Some(100.0))).collect();
+  let results = futures_util::future::join_all(orders.iter().map(|order| guard.create_order(order))).await;
+  assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+  for error in results.iter().filter_map(|result| result.as_ref().err()) {
+    assert_eq!(
+      error.downcast_ref::<RiskViolation>(),
+      Some(&RiskViolation::OrderRate { limit: 2 })
+    );
+  }
+}
@@ -0,0 +1,169 @@
+use alpaca_trade_api_rust::{
+  api::{
+    MarketMoversQueryParameter,
+    MostActivesBy,
+    MostActivesQueryParameter,
+    ScreenerApi,
+    resolve_screener_assets,
+    sync_screener_watch_list,
+  },
+  prelude::{
+    Client,
+    MarketType,
+  },
+};
+use httpmock::{
+  Method::{
+    GET,
+    POST,
+    PUT,
+  },
+  MockServer,
+};
+
+const WATCH_LIST_RESPONSE: &str = r#"
+{
+  "id": "3174d6df-7726-44b4-a5bd-7fda5ae6e009",
+  "account_id": "abe25343-a7ba-4255-bdeb-f7e013e9ee5d",
+  "created_at": "2025-11-14T13:35:14.803Z",
+  "updated_at": "2025-11-14T13:35:14.803Z",
+  "name": "today's movers",
+  "assets": []
+}
+"#;
+
+#[tokio::test]
+async fn test_get_most_actives_should_return_ranked_symbols() {
+  let ms = MockServer::start();
+  let mock_response_body = r#"
+  {
+    "most_actives": [
+      { "symbol": "NVDA", "trade_count": 1131418, "volume": 210421456 },
+      { "symbol": "TSLA", "trade_count": 1021102, "volume": 98452011 }
+    ],
+    "last_updated": "2025-11-14T20:59:59.123Z"
+  }
+  "#;
+  let endpoint_mock = ms.mock(|when, then| {
+    when
+      .method(GET)
+      .path("/v1beta1/screener/stocks/most-actives")
+      .query_param("by", "volume")
+      .query_param("top", "2");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(mock_response_body);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let parameter = MostActivesQueryParameter {
+    by: Some(MostActivesBy::Volume),
+    top: Some(2),
+  };
+
+  match api_client.get_most_actives(&parameter).await {
+    Ok(most_actives) => {
+      assert_eq!(most_actives.most_actives.len(), 2);
+      assert_eq!(most_actives.most_actives[0].symbol, "NVDA");
+      assert_eq!(most_actives.most_actives[1].trade_count, 1021102);
+    }
+    Err(error) => {
+      endpoint_mock.assert();
+      panic!("Error: {}", error)
+    }
+  }
+}
+
+#[tokio::test]
+async fn test_market_movers_should_resolve_assets_and_sync_watch_list() {
+  let ms = MockServer::start();
+  let movers_mock = ms.mock(|when, then| {
+    when.method(GET).path("/v1beta1/screener/stocks/movers");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"
+      {
+        "gainers": [ { "symbol": "AAPL", "percent_change": 7.21, "change": 16.5, "price": 245.1 } ],
+        "losers": [ { "symbol": "TSLA", "percent_change": -6.02, "change": -19.2, "price": 299.8 } ],
+        "market_type": "stocks",
+        "last_updated": "2025-11-14T20:59:59.123Z"
+      }
+      "#,
+    );
+  });
+  let asset_mock = ms.mock(|when, then| {
+    when.method(GET).path("/v2/assets/AAPL");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"
+      {
+        "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "class": "us_equity",
+        "cusip": "037833100",
+        "exchange": "NASDAQ",
+        "symbol": "AAPL",
+        "name": "Apple Inc. Common Stock",
+        "status": "active",
+        "tradable": true,
+        "marginable": true,
+        "shortable": true,
+        "easy_to_borrow": true,
+        "fractionable": true,
+        "attributes": ["has_options"]
+      }
+      "#,
+    );
+  });
+  let watch_lists_mock = ms.mock(|when, then| {
+    when.method(GET).path("/v2/watchlists");
+    then.status(200).header("Content-Type", "application/json").body(
+      r#"
+      [
+        {
+          "id": "3174d6df-7726-44b4-a5bd-7fda5ae6e009",
+          "account_id": "abe25343-a7ba-4255-bdeb-f7e013e9ee5d",
+          "created_at": "2025-11-13T13:35:14.803Z",
+          "updated_at": "2025-11-13T13:35:14.803Z",
+          "name": "today's movers"
+        }
+      ]
+      "#,
+    );
+  });
+  let update_mock = ms.mock(|when, then| {
+    when
+      .method(PUT)
+      .path("/v2/watchlists/3174d6df-7726-44b4-a5bd-7fda5ae6e009")
+      .json_body(serde_json::json!({ "name": "today's movers", "symbols": ["AAPL", "TSLA"] }));
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(WATCH_LIST_RESPONSE);
+  });
+  let create_mock = ms.mock(|when, then| {
+    when.method(POST).path("/v2/watchlists");
+    then
+      .status(200)
+      .header("Content-Type", "application/json")
+      .body(WATCH_LIST_RESPONSE);
+  });
+
+  let api_client = Client::new(ms.base_url(), "test_key".to_string(), "test_secret".to_string());
+  let movers = api_client
+    .get_market_movers(MarketType::Stocks, &MarketMoversQueryParameter { top: Some(1) })
+    .await
+    .unwrap();
+  movers_mock.assert();
+  assert_eq!(movers.gainers[0].percent_change, 7.21);
+
+  let assets = resolve_screener_assets(&api_client, &movers.gainers).await.unwrap();
+  asset_mock.assert();
+  assert_eq!(assets[0].name, "Apple Inc. Common Stock");
+
+  let watch_list = sync_screener_watch_list(&api_client, "today's movers", &movers.all())
+    .await
+    .unwrap();
+  watch_lists_mock.assert();
+  update_mock.assert();
+  create_mock.assert_calls(0);
+  assert_eq!(watch_list.name, "today's movers");
+}
@@ -1,8 +1,43 @@
-use alpaca_trade_api_rust::prelude::Client;
+//! Helpers shared by the integration tests. Test files declare it as `pub mod shared;` so the
+//! helpers a file does not use are not reported as dead code.
+
+#[cfg(feature = "sim")]
+use alpaca_trade_api_rust::sim::{
+  SimHandle,
+  SimServer,
+};
+use alpaca_trade_api_rust::{
+  api::{
+    OrderRequestBody,
+    TimeFrame,
+  },
+  backtest::Backtest,
+  prelude::{
+    Bar,
+    Client,
+    TimeInForce,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+};
+use chrono::{
+  TimeDelta,
+  TimeZone,
+  Utc,
+};
 use httpmock::{
   Method,
   MockServer,
 };
+use std::path::PathBuf;
+use uuid::Uuid;
+
 pub struct TestContext<'tst> {
   mock_server: &'tst MockServer,
   api_client: &'tst Client,
@@ -11,8 +46,8 @@ pub struct TestContext<'tst> {
 impl<'tst> TestContext<'tst> {
   pub fn new(mock_server: &'tst MockServer, api_client: &'tst Client) -> Self {
     Self {
-      mock_server: mock_server,
-      api_client: api_client,
+      mock_server,
+      api_client,
     }
   }
 
@@ -39,3 +74,81 @@ impl<'tst> TestContext<'tst> {
     assertion(self.api_client).await;
   }
 }
+
+///
+///Simple DAY order, a limit order when `limit_price` is set and a market order otherwise
+pub fn order(symbol: &str, side: Side, qty: f64, limit_price: Option<f64>) -> OrderRequestBody {
+  OrderRequestBody {
+    symbol: symbol.to_string(),
+    qty: Some(NumberAsString::from_f64(qty)),
+    notional: None,
+    side,
+    _type: if limit_price.is_some() {
+      OrderType::Limit
+    } else {
+      OrderType::Market
+    },
+    time_in_force: TimeInForce::DAY,
+    limit_price: limit_price.map(Money::from_f64),
+    stop_price: None,
+    trail_price: None,
+    trail_percent: None,
+    extended_hours: false,
+    client_order_id: None,
+    order_class: None,
+    legs: vec![],
+    take_profit: None,
+    stop_loss: None,
+    position_intent: None,
+  }
+}
+
+pub fn close(actual: f64, expected: f64) -> bool {
+  (actual - expected).abs() < 1e-9
+}
+
+///
+///Path of a state file in the temporary directory, unique to the calling test
+pub fn state_file(name: &str) -> PathBuf {
+  std::env::temp_dir().join(format!("{name}_{}.json", Uuid::new_v4()))
+}
+
+///
+///Flat bars at `prices`, `step` apart from 2025-12-01 15:00 UTC
+pub fn bars(step: TimeDelta, prices: &[f64]) -> Vec<Bar> {
+  let start = Utc.with_ymd_and_hms(2025, 12, 1, 15, 0, 0).unwrap();
+  prices
+    .iter()
+    .zip(0..)
+    .map(|(price, index)| Bar {
+      timestamp: start + step * index,
+      open: *price,
+      high: *price,
+      low: *price,
+      close: *price,
+      volume: 1_000_000.0,
+      trade_count: None,
+      vwap: None,
+    })
+    .collect()
+}
+
+///
+///Backtest of `equity` trading AAPL over `bars`, stepped to the first one
+pub fn backtest(equity: f64, timeframe: TimeFrame, bars: Vec<Bar>) -> Backtest {
+  let mut backtest = Backtest::new(equity, timeframe).with_bars("AAPL", bars);
+  backtest.step();
+  backtest
+}
+
+///
+///Spawn `server` with `prices` set, the server is handed back to drive the market
+#[cfg(feature = "sim")]
+pub async fn simulator(server: SimServer, prices: &[(&str, f64)]) -> (SimHandle, SimServer, Client) {
+  for (symbol, price) in prices {
+    server.set_price(symbol, *price);
+  }
+  let handle = server.clone().spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  (handle, server, client)
+}
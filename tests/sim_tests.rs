@@ -0,0 +1,249 @@
+#![cfg(feature = "sim")]
+
+use alpaca_trade_api_rust::{
+  api::{
+    AccountApi,
+    AddAssetReqBody,
+    AllOrdersQueryParameter,
+    ClockApi,
+    ClosePositionParam,
+    OrderApi,
+    OrderStatus as OrdersFilter,
+    PositionApi,
+    ReplaceOrderByIdRequestBody,
+    WatchListApi,
+    WatchListReqBody,
+  },
+  prelude::{
+    Client,
+    OrderStatus,
+    TimeInForce,
+    TradeUpdateEvent,
+    enums::{
+      OrderType,
+      Side,
+    },
+    utils::{
+      Money,
+      NumberAsString,
+    },
+  },
+  sim::{
+    Broker,
+    SimServer,
+  },
+  stream::{
+    StreamConfig,
+    TradeUpdatesStream,
+  },
+};
+
+pub mod shared;
+use shared::order;
+
+fn open_orders() -> AllOrdersQueryParameter {
+  AllOrdersQueryParameter {
+    status: Some(OrdersFilter::Open),
+    limit: None,
+    after: None,
+    until: None,
+    direction: None,
+    nested: None,
+    symbols: None,
+    side: None,
+    asset_class: None,
+    before_order_id: None,
+    after_order_id: None,
+  }
+}
+
+#[tokio::test]
+async fn test_simulator_should_fill_orders_and_update_account() {
+  let server = SimServer::new(Broker::new(10_000.0)).with_market_open(true);
+  server.set_price("AAPL", 200.0);
+  let handle = server.clone().spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+
+  assert!(client.get_market_clock_info().await.unwrap().is_open);
+
+  let market = client
+    .create_order(&order("AAPL", Side::Buy, 10.0, None))
+    .await
+    .unwrap();
+  assert_eq!(market.status, OrderStatus::Filled);
+  assert_eq!(market.filled_avg_price.unwrap().value(), 200.0);
+
+  let limit = client
+    .create_order(&order("AAPL", Side::Buy, 5.0, Some(190.0)))
+    .await
+    .unwrap();
+  assert_eq!(limit.status, OrderStatus::New);
+  let account = client.get_account().await.unwrap();
+  assert_eq!(account.cash.value(), 8_000.0);
+  assert_eq!(account.buying_power.value(), 7_050.0);
+  assert_eq!(client.get_all_orders(&open_orders()).await.unwrap().len(), 1);
+
+  let filled = server.set_price("AAPL", 189.0);
+  assert_eq!(filled[0].id, limit.id);
+  let limit = client.get_order_by_id(&limit.id).await.unwrap();
+  assert_eq!(limit.status, OrderStatus::Filled);
+
+  let position = client.get_open_position_by_symbol_or_id("AAPL").await.unwrap();
+  assert_eq!(position.qty.value(), 15.0);
+  assert_eq!(position.market_value.value(), 15.0 * 189.0);
+
+  let closed = client
+    .close_open_position_by_symbol_or_id("AAPL", &ClosePositionParam::Qty(5.0))
+    .await
+    .unwrap();
+  assert_eq!(closed.status, OrderStatus::Filled);
+  let positions = client.get_all_open_positions().await.unwrap();
+  assert_eq!(positions[0].qty.value(), 10.0);
+  assert_eq!(client.get_account().await.unwrap().cash.value(), 8_000.0);
+
+  let error = client
+    .create_order(&order("AAPL", Side::Sell, 50.0, None))
+    .await
+    .unwrap_err();
+  assert!(error.to_string().contains("insufficient qty"));
+}
+
+#[tokio::test]
+async fn test_simulator_should_cancel_orders_and_manage_watch_lists() {
+  let server = SimServer::new(Broker::new(1_000.0));
+  server.set_price("AAPL", 100.0);
+  server.set_price("BTC/USD", 50_000.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+
+  let resting = client
+    .create_order(&order("AAPL", Side::Buy, 2.0, Some(90.0)))
+    .await
+    .unwrap();
+  client.delete_order_by_id(&resting.id).await.unwrap();
+  assert_eq!(
+    client.get_order_by_id(&resting.id).await.unwrap().status,
+    OrderStatus::Canceled
+  );
+  assert!(client.delete_order_by_id(&resting.id).await.is_err());
+
+  let watch_list = client
+    .create_watch_list(&WatchListReqBody {
+      name: "sim".to_string(),
+      symbols: vec!["AAPL".to_string()],
+    })
+    .await
+    .unwrap();
+  let watch_list = client
+    .add_asset_to_watch_list(
+      &watch_list.id,
+      &AddAssetReqBody {
+        symbol: "BTC/USD".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(watch_list.assets.len(), 2);
+  assert_eq!(client.get_watch_list_by_name("sim").await.unwrap().id, watch_list.id);
+  client.delete_watch_list_by_id(&watch_list.id).await.unwrap();
+  assert!(client.get_all_watch_lists().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_simulator_should_report_positions_it_could_not_close() {
+  let server = SimServer::new(Broker::new(10_000.0)).with_market_open(true);
+  server.set_price("AAPL", 100.0);
+  server.set_price("MSFT", 200.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+
+  client
+    .create_order(&order("AAPL", Side::Buy, 10.0, None))
+    .await
+    .unwrap();
+  client.create_order(&order("MSFT", Side::Buy, 5.0, None)).await.unwrap();
+  // the resting sell holds every AAPL share while its orders are kept
+  client
+    .create_order(&order("AAPL", Side::Sell, 10.0, Some(150.0)))
+    .await
+    .unwrap();
+
+  let mut infos = client.clost_all_open_positions(false).await.unwrap();
+  infos.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+  assert_eq!(infos.len(), 2);
+  assert_eq!(infos[0].symbol, "AAPL");
+  assert_eq!(infos[0].status, "403");
+  assert!(infos[0].order().is_none());
+  assert_eq!(infos[1].symbol, "MSFT");
+  assert_eq!(infos[1].status, "200");
+  assert_eq!(infos[1].order().unwrap().side, Side::Sell);
+}
+
+#[tokio::test]
+async fn test_simulator_should_keep_the_prices_a_replacement_leaves_out() {
+  let server = SimServer::new(Broker::new(10_000.0)).with_market_open(true);
+  server.set_price("AAPL", 200.0);
+  let handle = server.spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+
+  let mut stop_limit = order("AAPL", Side::Buy, 5.0, Some(215.0));
+  stop_limit._type = OrderType::StopLimit;
+  stop_limit.stop_price = Some(Money::from_f64(210.0));
+  let original = client.create_order(&stop_limit).await.unwrap();
+
+  let replacement = client
+    .replace_order_by_id(
+      &original.id,
+      &ReplaceOrderByIdRequestBody {
+        qty: NumberAsString::from_f64(5.0),
+        time_in_force: TimeInForce::GTC,
+        limit_price: None,
+        stop_price: Some(Money::from_f64(205.0)),
+        trail: None,
+        client_order_id: "replacement".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(replacement.replaces, Some(original.id));
+  assert_eq!(replacement.limit_price.as_deref(), Some("215"));
+  assert_eq!(replacement.stop_price.as_deref(), Some("205"));
+}
+
+#[tokio::test]
+async fn test_simulator_should_stream_trade_updates() {
+  let server = SimServer::new(Broker::new(10_000.0)).with_market_open(true);
+  server.set_price("AAPL", 200.0);
+  let handle = server.clone().spawn("127.0.0.1:0").await.unwrap();
+  let client = Client::new(handle.base_url(), "key".to_string(), "secret".to_string());
+  let config = StreamConfig::trade_updates(handle.base_url(), "key".to_string(), "secret".to_string());
+  let mut stream = TradeUpdatesStream::connect(config).await.unwrap();
+
+  let limit = client
+    .create_order(&order("AAPL", Side::Buy, 5.0, Some(190.0)))
+    .await
+    .unwrap();
+  let new = stream.next().await.unwrap();
+  assert_eq!(new.event, TradeUpdateEvent::New);
+  assert_eq!(new.order.id, limit.id);
+  assert!(new.price.is_none());
+
+  server.set_price("AAPL", 189.0);
+  let fill = stream.next().await.unwrap();
+  assert_eq!(fill.event, TradeUpdateEvent::Fill);
+  assert_eq!(fill.order.id, limit.id);
+  assert_eq!(fill.price.unwrap().value(), 189.0);
+  assert_eq!(fill.qty.unwrap().value(), 5.0);
+  assert_eq!(fill.position_qty.unwrap().value(), 5.0);
+
+  let resting = client
+    .create_order(&order("AAPL", Side::Buy, 1.0, Some(150.0)))
+    .await
+    .unwrap();
+  client.delete_order_by_id(&resting.id).await.unwrap();
+  assert_eq!(stream.next().await.unwrap().event, TradeUpdateEvent::New);
+  let canceled = stream.next().await.unwrap();
+  assert_eq!(canceled.event, TradeUpdateEvent::Canceled);
+  assert_eq!(canceled.order.id, resting.id);
+  assert!(stream.try_next().is_none());
+}